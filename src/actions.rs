@@ -1,12 +1,36 @@
-use objc2_app_kit::{NSModalResponseOK, NSSavePanel};
-use objc2_core_foundation::{CFRetained, CGFloat, CGPoint, CGRect, CGSize};
+use std::path::{Path, PathBuf};
+
+use objc2::rc::Retained;
+use objc2::runtime::{AnyClass, AnyObject, ProtocolObject};
+use objc2::{msg_send, sel, AnyThread};
+use objc2_app_kit::{
+    NSBitmapImageRep, NSImage, NSModalResponseOK, NSOpenPanel, NSPasteboard, NSPopUpButton,
+    NSSavePanel, NSSlider, NSSound, NSTextField, NSView, NSWorkspace,
+};
+use objc2_core_foundation::{
+    CFArray, CFBoolean, CFDictionary, CFNumber, CFRetained, CFString, CFType, CFURL, CGFloat,
+    CGPoint, CGRect, CGSize,
+};
 use objc2_core_graphics::{
     CGBitmapContextCreate, CGBitmapContextCreateImage, CGColorSpace, CGContext, CGImage,
-    CGImageAlphaInfo,
+    CGImageAlphaInfo, CGInterpolationQuality,
+};
+use objc2_foundation::{MainThreadMarker, NSArray, NSDate, NSDateFormatter, NSString, NSURL};
+use objc2_image_io::{
+    kCGImageDestinationLossyCompressionQuality, kCGImagePropertyOrientation,
+    kCGImagePropertyPixelHeight, kCGImagePropertyPixelWidth,
+    kCGImageSourceCreateThumbnailFromImageAlways, kCGImageSourceCreateThumbnailWithTransform,
+    kCGImageSourceThumbnailMaxPixelSize, CGImageDestination, CGImageSource,
 };
-use objc2_foundation::{MainThreadMarker, NSString};
 
 use crate::annotation::model::Annotation;
+use crate::prefs::ImageFormat;
+
+/// Tag of the save panel label showing the JPEG quality percentage.
+pub const JPEG_QUALITY_LABEL_TAG: isize = 1;
+
+/// Default export size in the save panel: keep the captured pixels.
+const FULL_SIZE: u32 = 100;
 
 /// Crop the captured CGImage to the selection area, compositing annotations on top.
 pub fn crop_and_composite(
@@ -90,45 +114,354 @@ pub fn copy_to_clipboard(image: &CGImage) -> Result<(), String> {
     Ok(())
 }
 
-/// Save a CGImage to a file via NSSavePanel.
-pub fn save_to_file(image: &CGImage, mtm: MainThreadMarker) {
-    let width = CGImage::width(Some(image));
-    let height = CGImage::height(Some(image));
+/// Put a reference to the file at `path` on the clipboard, for pasting into
+/// Finder or apps that take files rather than bitmaps.
+pub fn copy_file_to_clipboard(path: &Path) -> Result<(), String> {
+    let path_str = path.to_str().ok_or("Invalid path")?;
+    let url = NSURL::fileURLWithPath(&NSString::from_str(path_str));
+    let pasteboard = NSPasteboard::generalPasteboard();
+    pasteboard.clearContents();
+    let objects = NSArray::from_retained_slice(&[ProtocolObject::from_retained(url)]);
+    if !pasteboard.writeObjects(&objects) {
+        return Err("Failed to write to the clipboard".to_string());
+    }
+    eprintln!("File copied to clipboard: {}", path.display());
+    Ok(())
+}
 
-    let rgba = match cgimage_to_rgba(image) {
-        Ok(data) => data,
-        Err(e) => {
-            eprintln!("Failed to convert image: {}", e);
-            return;
+/// Save a CGImage to a file via NSSavePanel, or straight to the save folder
+/// when auto-save is on. The panel's format pop-up and JPEG quality slider
+/// start at the last values used and remember the choice. Returns where the
+/// image was saved, or `None` if cancelled or failed.
+pub fn save_to_file(image: &CGImage, mtm: MainThreadMarker) -> Option<PathBuf> {
+    let format = saveable_format(crate::prefs::image_format());
+    if crate::prefs::auto_save() {
+        let path = default_save_path(format.extension());
+        return saved(save_image(image, &path, format), path);
+    }
+
+    let panel = NSSavePanel::new(mtm);
+    prepare_save_panel(&panel, format.extension());
+    panel.setExtensionHidden(false);
+
+    // Format pop-up, quality slider, and export size in the accessory view.
+    // imageFormatChanged: and jpegQualityChanged: (on the app delegate, via the
+    // responder chain) keep the extension and the percentage label in step.
+    let accessory = NSView::initWithFrame(
+        mtm.alloc(),
+        CGRect::new(CGPoint::ZERO, CGSize::new(320.0, 100.0)),
+    );
+    let size_label = NSTextField::labelWithString(&NSString::from_str("Size:"), mtm);
+    size_label.setFrame(CGRect::new(CGPoint::new(20.0, 71.0), CGSize::new(60.0, 17.0)));
+    accessory.addSubview(&size_label);
+    let size_popup = NSPopUpButton::initWithFrame_pullsDown(
+        mtm.alloc(),
+        CGRect::new(CGPoint::new(80.0, 66.0), CGSize::new(80.0, 26.0)),
+        false,
+    );
+    for percent in crate::prefs::EXPORT_SCALES {
+        size_popup.addItemWithTitle(&NSString::from_str(&format!("{}%", percent)));
+    }
+    let scale = crate::prefs::export_scale();
+    let index = crate::prefs::EXPORT_SCALES.iter().position(|&p| p == scale).unwrap_or(0);
+    size_popup.selectItemAtIndex(index as isize);
+    accessory.addSubview(&size_popup);
+    let width_label = NSTextField::labelWithString(&NSString::from_str("Max width:"), mtm);
+    width_label.setFrame(CGRect::new(CGPoint::new(170.0, 71.0), CGSize::new(70.0, 17.0)));
+    accessory.addSubview(&width_label);
+    let width_frame = CGRect::new(CGPoint::new(242.0, 68.0), CGSize::new(60.0, 22.0));
+    let width_field: Retained<NSTextField> =
+        unsafe { msg_send![mtm.alloc::<NSTextField>(), initWithFrame: width_frame] };
+    if let Some(width) = crate::prefs::export_max_width() {
+        width_field.setStringValue(&NSString::from_str(&width.to_string()));
+    }
+    width_field.setPlaceholderString(Some(&NSString::from_str("px")));
+    accessory.addSubview(&width_field);
+
+    let label = NSTextField::labelWithString(&NSString::from_str("Format:"), mtm);
+    label.setFrame(CGRect::new(CGPoint::new(20.0, 41.0), CGSize::new(60.0, 17.0)));
+    accessory.addSubview(&label);
+    let popup = NSPopUpButton::initWithFrame_pullsDown(
+        mtm.alloc(),
+        CGRect::new(CGPoint::new(80.0, 36.0), CGSize::new(160.0, 26.0)),
+        false,
+    );
+    // Formats this macOS can't encode (WebP on older systems) stay listed but disabled
+    popup.setAutoenablesItems(false);
+    for f in ImageFormat::ALL {
+        popup.addItemWithTitle(&NSString::from_str(f.title()));
+        if let Some(item) = popup.lastItem() {
+            item.setEnabled(can_encode(f));
         }
-    };
+    }
+    let index = ImageFormat::ALL.iter().position(|&f| f == format).unwrap_or(0);
+    popup.selectItemAtIndex(index as isize);
+    unsafe {
+        popup.setAction(Some(sel!(imageFormatChanged:)));
+        popup.setTarget(None);
+    }
+    accessory.addSubview(&popup);
 
-    let img_buf = match image::RgbaImage::from_raw(width as u32, height as u32, rgba) {
-        Some(buf) => buf,
-        None => {
-            eprintln!("Failed to create image buffer");
-            return;
+    let quality = crate::prefs::jpeg_quality();
+    let quality_label = NSTextField::labelWithString(&NSString::from_str("Quality:"), mtm);
+    quality_label.setFrame(CGRect::new(CGPoint::new(20.0, 11.0), CGSize::new(60.0, 17.0)));
+    accessory.addSubview(&quality_label);
+    let slider = NSSlider::initWithFrame(
+        mtm.alloc(),
+        CGRect::new(CGPoint::new(80.0, 8.0), CGSize::new(160.0, 24.0)),
+    );
+    slider.setMinValue(crate::prefs::MIN_JPEG_QUALITY);
+    slider.setMaxValue(1.0);
+    // Ticks every 5%
+    slider.setNumberOfTickMarks(19);
+    slider.setAllowsTickMarkValuesOnly(true);
+    slider.setDoubleValue(quality);
+    slider.setEnabled(format == ImageFormat::Jpeg);
+    unsafe {
+        slider.setAction(Some(sel!(jpegQualityChanged:)));
+        slider.setTarget(None);
+    }
+    accessory.addSubview(&slider);
+    let percent = NSTextField::labelWithString(&NSString::from_str(&quality_title(quality)), mtm);
+    percent.setFrame(CGRect::new(CGPoint::new(246.0, 11.0), CGSize::new(44.0, 17.0)));
+    percent.setTag(JPEG_QUALITY_LABEL_TAG);
+    accessory.addSubview(&percent);
+    panel.setAccessoryView(Some(&accessory));
+
+    if panel.runModal() != NSModalResponseOK {
+        return None;
+    }
+    let path = panel.URL()?.path()?;
+    let format = ImageFormat::ALL
+        .get(popup.indexOfSelectedItem() as usize)
+        .copied()
+        .unwrap_or(ImageFormat::Png);
+    crate::prefs::set_image_format(format);
+    crate::prefs::set_jpeg_quality(slider.doubleValue());
+    let percent = crate::prefs::EXPORT_SCALES
+        .get(size_popup.indexOfSelectedItem() as usize)
+        .copied()
+        .unwrap_or(FULL_SIZE);
+    let max_width = width_field.stringValue().to_string().trim().parse().ok().filter(|&w| w > 0);
+    crate::prefs::set_export_scale(percent);
+    crate::prefs::set_export_max_width(max_width);
+    let path = PathBuf::from(path.to_string());
+    // The size only applies here, where it is shown; other saves keep full size
+    let result = resize_for_export(image, percent, max_width).and_then(|resized| {
+        save_image(resized.as_deref().unwrap_or(image), &path, format)
+    });
+    saved(result, path)
+}
+
+/// Log the outcome of a save, returning the path if it succeeded.
+fn saved(result: Result<(), String>, path: PathBuf) -> Option<PathBuf> {
+    match result {
+        Ok(()) => {
+            eprintln!("Saved to {}", path.display());
+            Some(path)
+        }
+        Err(e) => {
+            eprintln!("Failed to save: {}", e);
+            None
         }
+    }
+}
+
+/// Percentage shown next to the quality slider, e.g. "80%".
+pub fn quality_title(quality: f64) -> String {
+    format!("{}%", (quality * 100.0).round())
+}
+
+/// Save a CGImage to the save folder under the templated name, without prompting.
+pub fn quick_save(image: &CGImage) -> Result<PathBuf, String> {
+    let format = saveable_format(crate::prefs::image_format());
+    let path = default_save_path(format.extension());
+    save_image(image, &path, format)?;
+    eprintln!("Saved to {}", path.display());
+    Ok(path)
+}
+
+/// Expand the file name template: `{date}` (2024-05-31), `{time}` (14.05.09),
+/// and `{seq}` (a counter that advances on each save).
+pub fn templated_file_name() -> String {
+    let template = crate::prefs::filename_template();
+    let now = NSDate::now();
+    let format = |pattern: &str| {
+        let formatter = NSDateFormatter::new();
+        formatter.setDateFormat(Some(&NSString::from_str(pattern)));
+        formatter.stringFromDate(&now).to_string()
     };
+    let mut name = template.replace("{date}", &format("yyyy-MM-dd"));
+    name = name.replace("{time}", &format("HH.mm.ss"));
+    if name.contains("{seq}") {
+        name = name.replace("{seq}", &crate::prefs::next_save_sequence().to_string());
+    }
+    // A slash would point into another folder
+    name.replace('/', "-")
+}
 
-    let panel = NSSavePanel::new(mtm);
-    panel.setNameFieldStringValue(&NSString::from_str("clipshot.png"));
-
-    let response = panel.runModal();
-    if response == NSModalResponseOK {
-        if let Some(url) = panel.URL() {
-            if let Some(path) = url.path() {
-                let path_str = path.to_string();
-                if let Err(e) = img_buf.save(&path_str) {
-                    eprintln!("Failed to save: {}", e);
-                } else {
-                    eprintln!("Saved to {}", path_str);
-                }
-            }
-        }
+/// A new file in the save folder named by the template, numbered if the name is taken.
+pub fn default_save_path(extension: &str) -> PathBuf {
+    let folder = crate::prefs::save_folder();
+    let name = templated_file_name();
+    let mut path = folder.join(format!("{}.{}", name, extension));
+    let mut n = 2;
+    while path.exists() {
+        path = folder.join(format!("{} {}.{}", name, n, extension));
+        n += 1;
+    }
+    path
+}
+
+/// The sound macOS plays when it takes a screenshot.
+const SCREENSHOT_SOUND: &str = "/System/Library/Components/CoreAudio.component/Contents/\
+    SharedSupport/SystemSounds/system/Screen Capture.aif";
+
+/// Play the system screenshot sound (silently does nothing if it is missing).
+pub fn play_screenshot_sound() {
+    let path = NSString::from_str(SCREENSHOT_SOUND);
+    let sound = NSSound::initWithContentsOfFile_byReference(NSSound::alloc(), &path, true);
+    if let Some(sound) = sound {
+        sound.play();
     }
 }
 
+/// Select `path` in a Finder window.
+pub fn reveal_in_finder(path: &Path) {
+    let url = NSURL::fileURLWithPath(&NSString::from_str(&path.to_string_lossy()));
+    NSWorkspace::sharedWorkspace().activateFileViewerSelectingURLs(&NSArray::from_slice(&[&*url]));
+}
+
+/// Start `panel` in the save folder with the templated name and `extension`.
+pub fn prepare_save_panel(panel: &NSSavePanel, extension: &str) {
+    set_allowed_extension(panel, extension);
+    let name = format!("{}.{}", templated_file_name(), extension);
+    panel.setNameFieldStringValue(&NSString::from_str(&name));
+    let folder = crate::prefs::save_folder();
+    let url = NSURL::fileURLWithPath(&NSString::from_str(&folder.to_string_lossy()));
+    panel.setDirectoryURL(Some(&url));
+}
+
+/// Encode a CGImage in `format` with ImageIO and write it to `path`
+/// (JPEG uses the remembered quality).
+pub fn save_image(image: &CGImage, path: &Path, format: ImageFormat) -> Result<(), String> {
+    match format {
+        ImageFormat::Png => save_png(image, path),
+        ImageFormat::Jpeg => save_jpeg(image, path, crate::prefs::jpeg_quality()),
+        ImageFormat::WebP => write_with_image_io(image, path, format, None),
+    }
+}
+
+/// Scale `image` down to `scale` percent and at most `max_width` pixels wide,
+/// keeping its aspect ratio. Returns `None` when it is already small enough.
+fn resize_for_export(
+    image: &CGImage,
+    scale: u32,
+    max_width: Option<usize>,
+) -> Result<Option<CFRetained<CGImage>>, String> {
+    let width = CGImage::width(Some(image));
+    let height = CGImage::height(Some(image));
+    let mut new_width = width * scale as usize / FULL_SIZE as usize;
+    if let Some(max_width) = max_width {
+        new_width = new_width.min(max_width);
+    }
+    if new_width >= width || width == 0 {
+        return Ok(None);
+    }
+    let new_width = new_width.max(1);
+    let new_height = (height * new_width / width).max(1);
+    let resized = crate::editor::export::scale_image(
+        image,
+        new_width,
+        new_height,
+        CGInterpolationQuality::High,
+    )
+    .ok_or("Failed to resize image")?;
+    eprintln!("Exporting at {}x{} (from {}x{})", new_width, new_height, width, height);
+    Ok(Some(resized))
+}
+
+/// Whether ImageIO on this system can write `format`.
+pub fn can_encode(format: ImageFormat) -> bool {
+    let types = unsafe { CGImageDestination::type_identifiers() };
+    // SAFETY: CGImageDestinationCopyTypeIdentifiers returns an array of CFStrings.
+    let types = unsafe { CFRetained::cast_unchecked::<CFArray<CFString>>(types) };
+    types.iter().any(|uti| uti.to_string() == format.uti())
+}
+
+/// `format`, or PNG when this system can't encode it.
+fn saveable_format(format: ImageFormat) -> ImageFormat {
+    if can_encode(format) {
+        format
+    } else {
+        ImageFormat::Png
+    }
+}
+
+/// Encode a CGImage as JPEG at `quality` (0.0–1.0) and write it to `path`.
+/// Transparent areas (window shadows) end up on white, as JPEG has no alpha.
+pub fn save_jpeg(image: &CGImage, path: &Path, quality: f64) -> Result<(), String> {
+    let flattened = flatten_on_white(image).ok_or("Failed to flatten image")?;
+    let quality = CFNumber::new_f64(quality);
+    let properties = CFDictionary::<CFString, CFType>::from_slices(
+        &[unsafe { kCGImageDestinationLossyCompressionQuality }],
+        &[&quality],
+    );
+    write_with_image_io(&flattened, path, ImageFormat::Jpeg, Some(&properties))
+}
+
+/// Write a single image to `path` through a `CGImageDestination` for `format`.
+fn write_with_image_io(
+    image: &CGImage,
+    path: &Path,
+    format: ImageFormat,
+    properties: Option<&CFDictionary<CFString, CFType>>,
+) -> Result<(), String> {
+    let url = CFURL::from_file_path(path).ok_or("Invalid path")?;
+    let uti = CFString::from_str(format.uti());
+    let destination = unsafe { CGImageDestination::with_url(&url, &uti, 1, None) }
+        .ok_or_else(|| format!("{} encoding is not supported on this Mac", format.title()))?;
+    unsafe { destination.add_image(image, properties.map(|p| p.as_opaque())) };
+    if !unsafe { destination.finalize() } {
+        return Err(format!("Failed to write {}", format.title()));
+    }
+    Ok(())
+}
+
+/// Draw `image` over an opaque white background.
+fn flatten_on_white(image: &CGImage) -> Option<CFRetained<CGImage>> {
+    let width = CGImage::width(Some(image));
+    let height = CGImage::height(Some(image));
+    let color_space = CGColorSpace::new_device_rgb()?;
+    let ctx = unsafe {
+        CGBitmapContextCreate(
+            std::ptr::null_mut(),
+            width,
+            height,
+            8,
+            width * 4,
+            Some(&color_space),
+            CGImageAlphaInfo::NoneSkipLast.0,
+        )
+    }?;
+    let rect = CGRect::new(CGPoint::ZERO, CGSize::new(width as CGFloat, height as CGFloat));
+    CGContext::set_rgb_fill_color(Some(&ctx), 1.0, 1.0, 1.0, 1.0);
+    CGContext::fill_rect(Some(&ctx), rect);
+    CGContext::draw_image(Some(&ctx), rect, Some(image));
+    CGBitmapContextCreateImage(Some(&ctx))
+}
+
+/// Encode a CGImage as PNG and write it to `path`.
+pub fn save_png(image: &CGImage, path: &Path) -> Result<(), String> {
+    let width = CGImage::width(Some(image));
+    let height = CGImage::height(Some(image));
+
+    let rgba = cgimage_to_rgba(image)?;
+    let img_buf = image::RgbaImage::from_raw(width as u32, height as u32, rgba)
+        .ok_or("Failed to create image buffer")?;
+    img_buf.save(path).map_err(|e| e.to_string())
+}
+
 /// Convert a CGImage to an RGBA byte buffer.
 pub(crate) fn cgimage_to_rgba(image: &CGImage) -> Result<Vec<u8>, String> {
     let width = CGImage::width(Some(image));
@@ -162,3 +495,148 @@ pub(crate) fn cgimage_to_rgba(image: &CGImage) -> Result<Vec<u8>, String> {
 
     Ok(buffer)
 }
+
+/// Read the color of one pixel as un-premultiplied RGB components (0.0–1.0).
+pub fn sample_pixel(image: &CGImage, x: usize, y: usize) -> Option<(CGFloat, CGFloat, CGFloat)> {
+    let width = CGImage::width(Some(image));
+    let height = CGImage::height(Some(image));
+    if x >= width || y >= height {
+        return None;
+    }
+
+    // Decode just that pixel rather than the whole (often screen-sized) image
+    let pixel_rect = CGRect::new(CGPoint::new(x as CGFloat, y as CGFloat), CGSize::new(1.0, 1.0));
+    let pixel = CGImage::with_image_in_rect(Some(image), pixel_rect)?;
+    let rgba = cgimage_to_rgba(&pixel).ok()?;
+    let px = rgba.get(0..4)?;
+    let a = px[3] as CGFloat;
+    if a == 0.0 {
+        return None;
+    }
+    Some((
+        px[0] as CGFloat / a,
+        px[1] as CGFloat / a,
+        px[2] as CGFloat / a,
+    ))
+}
+
+/// Uniform type identifiers of the image formats ImageIO can decode on this Mac.
+pub fn readable_image_types() -> Vec<String> {
+    let types = unsafe { CGImageSource::type_identifiers() };
+    // SAFETY: CGImageSourceCopyTypeIdentifiers returns an array of CFStrings.
+    let types = unsafe { CFRetained::cast_unchecked::<CFArray<CFString>>(types) };
+    types.iter().map(|uti| uti.to_string()).collect()
+}
+
+/// Whether `path` holds an image in one of the `readable_image_types`.
+pub fn is_image_file(path: &Path) -> bool {
+    let uti = image_source(path).ok().and_then(|source| unsafe { source.r#type() });
+    uti.is_some_and(|uti| readable_image_types().contains(&uti.to_string()))
+}
+
+/// Load an image file from disk into a CGImage, in any format ImageIO reads.
+/// The image keeps the file's color profile, and photos are turned upright
+/// by their EXIF orientation.
+pub fn load_image_file(path: &Path) -> Result<CFRetained<CGImage>, String> {
+    let source = image_source(path)?;
+    let properties = unsafe { source.properties_at_index(0, None) }.map(|properties| {
+        // SAFETY: image properties are keyed by CFString.
+        unsafe { CFRetained::cast_unchecked::<CFDictionary<CFString, CFType>>(properties) }
+    });
+    let number = |key: &CFString| {
+        properties.as_ref()?.get(key)?.downcast::<CFNumber>().ok()?.as_i64()
+    };
+    let orientation = number(unsafe { kCGImagePropertyOrientation }).unwrap_or(1);
+    let width = number(unsafe { kCGImagePropertyPixelWidth }).unwrap_or(0);
+    let height = number(unsafe { kCGImagePropertyPixelHeight }).unwrap_or(0);
+    let image = if orientation == 1 || width == 0 || height == 0 {
+        unsafe { source.image_at_index(0, None) }
+    } else {
+        // A full-size "thumbnail" is the only ImageIO decode that applies the orientation
+        let max_size = CFNumber::new_i64(width.max(height));
+        let options = CFDictionary::<CFString, CFType>::from_slices(
+            &[
+                unsafe { kCGImageSourceCreateThumbnailFromImageAlways },
+                unsafe { kCGImageSourceCreateThumbnailWithTransform },
+                unsafe { kCGImageSourceThumbnailMaxPixelSize },
+            ],
+            &[CFBoolean::new(true), CFBoolean::new(true), &max_size],
+        );
+        unsafe { source.thumbnail_at_index(0, Some(options.as_opaque())) }
+    };
+    image.ok_or_else(|| format!("Failed to decode {}", path.display()))
+}
+
+fn image_source(path: &Path) -> Result<CFRetained<CGImageSource>, String> {
+    let url = CFURL::from_file_path(path).ok_or("Invalid path")?;
+    unsafe { CGImageSource::with_url(&url, None) }
+        .ok_or_else(|| format!("Failed to open {}", path.display()))
+}
+
+/// Read the image on the general pasteboard, at its full pixel size.
+pub fn image_from_pasteboard() -> Result<CFRetained<CGImage>, String> {
+    let pasteboard = NSPasteboard::generalPasteboard();
+    if !NSImage::canInitWithPasteboard(&pasteboard) {
+        return Err("The clipboard has no image".to_string());
+    }
+    let image = NSImage::initWithPasteboard(NSImage::alloc(), &pasteboard)
+        .ok_or("Failed to read the clipboard image")?;
+
+    // Prefer a bitmap rep: the image's point size would scale Retina pixels down
+    let bitmap = image.representations().iter().find_map(|rep| {
+        rep.downcast::<NSBitmapImageRep>().ok().and_then(|bitmap| bitmap.CGImage())
+    });
+    bitmap
+        .or_else(|| unsafe {
+            image.CGImageForProposedRect_context_hints(std::ptr::null_mut(), None, None)
+        })
+        .map(|image| CFRetained::from(&*image))
+        .ok_or_else(|| "Failed to convert the clipboard image".to_string())
+}
+
+/// Ask the user to pick a single image file via NSOpenPanel.
+pub fn choose_image_file(message: &str, mtm: MainThreadMarker) -> Option<PathBuf> {
+    let panel = NSOpenPanel::openPanel(mtm);
+    panel.setCanChooseFiles(true);
+    panel.setCanChooseDirectories(false);
+    panel.setAllowsMultipleSelection(false);
+    panel.setMessage(Some(&NSString::from_str(message)));
+    set_allowed_content_types(&panel, &readable_image_types());
+
+    if panel.runModal() != NSModalResponseOK {
+        return None;
+    }
+    let path = panel.URL()?.path()?;
+    Some(PathBuf::from(path.to_string()))
+}
+
+/// Have `panel` save with `extension`. AppKit then fixes up the typed name and
+/// asks before replacing a file under the name actually saved to.
+pub fn set_allowed_extension(panel: &NSSavePanel, extension: &str) {
+    let Some(class) = AnyClass::get(c"UTType") else {
+        return;
+    };
+    let uttype: Option<Retained<AnyObject>> = unsafe {
+        msg_send![class, typeWithFilenameExtension: &*NSString::from_str(extension)]
+    };
+    if let Some(uttype) = uttype {
+        let types = NSArray::from_retained_slice(&[uttype]);
+        let _: () = unsafe { msg_send![panel, setAllowedContentTypes: &*types] };
+    }
+}
+
+/// Only let `panel` pick files of the given uniform type identifiers. UTType
+/// lives in UniformTypeIdentifiers, which AppKit loads, so it is looked up at runtime.
+fn set_allowed_content_types(panel: &NSSavePanel, identifiers: &[String]) {
+    let Some(class) = AnyClass::get(c"UTType") else {
+        return;
+    };
+    let types: Vec<Retained<AnyObject>> = identifiers
+        .iter()
+        .filter_map(|id| unsafe {
+            msg_send![class, typeWithIdentifier: &*NSString::from_str(id)]
+        })
+        .collect();
+    let types = NSArray::from_retained_slice(&types);
+    let _: () = unsafe { msg_send![panel, setAllowedContentTypes: &*types] };
+}
@@ -1,16 +1,49 @@
+use objc2::rc::Retained;
+use objc2_app_kit::{NSFont, NSFontDescriptorSymbolicTraits};
 use objc2_core_foundation::{CGFloat, CGPoint, CGRect, CGSize};
 
 /// Padding added to bounding rects for hit-testing tolerance.
 const HIT_TEST_PADDING: CGFloat = 4.0;
 /// Tolerance for hitting a resize handle.
 const HANDLE_HIT_TOLERANCE: CGFloat = 6.0;
+/// How far a duplicated annotation is offset from the original (both axes).
+pub const DUPLICATE_OFFSET: CGFloat = 12.0;
+/// How far a drop shadow falls from its annotation (device space, so down-right on screen).
+pub const SHADOW_OFFSET: CGSize = CGSize { width: 2.0, height: -2.0 };
+/// Blur radius of a drop shadow.
+pub const SHADOW_BLUR: CGFloat = 4.0;
+/// Opacity of a drop shadow's black.
+pub const SHADOW_ALPHA: CGFloat = 0.5;
+/// Moves and resizes kept for undo; the oldest is dropped past this.
+const MAX_TRANSFORM_UNDO: usize = 100;
+/// Inset between a callout's bubble edge and its text.
+pub const CALLOUT_PADDING: CGFloat = 8.0;
+/// Corner radius of a callout's bubble.
+pub const CALLOUT_CORNER_RADIUS: CGFloat = 8.0;
+/// Margin between a text label and its background box.
+pub const TEXT_BACKGROUND_PADDING: CGFloat = 4.0;
+/// A highlighter stroke is this many times wider than the selected stroke width.
+pub const HIGHLIGHTER_WIDTH_SCALE: CGFloat = 4.0;
+/// Opacity of a highlighter stroke's color before it is multiplied onto the image.
+pub const HIGHLIGHTER_OPACITY: CGFloat = 0.5;
+/// Opacity of a text label's background box.
+const TEXT_BACKGROUND_ALPHA: CGFloat = 0.85;
+/// Font families offered for text labels: (display name, font name). `None` is the system font.
+pub const TEXT_FONTS: [(&str, Option<&str>); 6] = [
+    ("System", None),
+    ("Helvetica Neue", Some("HelveticaNeue")),
+    ("Avenir Next", Some("AvenirNext-Regular")),
+    ("Georgia", Some("Georgia")),
+    ("Menlo", Some("Menlo-Regular")),
+    ("Marker Felt", Some("MarkerFelt-Thin")),
+];
 
 /// Identifies a specific resize handle on an annotation.
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum HandleKind {
-    /// Arrow start control point.
+    /// Arrow/Line start control point.
     ArrowStart,
-    /// Arrow end control point.
+    /// Arrow/Line end control point.
     ArrowEnd,
     /// Rect/Ellipse corners and edges.
     TopLeft,
@@ -21,6 +54,82 @@ pub enum HandleKind {
     BottomLeft,
     Bottom,
     BottomRight,
+    /// Callout tail tip.
+    Tail,
+}
+
+/// Outline pattern for shapes and lines.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StrokeStyle {
+    Solid,
+    Dashed,
+    Dotted,
+}
+
+impl StrokeStyle {
+    /// The next style in the Solid → Dashed → Dotted cycle.
+    pub fn next(self) -> Self {
+        match self {
+            StrokeStyle::Solid => StrokeStyle::Dashed,
+            StrokeStyle::Dashed => StrokeStyle::Dotted,
+            StrokeStyle::Dotted => StrokeStyle::Solid,
+        }
+    }
+}
+
+/// How a Blur annotation obscures the pixels underneath.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BlurStyle {
+    /// Mosaic of averaged blocks.
+    Pixelate,
+    /// Smooth blur (approximated by repeated box blurs).
+    Gaussian,
+}
+
+impl BlurStyle {
+    /// The other blur style.
+    pub fn toggled(self) -> Self {
+        match self {
+            BlurStyle::Pixelate => BlurStyle::Gaussian,
+            BlurStyle::Gaussian => BlurStyle::Pixelate,
+        }
+    }
+}
+
+/// Shape of the undimmed region of a Spotlight annotation.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SpotlightShape {
+    Rect,
+    Ellipse,
+}
+
+impl SpotlightShape {
+    /// The other spotlight shape.
+    pub fn toggled(self) -> Self {
+        match self {
+            SpotlightShape::Rect => SpotlightShape::Ellipse,
+            SpotlightShape::Ellipse => SpotlightShape::Rect,
+        }
+    }
+}
+
+/// Marker shape behind a Step number.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StepShape {
+    Circle,
+    Square,
+    Diamond,
+}
+
+impl StepShape {
+    /// The next shape in the Circle → Square → Diamond cycle.
+    pub fn next(self) -> Self {
+        match self {
+            StepShape::Circle => StepShape::Square,
+            StepShape::Square => StepShape::Diamond,
+            StepShape::Diamond => StepShape::Circle,
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -30,29 +139,63 @@ pub enum Annotation {
         end: CGPoint,
         color: (CGFloat, CGFloat, CGFloat),
         width: CGFloat,
+        /// Draw a drop shadow under the stroke.
+        shadow: bool,
+    },
+    Line {
+        start: CGPoint,
+        end: CGPoint,
+        color: (CGFloat, CGFloat, CGFloat),
+        width: CGFloat,
+        style: StrokeStyle,
+        shadow: bool,
     },
     Rect {
         origin: CGPoint,
         size: CGSize,
         color: (CGFloat, CGFloat, CGFloat),
         width: CGFloat,
+        /// Fill the interior with `color` (solid box) in addition to the outline.
+        fill: bool,
+        /// Corner radius of the outline; 0 draws square corners.
+        corner_radius: CGFloat,
+        style: StrokeStyle,
+        shadow: bool,
     },
     Ellipse {
         origin: CGPoint,
         size: CGSize,
         color: (CGFloat, CGFloat, CGFloat),
         width: CGFloat,
+        /// Fill the interior with `color` in addition to the outline.
+        fill: bool,
+        style: StrokeStyle,
+        shadow: bool,
     },
     Pencil {
+        /// Raw mouse positions, used for hit-testing and bounds.
         points: Vec<CGPoint>,
         color: (CGFloat, CGFloat, CGFloat),
         width: CGFloat,
+        shadow: bool,
+        /// Draw a smooth curve through the points instead of straight segments.
+        smooth: bool,
+        /// A highlighter stroke: wide, translucent, and multiplied onto the image.
+        highlighter: bool,
     },
     Text {
         position: CGPoint,
         text: String,
         color: (CGFloat, CGFloat, CGFloat),
         font_size: CGFloat,
+        /// Box filled behind the text (RGBA), so it reads on any background.
+        background: Option<(CGFloat, CGFloat, CGFloat, CGFloat)>,
+        /// Outline the letters in a contrasting color (a halo).
+        outline: bool,
+        /// Font to draw with (see `TEXT_FONTS`); None is the system font.
+        font_name: Option<String>,
+        /// Bold and/or italic (`TraitBold`, `TraitItalic`); empty for regular text.
+        traits: NSFontDescriptorSymbolicTraits,
     },
     Highlight {
         origin: CGPoint,
@@ -65,19 +208,66 @@ pub enum Annotation {
         number: u32,
         color: (CGFloat, CGFloat, CGFloat),
         radius: CGFloat,
+        shape: StepShape,
     },
     Blur {
         origin: CGPoint,
         size: CGSize,
         block_size: usize,
+        style: BlurStyle,
+    },
+    /// Fully opaque bar for hiding sensitive content.
+    Redact {
+        origin: CGPoint,
+        size: CGSize,
+        color: (CGFloat, CGFloat, CGFloat),
+    },
+    /// Speech bubble: a rounded box with wrapped text and a tail pointing at `tail_tip`.
+    Callout {
+        rect: CGRect,
+        tail_tip: CGPoint,
+        text: String,
+        color: (CGFloat, CGFloat, CGFloat),
+        font_size: CGFloat,
+    },
+    /// Dims everything outside the region (`dim` is the darkening alpha).
+    Spotlight {
+        origin: CGPoint,
+        size: CGSize,
+        shape: SpotlightShape,
+        dim: CGFloat,
     },
 }
 
 impl Annotation {
-    /// Compute the bounding rectangle of this annotation.
+    /// Compute the bounding rectangle of this annotation, including any drop shadow.
     pub fn bounding_rect(&self) -> CGRect {
+        let rect = self.shape_rect();
+        if self.has_shadow() {
+            let reach = SHADOW_BLUR + SHADOW_OFFSET.width.abs().max(SHADOW_OFFSET.height.abs());
+            inflate_rect(rect, reach)
+        } else {
+            rect
+        }
+    }
+
+    /// Whether this annotation is drawn with a drop shadow.
+    pub fn has_shadow(&self) -> bool {
+        match self {
+            Annotation::Arrow { shadow, .. }
+            | Annotation::Line { shadow, .. }
+            | Annotation::Rect { shadow, .. }
+            | Annotation::Ellipse { shadow, .. }
+            | Annotation::Pencil { shadow, .. } => *shadow,
+            _ => false,
+        }
+    }
+
+    /// Bounds of the drawn shape itself (strokes included, shadow not).
+    fn shape_rect(&self) -> CGRect {
         match self {
-            Annotation::Arrow { start, end, width, .. } => {
+            Annotation::Arrow { start, end, width, .. }
+            | Annotation::Line { start, end, width, .. } => {
                 let min_x = start.x.min(end.x);
                 let min_y = start.y.min(end.y);
                 let max_x = start.x.max(end.x);
@@ -112,9 +302,17 @@ impl Annotation {
                     CGSize::new(max_x - min_x, max_y - min_y),
                 ), *width)
             }
-            Annotation::Text { position, text, font_size, .. } => {
-                let size = measure_text_size(text, *font_size);
-                CGRect::new(*position, size)
+            Annotation::Text {
+                position, text, font_size, background, outline, font_name, traits, ..
+            } => {
+                let font = text_font(font_name.as_deref(), *font_size, *traits);
+                let size = measure_text_size(text, &font);
+                let rect = CGRect::new(*position, size);
+                if background.is_some() || *outline {
+                    inflate_rect(rect, TEXT_BACKGROUND_PADDING)
+                } else {
+                    rect
+                }
             }
             Annotation::Highlight { origin, size, .. } => {
                 normalize_annotation_rect(*origin, *size)
@@ -125,9 +323,19 @@ impl Annotation {
                     CGSize::new(radius * 2.0, radius * 2.0),
                 )
             }
-            Annotation::Blur { origin, size, .. } => {
+            Annotation::Blur { origin, size, .. }
+            | Annotation::Redact { origin, size, .. }
+            | Annotation::Spotlight { origin, size, .. } => {
                 normalize_annotation_rect(*origin, *size)
             }
+            Annotation::Callout { rect, tail_tip, .. } => {
+                let r = normalize_annotation_rect(rect.origin, rect.size);
+                let min_x = r.origin.x.min(tail_tip.x);
+                let min_y = r.origin.y.min(tail_tip.y);
+                let max_x = (r.origin.x + r.size.width).max(tail_tip.x);
+                let max_y = (r.origin.y + r.size.height).max(tail_tip.y);
+                CGRect::new(CGPoint::new(min_x, min_y), CGSize::new(max_x - min_x, max_y - min_y))
+            }
         }
     }
 
@@ -140,10 +348,133 @@ impl Annotation {
             && point.y <= rect.origin.y + rect.size.height
     }
 
+    /// Change the pixelation block size of a Blur annotation. Returns false for other kinds.
+    pub fn set_block_size(&mut self, new_size: usize) -> bool {
+        match self {
+            Annotation::Blur { block_size, .. } => {
+                *block_size = new_size.max(1);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Change the color of any annotation that has one. Returns false for Blur and Spotlight.
+    pub fn set_color(&mut self, new_color: (CGFloat, CGFloat, CGFloat)) -> bool {
+        match self {
+            Annotation::Arrow { color, .. }
+            | Annotation::Line { color, .. }
+            | Annotation::Rect { color, .. }
+            | Annotation::Ellipse { color, .. }
+            | Annotation::Pencil { color, .. }
+            | Annotation::Highlight { color, .. }
+            | Annotation::Step { color, .. }
+            | Annotation::Redact { color, .. }
+            | Annotation::Callout { color, .. } => {
+                *color = new_color;
+                true
+            }
+            Annotation::Text { color, background, .. } => {
+                *color = new_color;
+                // Keep the box contrasting with the new text color
+                if background.is_some() {
+                    *background = Some(text_background_for(new_color));
+                }
+                true
+            }
+            Annotation::Blur { .. } | Annotation::Spotlight { .. } => false,
+        }
+    }
+
+    /// Change the stroke width of an outlined or drawn annotation. Returns false for other kinds.
+    pub fn set_stroke_width(&mut self, new_width: CGFloat) -> bool {
+        match self {
+            Annotation::Arrow { width, .. }
+            | Annotation::Line { width, .. }
+            | Annotation::Rect { width, .. }
+            | Annotation::Ellipse { width, .. } => {
+                *width = new_width;
+                true
+            }
+            Annotation::Pencil { width, highlighter, .. } => {
+                *width = if *highlighter { new_width * HIGHLIGHTER_WIDTH_SCALE } else { new_width };
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Change the font size of a Text annotation (its bounds follow the text).
+    /// Returns false for other kinds.
+    pub fn set_font_size(&mut self, new_size: CGFloat) -> bool {
+        match self {
+            Annotation::Text { font_size, .. } => {
+                *font_size = new_size;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Change the bold/italic styling of a Text annotation. Returns false for other kinds.
+    pub fn set_font_traits(&mut self, new_traits: NSFontDescriptorSymbolicTraits) -> bool {
+        match self {
+            Annotation::Text { traits, .. } => {
+                *traits = new_traits;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Change the opacity of a Highlight annotation. Returns false for other kinds.
+    pub fn set_opacity(&mut self, new_opacity: CGFloat) -> bool {
+        match self {
+            Annotation::Highlight { opacity, .. } => {
+                *opacity = new_opacity.clamp(0.0, 1.0);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Set a Callout's text, growing its bubble downward if the wrapped text doesn't fit.
+    /// Returns false for other kinds.
+    pub fn set_callout_text(&mut self, new_text: String) -> bool {
+        let Annotation::Callout { rect, text, font_size, .. } = self else {
+            return false;
+        };
+        let mut r = normalize_annotation_rect(rect.origin, rect.size);
+        let text_width = (r.size.width - CALLOUT_PADDING * 2.0).max(1.0);
+        let needed = measure_wrapped_text(&new_text, *font_size, text_width).height
+            + CALLOUT_PADDING * 2.0;
+        r.size.height = r.size.height.max(needed);
+        *rect = r;
+        *text = new_text;
+        true
+    }
+
+    /// Store text typed into the inline text field: a Callout's text, or a Step's number
+    /// (ignored unless it parses as a positive integer). Returns false for other kinds.
+    pub fn apply_edited_text(&mut self, text: String) -> bool {
+        match self {
+            Annotation::Callout { .. } => self.set_callout_text(text),
+            Annotation::Step { number, .. } => {
+                if let Ok(n) = text.trim().parse::<u32>()
+                    && n > 0
+                {
+                    *number = n;
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+
     /// Translate the annotation by (dx, dy).
     pub fn translate(&mut self, dx: CGFloat, dy: CGFloat) {
         match self {
-            Annotation::Arrow { start, end, .. } => {
+            Annotation::Arrow { start, end, .. } | Annotation::Line { start, end, .. } => {
                 start.x += dx;
                 start.y += dy;
                 end.x += dx;
@@ -169,10 +500,84 @@ impl Annotation {
                 center.x += dx;
                 center.y += dy;
             }
-            Annotation::Blur { origin, .. } => {
+            Annotation::Blur { origin, .. }
+            | Annotation::Redact { origin, .. }
+            | Annotation::Spotlight { origin, .. } => {
                 origin.x += dx;
                 origin.y += dy;
             }
+            Annotation::Callout { rect, tail_tip, .. } => {
+                rect.origin.x += dx;
+                rect.origin.y += dy;
+                tail_tip.x += dx;
+                tail_tip.y += dy;
+            }
+        }
+    }
+
+    /// Move every point through `map` and scale line widths, text, and markers by
+    /// `scale`, for when the image underneath is rotated or flipped. Boxes stay
+    /// upright, and text keeps reading left to right around its mapped center.
+    pub fn map_geometry(&mut self, map: impl Fn(CGPoint) -> CGPoint, scale: CGFloat) {
+        let map_rect = |origin: CGPoint, size: CGSize| {
+            let r = normalize_annotation_rect(origin, size);
+            let a = map(r.origin);
+            let b = map(CGPoint::new(r.origin.x + r.size.width, r.origin.y + r.size.height));
+            normalize_annotation_rect(a, CGSize::new(b.x - a.x, b.y - a.y))
+        };
+        let text_rect = self.shape_rect();
+        match self {
+            Annotation::Arrow { start, end, width, .. }
+            | Annotation::Line { start, end, width, .. } => {
+                *start = map(*start);
+                *end = map(*end);
+                *width *= scale;
+            }
+            Annotation::Rect { origin, size, width, corner_radius, .. } => {
+                let r = map_rect(*origin, *size);
+                (*origin, *size) = (r.origin, r.size);
+                *width *= scale;
+                *corner_radius *= scale;
+            }
+            Annotation::Ellipse { origin, size, width, .. } => {
+                let r = map_rect(*origin, *size);
+                (*origin, *size) = (r.origin, r.size);
+                *width *= scale;
+            }
+            Annotation::Highlight { origin, size, .. }
+            | Annotation::Blur { origin, size, .. }
+            | Annotation::Redact { origin, size, .. }
+            | Annotation::Spotlight { origin, size, .. } => {
+                let r = map_rect(*origin, *size);
+                (*origin, *size) = (r.origin, r.size);
+            }
+            Annotation::Pencil { points, width, .. } => {
+                for p in points.iter_mut() {
+                    *p = map(*p);
+                }
+                *width *= scale;
+            }
+            Annotation::Text { position, font_size, .. } => {
+                let center = map(CGPoint::new(
+                    text_rect.origin.x + text_rect.size.width / 2.0,
+                    text_rect.origin.y + text_rect.size.height / 2.0,
+                ));
+                let offset = (position.x - text_rect.origin.x, position.y - text_rect.origin.y);
+                *position = CGPoint::new(
+                    center.x - text_rect.size.width * scale / 2.0 + offset.0 * scale,
+                    center.y - text_rect.size.height * scale / 2.0 + offset.1 * scale,
+                );
+                *font_size *= scale;
+            }
+            Annotation::Step { center, radius, .. } => {
+                *center = map(*center);
+                *radius *= scale;
+            }
+            Annotation::Callout { rect, tail_tip, font_size, .. } => {
+                *rect = map_rect(rect.origin, rect.size);
+                *tail_tip = map(*tail_tip);
+                *font_size *= scale;
+            }
         }
     }
 
@@ -180,7 +585,7 @@ impl Annotation {
     /// Returns an empty vec for types that don't support resizing (Pencil, Text).
     pub fn resize_handles(&self) -> Vec<(HandleKind, CGPoint)> {
         match self {
-            Annotation::Arrow { start, end, .. } => {
+            Annotation::Arrow { start, end, .. } | Annotation::Line { start, end, .. } => {
                 vec![
                     (HandleKind::ArrowStart, *start),
                     (HandleKind::ArrowEnd, *end),
@@ -189,10 +594,18 @@ impl Annotation {
             Annotation::Rect { origin, size, .. }
             | Annotation::Ellipse { origin, size, .. }
             | Annotation::Highlight { origin, size, .. }
-            | Annotation::Blur { origin, size, .. } => {
+            | Annotation::Blur { origin, size, .. }
+            | Annotation::Redact { origin, size, .. }
+            | Annotation::Spotlight { origin, size, .. } => {
                 let r = normalize_annotation_rect(*origin, *size);
                 rect_handles(r)
             }
+            Annotation::Callout { rect, tail_tip, .. } => {
+                let r = normalize_annotation_rect(rect.origin, rect.size);
+                let mut handles = rect_handles(r);
+                handles.push((HandleKind::Tail, *tail_tip));
+                handles
+            }
             _ => vec![],
         }
     }
@@ -213,7 +626,7 @@ impl Annotation {
     /// Apply a resize operation by moving a specific handle to a new point.
     pub fn apply_resize(&mut self, handle: HandleKind, point: CGPoint) {
         match self {
-            Annotation::Arrow { start, end, .. } => match handle {
+            Annotation::Arrow { start, end, .. } | Annotation::Line { start, end, .. } => match handle {
                 HandleKind::ArrowStart => *start = point,
                 HandleKind::ArrowEnd => *end = point,
                 _ => {}
@@ -221,53 +634,336 @@ impl Annotation {
             Annotation::Rect { origin, size, .. }
             | Annotation::Ellipse { origin, size, .. }
             | Annotation::Highlight { origin, size, .. }
-            | Annotation::Blur { origin, size, .. } => {
+            | Annotation::Blur { origin, size, .. }
+            | Annotation::Redact { origin, size, .. }
+            | Annotation::Spotlight { origin, size, .. } => {
                 let r = normalize_annotation_rect(*origin, *size);
                 let new_r = apply_rect_resize(r, handle, point);
                 *origin = new_r.origin;
                 *size = new_r.size;
             }
+            Annotation::Callout { rect, tail_tip, .. } => {
+                if handle == HandleKind::Tail {
+                    *tail_tip = point;
+                } else {
+                    let r = normalize_annotation_rect(rect.origin, rect.size);
+                    *rect = apply_rect_resize(r, handle, point);
+                }
+            }
             _ => {}
         }
     }
 }
 
 /// Update an in-progress annotation with a new mouse position.
-pub fn update_annotation(ann: &mut Annotation, point: CGPoint) {
+/// `anchor` is where the drag started. With `constrain` (Shift held), boxes become
+/// squares/circles and lines snap to 45°. With `from_center` (Option held), boxes grow
+/// symmetrically around the anchor instead of from a corner.
+pub fn update_annotation(
+    ann: &mut Annotation,
+    anchor: CGPoint,
+    point: CGPoint,
+    constrain: bool,
+    from_center: bool,
+) {
     match ann {
-        Annotation::Arrow { end, .. } => {
-            *end = point;
+        Annotation::Arrow { start, end, .. } | Annotation::Line { start, end, .. } => {
+            *end = if constrain { snap_to_45(*start, point) } else { point };
         }
-        Annotation::Rect {
-            origin, size, ..
-        } => {
-            size.width = point.x - origin.x;
-            size.height = point.y - origin.y;
+        Annotation::Rect { origin, size, .. }
+        | Annotation::Ellipse { origin, size, .. }
+        | Annotation::Highlight { origin, size, .. }
+        | Annotation::Blur { origin, size, .. }
+        | Annotation::Redact { origin, size, .. }
+        | Annotation::Spotlight { origin, size, .. } => {
+            (*origin, *size) = drag_box(anchor, point, constrain, from_center);
         }
-        Annotation::Ellipse {
-            origin, size, ..
-        } => {
-            size.width = point.x - origin.x;
-            size.height = point.y - origin.y;
-        }
-        Annotation::Highlight {
-            origin, size, ..
-        } => {
-            size.width = point.x - origin.x;
-            size.height = point.y - origin.y;
+        Annotation::Callout { rect, tail_tip, .. } => {
+            let (origin, size) = drag_box(anchor, point, constrain, from_center);
+            *rect = CGRect::new(origin, size);
+            *tail_tip = default_tail_tip(normalize_annotation_rect(origin, size));
         }
         Annotation::Pencil { points, .. } => {
             points.push(point);
         }
         Annotation::Text { .. } => {}
         Annotation::Step { .. } => {}
-        Annotation::Blur {
-            origin, size, ..
-        } => {
-            size.width = point.x - origin.x;
-            size.height = point.y - origin.y;
+    }
+}
+
+/// Move the item at `idx` to the end (front of the paint order) or to the start (back).
+/// Returns its new index, or None if `idx` is out of range.
+pub fn reorder<T>(items: &mut Vec<T>, idx: usize, to_front: bool) -> Option<usize> {
+    if idx >= items.len() {
+        return None;
+    }
+    let item = items.remove(idx);
+    if to_front {
+        items.push(item);
+        Some(items.len() - 1)
+    } else {
+        items.insert(0, item);
+        Some(0)
+    }
+}
+
+/// Black or white, whichever reads better on top of `background`.
+pub fn contrasting_color(background: (CGFloat, CGFloat, CGFloat)) -> (CGFloat, CGFloat, CGFloat) {
+    let luminance = 0.299 * background.0 + 0.587 * background.1 + 0.114 * background.2;
+    if luminance > 0.6 { (0.0, 0.0, 0.0) } else { (1.0, 1.0, 1.0) }
+}
+
+/// Background box color for text drawn in `color`: black or white, slightly see-through.
+pub fn text_background_for(
+    color: (CGFloat, CGFloat, CGFloat),
+) -> (CGFloat, CGFloat, CGFloat, CGFloat) {
+    let (r, g, b) = contrasting_color(color);
+    (r, g, b, TEXT_BACKGROUND_ALPHA)
+}
+
+/// Indices of the annotations whose bounds overlap `rect` (a marquee drag), in paint order.
+pub fn annotations_in_rect<'a>(
+    annotations: impl IntoIterator<Item = (usize, &'a Annotation)>,
+    rect: CGRect,
+) -> Vec<usize> {
+    let r = normalize_annotation_rect(rect.origin, rect.size);
+    annotations
+        .into_iter()
+        .filter(|(_, ann)| {
+            let b = ann.bounding_rect();
+            b.origin.x <= r.origin.x + r.size.width
+                && r.origin.x <= b.origin.x + b.size.width
+                && b.origin.y <= r.origin.y + r.size.height
+                && r.origin.y <= b.origin.y + b.size.height
+        })
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// How close, in points, an edge or center has to come to another's to snap to it.
+pub const GUIDE_SNAP_DISTANCE: CGFloat = 5.0;
+
+/// A line shown while moving annotations, where an edge or center lines up with another.
+#[derive(Clone, Copy, Debug)]
+pub struct AlignmentGuide {
+    pub start: CGPoint,
+    pub end: CGPoint,
+}
+
+/// Smart guides for moving the annotations at `moving` by (dx, dy): the extra offset
+/// that lines their combined bounds up with the edges or center of another annotation
+/// or of `canvas` (zero on an axis where nothing is within `GUIDE_SNAP_DISTANCE`),
+/// and the guides to draw for every alignment after snapping.
+pub fn snap_to_guides<'a>(
+    annotations: impl IntoIterator<Item = (usize, &'a Annotation)>,
+    moving: &[usize],
+    dx: CGFloat,
+    dy: CGFloat,
+    canvas: CGRect,
+) -> (CGPoint, Vec<AlignmentGuide>) {
+    let mut moved: Option<CGRect> = None;
+    let mut targets = vec![canvas];
+    for (i, ann) in annotations {
+        let rect = ann.shape_rect();
+        if moving.contains(&i) {
+            moved = Some(moved.map_or(rect, |m| union_rect(m, rect)));
+        } else {
+            targets.push(rect);
         }
     }
+    let Some(moved) = moved else {
+        return (CGPoint::ZERO, Vec::new());
+    };
+    let moved = CGRect::new(CGPoint::new(moved.origin.x + dx, moved.origin.y + dy), moved.size);
+
+    // Left, center, right (or top, middle, bottom) of a rect
+    let xs = |r: CGRect| [r.origin.x, r.origin.x + r.size.width / 2.0, r.origin.x + r.size.width];
+    let ys = |r: CGRect| [r.origin.y, r.origin.y + r.size.height / 2.0, r.origin.y + r.size.height];
+    let nearest = |lines: &dyn Fn(CGRect) -> [CGFloat; 3]| {
+        let mut best: Option<CGFloat> = None;
+        for target in &targets {
+            for line in lines(*target) {
+                for own in lines(moved) {
+                    let d = line - own;
+                    if d.abs() <= GUIDE_SNAP_DISTANCE && best.is_none_or(|b| d.abs() < b.abs()) {
+                        best = Some(d);
+                    }
+                }
+            }
+        }
+        best.unwrap_or(0.0)
+    };
+    let offset = CGPoint::new(nearest(&xs), nearest(&ys));
+    let snapped = CGRect::new(
+        CGPoint::new(moved.origin.x + offset.x, moved.origin.y + offset.y),
+        moved.size,
+    );
+
+    // Each guide spans both rects along the aligned line
+    let mut guides = Vec::new();
+    for target in &targets {
+        let span = union_rect(snapped, *target);
+        for x in xs(*target) {
+            if xs(snapped).iter().any(|own| (own - x).abs() < 0.5) {
+                guides.push(AlignmentGuide {
+                    start: CGPoint::new(x, span.origin.y),
+                    end: CGPoint::new(x, span.origin.y + span.size.height),
+                });
+            }
+        }
+        for y in ys(*target) {
+            if ys(snapped).iter().any(|own| (own - y).abs() < 0.5) {
+                guides.push(AlignmentGuide {
+                    start: CGPoint::new(span.origin.x, y),
+                    end: CGPoint::new(span.origin.x + span.size.width, y),
+                });
+            }
+        }
+    }
+    (offset, guides)
+}
+
+/// Smallest rectangle containing both `a` and `b` (both normalized).
+pub fn union_rect(a: CGRect, b: CGRect) -> CGRect {
+    let min_x = a.origin.x.min(b.origin.x);
+    let min_y = a.origin.y.min(b.origin.y);
+    let max_x = (a.origin.x + a.size.width).max(b.origin.x + b.size.width);
+    let max_y = (a.origin.y + a.size.height).max(b.origin.y + b.size.height);
+    CGRect::new(CGPoint::new(min_x, min_y), CGSize::new(max_x - min_x, max_y - min_y))
+}
+
+/// Remove the items at `indices` (in any order; out-of-range ones are ignored).
+pub fn remove_indices<T>(items: &mut Vec<T>, indices: &[usize]) {
+    let mut sorted = indices.to_vec();
+    sorted.sort_unstable();
+    sorted.dedup();
+    for &i in sorted.iter().rev() {
+        if i < items.len() {
+            items.remove(i);
+        }
+    }
+}
+
+/// One move or resize of one or more annotations: each index with its shape on
+/// the other side of the edit, tagged with how many annotations existed at the time.
+struct TransformEdit<T> {
+    states: Vec<(usize, T)>,
+    count: usize,
+}
+
+/// Undo and redo for moves and resizes. Added annotations are undone by popping
+/// them, so each edit keeps the annotation count it happened at: it is next in
+/// line only while the count matches (nothing was added since).
+pub struct TransformHistory<T> {
+    undo: Vec<TransformEdit<T>>,
+    redo: Vec<TransformEdit<T>>,
+}
+
+impl<T> TransformHistory<T> {
+    pub fn new() -> Self {
+        TransformHistory { undo: Vec::new(), redo: Vec::new() }
+    }
+
+    /// Record the (index, shape) pairs from before a finished move or resize.
+    pub fn record(&mut self, before: Vec<(usize, T)>, count: usize) {
+        if before.is_empty() {
+            return;
+        }
+        self.redo.clear();
+        self.undo.push(TransformEdit { states: before, count });
+        if self.undo.len() > MAX_TRANSFORM_UNDO {
+            self.undo.remove(0);
+        }
+    }
+
+    /// Revert the newest move or resize if nothing was added after it. `shape`
+    /// picks the recorded part out of an item. Returns the indices of the reverted items.
+    pub fn undo<I>(
+        &mut self,
+        items: &mut [I],
+        shape: impl Fn(&mut I) -> &mut T,
+    ) -> Option<Vec<usize>> {
+        swap_edit(&mut self.undo, &mut self.redo, items, shape)
+    }
+
+    /// Re-apply the newest undone move or resize if it is next in line.
+    /// Returns the indices of the restored items.
+    pub fn redo<I>(
+        &mut self,
+        items: &mut [I],
+        shape: impl Fn(&mut I) -> &mut T,
+    ) -> Option<Vec<usize>> {
+        swap_edit(&mut self.redo, &mut self.undo, items, shape)
+    }
+
+    /// Forget everything, e.g. after indices shift from a deletion or reorder.
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+
+    /// Forget undone edits (a new annotation was added).
+    pub fn clear_redo(&mut self) {
+        self.redo.clear();
+    }
+}
+
+/// Pop the newest edit from `from` if its count matches `items`, swap its states
+/// into place, and push the replaced states onto `to`.
+fn swap_edit<T, I>(
+    from: &mut Vec<TransformEdit<T>>,
+    to: &mut Vec<TransformEdit<T>>,
+    items: &mut [I],
+    shape: impl Fn(&mut I) -> &mut T,
+) -> Option<Vec<usize>> {
+    let edit = from.last()?;
+    if edit.count != items.len() || edit.states.iter().any(|(i, _)| *i >= items.len()) {
+        return None;
+    }
+    let TransformEdit { states, count } = from.pop()?;
+    let indices = states.iter().map(|(i, _)| *i).collect();
+    let replaced = states
+        .into_iter()
+        .map(|(i, state)| (i, std::mem::replace(shape(&mut items[i]), state)))
+        .collect();
+    to.push(TransformEdit { states: replaced, count });
+    Some(indices)
+}
+
+/// Origin and size of a box dragged from `anchor` to `point` (see `update_annotation`).
+fn drag_box(anchor: CGPoint, point: CGPoint, constrain: bool, from_center: bool) -> (CGPoint, CGSize) {
+    let point = if constrain { square_from(anchor, point) } else { point };
+    let dx = point.x - anchor.x;
+    let dy = point.y - anchor.y;
+    if from_center {
+        (CGPoint::new(anchor.x - dx, anchor.y - dy), CGSize::new(dx * 2.0, dy * 2.0))
+    } else {
+        (anchor, CGSize::new(dx, dy))
+    }
+}
+
+/// Where a new callout's tail points: below the bubble, a quarter of the way in.
+/// The tail tip is a handle and can be dragged anywhere afterward.
+fn default_tail_tip(r: CGRect) -> CGPoint {
+    CGPoint::new(r.origin.x + r.size.width * 0.25, r.origin.y + r.size.height + 30.0)
+}
+
+/// Snap `point` so the segment from `start` lies on the nearest multiple of 45°.
+fn snap_to_45(start: CGPoint, point: CGPoint) -> CGPoint {
+    let dx = point.x - start.x;
+    let dy = point.y - start.y;
+    let len = (dx * dx + dy * dy).sqrt();
+    let step = std::f64::consts::FRAC_PI_4;
+    let angle = (dy.atan2(dx) / step).round() * step;
+    CGPoint::new(start.x + len * angle.cos(), start.y + len * angle.sin())
+}
+
+/// Move `point` so the box from `origin` to it is square, using the larger of |dx| and |dy|.
+fn square_from(origin: CGPoint, point: CGPoint) -> CGPoint {
+    let dx = point.x - origin.x;
+    let dy = point.y - origin.y;
+    let side = dx.abs().max(dy.abs());
+    CGPoint::new(origin.x + side.copysign(dx), origin.y + side.copysign(dy))
 }
 
 /// Normalize a rect that may have negative width/height.
@@ -321,8 +1017,38 @@ fn inflate_rect(rect: CGRect, amount: CGFloat) -> CGRect {
     )
 }
 
+/// The font for a text label: `font_name` at `font_size`, or the system font
+/// when no name is given or the font isn't installed, styled with `traits`
+/// (left regular when the family has no such variant).
+pub fn text_font(
+    font_name: Option<&str>,
+    font_size: CGFloat,
+    traits: NSFontDescriptorSymbolicTraits,
+) -> Retained<NSFont> {
+    use objc2_foundation::NSString;
+
+    let font = font_name
+        .and_then(|name| NSFont::fontWithName_size(&NSString::from_str(name), font_size))
+        .unwrap_or_else(|| NSFont::systemFontOfSize(font_size));
+    if traits.is_empty() {
+        return font;
+    }
+    let descriptor = font.fontDescriptor();
+    let styled = descriptor.fontDescriptorWithSymbolicTraits(descriptor.symbolicTraits() | traits);
+    NSFont::fontWithDescriptor_size(&styled, font_size).unwrap_or(font)
+}
+
 /// Measure text size using NSAttributedString for accurate bounding rect.
-fn measure_text_size(text: &str, font_size: CGFloat) -> CGSize {
+fn measure_text_size(text: &str, font: &NSFont) -> CGSize {
+    measure_text_in_font(text, font, 10000.0)
+}
+
+/// Measure text wrapped to `max_width` in the system font.
+pub fn measure_wrapped_text(text: &str, font_size: CGFloat, max_width: CGFloat) -> CGSize {
+    measure_text_in_font(text, &NSFont::systemFontOfSize(font_size), max_width)
+}
+
+fn measure_text_in_font(text: &str, font: &NSFont, max_width: CGFloat) -> CGSize {
     use objc2::msg_send;
     use objc2::runtime::AnyObject;
     use objc2_foundation::NSString;
@@ -332,8 +1058,6 @@ fn measure_text_size(text: &str, font_size: CGFloat) -> CGSize {
     }
 
     unsafe {
-        let font: *mut AnyObject =
-            msg_send![objc2::class!(NSFont), systemFontOfSize: font_size];
         let font_key = NSString::from_str("NSFont");
         let dict: *mut AnyObject = msg_send![
             objc2::class!(NSDictionary),
@@ -342,7 +1066,7 @@ fn measure_text_size(text: &str, font_size: CGFloat) -> CGSize {
         ];
 
         let ns_str = NSString::from_str(text);
-        let max_size = CGSize::new(10000.0, 10000.0);
+        let max_size = CGSize::new(max_width, 10000.0);
         // NSStringDrawingUsesLineFragmentOrigin (1) | NSStringDrawingUsesFontLeading (2)
         let options: usize = 3;
         let context: *mut AnyObject = std::ptr::null_mut();
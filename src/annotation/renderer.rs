@@ -1,44 +1,95 @@
 use std::ffi::CString;
 
+use objc2_app_kit::NSFont;
 use objc2_core_foundation::{CGAffineTransform, CGFloat, CGPoint, CGRect, CGSize};
-use objc2_core_graphics::{CGContext, CGImage};
+use objc2_core_graphics::{CGColor, CGContext, CGImage};
 
-use super::model::Annotation;
+use super::model::{
+    contrasting_color, text_font, Annotation, BlurStyle, SpotlightShape, StepShape, StrokeStyle,
+    CALLOUT_CORNER_RADIUS, CALLOUT_PADDING, HIGHLIGHTER_OPACITY, SHADOW_ALPHA, SHADOW_BLUR,
+    SHADOW_OFFSET,
+};
+
+/// Width of a text outline, as a percentage of the font size (NSStrokeWidth units).
+/// The stroke is centered on the glyph edges, so half of it shows around the letters.
+const TEXT_OUTLINE_WIDTH: CGFloat = 16.0;
 
 /// Draw an annotation onto a CGContext.
 /// `screenshot` is required for Blur annotations to sample underlying pixels.
 /// For non-Blur annotations, it is ignored.
 pub fn draw_annotation(ctx: &CGContext, ann: &Annotation, screenshot: Option<&CGImage>) {
+    if !ann.has_shadow() {
+        draw_shape(ctx, ann, screenshot);
+        return;
+    }
+    // One layer, so an arrow's head and shaft cast a single shadow
+    CGContext::save_g_state(Some(ctx));
+    let shadow_color = CGColor::new_srgb(0.0, 0.0, 0.0, SHADOW_ALPHA);
+    CGContext::set_shadow_with_color(Some(ctx), SHADOW_OFFSET, SHADOW_BLUR, Some(&shadow_color));
+    unsafe { CGContext::begin_transparency_layer(Some(ctx), None) };
+    draw_shape(ctx, ann, screenshot);
+    CGContext::end_transparency_layer(Some(ctx));
+    CGContext::restore_g_state(Some(ctx));
+}
+
+fn draw_shape(ctx: &CGContext, ann: &Annotation, screenshot: Option<&CGImage>) {
     match ann {
         Annotation::Arrow {
             start,
             end,
             color,
             width,
+            ..
         } => draw_arrow(ctx, *start, *end, *color, *width),
+        Annotation::Line {
+            start,
+            end,
+            color,
+            width,
+            style,
+            ..
+        } => draw_line(ctx, *start, *end, *color, *width, *style),
         Annotation::Rect {
             origin,
             size,
             color,
             width,
-        } => draw_rect(ctx, *origin, *size, *color, *width),
+            fill,
+            corner_radius,
+            style,
+            ..
+        } => draw_rect(ctx, *origin, *size, *color, *width, *fill, *corner_radius, *style),
         Annotation::Ellipse {
             origin,
             size,
             color,
             width,
-        } => draw_ellipse(ctx, *origin, *size, *color, *width),
+            fill,
+            style,
+            ..
+        } => draw_ellipse(ctx, *origin, *size, *color, *width, *fill, *style),
         Annotation::Pencil {
             points,
             color,
             width,
-        } => draw_pencil(ctx, points, *color, *width),
+            smooth,
+            highlighter,
+            ..
+        } => draw_pencil(ctx, points, *color, *width, *smooth, *highlighter),
         Annotation::Text {
             position,
             text,
             color,
             font_size,
-        } => draw_text(ctx, *position, text, *color, *font_size),
+            background,
+            outline,
+            font_name,
+            traits,
+        } => {
+            let background = background.map(|bg| (ann.bounding_rect(), bg));
+            let font = text_font(font_name.as_deref(), *font_size, *traits);
+            draw_text(ctx, *position, text, *color, &font, background, *outline)
+        }
         Annotation::Highlight {
             origin,
             size,
@@ -50,31 +101,100 @@ pub fn draw_annotation(ctx: &CGContext, ann: &Annotation, screenshot: Option<&CG
             number,
             color,
             radius,
-        } => draw_step(ctx, *center, *number, *color, *radius),
+            shape,
+        } => draw_step(ctx, *center, *number, *color, *radius, *shape),
         Annotation::Blur {
             origin,
             size,
             block_size,
-        } => draw_blur(ctx, *origin, *size, *block_size, screenshot),
+            style,
+        } => draw_blur(ctx, *origin, *size, *block_size, *style, screenshot),
+        Annotation::Redact { origin, size, color } => draw_redact(ctx, *origin, *size, *color),
+        Annotation::Callout {
+            rect,
+            tail_tip,
+            text,
+            color,
+            font_size,
+        } => draw_callout(ctx, *rect, *tail_tip, text, *color, *font_size),
+        Annotation::Spotlight {
+            origin,
+            size,
+            shape,
+            dim,
+        } => draw_spotlight(ctx, *origin, *size, *shape, *dim),
     }
 }
 
-fn draw_arrow(
+/// Draw an annotation at `opacity` (1.0 = as-is). The annotation is composited
+/// as one layer, so overlapping strokes within it don't show through each other.
+pub fn draw_annotation_with_opacity(
+    ctx: &CGContext,
+    ann: &Annotation,
+    screenshot: Option<&CGImage>,
+    opacity: CGFloat,
+) {
+    if opacity >= 1.0 {
+        draw_annotation(ctx, ann, screenshot);
+        return;
+    }
+    CGContext::save_g_state(Some(ctx));
+    CGContext::set_alpha(Some(ctx), opacity.max(0.0));
+    unsafe { CGContext::begin_transparency_layer(Some(ctx), None) };
+    draw_annotation(ctx, ann, screenshot);
+    CGContext::end_transparency_layer(Some(ctx));
+    CGContext::restore_g_state(Some(ctx));
+}
+
+/// Set the line dash pattern for a stroke style, scaled to the stroke width.
+/// Callers wrap this in save/restore_g_state so the dash resets to solid afterward.
+fn apply_stroke_style(ctx: &CGContext, style: StrokeStyle, width: CGFloat) {
+    let pattern: [CGFloat; 2] = match style {
+        StrokeStyle::Solid => return,
+        StrokeStyle::Dashed => [width * 3.0, width * 2.0],
+        // Zero-length dashes with round caps render as dots
+        StrokeStyle::Dotted => [0.0, width * 2.0],
+    };
+    if style == StrokeStyle::Dotted {
+        CGContext::set_line_cap(Some(ctx), objc2_core_graphics::CGLineCap::Round);
+    }
+    unsafe {
+        CGContext::set_line_dash(Some(ctx), 0.0, pattern.as_ptr(), pattern.len());
+    }
+}
+
+fn draw_line(
     ctx: &CGContext,
     start: CGPoint,
     end: CGPoint,
     color: (CGFloat, CGFloat, CGFloat),
     width: CGFloat,
+    style: StrokeStyle,
 ) {
     CGContext::save_g_state(Some(ctx));
     CGContext::set_rgb_stroke_color(Some(ctx), color.0, color.1, color.2, 1.0);
     CGContext::set_line_width(Some(ctx), width);
     CGContext::set_line_cap(Some(ctx), objc2_core_graphics::CGLineCap::Round);
+    apply_stroke_style(ctx, style, width);
 
     CGContext::move_to_point(Some(ctx), start.x, start.y);
     CGContext::add_line_to_point(Some(ctx), end.x, end.y);
     CGContext::stroke_path(Some(ctx));
 
+    CGContext::restore_g_state(Some(ctx));
+}
+
+fn draw_arrow(
+    ctx: &CGContext,
+    start: CGPoint,
+    end: CGPoint,
+    color: (CGFloat, CGFloat, CGFloat),
+    width: CGFloat,
+) {
+    draw_line(ctx, start, end, color, width, StrokeStyle::Solid);
+
+    CGContext::save_g_state(Some(ctx));
+
     // Arrowhead
     let dx = end.x - start.x;
     let dy = end.y - start.y;
@@ -104,32 +224,77 @@ fn draw_arrow(
     CGContext::restore_g_state(Some(ctx));
 }
 
+#[allow(clippy::too_many_arguments)]
 fn draw_rect(
     ctx: &CGContext,
     origin: CGPoint,
     size: CGSize,
     color: (CGFloat, CGFloat, CGFloat),
     width: CGFloat,
+    fill: bool,
+    corner_radius: CGFloat,
+    style: StrokeStyle,
 ) {
     let norm = normalize_rect(CGRect::new(origin, size));
     CGContext::save_g_state(Some(ctx));
+    CGContext::set_rgb_fill_color(Some(ctx), color.0, color.1, color.2, 1.0);
     CGContext::set_rgb_stroke_color(Some(ctx), color.0, color.1, color.2, 1.0);
     CGContext::set_line_width(Some(ctx), width);
-    CGContext::stroke_rect(Some(ctx), norm);
+    apply_stroke_style(ctx, style, width);
+    if corner_radius > 0.0 {
+        if fill {
+            add_rounded_rect_path(ctx, norm, corner_radius);
+            CGContext::fill_path(Some(ctx));
+        }
+        add_rounded_rect_path(ctx, norm, corner_radius);
+        CGContext::stroke_path(Some(ctx));
+    } else {
+        if fill {
+            CGContext::fill_rect(Some(ctx), norm);
+        }
+        CGContext::stroke_rect(Some(ctx), norm);
+    }
     CGContext::restore_g_state(Some(ctx));
 }
 
+/// Add a closed rounded-rectangle path to the context (radius clamped to half the shorter side).
+pub fn add_rounded_rect_path(ctx: &CGContext, rect: CGRect, radius: CGFloat) {
+    let min_x = rect.origin.x;
+    let min_y = rect.origin.y;
+    let max_x = min_x + rect.size.width;
+    let max_y = min_y + rect.size.height;
+    let r = radius.min(rect.size.width / 2.0).min(rect.size.height / 2.0);
+
+    CGContext::move_to_point(Some(ctx), min_x + r, min_y);
+    CGContext::add_line_to_point(Some(ctx), max_x - r, min_y);
+    CGContext::add_arc_to_point(Some(ctx), max_x, min_y, max_x, min_y + r, r);
+    CGContext::add_line_to_point(Some(ctx), max_x, max_y - r);
+    CGContext::add_arc_to_point(Some(ctx), max_x, max_y, max_x - r, max_y, r);
+    CGContext::add_line_to_point(Some(ctx), min_x + r, max_y);
+    CGContext::add_arc_to_point(Some(ctx), min_x, max_y, min_x, max_y - r, r);
+    CGContext::add_line_to_point(Some(ctx), min_x, min_y + r);
+    CGContext::add_arc_to_point(Some(ctx), min_x, min_y, min_x + r, min_y, r);
+    CGContext::close_path(Some(ctx));
+}
+
 fn draw_ellipse(
     ctx: &CGContext,
     origin: CGPoint,
     size: CGSize,
     color: (CGFloat, CGFloat, CGFloat),
     width: CGFloat,
+    fill: bool,
+    style: StrokeStyle,
 ) {
     let norm = normalize_rect(CGRect::new(origin, size));
     CGContext::save_g_state(Some(ctx));
+    if fill {
+        CGContext::set_rgb_fill_color(Some(ctx), color.0, color.1, color.2, 1.0);
+        CGContext::fill_ellipse_in_rect(Some(ctx), norm);
+    }
     CGContext::set_rgb_stroke_color(Some(ctx), color.0, color.1, color.2, 1.0);
     CGContext::set_line_width(Some(ctx), width);
+    apply_stroke_style(ctx, style, width);
     CGContext::stroke_ellipse_in_rect(Some(ctx), norm);
     CGContext::restore_g_state(Some(ctx));
 }
@@ -139,41 +304,154 @@ fn draw_pencil(
     points: &[CGPoint],
     color: (CGFloat, CGFloat, CGFloat),
     width: CGFloat,
+    smooth: bool,
+    highlighter: bool,
 ) {
     if points.len() < 2 {
         return;
     }
 
     CGContext::save_g_state(Some(ctx));
-    CGContext::set_rgb_stroke_color(Some(ctx), color.0, color.1, color.2, 1.0);
+    if highlighter {
+        // Like ink on paper: text underneath darkens instead of being covered
+        CGContext::set_blend_mode(Some(ctx), objc2_core_graphics::CGBlendMode::Multiply);
+        CGContext::set_rgb_stroke_color(Some(ctx), color.0, color.1, color.2, HIGHLIGHTER_OPACITY);
+    } else {
+        CGContext::set_rgb_stroke_color(Some(ctx), color.0, color.1, color.2, 1.0);
+    }
     CGContext::set_line_width(Some(ctx), width);
     CGContext::set_line_cap(Some(ctx), objc2_core_graphics::CGLineCap::Round);
     CGContext::set_line_join(Some(ctx), objc2_core_graphics::CGLineJoin::Round);
 
     CGContext::move_to_point(Some(ctx), points[0].x, points[0].y);
-    for p in &points[1..] {
-        CGContext::add_line_to_point(Some(ctx), p.x, p.y);
+    if smooth {
+        // Quadratic curves between segment midpoints, bent toward each raw point.
+        // The curve stays inside the points' hull, so the raw bounds still hold.
+        for pair in points[1..].windows(2) {
+            let mid = CGPoint::new((pair[0].x + pair[1].x) / 2.0, (pair[0].y + pair[1].y) / 2.0);
+            CGContext::add_quad_curve_to_point(Some(ctx), pair[0].x, pair[0].y, mid.x, mid.y);
+        }
+        let last = points[points.len() - 1];
+        CGContext::add_line_to_point(Some(ctx), last.x, last.y);
+    } else {
+        for p in &points[1..] {
+            CGContext::add_line_to_point(Some(ctx), p.x, p.y);
+        }
     }
     CGContext::stroke_path(Some(ctx));
     CGContext::restore_g_state(Some(ctx));
 }
 
+/// Draw a text label. `background` is the box to fill behind it and its RGBA color;
+/// `outline` strokes the letters in a contrasting color before filling them.
 fn draw_text(
     ctx: &CGContext,
     position: CGPoint,
     text: &str,
     color: (CGFloat, CGFloat, CGFloat),
+    font: &NSFont,
+    background: Option<(CGRect, (CGFloat, CGFloat, CGFloat, CGFloat))>,
+    outline: bool,
+) {
+    use objc2::msg_send;
+    use objc2_foundation::NSString;
+
+    CGContext::save_g_state(Some(ctx));
+    if let Some((rect, (r, g, b, a))) = background {
+        CGContext::set_rgb_fill_color(Some(ctx), r, g, b, a);
+        add_rounded_rect_path(ctx, rect, super::model::TEXT_BACKGROUND_PADDING);
+        CGContext::fill_path(Some(ctx));
+    }
+    with_ns_graphics_context(ctx, || unsafe {
+        let dict = text_attributes(font, color);
+        let outline_dict = outline.then(|| outline_attributes(font, contrasting_color(color)));
+        let line_height = font.pointSize() * 1.3;
+        for (i, line) in text.split('\n').enumerate() {
+            let ns_line = NSString::from_str(line);
+            let point = CGPoint::new(
+                position.x,
+                position.y + (i as CGFloat) * line_height,
+            );
+            if let Some(outline_dict) = outline_dict {
+                let _: () = msg_send![
+                    &*ns_line,
+                    drawAtPoint: point,
+                    withAttributes: outline_dict
+                ];
+            }
+            let _: () = msg_send![
+                &*ns_line,
+                drawAtPoint: point,
+                withAttributes: dict
+            ];
+        }
+    });
+    CGContext::restore_g_state(Some(ctx));
+}
+
+fn draw_callout(
+    ctx: &CGContext,
+    rect: CGRect,
+    tail_tip: CGPoint,
+    text: &str,
+    color: (CGFloat, CGFloat, CGFloat),
     font_size: CGFloat,
 ) {
     use objc2::msg_send;
-    use objc2::runtime::AnyObject;
     use objc2_foundation::NSString;
 
+    let norm = normalize_rect(rect);
     CGContext::save_g_state(Some(ctx));
+    CGContext::set_rgb_fill_color(Some(ctx), color.0, color.1, color.2, 1.0);
+
+    // Tail: a wedge from the bubble's center to the tip; the bubble covers its base
+    let center = CGPoint::new(
+        norm.origin.x + norm.size.width / 2.0,
+        norm.origin.y + norm.size.height / 2.0,
+    );
+    let (dx, dy) = (tail_tip.x - center.x, tail_tip.y - center.y);
+    let len = (dx * dx + dy * dy).sqrt();
+    if len > 1.0 {
+        let half = (norm.size.width.min(norm.size.height) * 0.2).clamp(4.0, 14.0);
+        let (px, py) = (-dy / len * half, dx / len * half);
+        CGContext::begin_path(Some(ctx));
+        CGContext::move_to_point(Some(ctx), center.x + px, center.y + py);
+        CGContext::add_line_to_point(Some(ctx), tail_tip.x, tail_tip.y);
+        CGContext::add_line_to_point(Some(ctx), center.x - px, center.y - py);
+        CGContext::close_path(Some(ctx));
+        CGContext::fill_path(Some(ctx));
+    }
+
+    CGContext::begin_path(Some(ctx));
+    add_rounded_rect_path(ctx, norm, CALLOUT_CORNER_RADIUS);
+    CGContext::fill_path(Some(ctx));
+
+    if !text.is_empty() {
+        let text_rect = CGRect::new(
+            CGPoint::new(norm.origin.x + CALLOUT_PADDING, norm.origin.y + CALLOUT_PADDING),
+            CGSize::new(
+                (norm.size.width - CALLOUT_PADDING * 2.0).max(1.0),
+                (norm.size.height - CALLOUT_PADDING * 2.0).max(1.0),
+            ),
+        );
+        with_ns_graphics_context(ctx, || unsafe {
+            let font = NSFont::systemFontOfSize(font_size);
+            let dict = text_attributes(&font, contrasting_color(color));
+            let ns_text = NSString::from_str(text);
+            // drawInRect wraps at word boundaries
+            let _: () = msg_send![&*ns_text, drawInRect: text_rect, withAttributes: dict];
+        });
+    }
+    CGContext::restore_g_state(Some(ctx));
+}
+
+/// Run `draw` with `ctx` wrapped in an NSGraphicsContext so NSString drawing works
+/// (needed for export paths that don't have a current NSGraphicsContext).
+fn with_ns_graphics_context(ctx: &CGContext, draw: impl FnOnce()) {
+    use objc2::msg_send;
+    use objc2::runtime::AnyObject;
 
     unsafe {
-        // Wrap the CGContext in an NSGraphicsContext so NSString drawing works
-        // (needed for export paths that don't have a current NSGraphicsContext)
         let prev_ctx: *mut AnyObject =
             msg_send![objc2::class!(NSGraphicsContext), currentContext];
         let ns_ctx: *mut AnyObject = msg_send![
@@ -186,9 +464,26 @@ fn draw_text(
             setCurrentContext: ns_ctx
         ];
 
-        // Create font and color
-        let font: *mut AnyObject =
-            msg_send![objc2::class!(NSFont), systemFontOfSize: font_size];
+        draw();
+
+        // Restore previous NSGraphicsContext
+        let _: () = msg_send![
+            objc2::class!(NSGraphicsContext),
+            setCurrentContext: prev_ctx
+        ];
+    }
+}
+
+/// Build an autoreleased NSString attributes dictionary with a font and a color.
+unsafe fn text_attributes(
+    font: &NSFont,
+    color: (CGFloat, CGFloat, CGFloat),
+) -> *mut objc2::runtime::AnyObject {
+    use objc2::msg_send;
+    use objc2::runtime::AnyObject;
+    use objc2_foundation::NSString;
+
+    unsafe {
         let ns_color: *mut AnyObject = msg_send![
             objc2::class!(NSColor),
             colorWithRed: color.0,
@@ -197,41 +492,62 @@ fn draw_text(
             alpha: 1.0 as CGFloat
         ];
 
-        // Build attributes dictionary
         let font_key = NSString::from_str("NSFont");
         let color_key = NSString::from_str("NSColor");
         let keys: [*const AnyObject; 2] =
             [&*font_key as *const _ as *const _, &*color_key as *const _ as *const _];
-        let vals: [*const AnyObject; 2] = [font as *const _, ns_color as *const _];
-        let dict: *mut AnyObject = msg_send![
+        let vals: [*const AnyObject; 2] =
+            [font as *const NSFont as *const _, ns_color as *const _];
+        msg_send![
             objc2::class!(NSDictionary),
             dictionaryWithObjects: vals.as_ptr(),
             forKeys: keys.as_ptr(),
             count: 2usize
-        ];
+        ]
+    }
+}
 
-        let line_height = font_size * 1.3;
-        for (i, line) in text.split('\n').enumerate() {
-            let ns_line = NSString::from_str(line);
-            let point = CGPoint::new(
-                position.x,
-                position.y + (i as CGFloat) * line_height,
-            );
-            let _: () = msg_send![
-                &*ns_line,
-                drawAtPoint: point,
-                withAttributes: dict
-            ];
-        }
+/// Attributes that stroke the glyph edges in `color` without filling them.
+unsafe fn outline_attributes(
+    font: &NSFont,
+    color: (CGFloat, CGFloat, CGFloat),
+) -> *mut objc2::runtime::AnyObject {
+    use objc2::msg_send;
+    use objc2::runtime::AnyObject;
+    use objc2_foundation::NSString;
 
-        // Restore previous NSGraphicsContext
-        let _: () = msg_send![
-            objc2::class!(NSGraphicsContext),
-            setCurrentContext: prev_ctx
+    unsafe {
+        let ns_color: *mut AnyObject = msg_send![
+            objc2::class!(NSColor),
+            colorWithRed: color.0,
+            green: color.1,
+            blue: color.2,
+            alpha: 1.0 as CGFloat
         ];
-    }
+        // A positive stroke width draws the outline only
+        let width: *mut AnyObject =
+            msg_send![objc2::class!(NSNumber), numberWithDouble: TEXT_OUTLINE_WIDTH];
 
-    CGContext::restore_g_state(Some(ctx));
+        let font_key = NSString::from_str("NSFont");
+        let color_key = NSString::from_str("NSStrokeColor");
+        let width_key = NSString::from_str("NSStrokeWidth");
+        let keys: [*const AnyObject; 3] = [
+            &*font_key as *const _ as *const _,
+            &*color_key as *const _ as *const _,
+            &*width_key as *const _ as *const _,
+        ];
+        let vals: [*const AnyObject; 3] = [
+            font as *const NSFont as *const _,
+            ns_color as *const _,
+            width as *const _,
+        ];
+        msg_send![
+            objc2::class!(NSDictionary),
+            dictionaryWithObjects: vals.as_ptr(),
+            forKeys: keys.as_ptr(),
+            count: 3usize
+        ]
+    }
 }
 
 fn draw_step(
@@ -240,19 +556,37 @@ fn draw_step(
     number: u32,
     color: (CGFloat, CGFloat, CGFloat),
     radius: CGFloat,
+    shape: StepShape,
 ) {
     CGContext::save_g_state(Some(ctx));
 
-    // Fill circle
-    let circle_rect = CGRect::new(
+    // Fill the marker (all shapes share the circle's bounding box)
+    let marker_rect = CGRect::new(
         CGPoint::new(center.x - radius, center.y - radius),
         CGSize::new(radius * 2.0, radius * 2.0),
     );
     CGContext::set_rgb_fill_color(Some(ctx), color.0, color.1, color.2, 1.0);
-    CGContext::fill_ellipse_in_rect(Some(ctx), circle_rect);
+    match shape {
+        StepShape::Circle => CGContext::fill_ellipse_in_rect(Some(ctx), marker_rect),
+        StepShape::Square => {
+            CGContext::begin_path(Some(ctx));
+            add_rounded_rect_path(ctx, marker_rect, radius * 0.25);
+            CGContext::fill_path(Some(ctx));
+        }
+        StepShape::Diamond => {
+            CGContext::begin_path(Some(ctx));
+            CGContext::move_to_point(Some(ctx), center.x, center.y - radius);
+            CGContext::add_line_to_point(Some(ctx), center.x + radius, center.y);
+            CGContext::add_line_to_point(Some(ctx), center.x, center.y + radius);
+            CGContext::add_line_to_point(Some(ctx), center.x - radius, center.y);
+            CGContext::close_path(Some(ctx));
+            CGContext::fill_path(Some(ctx));
+        }
+    }
 
-    // Draw white number centered in circle
-    CGContext::set_rgb_fill_color(Some(ctx), 1.0, 1.0, 1.0, 1.0);
+    // Draw the number centered, white unless the marker color is too light for it
+    let text_color = contrasting_color(color);
+    CGContext::set_rgb_fill_color(Some(ctx), text_color.0, text_color.1, text_color.2, 1.0);
     let num_str = format!("{}", number);
     let font_size = if number >= 10 { radius * 0.9 } else { radius * 1.2 };
 
@@ -310,11 +644,40 @@ fn draw_highlight(
     CGContext::restore_g_state(Some(ctx));
 }
 
+/// Solid, fully opaque fill — unlike blur, nothing underneath can be recovered.
+fn draw_redact(ctx: &CGContext, origin: CGPoint, size: CGSize, color: (CGFloat, CGFloat, CGFloat)) {
+    let norm = normalize_rect(CGRect::new(origin, size));
+    CGContext::save_g_state(Some(ctx));
+    CGContext::set_rgb_fill_color(Some(ctx), color.0, color.1, color.2, 1.0);
+    CGContext::fill_rect(Some(ctx), norm);
+    CGContext::restore_g_state(Some(ctx));
+}
+
+/// Darken the whole drawable area except the spotlight region.
+/// The clip bounding box is the image (or selection) being drawn, so this works
+/// the same in the views and in `crop_and_composite`/`composite_frame`.
+fn draw_spotlight(ctx: &CGContext, origin: CGPoint, size: CGSize, shape: SpotlightShape, dim: CGFloat) {
+    let norm = normalize_rect(CGRect::new(origin, size));
+    let bounds = CGContext::clip_bounding_box(Some(ctx));
+    CGContext::save_g_state(Some(ctx));
+    CGContext::set_rgb_fill_color(Some(ctx), 0.0, 0.0, 0.0, dim);
+    // Even-odd fill punches the spotlight out of the dimmed bounds
+    CGContext::begin_path(Some(ctx));
+    CGContext::add_rect(Some(ctx), bounds);
+    match shape {
+        SpotlightShape::Rect => CGContext::add_rect(Some(ctx), norm),
+        SpotlightShape::Ellipse => CGContext::add_ellipse_in_rect(Some(ctx), norm),
+    }
+    CGContext::eo_fill_path(Some(ctx));
+    CGContext::restore_g_state(Some(ctx));
+}
+
 fn draw_blur(
     ctx: &CGContext,
     origin: CGPoint,
     size: CGSize,
     block_size: usize,
+    style: BlurStyle,
     screenshot: Option<&CGImage>,
 ) {
     let norm = normalize_rect(CGRect::new(origin, size));
@@ -371,11 +734,28 @@ fn draw_blur(
         return;
     }
 
-    let rgba = match crate::actions::cgimage_to_rgba(&cropped) {
+    let mut rgba = match crate::actions::cgimage_to_rgba(&cropped) {
         Ok(data) => data,
         Err(_) => return,
     };
 
+    if style == BlurStyle::Gaussian {
+        // Three box-blur passes approximate a Gaussian with sigma ~ block_size
+        for _ in 0..3 {
+            box_blur_rgba(&mut rgba, crop_w, crop_h, block_size.max(1));
+        }
+        let Some(blurred) = crate::stitch::rgba_to_cgimage(&mut rgba, crop_w, crop_h) else {
+            return;
+        };
+        // Annotation coords are top-down; flip locally so the image isn't drawn upside-down
+        CGContext::save_g_state(Some(ctx));
+        CGContext::translate_ctm(Some(ctx), norm.origin.x, norm.origin.y + norm.size.height);
+        CGContext::scale_ctm(Some(ctx), 1.0, -1.0);
+        CGContext::draw_image(Some(ctx), CGRect::new(CGPoint::ZERO, norm.size), Some(&blurred));
+        CGContext::restore_g_state(Some(ctx));
+        return;
+    }
+
     // Draw pixelated blocks in view coordinates
     CGContext::save_g_state(Some(ctx));
     let bs = block_size.max(2);
@@ -427,6 +807,34 @@ fn draw_blur(
     CGContext::restore_g_state(Some(ctx));
 }
 
+/// One horizontal + vertical box-blur pass over an RGBA buffer, clamping at the edges.
+fn box_blur_rgba(rgba: &mut [u8], width: usize, height: usize, radius: usize) {
+    let mut scratch = vec![0u8; rgba.len()];
+    blur_lines(rgba, &mut scratch, height, width, 4, width * 4, radius);
+    blur_lines(&scratch, rgba, width, height, width * 4, 4, radius);
+}
+
+/// Blur `lines` runs of `len` pixels each. `step` is the byte distance between
+/// neighbouring pixels in a run, `stride` the distance between runs.
+fn blur_lines(src: &[u8], dst: &mut [u8], lines: usize, len: usize, step: usize, stride: usize, radius: usize) {
+    let window = (radius * 2 + 1) as u32;
+    for line in 0..lines {
+        let base = line * stride;
+        let at = |i: isize| base + (i.clamp(0, len as isize - 1) as usize) * step;
+        for c in 0..4 {
+            // Running sum over [i - radius, i + radius]
+            let mut sum: u32 = (-(radius as isize)..=radius as isize)
+                .map(|i| src[at(i) + c] as u32)
+                .sum();
+            for i in 0..len as isize {
+                dst[base + i as usize * step + c] = (sum / window) as u8;
+                sum += src[at(i + radius as isize + 1) + c] as u32;
+                sum -= src[at(i - radius as isize) + c] as u32;
+            }
+        }
+    }
+}
+
 fn apply_transform(t: CGAffineTransform, p: CGPoint) -> CGPoint {
     CGPoint::new(
         t.a * p.x + t.c * p.y + t.tx,
@@ -122,38 +122,50 @@ impl AnnotationTextView {
         let text: Retained<NSString> = unsafe { msg_send![self, string] };
         let font: *mut AnyObject = unsafe { msg_send![self, font] };
 
-        let measured = measure_text_size(&text.to_string(), font);
-
-        let new_width = (measured.width + PADDING * 2.0).max(MIN_WIDTH + PADDING * 2.0);
-        let new_height = measured.height.max(18.0) + PADDING * 2.0;
+        let size = container_size(&text.to_string(), font);
 
         // Resize text view
         self.setFrame(CGRect::new(
             CGPoint::new(PADDING, PADDING),
-            CGSize::new(new_width - PADDING * 2.0, new_height - PADDING * 2.0),
+            CGSize::new(size.width - PADDING * 2.0, size.height - PADDING * 2.0),
         ));
 
         // Resize container (superview)
         unsafe {
             if let Some(container) = self.superview() {
-                let origin = container.frame().origin;
-                container.setFrame(CGRect::new(
-                    origin,
-                    CGSize::new(new_width, new_height),
-                ));
+                let old_frame = container.frame();
+                container.setFrame(CGRect::new(old_frame.origin, size));
                 container.setNeedsDisplay(true);
+                // Erase the old dotted border when the field shrinks (e.g. after a
+                // deleted line)
+                if let Some(parent_view) = container.superview() {
+                    parent_view.setNeedsDisplayInRect(old_frame);
+                }
             }
         }
     }
 }
 
+/// Size of the dotted container that fits `text`, padding included.
+fn container_size(text: &str, font: *mut AnyObject) -> CGSize {
+    let measured = measure_text_size(text, font);
+    CGSize::new(
+        measured.width.max(MIN_WIDTH) + PADDING * 2.0,
+        measured.height + PADDING * 2.0,
+    )
+}
+
 fn measure_text_size(text: &str, font: *mut AnyObject) -> CGSize {
-    if text.is_empty() {
-        return CGSize::new(MIN_WIDTH, 18.0);
-    }
+    // An empty text, or the empty line after a Shift+Enter, has no height of its
+    // own; measure a placeholder so the field is always tall enough for the caret
+    let text = if text.is_empty() || text.ends_with('\n') {
+        format!("{}X", text)
+    } else {
+        text.to_string()
+    };
 
     unsafe {
-        let ns_str = NSString::from_str(text);
+        let ns_str = NSString::from_str(&text);
         let font_attr_key = NSString::from_str("NSFont");
         let dict: *mut AnyObject = msg_send![
             objc2::class!(NSDictionary),
@@ -187,23 +199,12 @@ pub fn create_text_input(
     mtm: MainThreadMarker,
     position: CGPoint,
     initial_text: &str,
-    font_size: CGFloat,
+    font: &NSFont,
     color: (CGFloat, CGFloat, CGFloat),
 ) -> (Retained<NSView>, Retained<NSView>) {
-    let font = NSFont::systemFontOfSize(font_size);
-    let font_ptr: *mut AnyObject = unsafe { std::mem::transmute_copy(&font) };
-
-    let initial_size = if initial_text.is_empty() {
-        CGSize::new(MIN_WIDTH + PADDING * 2.0, font_size * 1.5 + PADDING * 2.0)
-    } else {
-        let m = measure_text_size(initial_text, font_ptr);
-        CGSize::new(
-            (m.width + PADDING * 2.0).max(MIN_WIDTH + PADDING * 2.0),
-            (m.height + PADDING * 2.0).max(font_size * 1.5 + PADDING * 2.0),
-        )
-    };
+    let font_ptr = font as *const NSFont as *mut AnyObject;
 
-    let container_frame = CGRect::new(position, initial_size);
+    let container_frame = CGRect::new(position, container_size(initial_text, font_ptr));
 
     // Create container view (dotted border)
     let container_alloc = mtm.alloc().set_ivars(TextContainerViewIvars);
@@ -227,7 +228,7 @@ pub fn create_text_input(
     // Configure NSTextView properties
     let _: () = unsafe { msg_send![&*text_view, setDrawsBackground: false] };
     let _: () = unsafe { msg_send![&*text_view, setRichText: false] };
-    let _: () = unsafe { msg_send![&*text_view, setFont: &*font] };
+    let _: () = unsafe { msg_send![&*text_view, setFont: font] };
     let _: () = unsafe { msg_send![&*text_view, setAllowsUndo: true] };
 
     // Set text color
@@ -1,26 +1,81 @@
 use std::cell::{Cell, RefCell};
-use std::path::PathBuf;
+use std::panic::AssertUnwindSafe;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
 
 use objc2::rc::Retained;
-use objc2::runtime::AnyObject;
+use objc2::runtime::{AnyObject, Bool, ProtocolObject};
 use objc2::{define_class, msg_send, sel, DefinedClass, MainThreadOnly};
-use objc2_app_kit::{NSApplication, NSApplicationDelegate, NSColorPanel, NSModalResponseOK, NSSavePanel};
-use objc2_core_foundation::{CFRetained, CGFloat, CGPoint};
-use objc2_core_graphics::{CGDisplayBounds, CGImage};
+use objc2_app_kit::{
+    NSApplication, NSApplicationDelegate, NSBeep, NSColorPanel, NSDragOperation, NSDraggingInfo,
+    NSAlert, NSAlertFirstButtonReturn, NSButton, NSControlStateValueOff, NSControlStateValueOn,
+    NSModalResponseOK, NSOpenPanel, NSPasteboardTypeFileURL, NSPopUpButton, NSSavePanel,
+    NSSecureTextField, NSSlider, NSTextField, NSView,
+};
+use objc2_core_foundation::{CFRetained, CGFloat, CGPoint, CGRect, CGSize};
+use objc2_core_graphics::{CGDirectDisplayID, CGDisplayBounds, CGImage, kCGOverlayWindowLevel};
+use objc2_foundation::{
+    MainThreadMarker, NSNotification, NSObject, NSObjectProtocol, NSString, NSTimer, NSURL,
+};
+// Deprecated, but it is what crate::notify posts banners through
+#[allow(deprecated)]
 use objc2_foundation::{
-    MainThreadMarker, NSNotification, NSObject, NSObjectProtocol, NSString, NSTimer,
+    NSUserNotification, NSUserNotificationCenter, NSUserNotificationCenterDelegate,
 };
 
+use crate::annotation::model::{BlurStyle, StepShape, StrokeStyle};
 use crate::border::RecordingBorder;
+use crate::editor::export::VideoFormat;
+use crate::editor::model::{
+    BackgroundStyle, ImageAdjustment, ImageTransform, WindowFrame, BACKGROUND_FILLS,
+};
+use crate::editor::view::CropAspect;
 use crate::editor::window::EditorWindow;
-use crate::hotkey::HotkeyManager;
+use crate::hotkey::{HotkeyAction, HotkeyManager};
 use crate::overlay::view::ActiveTool;
 use crate::overlay::OverlayWindow;
+use crate::palette::CommandPalette;
+use crate::prefs::{
+    FrozenMargin, ImageFormat, PostCaptureAction, RecordingLimit, ScrollSettleDelay,
+    ScrollStepLimit, ScrollStepSize, SnapGrid, TimelineUnit, VideoCodec, VideoQuality,
+};
+use crate::history::HistoryKind;
+use crate::pin::PinnedWindow;
 use crate::recording::RecordingState;
-use crate::scroll_capture::ScrollCaptureState;
+use crate::scroll_capture::{ScrollCaptureState, ScrollDirection};
+use crate::shortcuts::ShortcutsWindow;
 use crate::statusbar::StatusBar;
 use crate::toolbar::ToolbarWindow;
 
+/// Stands in for a display ID when the overlay spans every display (no real display uses 0).
+const ALL_DISPLAYS: CGDirectDisplayID = 0;
+
+/// Stitched scroll capture, or why it couldn't be made.
+type StitchResult = Result<CFRetained<CGImage>, String>;
+
+/// What a background Vision request reads off a capture.
+#[derive(Clone, Copy)]
+enum VisionRead {
+    Text,
+    Codes,
+}
+
+/// What a background Vision request read off a capture.
+enum VisionResult {
+    /// Copy Text: the recognized lines.
+    Text(Result<String, String>),
+    /// Decode Barcode: the payloads of the codes found.
+    Codes(Result<Vec<String>, String>),
+}
+
+/// Copy Text or Decode Barcode running in the background.
+struct VisionRequest {
+    result: mpsc::Receiver<VisionResult>,
+    /// The overlay's capture when the request came from the overlay, to close
+    /// that selection (and no later one) once something is copied.
+    capture: Option<CFRetained<CGImage>>,
+}
+
 pub struct AppDelegateIvars {
     status_bar: RefCell<Option<StatusBar>>,
     hotkey_manager: RefCell<Option<HotkeyManager>>,
@@ -28,6 +83,8 @@ pub struct AppDelegateIvars {
     toolbar: RefCell<Option<ToolbarWindow>>,
     /// The full-screen CGImage from the last capture (for cropping)
     captured_image: RefCell<Option<CFRetained<CGImage>>>,
+    /// Display the overlay was last shown on (key for the remembered selection)
+    capture_display: Cell<CGDirectDisplayID>,
     /// True when the overlay is being used for recording region selection
     recording_mode: Cell<bool>,
     /// Active recording state (encoder + timer)
@@ -38,10 +95,32 @@ pub struct AppDelegateIvars {
     editor_window: RefCell<Option<EditorWindow>>,
     /// True when the overlay is being used for scroll capture region selection
     scroll_capture_mode: Cell<bool>,
+    /// Direction to scroll for the pending scroll capture
+    scroll_capture_direction: Cell<ScrollDirection>,
+    /// True when the pending scroll capture is manual (the user scrolls)
+    scroll_capture_manual: Cell<bool>,
     /// Active scroll capture state (frames + timer)
     scroll_capture_state: RefCell<Option<ScrollCaptureState>>,
+    /// Result of the stitch running in the background after a scroll capture
+    scroll_stitch_result: RefCell<Option<mpsc::Receiver<StitchResult>>>,
+    /// Vision request running in the background for Copy Text or Decode Barcode
+    vision_request: RefCell<Option<VisionRequest>>,
+    /// Once-a-second timer counting down to a delayed screenshot
+    capture_countdown: RefCell<Option<Retained<NSTimer>>>,
+    /// Seconds left before the delayed screenshot
+    capture_countdown_left: Cell<u64>,
     /// True when the editor is being closed via cancel (discard without saving)
     editor_cancelled: Cell<bool>,
+    /// First frame of the recording in the editor, previewing it in the history once saved.
+    recording_thumbnail: RefCell<Option<CFRetained<CGImage>>>,
+    /// Tool to return to after the eyedropper samples a color.
+    tool_before_eyedropper: Cell<ActiveTool>,
+    /// Cmd+K command palette, shared by the overlay and the editor.
+    command_palette: RefCell<Option<CommandPalette>>,
+    /// Keyboard Shortcuts window (created on first open)
+    shortcuts_window: RefCell<Option<ShortcutsWindow>>,
+    /// Screenshots pinned to the screen, oldest first
+    pinned_windows: RefCell<Vec<PinnedWindow>>,
 }
 
 define_class!(
@@ -53,6 +132,31 @@ define_class!(
 
     unsafe impl NSObjectProtocol for AppDelegate {}
 
+    // Delegate of Notification Center, so clicking a "Saved" banner reveals the file
+    #[allow(deprecated)]
+    unsafe impl NSUserNotificationCenterDelegate for AppDelegate {
+        #[unsafe(method(userNotificationCenter:didActivateNotification:))]
+        fn user_notification_center_did_activate_notification(
+            &self,
+            _center: &NSUserNotificationCenter,
+            notification: &NSUserNotification,
+        ) {
+            if let Some(path) = crate::notify::saved_path(notification) {
+                crate::actions::reveal_in_finder(&path);
+            }
+        }
+
+        // Banners would otherwise be hidden while a save panel has made the app active
+        #[unsafe(method(userNotificationCenter:shouldPresentNotification:))]
+        fn user_notification_center_should_present_notification(
+            &self,
+            _center: &NSUserNotificationCenter,
+            _notification: &NSUserNotification,
+        ) -> bool {
+            true
+        }
+    }
+
     unsafe impl NSApplicationDelegate for AppDelegate {
         #[unsafe(method(applicationShouldTerminateAfterLastWindowClosed:))]
         fn application_should_terminate_after_last_window_closed(
@@ -67,20 +171,42 @@ define_class!(
             let mtm = MainThreadMarker::from(self);
 
             let status_bar = StatusBar::new(mtm);
+            status_bar.accept_file_drops(mtm);
             *self.ivars().status_bar.borrow_mut() = Some(status_bar);
+            #[allow(deprecated)]
+            unsafe {
+                NSUserNotificationCenter::defaultUserNotificationCenter()
+                    .setDelegate(Some(ProtocolObject::from_ref(self)));
+            }
 
             let hotkey_manager = HotkeyManager::new();
+            if let Some(sb) = self.ivars().status_bar.borrow().as_ref() {
+                for action in HotkeyAction::ALL {
+                    let hotkey = hotkey_manager.hotkey(action);
+                    sb.set_hotkey(action, &hotkey, hotkey_manager.is_enabled(action));
+                }
+            }
             *self.ivars().hotkey_manager.borrow_mut() = Some(hotkey_manager);
+            self.refresh_recent_captures();
 
             let overlay = OverlayWindow::new(mtm);
+            overlay.view.ivars().selection_aspect.set(crate::prefs::selection_aspect());
+            overlay.view.ivars().show_crosshair.set(crate::prefs::crosshair_guides());
+            overlay.view.ivars().snap_grid.set(crate::prefs::snap_grid().spacing());
+            overlay.view.ivars().keep_inside_selection.set(crate::prefs::keep_inside_selection());
             *self.ivars().overlay.borrow_mut() = Some(overlay);
 
             let toolbar = ToolbarWindow::new(mtm);
+            toolbar.set_vertical(crate::prefs::vertical_toolbar());
             *self.ivars().toolbar.borrow_mut() = Some(toolbar);
+            self.restore_annotation_settings(false);
 
             let border = RecordingBorder::new(mtm);
             *self.ivars().recording_border.borrow_mut() = Some(border);
 
+            let palette = CommandPalette::new(mtm);
+            *self.ivars().command_palette.borrow_mut() = Some(palette);
+
             // Set up a timer to poll for global hotkey events (100ms interval)
             let target: &AnyObject = unsafe { &*(self as *const Self as *const AnyObject) };
             unsafe {
@@ -93,7 +219,7 @@ define_class!(
                 );
             }
 
-            eprintln!("ClipShot started. Ctrl+Cmd+A=capture, Ctrl+Cmd+Z=record, Ctrl+Cmd+S=scroll capture.");
+            eprintln!("ClipShot started.");
         }
     }
 
@@ -104,34 +230,30 @@ define_class!(
             use global_hotkey::GlobalHotKeyEvent;
             if let Ok(event) = GlobalHotKeyEvent::receiver().try_recv() {
                 if event.state() == global_hotkey::HotKeyState::Pressed {
-                    let hk = self.ivars().hotkey_manager.borrow();
-                    let hk = hk.as_ref().unwrap();
-
-                    if event.id() == hk.capture_hotkey_id {
-                        // Don't allow screenshot while recording, editing, or scroll capturing
-                        if self.ivars().recording_state.borrow().is_some() {
-                            eprintln!("Cannot capture screenshot while recording");
-                            return;
-                        }
-                        if self.ivars().editor_window.borrow().is_some() {
-                            eprintln!("Cannot capture screenshot while editing");
-                            return;
-                        }
-                        if self.ivars().scroll_capture_state.borrow().is_some() {
-                            eprintln!("Cannot capture screenshot while scroll capturing");
+                    let action = self
+                        .ivars()
+                        .hotkey_manager
+                        .borrow()
+                        .as_ref()
+                        .and_then(|hk| hk.action_for(event.id()));
+
+                    if action == Some(HotkeyAction::Capture) {
+                        if !self.can_capture_screenshot() {
                             return;
                         }
                         eprintln!("Capture hotkey pressed");
                         self.do_capture();
-                    } else if event.id() == hk.record_hotkey_id {
+                    } else if action == Some(HotkeyAction::Record) {
                         self.handle_record_hotkey();
-                    } else if event.id() == hk.scroll_capture_hotkey_id {
+                    } else if action == Some(HotkeyAction::ScrollCapture) {
                         // If already capturing, stop and stitch
                         if self.ivars().scroll_capture_state.borrow().is_some() {
                             self.stop_scroll_capture();
                         } else {
-                            self.handle_scroll_capture_hotkey();
+                            self.handle_scroll_capture_hotkey(ScrollDirection::Down, false);
                         }
+                    } else if action == Some(HotkeyAction::AnnotateClipboard) {
+                        self.annotate_clipboard_image();
                     }
                 }
             }
@@ -142,22 +264,83 @@ define_class!(
     impl AppDelegate {
         #[unsafe(method(captureScreenshot:))]
         fn capture_screenshot(&self, _sender: &AnyObject) {
-            if self.ivars().recording_state.borrow().is_some() {
-                eprintln!("Cannot capture screenshot while recording");
+            if !self.can_capture_screenshot() {
                 return;
             }
-            if self.ivars().editor_window.borrow().is_some() {
-                eprintln!("Cannot capture screenshot while editing");
+            eprintln!("Capture triggered from menu!");
+            self.do_capture();
+        }
+
+        #[unsafe(method(captureWindow:))]
+        fn capture_window(&self, _sender: &AnyObject) {
+            if !self.can_capture_screenshot() {
                 return;
             }
-            if self.ivars().scroll_capture_state.borrow().is_some() {
-                eprintln!("Cannot capture screenshot while scroll capturing");
+            eprintln!("Window capture triggered from menu — click a window");
+            self.do_capture();
+            self.start_window_pick();
+        }
+
+        #[unsafe(method(captureAllDisplays:))]
+        fn capture_all_displays(&self, _sender: &AnyObject) {
+            if !self.can_capture_screenshot() {
                 return;
             }
-            eprintln!("Capture triggered from menu!");
+            eprintln!("All-displays capture triggered from menu!");
+            self.do_capture_all_displays();
+        }
+
+        #[unsafe(method(captureAfterDelay:))]
+        fn capture_after_delay(&self, sender: &AnyObject) {
+            let tag: isize = unsafe { msg_send![sender, tag] };
+            let Some(&seconds) = crate::statusbar::CAPTURE_DELAY_SECONDS.get(tag as usize) else {
+                return;
+            };
+            if !self.can_capture_screenshot() {
+                return;
+            }
+            self.ivars().capture_countdown_left.set(seconds);
+            if let Some(sb) = self.ivars().status_bar.borrow().as_ref() {
+                sb.set_capture_countdown(MainThreadMarker::from(self), Some(seconds));
+            }
+            let target: &AnyObject = unsafe { &*(self as *const Self as *const AnyObject) };
+            let timer = unsafe {
+                NSTimer::scheduledTimerWithTimeInterval_target_selector_userInfo_repeats(
+                    1.0,
+                    target,
+                    sel!(captureCountdownTick:),
+                    None,
+                    true,
+                )
+            };
+            *self.ivars().capture_countdown.borrow_mut() = Some(timer);
+            eprintln!("Capturing screenshot in {} seconds", seconds);
+        }
+
+        #[unsafe(method(captureCountdownTick:))]
+        fn capture_countdown_tick(&self, _timer: &NSObject) {
+            let left = self.ivars().capture_countdown_left.get().saturating_sub(1);
+            self.ivars().capture_countdown_left.set(left);
+            if left > 0 {
+                if let Some(sb) = self.ivars().status_bar.borrow().as_ref() {
+                    sb.set_capture_countdown(MainThreadMarker::from(self), Some(left));
+                }
+                return;
+            }
+            self.cancel_capture_countdown();
+            if !self.can_capture_screenshot() {
+                return;
+            }
+            eprintln!("Delayed capture triggered");
             self.do_capture();
         }
 
+        #[unsafe(method(cancelCaptureDelay:))]
+        fn cancel_capture_delay(&self, _sender: &AnyObject) {
+            self.cancel_capture_countdown();
+            eprintln!("Delayed capture cancelled");
+        }
+
         #[unsafe(method(startRecording:))]
         fn start_recording_menu(&self, _sender: &AnyObject) {
             self.handle_record_hotkey();
@@ -165,7 +348,32 @@ define_class!(
 
         #[unsafe(method(startScrollCapture:))]
         fn start_scroll_capture_menu(&self, _sender: &AnyObject) {
-            self.handle_scroll_capture_hotkey();
+            self.handle_scroll_capture_hotkey(ScrollDirection::Down, false);
+        }
+
+        #[unsafe(method(compareImages:))]
+        fn compare_images_menu(&self, _sender: &AnyObject) {
+            self.compare_images();
+        }
+
+        #[unsafe(method(annotateClipboardImage:))]
+        fn annotate_clipboard_image_menu(&self, _sender: &AnyObject) {
+            self.annotate_clipboard_image();
+        }
+
+        #[unsafe(method(startScrollCaptureUp:))]
+        fn start_scroll_capture_up_menu(&self, _sender: &AnyObject) {
+            self.handle_scroll_capture_hotkey(ScrollDirection::Up, false);
+        }
+
+        #[unsafe(method(startScrollCaptureRight:))]
+        fn start_scroll_capture_right_menu(&self, _sender: &AnyObject) {
+            self.handle_scroll_capture_hotkey(ScrollDirection::Right, false);
+        }
+
+        #[unsafe(method(startScrollCaptureManual:))]
+        fn start_scroll_capture_manual_menu(&self, _sender: &AnyObject) {
+            self.handle_scroll_capture_hotkey(ScrollDirection::Down, true);
         }
     }
 
@@ -181,6 +389,11 @@ define_class!(
             self.set_active_tool(ActiveTool::Arrow);
         }
 
+        #[unsafe(method(toolLine:))]
+        fn tool_line(&self, _sender: &AnyObject) {
+            self.set_active_tool(ActiveTool::Line);
+        }
+
         #[unsafe(method(toolRect:))]
         fn tool_rect(&self, _sender: &AnyObject) {
             self.set_active_tool(ActiveTool::Rectangle);
@@ -206,6 +419,11 @@ define_class!(
             self.set_active_tool(ActiveTool::Highlight);
         }
 
+        #[unsafe(method(toolHighlighter:))]
+        fn tool_highlighter(&self, _sender: &AnyObject) {
+            self.set_active_tool(ActiveTool::Highlighter);
+        }
+
         #[unsafe(method(toolStep:))]
         fn tool_step(&self, _sender: &AnyObject) {
             self.set_active_tool(ActiveTool::Step);
@@ -216,10 +434,30 @@ define_class!(
             self.set_active_tool(ActiveTool::Blur);
         }
 
+        #[unsafe(method(toolRedact:))]
+        fn tool_redact(&self, _sender: &AnyObject) {
+            self.set_active_tool(ActiveTool::Redact);
+        }
+
+        #[unsafe(method(toolSpotlight:))]
+        fn tool_spotlight(&self, _sender: &AnyObject) {
+            self.set_active_tool(ActiveTool::Spotlight);
+        }
+
+        #[unsafe(method(toolCallout:))]
+        fn tool_callout(&self, _sender: &AnyObject) {
+            self.set_active_tool(ActiveTool::Callout);
+        }
+
         #[unsafe(method(toolCrop:))]
         fn tool_crop(&self, _sender: &AnyObject) {
             self.set_active_tool(ActiveTool::Crop);
         }
+
+        #[unsafe(method(toolEyedropper:))]
+        fn tool_eyedropper(&self, _sender: &AnyObject) {
+            self.set_active_tool(ActiveTool::Eyedropper);
+        }
     }
 
     // --- Color selection ---
@@ -274,186 +512,1526 @@ define_class!(
         }
     }
 
-    // --- Stroke width selection ---
+    // --- Hex color entry (toolbar field) ---
     impl AppDelegate {
-        #[unsafe(method(strokeThin:))]
-        fn stroke_thin(&self, _sender: &AnyObject) {
-            self.set_stroke_width(1.5, 14.0, 0);
-        }
+        #[unsafe(method(hexColorEntered:))]
+        fn hex_color_entered(&self, _sender: &AnyObject) {
+            let toolbar_ref = self.ivars().toolbar.borrow();
+            let Some(toolbar) = toolbar_ref.as_ref() else {
+                return;
+            };
+            let text = toolbar.view.hex_field_value();
+            match crate::toolbar::view::parse_hex_color(&text) {
+                Some(color) => {
+                    drop(toolbar_ref);
+                    self.set_preset_color(color);
+                }
+                None => {
+                    // Reject: put the current color back in the field
+                    eprintln!("Invalid hex color: {:?}", text);
+                    let (r, g, b) = toolbar.view.color();
+                    toolbar.view.set_color(r, g, b);
+                    drop(toolbar_ref);
+                }
+            }
 
-        #[unsafe(method(strokeMedium:))]
-        fn stroke_medium(&self, _sender: &AnyObject) {
-            self.set_stroke_width(3.0, 18.0, 1);
+            // Hand keyboard focus back to the annotation surface
+            if let Some(ref editor) = *self.ivars().editor_window.borrow() {
+                editor.window.makeKeyWindow();
+                return;
+            }
+            if let Some(overlay) = self.ivars().overlay.borrow().as_ref() {
+                overlay.window.makeKeyWindow();
+            }
         }
+    }
 
-        #[unsafe(method(strokeThick:))]
-        fn stroke_thick(&self, _sender: &AnyObject) {
-            self.set_stroke_width(5.5, 24.0, 2);
+    // --- Eyedropper ---
+    impl AppDelegate {
+        #[unsafe(method(sampleColorAtX:y:))]
+        fn sample_color_at(&self, x: CGFloat, y: CGFloat) {
+            let color = if let Some(ref editor) = *self.ivars().editor_window.borrow() {
+                // Editor view may be scaled relative to the frame's pixels
+                let bounds = editor.view.bounds();
+                editor.view.ivars().current_cgimage.borrow().as_ref().and_then(|img| {
+                    let sx = CGImage::width(Some(img)) as CGFloat / bounds.size.width;
+                    let sy = CGImage::height(Some(img)) as CGFloat / bounds.size.height;
+                    crate::actions::sample_pixel(img, (x * sx) as usize, (y * sy) as usize)
+                })
+            } else {
+                let scale = self
+                    .ivars()
+                    .overlay
+                    .borrow()
+                    .as_ref()
+                    .map_or(1.0, |overlay| overlay.view.ivars().scale_factor.get());
+                self.ivars().captured_image.borrow().as_ref().and_then(|img| {
+                    crate::actions::sample_pixel(img, (x * scale) as usize, (y * scale) as usize)
+                })
+            };
+
+            if let Some(color) = color {
+                self.set_preset_color(color);
+                eprintln!(
+                    "Sampled color #{:02X}{:02X}{:02X}",
+                    (color.0 * 255.0).round() as u8,
+                    (color.1 * 255.0).round() as u8,
+                    (color.2 * 255.0).round() as u8
+                );
+            }
+            self.set_active_tool(self.ivars().tool_before_eyedropper.get());
         }
     }
 
-    // --- Actions ---
+    // --- Shape fill ---
     impl AppDelegate {
-        #[unsafe(method(actionUndo:))]
-        fn action_undo(&self, _sender: &AnyObject) {
+        #[unsafe(method(toggleFill:))]
+        fn toggle_fill(&self, _sender: &AnyObject) {
+            let fill = self
+                .ivars()
+                .toolbar
+                .borrow()
+                .as_ref()
+                .is_some_and(|toolbar| !toolbar.view.is_fill_active());
+            if let Some(toolbar) = self.ivars().toolbar.borrow().as_ref() {
+                toolbar.view.set_fill_active(fill);
+            }
             if let Some(ref editor) = *self.ivars().editor_window.borrow() {
-                let mtm = MainThreadMarker::from(self);
-                editor.undo_annotation(mtm);
+                editor.view.ivars().annotation_fill.set(fill);
                 return;
             }
             if let Some(overlay) = self.ivars().overlay.borrow().as_ref() {
-                if let Some(ann) = overlay.view.ivars().annotations.borrow_mut().pop() {
-                    overlay.view.ivars().redo_stack.borrow_mut().push(ann);
-                }
-                overlay.view.setNeedsDisplay(true);
+                overlay.view.ivars().annotation_fill.set(fill);
             }
         }
+    }
 
-        #[unsafe(method(actionRedo:))]
-        fn action_redo(&self, _sender: &AnyObject) {
+    // --- Drop shadow ---
+    impl AppDelegate {
+        #[unsafe(method(toggleShadow:))]
+        fn toggle_shadow(&self, _sender: &AnyObject) {
+            let shadow = self
+                .ivars()
+                .toolbar
+                .borrow()
+                .as_ref()
+                .is_some_and(|toolbar| !toolbar.view.is_shadow_active());
+            if let Some(toolbar) = self.ivars().toolbar.borrow().as_ref() {
+                toolbar.view.set_shadow_active(shadow);
+            }
             if let Some(ref editor) = *self.ivars().editor_window.borrow() {
-                let mtm = MainThreadMarker::from(self);
-                editor.redo_annotation(mtm);
+                editor.view.ivars().annotation_shadow.set(shadow);
                 return;
             }
             if let Some(overlay) = self.ivars().overlay.borrow().as_ref() {
-                if let Some(ann) = overlay.view.ivars().redo_stack.borrow_mut().pop() {
-                    overlay.view.ivars().annotations.borrow_mut().push(ann);
-                }
-                overlay.view.setNeedsDisplay(true);
+                overlay.view.ivars().annotation_shadow.set(shadow);
             }
         }
+    }
 
-        #[unsafe(method(actionCancel:))]
-        fn action_cancel(&self, _sender: &AnyObject) {
-            // If editor is open, mark as cancelled (discard without saving) and close
+    // --- Text font, background, and outline ---
+    impl AppDelegate {
+        #[unsafe(method(cycleTextFont:))]
+        fn cycle_text_font(&self, _sender: &AnyObject) {
+            let fonts = crate::annotation::model::TEXT_FONTS;
+            let mut font_name = None;
+            if let Some(toolbar) = self.ivars().toolbar.borrow().as_ref() {
+                let index = (toolbar.view.font_index() + 1) % fonts.len();
+                toolbar.view.set_font_index(index);
+                font_name = fonts[index].1.map(str::to_string);
+            }
             if let Some(ref editor) = *self.ivars().editor_window.borrow() {
-                self.ivars().editor_cancelled.set(true);
-                editor.window.close();
+                *editor.view.ivars().annotation_font_name.borrow_mut() = font_name;
                 return;
             }
-            self.ivars().recording_mode.set(false);
-            self.ivars().scroll_capture_mode.set(false);
-            self.dismiss_all();
+            if let Some(overlay) = self.ivars().overlay.borrow().as_ref() {
+                *overlay.view.ivars().annotation_font_name.borrow_mut() = font_name;
+            }
         }
 
-        #[unsafe(method(actionSave:))]
-        fn action_save(&self, _sender: &AnyObject) {
-            // If editor is open, export with annotations and save
-            if self.ivars().editor_window.borrow().is_some() {
-                self.export_editor();
-                return;
+        #[unsafe(method(toggleBold:))]
+        fn toggle_bold(&self, _sender: &AnyObject) {
+            if let Some(toolbar) = self.ivars().toolbar.borrow().as_ref() {
+                toolbar.view.set_bold_active(!toolbar.view.is_bold_active());
             }
+            self.apply_font_traits();
+        }
 
-            let mtm = MainThreadMarker::from(self);
-            let image = self.get_final_image();
-            // Dismiss overlay first so the NSSavePanel isn't hidden behind it.
-            self.dismiss_all();
-            if let Some(image) = image {
-                crate::actions::save_to_file(&image, mtm);
+        #[unsafe(method(toggleItalic:))]
+        fn toggle_italic(&self, _sender: &AnyObject) {
+            if let Some(toolbar) = self.ivars().toolbar.borrow().as_ref() {
+                toolbar.view.set_italic_active(!toolbar.view.is_italic_active());
             }
+            self.apply_font_traits();
         }
 
-        #[unsafe(method(actionConfirm:))]
-        fn action_confirm(&self, _sender: &AnyObject) {
-            // If editor is open, check for crop mode first
-            if self.ivars().editor_window.borrow().is_some() {
-                let has_crop = {
-                    let editor_ref = self.ivars().editor_window.borrow();
-                    let editor = editor_ref.as_ref().unwrap();
-                    let is_crop_tool = editor.view.ivars().active_tool.get() == ActiveTool::Crop;
-                    let has_crop_rect = editor.view.ivars().crop_rect.get().is_some();
-                    is_crop_tool && has_crop_rect
-                };
-                if has_crop {
-                    self.apply_crop();
-                } else {
-                    self.export_editor();
-                }
+        #[unsafe(method(toggleTextBackground:))]
+        fn toggle_text_background(&self, _sender: &AnyObject) {
+            let background = self
+                .ivars()
+                .toolbar
+                .borrow()
+                .as_ref()
+                .is_some_and(|toolbar| !toolbar.view.is_text_background_active());
+            if let Some(toolbar) = self.ivars().toolbar.borrow().as_ref() {
+                toolbar.view.set_text_background_active(background);
+            }
+            if let Some(ref editor) = *self.ivars().editor_window.borrow() {
+                editor.view.ivars().annotation_text_background.set(background);
                 return;
             }
+            if let Some(overlay) = self.ivars().overlay.borrow().as_ref() {
+                overlay.view.ivars().annotation_text_background.set(background);
+            }
+        }
 
-            if self.ivars().scroll_capture_mode.get() {
-                self.start_scroll_capture_with_selection();
+        #[unsafe(method(toggleTextOutline:))]
+        fn toggle_text_outline(&self, _sender: &AnyObject) {
+            let outline = self
+                .ivars()
+                .toolbar
+                .borrow()
+                .as_ref()
+                .is_some_and(|toolbar| !toolbar.view.is_text_outline_active());
+            if let Some(toolbar) = self.ivars().toolbar.borrow().as_ref() {
+                toolbar.view.set_text_outline_active(outline);
+            }
+            if let Some(ref editor) = *self.ivars().editor_window.borrow() {
+                editor.view.ivars().annotation_text_outline.set(outline);
                 return;
             }
-
-            // Normal screenshot mode: copy to clipboard
-            if let Some(image) = self.get_final_image() {
-                if let Err(e) = crate::actions::copy_to_clipboard(&image) {
-                    eprintln!("Clipboard error: {}", e);
-                }
+            if let Some(overlay) = self.ivars().overlay.borrow().as_ref() {
+                overlay.view.ivars().annotation_text_outline.set(outline);
             }
-            self.dismiss_all();
         }
     }
 
-    // --- Selection notification (called from overlay view) ---
+    // --- Pencil smoothing ---
     impl AppDelegate {
-        #[unsafe(method(selectionChanged:))]
-        fn selection_changed(&self, _sender: &AnyObject) {
-            // In scroll capture mode, auto-start once a valid selection is drawn
-            if self.ivars().scroll_capture_mode.get() {
-                if let Some(overlay) = self.ivars().overlay.borrow().as_ref() {
-                    if let Some(sel) = overlay.view.ivars().selection.get() {
-                        let norm = crate::overlay::view::normalize_rect(sel);
-                        if norm.size.width > 5.0 && norm.size.height > 5.0 {
-                            self.start_scroll_capture_with_selection();
-                            return;
-                        }
-                    }
-                }
+        #[unsafe(method(toggleSmoothing:))]
+        fn toggle_smoothing(&self, _sender: &AnyObject) {
+            let smooth = self
+                .ivars()
+                .toolbar
+                .borrow()
+                .as_ref()
+                .is_some_and(|toolbar| !toolbar.view.is_smooth_active());
+            if let Some(toolbar) = self.ivars().toolbar.borrow().as_ref() {
+                toolbar.view.set_smooth_active(smooth);
             }
-
-            // In recording mode, auto-start once a valid selection is drawn
-            if self.ivars().recording_mode.get() {
-                if let Some(overlay) = self.ivars().overlay.borrow().as_ref() {
-                    if let Some(sel) = overlay.view.ivars().selection.get() {
-                        let norm = crate::overlay::view::normalize_rect(sel);
-                        if norm.size.width > 5.0 && norm.size.height > 5.0 {
-                            self.start_recording_with_selection();
-                            return;
-                        }
-                    }
-                }
+            if let Some(ref editor) = *self.ivars().editor_window.borrow() {
+                editor.view.ivars().annotation_smooth.set(smooth);
+                return;
+            }
+            if let Some(overlay) = self.ivars().overlay.borrow().as_ref() {
+                overlay.view.ivars().annotation_smooth.set(smooth);
             }
-
-            let mtm = MainThreadMarker::from(self);
-            self.update_toolbar_position(mtm);
         }
     }
 
-    // --- Recording frame capture (called by NSTimer) ---
+    // --- Rectangle corner radius ---
     impl AppDelegate {
-        #[unsafe(method(captureRecordingFrame:))]
-        fn capture_recording_frame(&self, _timer: &NSObject) {
-            let mut state = self.ivars().recording_state.borrow_mut();
-            if let Some(ref mut recording) = *state {
-                recording.capture_frame();
+        #[unsafe(method(cycleCornerRadius:))]
+        fn cycle_corner_radius(&self, _sender: &AnyObject) {
+            let radii = crate::toolbar::view::CORNER_RADII;
+            let mut radius = 0.0;
+            if let Some(toolbar) = self.ivars().toolbar.borrow().as_ref() {
+                let index = (toolbar.view.corner_radius_index() + 1) % radii.len();
+                toolbar.view.set_corner_radius_index(index);
+                radius = radii[index];
+            }
+            if let Some(ref editor) = *self.ivars().editor_window.borrow() {
+                editor.view.ivars().annotation_corner_radius.set(radius);
+                return;
+            }
+            if let Some(overlay) = self.ivars().overlay.borrow().as_ref() {
+                overlay.view.ivars().annotation_corner_radius.set(radius);
             }
         }
     }
 
-    // --- Stop recording (called from status bar menu) ---
+    // --- Highlight opacity ---
     impl AppDelegate {
-        #[unsafe(method(stopRecording:))]
-        fn stop_recording_action(&self, _sender: &AnyObject) {
-            self.stop_recording();
+        #[unsafe(method(cycleHighlightOpacity:))]
+        fn cycle_highlight_opacity(&self, _sender: &AnyObject) {
+            let opacities = crate::toolbar::view::HIGHLIGHT_OPACITIES;
+            let mut opacity = opacities[1];
+            if let Some(toolbar) = self.ivars().toolbar.borrow().as_ref() {
+                let index = (toolbar.view.opacity_index() + 1) % opacities.len();
+                toolbar.view.set_opacity_index(index);
+                opacity = opacities[index];
+            }
+            if let Some(ref editor) = *self.ivars().editor_window.borrow() {
+                editor.view.ivars().annotation_highlight_opacity.set(opacity);
+                let mtm = MainThreadMarker::from(self);
+                editor.set_active_highlight_opacity(opacity, mtm);
+                return;
+            }
+            if let Some(overlay) = self.ivars().overlay.borrow().as_ref() {
+                overlay.view.ivars().annotation_highlight_opacity.set(opacity);
+                if let Some(idx) = overlay.view.ivars().active_annotation_index.get()
+                    && let Some(ann) = overlay.view.ivars().annotations.borrow_mut().get_mut(idx)
+                    && ann.set_opacity(opacity)
+                {
+                    overlay.view.setNeedsDisplay(true);
+                }
+            }
         }
     }
 
-    // --- Scroll capture timer (called by NSTimer) ---
+    // --- Stroke style ---
     impl AppDelegate {
-        #[unsafe(method(scrollCaptureTick:))]
-        fn scroll_capture_tick(&self, _timer: &NSObject) {
-            let mut state_ref = self.ivars().scroll_capture_state.borrow_mut();
+        #[unsafe(method(cycleStrokeStyle:))]
+        fn cycle_stroke_style(&self, _sender: &AnyObject) {
+            let mut style = StrokeStyle::Solid;
+            if let Some(toolbar) = self.ivars().toolbar.borrow().as_ref() {
+                style = toolbar.view.stroke_style().next();
+                toolbar.view.set_stroke_style(style);
+            }
+            if let Some(ref editor) = *self.ivars().editor_window.borrow() {
+                editor.view.ivars().annotation_stroke_style.set(style);
+                return;
+            }
+            if let Some(overlay) = self.ivars().overlay.borrow().as_ref() {
+                overlay.view.ivars().annotation_stroke_style.set(style);
+            }
+        }
+    }
+
+    // --- Step counter and shape ---
+    impl AppDelegate {
+        #[unsafe(method(cycleStepShape:))]
+        fn cycle_step_shape(&self, _sender: &AnyObject) {
+            let mut shape = StepShape::Circle;
+            if let Some(toolbar) = self.ivars().toolbar.borrow().as_ref() {
+                shape = toolbar.view.step_shape().next();
+                toolbar.view.set_step_shape(shape);
+            }
+            if let Some(ref editor) = *self.ivars().editor_window.borrow() {
+                editor.view.ivars().annotation_step_shape.set(shape);
+                return;
+            }
+            if let Some(overlay) = self.ivars().overlay.borrow().as_ref() {
+                overlay.view.ivars().annotation_step_shape.set(shape);
+            }
+        }
+
+        #[unsafe(method(cropAspectChanged:))]
+        fn crop_aspect_changed(&self, _sender: &AnyObject) {
+            let Some(aspect) = self.ivars().toolbar.borrow().as_ref().map(|t| t.view.crop_aspect()) else {
+                return;
+            };
+            if let Some(ref editor) = *self.ivars().editor_window.borrow() {
+                editor.view.set_crop_aspect(aspect);
+                editor.window.makeKeyWindow();
+            }
+        }
+
+        #[unsafe(method(resetStepCounter:))]
+        fn reset_step_counter(&self, _sender: &AnyObject) {
+            if let Some(ref editor) = *self.ivars().editor_window.borrow() {
+                editor.view.ivars().next_step_number.set(1);
+                return;
+            }
+            if let Some(overlay) = self.ivars().overlay.borrow().as_ref() {
+                overlay.view.ivars().next_step_number.set(1);
+            }
+        }
+    }
+
+    // --- Spotlight shape ---
+    impl AppDelegate {
+        #[unsafe(method(toggleSpotlightShape:))]
+        fn toggle_spotlight_shape(&self, _sender: &AnyObject) {
+            if let Some(ref editor) = *self.ivars().editor_window.borrow() {
+                let shape = &editor.view.ivars().annotation_spotlight_shape;
+                shape.set(shape.get().toggled());
+                return;
+            }
+            if let Some(overlay) = self.ivars().overlay.borrow().as_ref() {
+                let shape = &overlay.view.ivars().annotation_spotlight_shape;
+                shape.set(shape.get().toggled());
+            }
+        }
+    }
+
+    // --- Blur style ---
+    impl AppDelegate {
+        #[unsafe(method(toggleBlurStyle:))]
+        fn toggle_blur_style(&self, _sender: &AnyObject) {
+            let mut style = BlurStyle::Pixelate;
+            if let Some(toolbar) = self.ivars().toolbar.borrow().as_ref() {
+                style = toolbar.view.blur_style().toggled();
+                toolbar.view.set_blur_style(style);
+            }
+            if let Some(ref editor) = *self.ivars().editor_window.borrow() {
+                editor.view.ivars().annotation_blur_style.set(style);
+                return;
+            }
+            if let Some(overlay) = self.ivars().overlay.borrow().as_ref() {
+                overlay.view.ivars().annotation_blur_style.set(style);
+            }
+        }
+    }
+
+    // --- Preset colors (command palette) ---
+    impl AppDelegate {
+        #[unsafe(method(colorRed:))]
+        fn color_red(&self, _sender: &AnyObject) {
+            self.set_preset_color((1.0, 0.0, 0.0));
+        }
+
+        #[unsafe(method(colorOrange:))]
+        fn color_orange(&self, _sender: &AnyObject) {
+            self.set_preset_color((1.0, 0.58, 0.0));
+        }
+
+        #[unsafe(method(colorYellow:))]
+        fn color_yellow(&self, _sender: &AnyObject) {
+            self.set_preset_color((1.0, 0.84, 0.0));
+        }
+
+        #[unsafe(method(colorGreen:))]
+        fn color_green(&self, _sender: &AnyObject) {
+            self.set_preset_color((0.2, 0.78, 0.35));
+        }
+
+        #[unsafe(method(colorBlue:))]
+        fn color_blue(&self, _sender: &AnyObject) {
+            self.set_preset_color((0.0, 0.48, 1.0));
+        }
+
+        #[unsafe(method(colorBlack:))]
+        fn color_black(&self, _sender: &AnyObject) {
+            self.set_preset_color((0.0, 0.0, 0.0));
+        }
+
+        #[unsafe(method(colorWhite:))]
+        fn color_white(&self, _sender: &AnyObject) {
+            self.set_preset_color((1.0, 1.0, 1.0));
+        }
+    }
+
+    // --- Command palette ---
+    impl AppDelegate {
+        #[unsafe(method(showCommandPalette:))]
+        fn show_command_palette(&self, _sender: &AnyObject) {
+            let mtm = MainThreadMarker::from(self);
+            let palette_ref = self.ivars().command_palette.borrow();
+            let Some(palette) = palette_ref.as_ref() else {
+                return;
+            };
+            if palette.is_visible() {
+                palette.hide();
+                return;
+            }
+
+            // The palette floats over whichever annotation surface is active
+            if let Some(ref editor) = *self.ivars().editor_window.borrow() {
+                palette.show_over(&editor.window, mtm);
+                return;
+            }
+            if let Some(overlay) = self.ivars().overlay.borrow().as_ref()
+                && overlay.window.isVisible()
+            {
+                palette.show_over(&overlay.window, mtm);
+            }
+        }
+
+        #[unsafe(method(hideCommandPalette:))]
+        fn hide_command_palette(&self, _sender: &AnyObject) {
+            if let Some(palette) = self.ivars().command_palette.borrow().as_ref() {
+                palette.hide();
+            }
+        }
+    }
+
+    // --- Stroke width selection ---
+    impl AppDelegate {
+        #[unsafe(method(strokeThin:))]
+        fn stroke_thin(&self, _sender: &AnyObject) {
+            self.set_stroke_width(0);
+        }
+
+        #[unsafe(method(strokeMedium:))]
+        fn stroke_medium(&self, _sender: &AnyObject) {
+            self.set_stroke_width(1);
+        }
+
+        #[unsafe(method(strokeThick:))]
+        fn stroke_thick(&self, _sender: &AnyObject) {
+            self.set_stroke_width(2);
+        }
+    }
+
+    // --- Actions ---
+    impl AppDelegate {
+        #[unsafe(method(actionUndo:))]
+        fn action_undo(&self, _sender: &AnyObject) {
+            if let Some(ref editor) = *self.ivars().editor_window.borrow() {
+                let mtm = MainThreadMarker::from(self);
+                editor.undo_annotation(mtm);
+                return;
+            }
+            if let Some(overlay) = self.ivars().overlay.borrow().as_ref() {
+                overlay.view.undo();
+            }
+        }
+
+        #[unsafe(method(actionSelectAllAnnotations:))]
+        fn action_select_all_annotations(&self, _sender: &AnyObject) {
+            if let Some(ref editor) = *self.ivars().editor_window.borrow() {
+                editor.select_all_annotations(MainThreadMarker::from(self));
+                return;
+            }
+            if let Some(overlay) = self.ivars().overlay.borrow().as_ref() {
+                overlay.view.select_all_annotations();
+            }
+        }
+
+        #[unsafe(method(actionClearAnnotations:))]
+        fn action_clear_annotations(&self, _sender: &AnyObject) {
+            let mtm = MainThreadMarker::from(self);
+            if let Some(ref editor) = *self.ivars().editor_window.borrow() {
+                let count = editor.state.borrow().annotations.len();
+                if count > 0 && confirm_clear_annotations(count, mtm) {
+                    editor.clear_all_annotations(mtm);
+                }
+                return;
+            }
+            if let Some(overlay) = self.ivars().overlay.borrow().as_ref() {
+                let count = overlay.view.ivars().annotations.borrow().len();
+                if count > 0 && confirm_clear_annotations(count, mtm) {
+                    overlay.view.clear_annotations();
+                }
+            }
+        }
+
+        #[unsafe(method(actionRedo:))]
+        fn action_redo(&self, _sender: &AnyObject) {
+            if let Some(ref editor) = *self.ivars().editor_window.borrow() {
+                let mtm = MainThreadMarker::from(self);
+                editor.redo_annotation(mtm);
+                return;
+            }
+            if let Some(overlay) = self.ivars().overlay.borrow().as_ref() {
+                overlay.view.redo();
+            }
+        }
+
+        #[unsafe(method(actionCancel:))]
+        fn action_cancel(&self, _sender: &AnyObject) {
+            // If editor is open, mark as cancelled (discard without saving) and close
+            if let Some(ref editor) = *self.ivars().editor_window.borrow() {
+                self.ivars().editor_cancelled.set(true);
+                editor.window.close();
+                return;
+            }
+            self.ivars().recording_mode.set(false);
+            self.ivars().scroll_capture_mode.set(false);
+            self.dismiss_all();
+        }
+
+        #[unsafe(method(actionSave:))]
+        fn action_save(&self, _sender: &AnyObject) {
+            // If editor is open, export with annotations and save
+            if self.ivars().editor_window.borrow().is_some() {
+                self.export_editor();
+                return;
+            }
+
+            let mtm = MainThreadMarker::from(self);
+            self.remember_selection();
+            let image = self.get_final_image();
+            // Dismiss overlay first so the NSSavePanel isn't hidden behind it.
+            self.dismiss_all();
+            if let Some(image) = image {
+                self.add_screenshot_to_history(&image);
+                self.save_image_to_file(&image, mtm);
+            }
+        }
+
+        #[unsafe(method(actionPin:))]
+        fn action_pin(&self, _sender: &AnyObject) {
+            // Pinning works on overlay screenshots only
+            if self.ivars().editor_window.borrow().is_some()
+                || self.ivars().recording_mode.get()
+                || self.ivars().scroll_capture_mode.get()
+            {
+                return;
+            }
+
+            let mtm = MainThreadMarker::from(self);
+            let placement = self.ivars().overlay.borrow().as_ref().and_then(|overlay| {
+                let selection = overlay.view.ivars().selection.get()?;
+                Some((crate::overlay::view::normalize_rect(selection), overlay.frame()))
+            });
+            self.remember_selection();
+            let image = self.get_final_image();
+            self.dismiss_all();
+            if let (Some(image), Some((selection, screen_frame))) = (image, placement) {
+                self.add_screenshot_to_history(&image);
+                let pin = PinnedWindow::new(image, selection, screen_frame, mtm);
+                let mut pins = self.ivars().pinned_windows.borrow_mut();
+                pins.push(pin);
+                eprintln!("Pinned screenshot ({} open)", pins.len());
+            }
+        }
+
+        #[unsafe(method(actionUpload:))]
+        fn action_upload(&self, _sender: &AnyObject) {
+            if self.ivars().recording_mode.get() || self.ivars().scroll_capture_mode.get() {
+                return;
+            }
+            if crate::prefs::upload_url().is_none() {
+                NSBeep();
+                crate::notify::show(
+                    "Upload Failed",
+                    "Set an upload URL with Upload Settings\u{2026} in the menu bar.",
+                );
+                return;
+            }
+
+            // In the editor, upload what the save panel exported
+            if self.ivars().editor_window.borrow().is_some() {
+                if let Some(path) = self.export_editor() {
+                    upload(&path);
+                }
+                return;
+            }
+
+            self.remember_selection();
+            let image = self.get_final_image();
+            self.dismiss_all();
+            let Some(image) = image else {
+                return;
+            };
+            self.add_screenshot_to_history(&image);
+            let path = std::env::temp_dir().join("clipshot.png");
+            match crate::actions::save_png(&image, &path) {
+                Ok(()) => upload(&path),
+                Err(e) => eprintln!("Failed to prepare upload: {}", e),
+            }
+            let _ = std::fs::remove_file(&path);
+        }
+
+        #[unsafe(method(actionCopyText:))]
+        fn action_copy_text(&self, _sender: &AnyObject) {
+            if self.ivars().recording_mode.get() || self.ivars().scroll_capture_mode.get() {
+                return;
+            }
+
+            self.start_vision_request(VisionRead::Text);
+        }
+
+        #[unsafe(method(actionDecodeBarcode:))]
+        fn action_decode_barcode(&self, _sender: &AnyObject) {
+            if self.ivars().recording_mode.get() || self.ivars().scroll_capture_mode.get() {
+                return;
+            }
+
+            self.start_vision_request(VisionRead::Codes);
+        }
+
+        #[unsafe(method(actionConfirm:))]
+        fn action_confirm(&self, _sender: &AnyObject) {
+            // If editor is open, check for crop mode first
+            if self.ivars().editor_window.borrow().is_some() {
+                let has_crop = {
+                    let editor_ref = self.ivars().editor_window.borrow();
+                    let editor = editor_ref.as_ref().unwrap();
+                    let is_crop_tool = editor.view.ivars().active_tool.get() == ActiveTool::Crop;
+                    let has_crop_rect = editor.view.ivars().crop_rect.get().is_some();
+                    is_crop_tool && has_crop_rect
+                };
+                if has_crop {
+                    self.apply_crop();
+                } else {
+                    self.export_editor();
+                }
+                return;
+            }
+
+            if self.ivars().scroll_capture_mode.get() {
+                self.start_scroll_capture_with_selection();
+                return;
+            }
+            if self.ivars().recording_mode.get() {
+                self.start_recording_with_selection();
+                return;
+            }
+
+            // Normal screenshot mode: the toolbar's confirm always copies
+            self.finish_screenshot(PostCaptureAction::Copy);
+        }
+    }
+
+    // --- Pinned screenshots (called from pin view) ---
+    impl AppDelegate {
+        #[unsafe(method(pinClosed:))]
+        fn pin_closed(&self, sender: &AnyObject) {
+            let mut pins = self.ivars().pinned_windows.borrow_mut();
+            let sender_ptr = sender as *const AnyObject;
+            if let Some(i) = pins
+                .iter()
+                .position(|pin| &*pin.view as *const _ as *const AnyObject == sender_ptr)
+            {
+                pins.remove(i).close();
+            }
+        }
+    }
+
+    // --- Selection notification (called from overlay view) ---
+    impl AppDelegate {
+        #[unsafe(method(selectionChanged:))]
+        fn selection_changed(&self, _sender: &AnyObject) {
+            // In scroll capture mode, auto-start once a valid selection is drawn
+            if self.ivars().scroll_capture_mode.get() {
+                if let Some(overlay) = self.ivars().overlay.borrow().as_ref() {
+                    if let Some(sel) = overlay.view.ivars().selection.get() {
+                        let norm = crate::overlay::view::normalize_rect(sel);
+                        if norm.size.width > 5.0 && norm.size.height > 5.0 {
+                            self.start_scroll_capture_with_selection();
+                            return;
+                        }
+                    }
+                }
+            }
+
+            // In recording mode, auto-start once a valid selection is drawn
+            if self.ivars().recording_mode.get() {
+                if let Some(overlay) = self.ivars().overlay.borrow().as_ref() {
+                    if let Some(sel) = overlay.view.ivars().selection.get() {
+                        let norm = crate::overlay::view::normalize_rect(sel);
+                        if norm.size.width > 5.0 && norm.size.height > 5.0 {
+                            self.start_recording_with_selection();
+                            return;
+                        }
+                    }
+                }
+            }
+
+            // Screenshot mode: go straight to the preferred outcome, skipping the toolbar
+            let action = crate::prefs::post_capture_action();
+            if action != PostCaptureAction::ShowToolbar {
+                let has_valid_selection = self.ivars().overlay.borrow().as_ref().is_some_and(|overlay| {
+                    overlay.view.ivars().selection.get().is_some_and(|sel| {
+                        let norm = crate::overlay::view::normalize_rect(sel);
+                        norm.size.width > 5.0 && norm.size.height > 5.0
+                    })
+                });
+                if has_valid_selection {
+                    self.finish_screenshot(action);
+                    return;
+                }
+            }
+
+            // The remembered tool takes over from Select once the toolbar first appears
+            let toolbar_shown =
+                self.ivars().toolbar.borrow().as_ref().is_some_and(|t| t.panel.isVisible());
+            let selecting = self.ivars().overlay.borrow().as_ref().is_some_and(|overlay| {
+                overlay.view.ivars().active_tool.get() == ActiveTool::Select
+            });
+            if !toolbar_shown
+                && selecting
+                && let Some(tool) = crate::prefs::annotation_tool()
+            {
+                self.set_active_tool(tool);
+            }
+
+            let mtm = MainThreadMarker::from(self);
+            self.update_toolbar_position(mtm);
+        }
+    }
+
+    // --- Window capture (called from overlay view) ---
+    impl AppDelegate {
+        #[unsafe(method(windowPicked:))]
+        fn window_picked(&self, _sender: &AnyObject) {
+            let picked = self.ivars().overlay.borrow().as_ref().and_then(|overlay| {
+                overlay.view.ivars().hovered_window.get()
+            });
+            self.dismiss_all();
+            let Some((window_id, _)) = picked else {
+                return;
+            };
+            let Some(image) = crate::capture::capture_window(window_id) else {
+                return;
+            };
+            // There's no region on the overlay to annotate, so the toolbar opens the editor
+            let action = match crate::prefs::post_capture_action() {
+                PostCaptureAction::ShowToolbar => PostCaptureAction::OpenEditor,
+                action => action,
+            };
+            self.deliver_screenshot(image, action, MainThreadMarker::from(self));
+        }
+    }
+
+    // --- Selection aspect ratio (status bar submenu) ---
+    impl AppDelegate {
+        #[unsafe(method(setSelectionAspect:))]
+        fn set_selection_aspect_menu(&self, sender: &AnyObject) {
+            let tag: isize = unsafe { msg_send![sender, tag] };
+            let Some(&aspect) = CropAspect::ALL.get(tag as usize) else {
+                return;
+            };
+            crate::prefs::set_selection_aspect(aspect);
+            if let Some(overlay) = self.ivars().overlay.borrow().as_ref() {
+                overlay.view.ivars().selection_aspect.set(aspect);
+            }
+            if let Some(sb) = self.ivars().status_bar.borrow().as_ref() {
+                sb.set_selection_aspect(aspect);
+            }
+            eprintln!("Selection aspect ratio: {}", aspect.label());
+        }
+    }
+
+    // --- Crosshair guides toggle (status bar) ---
+    impl AppDelegate {
+        #[unsafe(method(toggleCrosshairGuides:))]
+        fn toggle_crosshair_guides(&self, _sender: &AnyObject) {
+            let enabled = !crate::prefs::crosshair_guides();
+            crate::prefs::set_crosshair_guides(enabled);
+            if let Some(overlay) = self.ivars().overlay.borrow().as_ref() {
+                overlay.view.ivars().show_crosshair.set(enabled);
+            }
+            if let Some(sb) = self.ivars().status_bar.borrow().as_ref() {
+                sb.set_crosshair_guides(enabled);
+            }
+            eprintln!("Crosshair guides: {}", enabled);
+        }
+
+        #[unsafe(method(toggleVerticalToolbar:))]
+        fn toggle_vertical_toolbar(&self, _sender: &AnyObject) {
+            let vertical = !crate::prefs::vertical_toolbar();
+            crate::prefs::set_vertical_toolbar(vertical);
+            if let Some(toolbar) = self.ivars().toolbar.borrow().as_ref() {
+                toolbar.set_vertical(vertical);
+            }
+            if let Some(sb) = self.ivars().status_bar.borrow().as_ref() {
+                sb.set_vertical_toolbar(vertical);
+            }
+            eprintln!("Vertical toolbar: {}", vertical);
+        }
+    }
+
+    // --- Keep annotations inside the selection toggle (status bar) ---
+    impl AppDelegate {
+        #[unsafe(method(toggleKeepInsideSelection:))]
+        fn toggle_keep_inside_selection(&self, _sender: &AnyObject) {
+            let enabled = !crate::prefs::keep_inside_selection();
+            crate::prefs::set_keep_inside_selection(enabled);
+            if let Some(overlay) = self.ivars().overlay.borrow().as_ref() {
+                overlay.view.ivars().keep_inside_selection.set(enabled);
+            }
+            if let Some(sb) = self.ivars().status_bar.borrow().as_ref() {
+                sb.set_keep_inside_selection(enabled);
+            }
+            eprintln!("Keep annotations inside selection: {}", enabled);
+        }
+    }
+
+    // --- Snap to grid (status bar submenu) ---
+    impl AppDelegate {
+        #[unsafe(method(setSnapGrid:))]
+        fn set_snap_grid_menu(&self, sender: &AnyObject) {
+            let tag: isize = unsafe { msg_send![sender, tag] };
+            let Some(&grid) = SnapGrid::ALL.get(tag as usize) else {
+                return;
+            };
+            crate::prefs::set_snap_grid(grid);
+            if let Some(overlay) = self.ivars().overlay.borrow().as_ref() {
+                overlay.view.ivars().snap_grid.set(grid.spacing());
+            }
+            if let Some(editor) = self.ivars().editor_window.borrow().as_ref() {
+                editor.view.ivars().snap_grid.set(grid.spacing());
+            }
+            if let Some(sb) = self.ivars().status_bar.borrow().as_ref() {
+                sb.set_snap_grid(grid);
+            }
+            eprintln!("Snap to grid: {}", grid.title());
+        }
+    }
+
+    // --- Open image (status bar menu, or a file dropped on the icon) ---
+    impl AppDelegate {
+        #[unsafe(method(openImage:))]
+        fn open_image_menu(&self, _sender: &AnyObject) {
+            let mtm = MainThreadMarker::from(self);
+            #[allow(deprecated)]
+            NSApplication::sharedApplication(mtm).activateIgnoringOtherApps(true);
+            let path = crate::actions::choose_image_file("Choose an image to annotate", mtm);
+            if let Some(path) = path {
+                self.open_image_file(&path);
+            }
+        }
+
+        #[unsafe(method(draggingEntered:))]
+        fn dragging_entered(&self, sender: &ProtocolObject<dyn NSDraggingInfo>) -> NSDragOperation {
+            if dragged_image_path(sender).is_some() {
+                NSDragOperation::Copy
+            } else {
+                NSDragOperation::None
+            }
+        }
+
+        #[unsafe(method(performDragOperation:))]
+        fn perform_drag_operation(&self, sender: &ProtocolObject<dyn NSDraggingInfo>) -> Bool {
+            let Some(path) = dragged_image_path(sender) else {
+                return Bool::NO;
+            };
+            self.open_image_file(&path);
+            Bool::YES
+        }
+    }
+
+    // --- Capture history (status bar submenu) ---
+    impl AppDelegate {
+        #[unsafe(method(openRecentCapture:))]
+        fn open_recent_capture(&self, sender: &AnyObject) {
+            let mtm = MainThreadMarker::from(self);
+            if self.ivars().recording_state.borrow().is_some() {
+                eprintln!("Cannot open a recent capture while recording");
+                return;
+            }
+            if self.ivars().editor_window.borrow().is_some() {
+                eprintln!("Cannot open a recent capture while editing");
+                return;
+            }
+
+            let tag: isize = unsafe { msg_send![sender, tag] };
+            let Some(item) = crate::history::items().into_iter().nth(tag as usize) else {
+                return;
+            };
+
+            #[allow(deprecated)]
+            NSApplication::sharedApplication(mtm).activateIgnoringOtherApps(true);
+
+            match item.kind {
+                HistoryKind::Screenshot => match crate::actions::load_image_file(&item.path) {
+                    Ok(image) => self.open_screenshot_editor(image, "Edit Screenshot", mtm),
+                    Err(e) => {
+                        eprintln!("Failed to open recent capture: {}", e);
+                        show_open_failed(&item.path, &e, mtm);
+                    }
+                },
+                HistoryKind::Recording => {
+                    // The editor deletes its video when closed, so give it a copy
+                    let ext = item.path.extension().and_then(|e| e.to_str()).unwrap_or("mp4");
+                    let tmp_path = temp_capture_path("clipshot_history", ext);
+                    match std::fs::copy(&item.path, &tmp_path) {
+                        Ok(_) => self.open_editor(&tmp_path, mtm),
+                        Err(e) => eprintln!("Failed to open recent capture: {}", e),
+                    }
+                }
+            }
+        }
+
+        #[unsafe(method(toggleKeepHistory:))]
+        fn toggle_keep_history(&self, _sender: &AnyObject) {
+            let enabled = !crate::prefs::keep_history();
+            crate::prefs::set_keep_history(enabled);
+            if let Some(sb) = self.ivars().status_bar.borrow().as_ref() {
+                sb.set_keep_history(enabled);
+            }
+            eprintln!("Keep recent captures: {}", enabled);
+        }
+
+        #[unsafe(method(clearHistory:))]
+        fn clear_history(&self, _sender: &AnyObject) {
+            if let Err(e) = crate::history::clear() {
+                eprintln!("Failed to clear recent captures: {}", e);
+            }
+            self.refresh_recent_captures();
+        }
+
+        /// Posted to the main queue when a capture has been added to the history.
+        #[unsafe(method(historyChanged:))]
+        fn history_changed(&self, _sender: Option<&AnyObject>) {
+            self.refresh_recent_captures();
+        }
+    }
+
+    // --- Post-capture preference (status bar submenu) ---
+    impl AppDelegate {
+        #[unsafe(method(setPostCaptureAction:))]
+        fn set_post_capture_action_menu(&self, sender: &AnyObject) {
+            let tag: isize = unsafe { msg_send![sender, tag] };
+            let Some(&action) = PostCaptureAction::ALL.get(tag as usize) else {
+                return;
+            };
+            crate::prefs::set_post_capture_action(action);
+            if let Some(sb) = self.ivars().status_bar.borrow().as_ref() {
+                sb.set_post_capture_action(action);
+            }
+            eprintln!("Post-capture action: {}", action.title());
+        }
+    }
+
+    // --- Save folder and file name (status bar) ---
+    impl AppDelegate {
+        #[unsafe(method(chooseSaveFolder:))]
+        fn choose_save_folder(&self, _sender: &AnyObject) {
+            let mtm = MainThreadMarker::from(self);
+            #[allow(deprecated)]
+            NSApplication::sharedApplication(mtm).activateIgnoringOtherApps(true);
+
+            let panel = NSOpenPanel::openPanel(mtm);
+            panel.setCanChooseFiles(false);
+            panel.setCanChooseDirectories(true);
+            panel.setCanCreateDirectories(true);
+            panel.setAllowsMultipleSelection(false);
+            panel.setPrompt(Some(&NSString::from_str("Choose")));
+            panel.setMessage(Some(&NSString::from_str("Choose where captures are saved")));
+            let current = crate::prefs::save_folder().to_string_lossy().into_owned();
+            panel.setDirectoryURL(Some(&NSURL::fileURLWithPath(&NSString::from_str(&current))));
+            if panel.runModal() != NSModalResponseOK {
+                return;
+            }
+            let Some(path) = panel.URL().and_then(|url| url.path()) else {
+                return;
+            };
+            let folder = PathBuf::from(path.to_string());
+            crate::prefs::set_save_folder(&folder);
+            if let Some(sb) = self.ivars().status_bar.borrow().as_ref() {
+                sb.set_save_folder(&folder);
+            }
+            eprintln!("Save folder: {}", folder.display());
+        }
+
+        #[unsafe(method(editFilenameTemplate:))]
+        fn edit_filename_template(&self, _sender: &AnyObject) {
+            let mtm = MainThreadMarker::from(self);
+            #[allow(deprecated)]
+            NSApplication::sharedApplication(mtm).activateIgnoringOtherApps(true);
+
+            let alert = NSAlert::new(mtm);
+            alert.setMessageText(&NSString::from_str("File Name"));
+            alert.setInformativeText(&NSString::from_str(
+                "Name saved captures with {date}, {time}, and {seq} (a number that counts up \
+                 with each save). The file extension is added for you.",
+            ));
+            alert.addButtonWithTitle(&NSString::from_str("OK"));
+            alert.addButtonWithTitle(&NSString::from_str("Cancel"));
+            let field_frame = CGRect::new(CGPoint::ZERO, CGSize::new(300.0, 24.0));
+            let field: Retained<NSTextField> =
+                unsafe { msg_send![mtm.alloc::<NSTextField>(), initWithFrame: field_frame] };
+            field.setStringValue(&NSString::from_str(&crate::prefs::filename_template()));
+            field.setPlaceholderString(Some(&NSString::from_str(
+                crate::prefs::DEFAULT_FILENAME_TEMPLATE,
+            )));
+            alert.setAccessoryView(Some(&field));
+            alert.window().setInitialFirstResponder(Some(&field));
+            if alert.runModal() != NSAlertFirstButtonReturn {
+                return;
+            }
+            let template = field.stringValue().to_string();
+            crate::prefs::set_filename_template(template.trim());
+            eprintln!("File name template: {}", crate::prefs::filename_template());
+        }
+
+        #[unsafe(method(editUploadSettings:))]
+        fn edit_upload_settings(&self, _sender: &AnyObject) {
+            let mtm = MainThreadMarker::from(self);
+            #[allow(deprecated)]
+            NSApplication::sharedApplication(mtm).activateIgnoringOtherApps(true);
+
+            let alert = NSAlert::new(mtm);
+            alert.setMessageText(&NSString::from_str("Upload Settings"));
+            alert.setInformativeText(&NSString::from_str(
+                "The Upload button POSTs the capture as multipart form data (field \"file\") \
+                 to this URL and copies the first link in the response. The optional header \
+                 is sent with each upload, e.g. \"Authorization: Bearer <token>\".",
+            ));
+            alert.addButtonWithTitle(&NSString::from_str("OK"));
+            alert.addButtonWithTitle(&NSString::from_str("Cancel"));
+            let accessory = NSView::initWithFrame(
+                mtm.alloc(),
+                CGRect::new(CGPoint::ZERO, CGSize::new(300.0, 56.0)),
+            );
+            let url_frame = CGRect::new(CGPoint::new(0.0, 32.0), CGSize::new(300.0, 24.0));
+            let url_field: Retained<NSTextField> =
+                unsafe { msg_send![mtm.alloc::<NSTextField>(), initWithFrame: url_frame] };
+            url_field.setStringValue(&NSString::from_str(
+                &crate::prefs::upload_url().unwrap_or_default(),
+            ));
+            url_field.setPlaceholderString(Some(&NSString::from_str(
+                "https://example.com/upload",
+            )));
+            accessory.addSubview(&url_field);
+            // Masked, since the header usually carries a token
+            let header_frame = CGRect::new(CGPoint::ZERO, CGSize::new(300.0, 24.0));
+            let header_field: Retained<NSSecureTextField> = unsafe {
+                msg_send![mtm.alloc::<NSSecureTextField>(), initWithFrame: header_frame]
+            };
+            header_field.setStringValue(&NSString::from_str(
+                &crate::prefs::upload_auth_header().unwrap_or_default(),
+            ));
+            header_field.setPlaceholderString(Some(&NSString::from_str(
+                "Header (optional)",
+            )));
+            accessory.addSubview(&header_field);
+            alert.setAccessoryView(Some(&accessory));
+            alert.window().setInitialFirstResponder(Some(&url_field));
+            if alert.runModal() != NSAlertFirstButtonReturn {
+                return;
+            }
+            crate::prefs::set_upload_url(url_field.stringValue().to_string().trim());
+            let header = header_field.stringValue().to_string();
+            if let Err(e) = crate::prefs::set_upload_auth_header(header.trim()) {
+                eprintln!("Failed to save upload header: {}", e);
+            }
+            eprintln!("Upload URL: {}", crate::prefs::upload_url().unwrap_or_default());
+        }
+
+        #[unsafe(method(toggleAutoSave:))]
+        fn toggle_auto_save(&self, _sender: &AnyObject) {
+            let enabled = !crate::prefs::auto_save();
+            crate::prefs::set_auto_save(enabled);
+            if let Some(sb) = self.ivars().status_bar.borrow().as_ref() {
+                sb.set_auto_save(enabled);
+            }
+            eprintln!("Save without asking: {}", enabled);
+        }
+
+        #[unsafe(method(toggleNotifyOnSave:))]
+        fn toggle_notify_on_save(&self, _sender: &AnyObject) {
+            let enabled = !crate::prefs::notify_on_save();
+            crate::prefs::set_notify_on_save(enabled);
+            if let Some(sb) = self.ivars().status_bar.borrow().as_ref() {
+                sb.set_notify_on_save(enabled);
+            }
+            eprintln!("Notify when saved: {}", enabled);
+        }
+
+        #[unsafe(method(toggleSaveSound:))]
+        fn toggle_save_sound(&self, _sender: &AnyObject) {
+            let enabled = !crate::prefs::save_sound();
+            crate::prefs::set_save_sound(enabled);
+            if let Some(sb) = self.ivars().status_bar.borrow().as_ref() {
+                sb.set_save_sound(enabled);
+            }
+            eprintln!("Play sound when saved: {}", enabled);
+        }
+
+        #[unsafe(method(toggleRevealOnSave:))]
+        fn toggle_reveal_on_save(&self, _sender: &AnyObject) {
+            let enabled = !crate::prefs::reveal_on_save();
+            crate::prefs::set_reveal_on_save(enabled);
+            if let Some(sb) = self.ivars().status_bar.borrow().as_ref() {
+                sb.set_reveal_on_save(enabled);
+            }
+            eprintln!("Reveal in Finder when saved: {}", enabled);
+        }
+
+        #[unsafe(method(toggleCopyOnSave:))]
+        fn toggle_copy_on_save(&self, _sender: &AnyObject) {
+            let enabled = !crate::prefs::copy_on_save();
+            crate::prefs::set_copy_on_save(enabled);
+            if let Some(sb) = self.ivars().status_bar.borrow().as_ref() {
+                sb.set_copy_on_save(enabled);
+            }
+            eprintln!("Copy to clipboard when saving: {}", enabled);
+        }
+
+        #[unsafe(method(toggleCopySavedFile:))]
+        fn toggle_copy_saved_file(&self, _sender: &AnyObject) {
+            let enabled = !crate::prefs::copy_saved_file();
+            crate::prefs::set_copy_saved_file(enabled);
+            if let Some(sb) = self.ivars().status_bar.borrow().as_ref() {
+                sb.set_copy_saved_file(enabled);
+            }
+            eprintln!("Copy saved file to clipboard: {}", enabled);
+        }
+
+        #[unsafe(method(setImageFormat:))]
+        fn set_image_format_menu(&self, sender: &AnyObject) {
+            let tag: isize = unsafe { msg_send![sender, tag] };
+            let Some(&format) = ImageFormat::ALL.get(tag as usize) else {
+                return;
+            };
+            crate::prefs::set_image_format(format);
+            if let Some(sb) = self.ivars().status_bar.borrow().as_ref() {
+                sb.set_image_format(format);
+            }
+            eprintln!("Image format: {}", format.title());
+        }
+    }
+
+    // --- Recording length limit (status bar submenu) ---
+    impl AppDelegate {
+        #[unsafe(method(setRecordingLimit:))]
+        fn set_recording_limit_menu(&self, sender: &AnyObject) {
+            let tag: isize = unsafe { msg_send![sender, tag] };
+            let Some(&limit) = RecordingLimit::ALL.get(tag as usize) else {
+                return;
+            };
+            crate::prefs::set_recording_limit(limit);
+            if let Some(sb) = self.ivars().status_bar.borrow().as_ref() {
+                sb.set_recording_limit(limit);
+            }
+            eprintln!("Max recording length: {}", limit.title());
+        }
+    }
+
+    // --- Recording save panel ---
+    impl AppDelegate {
+        #[unsafe(method(recordingFormatChanged:))]
+        fn recording_format_changed(&self, sender: &NSPopUpButton) {
+            let Some(&format) = VideoFormat::ALL.get(sender.indexOfSelectedItem() as usize) else {
+                return;
+            };
+            let Some(window) = sender.window() else {
+                return;
+            };
+            let Some(panel) = window.downcast_ref::<NSSavePanel>() else {
+                return;
+            };
+            crate::actions::set_allowed_extension(panel, format.extension());
+            let name = panel.nameFieldStringValue().to_string();
+            let renamed = std::path::Path::new(&name).with_extension(format.extension());
+            panel.setNameFieldStringValue(&NSString::from_str(&renamed.to_string_lossy()));
+        }
+    }
+
+    // --- Image save panel (format pop-up, reached via the responder chain) ---
+    impl AppDelegate {
+        #[unsafe(method(imageFormatChanged:))]
+        fn image_format_changed(&self, sender: &NSPopUpButton) {
+            let Some(&format) = ImageFormat::ALL.get(sender.indexOfSelectedItem() as usize) else {
+                return;
+            };
+            let Some(window) = sender.window() else {
+                return;
+            };
+            let Some(panel) = window.downcast_ref::<NSSavePanel>() else {
+                return;
+            };
+            crate::actions::set_allowed_extension(panel, format.extension());
+            let name = panel.nameFieldStringValue().to_string();
+            let renamed = std::path::Path::new(&name).with_extension(format.extension());
+            panel.setNameFieldStringValue(&NSString::from_str(&renamed.to_string_lossy()));
+
+            // The quality slider only applies to JPEG
+            if let Some(accessory) = unsafe { sender.superview() } {
+                for view in accessory.subviews() {
+                    if let Some(slider) = view.downcast_ref::<NSSlider>() {
+                        slider.setEnabled(format == ImageFormat::Jpeg);
+                    }
+                }
+            }
+        }
+
+        #[unsafe(method(jpegQualityChanged:))]
+        fn jpeg_quality_changed(&self, sender: &NSSlider) {
+            let Some(accessory) = (unsafe { sender.superview() }) else {
+                return;
+            };
+            let Some(label) = accessory.viewWithTag(crate::actions::JPEG_QUALITY_LABEL_TAG) else {
+                return;
+            };
+            if let Some(label) = label.downcast_ref::<NSTextField>() {
+                let title = crate::actions::quality_title(sender.doubleValue());
+                label.setStringValue(&NSString::from_str(&title));
+            }
+        }
+    }
+
+    // --- Video quality (status bar submenu) ---
+    impl AppDelegate {
+        #[unsafe(method(setVideoQuality:))]
+        fn set_video_quality_menu(&self, sender: &AnyObject) {
+            let tag: isize = unsafe { msg_send![sender, tag] };
+            let Some(&quality) = VideoQuality::ALL.get(tag as usize) else {
+                return;
+            };
+            crate::prefs::set_video_quality(quality);
+            if let Some(sb) = self.ivars().status_bar.borrow().as_ref() {
+                sb.set_video_quality(quality);
+            }
+            eprintln!("Video quality: {}", quality.title());
+        }
+    }
+
+    // --- Video codec (status bar submenu) ---
+    impl AppDelegate {
+        #[unsafe(method(setVideoCodec:))]
+        fn set_video_codec_menu(&self, sender: &AnyObject) {
+            let tag: isize = unsafe { msg_send![sender, tag] };
+            let Some(&codec) = VideoCodec::ALL.get(tag as usize) else {
+                return;
+            };
+            crate::prefs::set_video_codec(codec);
+            if let Some(sb) = self.ivars().status_bar.borrow().as_ref() {
+                sb.set_video_codec(codec);
+            }
+            eprintln!("Video codec: {}", codec.title());
+        }
+    }
+
+    // --- Scroll capture settings (status bar) ---
+    impl AppDelegate {
+        #[unsafe(method(setScrollSettleDelay:))]
+        fn set_scroll_settle_delay_menu(&self, sender: &AnyObject) {
+            let tag: isize = unsafe { msg_send![sender, tag] };
+            let Some(&delay) = ScrollSettleDelay::ALL.get(tag as usize) else {
+                return;
+            };
+            crate::prefs::set_scroll_settle_delay(delay);
+            if let Some(sb) = self.ivars().status_bar.borrow().as_ref() {
+                sb.set_scroll_settle_delay(delay);
+            }
+            eprintln!("Scroll settle delay: {}", delay.title());
+        }
+
+        #[unsafe(method(setScrollStepLimit:))]
+        fn set_scroll_step_limit_menu(&self, sender: &AnyObject) {
+            let tag: isize = unsafe { msg_send![sender, tag] };
+            let Some(&limit) = ScrollStepLimit::ALL.get(tag as usize) else {
+                return;
+            };
+            crate::prefs::set_scroll_step_limit(limit);
+            if let Some(sb) = self.ivars().status_bar.borrow().as_ref() {
+                sb.set_scroll_step_limit(limit);
+            }
+            eprintln!("Scroll step limit: {}", limit.title());
+        }
+
+        #[unsafe(method(setScrollStepSize:))]
+        fn set_scroll_step_size_menu(&self, sender: &AnyObject) {
+            let tag: isize = unsafe { msg_send![sender, tag] };
+            let Some(&size) = ScrollStepSize::ALL.get(tag as usize) else {
+                return;
+            };
+            crate::prefs::set_scroll_step_size(size);
+            if let Some(sb) = self.ivars().status_bar.borrow().as_ref() {
+                sb.set_scroll_step_size(size);
+            }
+            eprintln!("Scroll step size: {}", size.title());
+        }
+
+        #[unsafe(method(setScrollFrozenTop:))]
+        fn set_scroll_frozen_top_menu(&self, sender: &AnyObject) {
+            let tag: isize = unsafe { msg_send![sender, tag] };
+            let Some(&margin) = FrozenMargin::ALL.get(tag as usize) else {
+                return;
+            };
+            crate::prefs::set_scroll_frozen_top(margin);
+            if let Some(sb) = self.ivars().status_bar.borrow().as_ref() {
+                sb.set_scroll_frozen_top(margin);
+            }
+            eprintln!("Scroll sticky header: {}", margin.title());
+        }
+
+        #[unsafe(method(setScrollFrozenBottom:))]
+        fn set_scroll_frozen_bottom_menu(&self, sender: &AnyObject) {
+            let tag: isize = unsafe { msg_send![sender, tag] };
+            let Some(&margin) = FrozenMargin::ALL.get(tag as usize) else {
+                return;
+            };
+            crate::prefs::set_scroll_frozen_bottom(margin);
+            if let Some(sb) = self.ivars().status_bar.borrow().as_ref() {
+                sb.set_scroll_frozen_bottom(margin);
+            }
+            eprintln!("Scroll sticky footer: {}", margin.title());
+        }
+    }
+
+    // --- Editor timeline unit (status bar) ---
+    impl AppDelegate {
+        #[unsafe(method(setTimelineUnit:))]
+        fn set_timeline_unit_menu(&self, sender: &AnyObject) {
+            let tag: isize = unsafe { msg_send![sender, tag] };
+            let Some(&unit) = TimelineUnit::ALL.get(tag as usize) else {
+                return;
+            };
+            crate::prefs::set_timeline_unit(unit);
+            if let Some(sb) = self.ivars().status_bar.borrow().as_ref() {
+                sb.set_timeline_unit(unit);
+            }
+            if let Some(ref editor) = *self.ivars().editor_window.borrow() {
+                editor.set_timeline_unit(unit, MainThreadMarker::from(self));
+            }
+            eprintln!("Editor timeline unit: {}", unit.title());
+        }
+    }
+
+    // --- Keyboard shortcuts (status bar) ---
+    impl AppDelegate {
+        #[unsafe(method(showKeyboardShortcuts:))]
+        fn show_keyboard_shortcuts(&self, _sender: &AnyObject) {
+            let mtm = MainThreadMarker::from(self);
+            let Some(hotkeys) = self
+                .ivars()
+                .hotkey_manager
+                .borrow()
+                .as_ref()
+                .map(|hk| HotkeyAction::ALL.map(|a| (hk.hotkey(a), hk.is_enabled(a))))
+            else {
+                return;
+            };
+            let mut shortcuts = self.ivars().shortcuts_window.borrow_mut();
+            shortcuts
+                .get_or_insert_with(|| ShortcutsWindow::new(mtm, hotkeys))
+                .show(mtm);
+        }
+
+        /// A recorder started listening: let its combos reach the window.
+        #[unsafe(method(shortcutRecordingBegan:))]
+        fn shortcut_recording_began(&self, _sender: &AnyObject) {
+            if let Some(hk) = self.ivars().hotkey_manager.borrow().as_ref() {
+                hk.suspend();
+            }
+        }
+
+        #[unsafe(method(shortcutRecorded:))]
+        fn shortcut_recorded(&self, _sender: &AnyObject) {
+            let mtm = MainThreadMarker::from(self);
+            let mut manager = self.ivars().hotkey_manager.borrow_mut();
+            let Some(hk) = manager.as_mut() else {
+                return;
+            };
+            hk.resume();
+
+            let recorded = self
+                .ivars()
+                .shortcuts_window
+                .borrow()
+                .as_ref()
+                .and_then(|w| w.take_recorded_hotkey());
+            let Some((action, hotkey)) = recorded else {
+                return;
+            };
+            if let Err(e) = hk.set_hotkey(action, hotkey) {
+                drop(manager);
+                eprintln!("Cannot use hotkey: {}", e);
+                crate::shortcuts::show_unavailable_alert(&e, mtm);
+                return;
+            }
+            let enabled = hk.is_enabled(action);
+            drop(manager);
+            crate::prefs::set_hotkey(action, hotkey);
+            if let Some(sb) = self.ivars().status_bar.borrow().as_ref() {
+                sb.set_hotkey(action, &hotkey, enabled);
+            }
+            if let Some(shortcuts) = self.ivars().shortcuts_window.borrow().as_ref() {
+                shortcuts.set_hotkey(action, hotkey);
+            }
+        }
+
+        #[unsafe(method(toggleHotkeyEnabled:))]
+        fn toggle_hotkey_enabled(&self, sender: &AnyObject) {
+            let mtm = MainThreadMarker::from(self);
+            let tag: isize = unsafe { msg_send![sender, tag] };
+            let Some(&action) = HotkeyAction::ALL.get(tag as usize) else {
+                return;
+            };
+            let state: isize = unsafe { msg_send![sender, state] };
+            let enabled = state == objc2_app_kit::NSControlStateValueOn;
+
+            let mut manager = self.ivars().hotkey_manager.borrow_mut();
+            let Some(hk) = manager.as_mut() else {
+                return;
+            };
+            let result = hk.set_enabled(action, enabled);
+            let hotkey = hk.hotkey(action);
+            drop(manager);
+            if let Err(e) = result {
+                eprintln!("Cannot enable hotkey: {}", e);
+                if let Some(shortcuts) = self.ivars().shortcuts_window.borrow().as_ref() {
+                    shortcuts.set_enabled(action, false);
+                }
+                crate::shortcuts::show_unavailable_alert(&e, mtm);
+                return;
+            }
+            crate::prefs::set_hotkey_enabled(action, enabled);
+            if let Some(sb) = self.ivars().status_bar.borrow().as_ref() {
+                sb.set_hotkey(action, &hotkey, enabled);
+            }
+            eprintln!("{} hotkey enabled: {}", action.title(), enabled);
+        }
+    }
+
+    // --- Microphone toggle (status bar) ---
+    impl AppDelegate {
+        #[unsafe(method(toggleRecordMicrophone:))]
+        fn toggle_record_microphone(&self, _sender: &AnyObject) {
+            let enabled = !crate::prefs::record_microphone();
+            crate::prefs::set_record_microphone(enabled);
+            if let Some(sb) = self.ivars().status_bar.borrow().as_ref() {
+                sb.set_record_microphone(enabled);
+            }
+            if enabled {
+                crate::microphone::request_access();
+            }
+            eprintln!("Record microphone: {}", enabled);
+        }
+    }
+
+    // --- Recording frame capture (called by NSTimer) ---
+    impl AppDelegate {
+        #[unsafe(method(captureRecordingFrame:))]
+        fn capture_recording_frame(&self, _timer: &NSObject) {
+            let mut state = self.ivars().recording_state.borrow_mut();
+            let Some(ref mut recording) = *state else {
+                return;
+            };
+            recording.capture_frame();
+            let limit_reached = recording.limit_reached();
+            let seconds_left = recording.seconds_left();
+            drop(state);
+
+            if limit_reached {
+                eprintln!("Recording length limit reached");
+                self.stop_recording();
+                return;
+            }
+            if let Some(seconds) = seconds_left
+                && let Some(sb) = self.ivars().status_bar.borrow().as_ref()
+            {
+                sb.set_recording_time_left(seconds);
+            }
+        }
+    }
+
+    // --- Stop recording (called from status bar menu) ---
+    impl AppDelegate {
+        #[unsafe(method(stopRecording:))]
+        fn stop_recording_action(&self, _sender: &AnyObject) {
+            self.stop_recording();
+        }
+    }
+
+    // --- Scroll capture timer (called by NSTimer) ---
+    impl AppDelegate {
+        #[unsafe(method(scrollCaptureTick:))]
+        fn scroll_capture_tick(&self, _timer: &NSObject) {
+            let mut state_ref = self.ivars().scroll_capture_state.borrow_mut();
             if let Some(ref mut state) = *state_ref {
-                if !state.tick() {
-                    drop(state_ref);
+                let frames_before = state.frames.len();
+                let keep_going = state.tick();
+                let frame_count = state.frames.len();
+                drop(state_ref);
+                if frame_count != frames_before {
+                    self.show_scroll_capture_progress(frame_count);
+                }
+                if !keep_going {
                     self.stop_scroll_capture();
                 }
             }
         }
+
+        /// Posted to the main queue when the background stitch finishes.
+        #[unsafe(method(scrollCaptureStitched:))]
+        fn scroll_capture_stitched(&self, _sender: Option<&AnyObject>) {
+            self.open_stitched_scroll_capture();
+        }
+
+        /// Posted to the main queue when a background Vision request finishes.
+        #[unsafe(method(visionRequestFinished:))]
+        fn vision_request_finished(&self, _sender: Option<&AnyObject>) {
+            self.finish_vision_request();
+        }
     }
 
     // --- Editor actions ---
@@ -476,6 +2054,15 @@ define_class!(
             }
         }
 
+        #[unsafe(method(editorPlaybackSpeedChanged:))]
+        fn editor_playback_speed_changed(&self, _sender: &AnyObject) {
+            let target: &AnyObject = unsafe { &*(self as *const Self as *const AnyObject) };
+            if let Some(ref editor) = *self.ivars().editor_window.borrow() {
+                editor.playback_speed_changed(target);
+                editor.window.makeFirstResponder(Some(&*editor.view));
+            }
+        }
+
         #[unsafe(method(editorAnnotationAdded:))]
         fn editor_annotation_added(&self, _sender: &AnyObject) {
             let mtm = MainThreadMarker::from(self);
@@ -486,6 +2073,16 @@ define_class!(
             }
         }
 
+        #[unsafe(method(editorAnnotationTextEdited:))]
+        fn editor_annotation_text_edited(&self, _sender: &AnyObject) {
+            let mtm = MainThreadMarker::from(self);
+            if let Some(ref editor) = *self.ivars().editor_window.borrow()
+                && let Some((idx, ann)) = editor.view.take_pending_text_edit()
+            {
+                editor.replace_annotation_text(idx, ann, mtm);
+            }
+        }
+
         #[unsafe(method(editorTimerTick:))]
         fn editor_timer_tick(&self, _timer: &NSObject) {
             let mtm = MainThreadMarker::from(self);
@@ -559,11 +2156,44 @@ define_class!(
             }
         }
 
-        #[unsafe(method(editorDeleteAnnotation:))]
-        fn editor_delete_annotation(&self, _sender: &AnyObject) {
+        #[unsafe(method(editorMarqueeSelection:))]
+        fn editor_marquee_selection(&self, _sender: &AnyObject) {
+            let mtm = MainThreadMarker::from(self);
+            if let Some(ref editor) = *self.ivars().editor_window.borrow() {
+                let indices = editor.view.marquee_selection();
+                editor.select_annotations(indices, mtm);
+            }
+        }
+
+        #[unsafe(method(editorDeleteAnnotation:))]
+        fn editor_delete_annotation(&self, _sender: &AnyObject) {
+            let mtm = MainThreadMarker::from(self);
+            if let Some(ref editor) = *self.ivars().editor_window.borrow() {
+                editor.delete_active_annotation(mtm);
+            }
+        }
+
+        #[unsafe(method(editorDuplicateAnnotation:))]
+        fn editor_duplicate_annotation(&self, _sender: &AnyObject) {
+            let mtm = MainThreadMarker::from(self);
+            if let Some(ref editor) = *self.ivars().editor_window.borrow() {
+                editor.duplicate_active_annotation(mtm);
+            }
+        }
+
+        #[unsafe(method(editorBringToFront:))]
+        fn editor_bring_to_front(&self, _sender: &AnyObject) {
             let mtm = MainThreadMarker::from(self);
             if let Some(ref editor) = *self.ivars().editor_window.borrow() {
-                editor.delete_active_annotation(mtm);
+                editor.reorder_active_annotation(true, mtm);
+            }
+        }
+
+        #[unsafe(method(editorSendToBack:))]
+        fn editor_send_to_back(&self, _sender: &AnyObject) {
+            let mtm = MainThreadMarker::from(self);
+            if let Some(ref editor) = *self.ivars().editor_window.borrow() {
+                editor.reorder_active_annotation(false, mtm);
             }
         }
 
@@ -571,11 +2201,14 @@ define_class!(
         fn editor_move_annotation(&self, dx: CGFloat, dy: CGFloat) {
             let mtm = MainThreadMarker::from(self);
             if let Some(ref editor) = *self.ivars().editor_window.borrow() {
-                let active = editor.state.borrow().active_annotation;
-                if let Some(idx) = active {
+                let indices = editor.state.borrow().selected_indices();
+                if !indices.is_empty() {
                     let mut state = editor.state.borrow_mut();
-                    if let Some(ta) = state.annotations.get_mut(idx) {
-                        ta.annotation.translate(dx, dy);
+                    state.begin_transform(&indices);
+                    for idx in indices {
+                        if let Some(ta) = state.annotations.get_mut(idx) {
+                            ta.annotation.translate(dx, dy);
+                        }
                     }
                     drop(state);
                     editor.display_current_frame(mtm);
@@ -583,6 +2216,13 @@ define_class!(
             }
         }
 
+        #[unsafe(method(editorTransformEnded:))]
+        fn editor_transform_ended(&self, _sender: &AnyObject) {
+            if let Some(ref editor) = *self.ivars().editor_window.borrow() {
+                editor.state.borrow_mut().finish_transform();
+            }
+        }
+
         #[unsafe(method(editorResizeAnnotation:x:y:))]
         fn editor_resize_annotation(&self, handle_val: u32, x: CGFloat, y: CGFloat) {
             let mtm = MainThreadMarker::from(self);
@@ -603,6 +2243,7 @@ define_class!(
                 let active = editor.state.borrow().active_annotation;
                 if let Some(idx) = active {
                     let mut state = editor.state.borrow_mut();
+                    state.begin_transform(&[idx]);
                     if let Some(ta) = state.annotations.get_mut(idx) {
                         ta.annotation.apply_resize(handle, CGPoint::new(x, y));
                     }
@@ -617,6 +2258,124 @@ define_class!(
             self.apply_crop();
         }
 
+        #[unsafe(method(editorSetTrimStart:))]
+        fn editor_set_trim_start(&self, _sender: &AnyObject) {
+            if let Some(ref editor) = *self.ivars().editor_window.borrow() {
+                editor.set_trim_start_at_current();
+            }
+        }
+
+        #[unsafe(method(editorSetTrimEnd:))]
+        fn editor_set_trim_end(&self, _sender: &AnyObject) {
+            if let Some(ref editor) = *self.ivars().editor_window.borrow() {
+                editor.set_trim_end_at_current();
+            }
+        }
+
+        #[unsafe(method(editorClearTrim:))]
+        fn editor_clear_trim(&self, _sender: &AnyObject) {
+            if let Some(ref editor) = *self.ivars().editor_window.borrow() {
+                editor.clear_trim();
+            }
+        }
+
+        #[unsafe(method(editorRotateLeft:))]
+        fn editor_rotate_left(&self, _sender: &AnyObject) {
+            self.transform_editor_image(ImageTransform::RotateLeft);
+        }
+
+        #[unsafe(method(editorRotateRight:))]
+        fn editor_rotate_right(&self, _sender: &AnyObject) {
+            self.transform_editor_image(ImageTransform::RotateRight);
+        }
+
+        #[unsafe(method(editorFlipHorizontal:))]
+        fn editor_flip_horizontal(&self, _sender: &AnyObject) {
+            self.transform_editor_image(ImageTransform::FlipHorizontal);
+        }
+
+        #[unsafe(method(editorFlipVertical:))]
+        fn editor_flip_vertical(&self, _sender: &AnyObject) {
+            self.transform_editor_image(ImageTransform::FlipVertical);
+        }
+
+        #[unsafe(method(editorGrayscale:))]
+        fn editor_grayscale(&self, _sender: &AnyObject) {
+            self.adjust_editor_image(Some(ImageAdjustment::Grayscale));
+        }
+
+        #[unsafe(method(editorInvertColors:))]
+        fn editor_invert_colors(&self, _sender: &AnyObject) {
+            self.adjust_editor_image(Some(ImageAdjustment::Invert));
+        }
+
+        #[unsafe(method(editorRemoveAdjustments:))]
+        fn editor_remove_adjustments(&self, _sender: &AnyObject) {
+            self.adjust_editor_image(None);
+        }
+
+        #[unsafe(method(editorAddBackground:))]
+        fn editor_add_background(&self, _sender: &AnyObject) {
+            let mtm = MainThreadMarker::from(self);
+            if !self.ivars().editor_window.borrow().as_ref().is_some_and(|e| e.is_single_frame) {
+                NSBeep();
+                return;
+            }
+            let Some(style) = self.ask_background_style(mtm) else {
+                return;
+            };
+            self.replace_editor_image("add background", |editor, mtm| {
+                editor.add_background(&style, mtm)
+            });
+        }
+
+        #[unsafe(method(editorAddWindowFrame:))]
+        fn editor_add_window_frame(&self, _sender: &AnyObject) {
+            let mtm = MainThreadMarker::from(self);
+            if !self.ivars().editor_window.borrow().as_ref().is_some_and(|e| e.is_single_frame) {
+                NSBeep();
+                return;
+            }
+            let Some(frame) = self.ask_window_frame(mtm) else {
+                return;
+            };
+            self.replace_editor_image("add window frame", |editor, mtm| {
+                editor.add_window_frame(frame, mtm)
+            });
+        }
+
+        #[unsafe(method(editorCycleFadeIn:))]
+        fn editor_cycle_fade_in(&self, _sender: &AnyObject) {
+            let mtm = MainThreadMarker::from(self);
+            if let Some(ref editor) = *self.ivars().editor_window.borrow() {
+                editor.cycle_active_fade(false, mtm);
+            }
+        }
+
+        #[unsafe(method(editorCycleFadeOut:))]
+        fn editor_cycle_fade_out(&self, _sender: &AnyObject) {
+            let mtm = MainThreadMarker::from(self);
+            if let Some(ref editor) = *self.ivars().editor_window.borrow() {
+                editor.cycle_active_fade(true, mtm);
+            }
+        }
+
+        #[unsafe(method(editorDiffThresholdUp:))]
+        fn editor_diff_threshold_up(&self, _sender: &AnyObject) {
+            let mtm = MainThreadMarker::from(self);
+            if let Some(ref editor) = *self.ivars().editor_window.borrow() {
+                editor.adjust_diff_threshold(8, mtm);
+            }
+        }
+
+        #[unsafe(method(editorDiffThresholdDown:))]
+        fn editor_diff_threshold_down(&self, _sender: &AnyObject) {
+            let mtm = MainThreadMarker::from(self);
+            if let Some(ref editor) = *self.ivars().editor_window.borrow() {
+                editor.adjust_diff_threshold(-8, mtm);
+            }
+        }
+
         #[unsafe(method(editorMiniBarChanged:))]
         fn editor_mini_bar_changed(&self, _sender: &AnyObject) {
             let mtm = MainThreadMarker::from(self);
@@ -627,6 +2386,16 @@ define_class!(
             }
         }
 
+        #[unsafe(method(editorThumbnailScrubbed:))]
+        fn editor_thumbnail_scrubbed(&self, _sender: &AnyObject) {
+            let mtm = MainThreadMarker::from(self);
+            if let Some(ref editor) = *self.ivars().editor_window.borrow()
+                && let Some(seek_frame) = editor.thumbnail_strip.take_pending_seek_frame()
+            {
+                editor.seek_to_frame(seek_frame, mtm);
+            }
+        }
+
         #[unsafe(method(editorMiniBarDragEnded:))]
         fn editor_mini_bar_drag_ended(&self, _sender: &AnyObject) {
             let mtm = MainThreadMarker::from(self);
@@ -656,33 +2425,132 @@ impl AppDelegate {
             overlay: RefCell::new(None),
             toolbar: RefCell::new(None),
             captured_image: RefCell::new(None),
+            capture_display: Cell::new(0),
             recording_mode: Cell::new(false),
             recording_state: RefCell::new(None),
             recording_border: RefCell::new(None),
             editor_window: RefCell::new(None),
             scroll_capture_mode: Cell::new(false),
+            scroll_capture_direction: Cell::new(ScrollDirection::Down),
+            scroll_capture_manual: Cell::new(false),
             scroll_capture_state: RefCell::new(None),
+            scroll_stitch_result: RefCell::new(None),
+            vision_request: RefCell::new(None),
+            capture_countdown: RefCell::new(None),
+            capture_countdown_left: Cell::new(0),
             editor_cancelled: Cell::new(false),
+            recording_thumbnail: RefCell::new(None),
+            tool_before_eyedropper: Cell::new(ActiveTool::Select),
+            command_palette: RefCell::new(None),
+            shortcuts_window: RefCell::new(None),
+            pinned_windows: RefCell::new(Vec::new()),
         });
         unsafe { msg_send![super(this), init] }
     }
 
+    /// Returns false (and logs why) if a screenshot can't start now: while
+    /// recording, editing, scroll capturing, or counting down to a delayed capture.
+    fn can_capture_screenshot(&self) -> bool {
+        if self.ivars().recording_state.borrow().is_some() {
+            eprintln!("Cannot capture screenshot while recording");
+            return false;
+        }
+        if self.ivars().editor_window.borrow().is_some() {
+            eprintln!("Cannot capture screenshot while editing");
+            return false;
+        }
+        if self.ivars().scroll_capture_state.borrow().is_some() {
+            eprintln!("Cannot capture screenshot while scroll capturing");
+            return false;
+        }
+        if self.ivars().capture_countdown.borrow().is_some() {
+            eprintln!("Cannot capture screenshot while a delayed capture is pending");
+            return false;
+        }
+        true
+    }
+
+    /// Stop a delayed capture's countdown and restore the menu bar item.
+    fn cancel_capture_countdown(&self) {
+        if let Some(timer) = self.ivars().capture_countdown.borrow_mut().take() {
+            timer.invalidate();
+        }
+        if let Some(sb) = self.ivars().status_bar.borrow().as_ref() {
+            sb.set_capture_countdown(MainThreadMarker::from(self), None);
+        }
+    }
+
     fn do_capture(&self) {
         let mtm = MainThreadMarker::from(self);
 
+        // Find the screen containing the mouse cursor
+        let screen = crate::screen::screen_with_mouse(mtm);
+        let display_id = crate::screen::display_with_mouse();
+
+        if let Some(cg_image) = crate::capture::capture_full_screen() {
+            let scale_factor = screen.backingScaleFactor();
+            self.show_capture_overlay(cg_image, screen.frame(), scale_factor, display_id);
+        }
+    }
+
+    /// Capture every display into one image and show the overlay across all of them.
+    fn do_capture_all_displays(&self) {
+        let frame = crate::screen::all_screens_frame(MainThreadMarker::from(self));
+        if let Some(cg_image) = crate::capture::capture_all_displays(frame) {
+            // Displays with different scale factors are captured at the highest one
+            let scale_factor = CGImage::width(Some(&cg_image)) as CGFloat / frame.size.width;
+            self.show_capture_overlay(cg_image, frame, scale_factor, ALL_DISPLAYS);
+        }
+    }
+
+    /// Show the selection overlay over `frame` (AppKit coordinates) with a fresh capture.
+    fn show_capture_overlay(
+        &self,
+        cg_image: CFRetained<CGImage>,
+        frame: CGRect,
+        scale_factor: CGFloat,
+        display_id: CGDirectDisplayID,
+    ) {
+        let mtm = MainThreadMarker::from(self);
+
         // Hide toolbar from previous session
         if let Some(toolbar) = self.ivars().toolbar.borrow().as_ref() {
             toolbar.hide();
         }
 
-        // Find the screen containing the mouse cursor
-        let screen = crate::screen::screen_with_mouse(mtm);
+        self.ivars().capture_display.set(display_id);
+        if let Some(overlay) = self.ivars().overlay.borrow().as_ref() {
+            overlay.show_with_screenshot(&cg_image, frame, scale_factor, mtm);
+            let last_selection = crate::prefs::last_selection(display_id);
+            overlay.view.ivars().last_selection.set(last_selection);
+            // Recordings, scroll captures, and instant post-capture actions need a real region
+            let full_screen_canvas = !self.ivars().recording_mode.get()
+                && !self.ivars().scroll_capture_mode.get()
+                && crate::prefs::post_capture_action() == PostCaptureAction::ShowToolbar;
+            overlay.view.ivars().full_screen_canvas.set(full_screen_canvas);
+        }
+        *self.ivars().captured_image.borrow_mut() = Some(cg_image);
+    }
 
-        if let Some(cg_image) = crate::capture::capture_full_screen() {
-            if let Some(overlay) = self.ivars().overlay.borrow().as_ref() {
-                overlay.show_with_screenshot(&cg_image, &screen, mtm);
-            }
-            *self.ivars().captured_image.borrow_mut() = Some(cg_image);
+    /// Switch the overlay to picking a window instead of drawing a region.
+    fn start_window_pick(&self) {
+        if let Some(overlay) = self.ivars().overlay.borrow().as_ref()
+            && overlay.window.isVisible()
+        {
+            overlay.view.pick_window();
+        }
+    }
+
+    /// Remember the overlay's selection for this display, so Cmd+R can restore it.
+    fn remember_selection(&self) {
+        let selection = self.ivars().overlay.borrow().as_ref().and_then(|overlay| {
+            overlay.view.ivars().selection.get().map(crate::overlay::view::normalize_rect)
+        });
+        if let Some(selection) = selection
+            && selection.size.width > 5.0
+            && selection.size.height > 5.0
+        {
+            crate::prefs::set_last_selection(self.ivars().capture_display.get(), selection);
         }
     }
 
@@ -698,6 +2566,10 @@ impl AppDelegate {
             eprintln!("Cannot start recording while editing");
             return;
         }
+        if self.ivars().capture_countdown.borrow().is_some() {
+            eprintln!("Cannot start recording while a delayed capture is pending");
+            return;
+        }
 
         // Set recording mode and show overlay for region selection
         self.ivars().recording_mode.set(true);
@@ -707,6 +2579,7 @@ impl AppDelegate {
 
     fn start_recording_with_selection(&self) {
         let mtm = MainThreadMarker::from(self);
+        self.remember_selection();
 
         // Read selection from overlay, and get display info from the overlay's screen
         let (selection, scale_factor, display_id, screen_frame) = {
@@ -723,7 +2596,7 @@ impl AppDelegate {
                 }
             };
             let sf = overlay.view.ivars().scale_factor.get();
-            let sf_frame = overlay.window.frame();
+            let sf_frame = overlay.frame();
             let did = crate::screen::display_with_mouse();
             (sel, sf, did, sf_frame)
         };
@@ -742,18 +2615,16 @@ impl AppDelegate {
         }
 
         // Create temp file path
-        let tmp_path = std::env::temp_dir().join(format!(
-            "clipshot_recording_{}.mp4",
-            std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap_or_default()
-                .as_secs()
-        ));
+        let tmp_path = temp_capture_path("clipshot_recording", "mp4");
 
         eprintln!("Starting recording: {}x{} -> {:?}", pixel_w, pixel_h, tmp_path);
 
         // Create encoder
-        let encoder = match crate::encoder::VideoEncoder::new(&tmp_path, pixel_w, pixel_h, 30) {
+        let quality = crate::prefs::video_quality();
+        let codec = crate::prefs::video_codec();
+        let encoder =
+            crate::encoder::VideoEncoder::new(&tmp_path, pixel_w, pixel_h, 30, quality, codec);
+        let mut encoder = match encoder {
             Ok(e) => e,
             Err(e) => {
                 eprintln!("Failed to create encoder: {}", e);
@@ -761,12 +2632,33 @@ impl AppDelegate {
             }
         };
 
+        // Add an audio track if microphone recording is on and allowed
+        if crate::prefs::record_microphone() {
+            if crate::microphone::is_authorized() {
+                if let Err(e) = encoder.add_audio_input() {
+                    eprintln!("Recording without audio: {}", e);
+                }
+            } else {
+                eprintln!("Recording without audio: microphone access not granted");
+                crate::microphone::request_access();
+            }
+        }
+
         // Start encoder
         let mut recording = RecordingState::new(encoder, selection, scale_factor, display_id);
+        recording.max_frames = crate::prefs::recording_limit()
+            .seconds()
+            .map(|secs| secs * recording.encoder.fps() as u64);
         if let Err(e) = recording.encoder.start() {
             eprintln!("Failed to start recording: {}", e);
             return;
         }
+        if let Some((input, clock_start)) = recording.encoder.audio_track() {
+            match crate::microphone::MicrophoneCapture::start(input, clock_start) {
+                Ok(mic) => recording.microphone = Some(mic),
+                Err(e) => eprintln!("Recording without audio: {}", e),
+            }
+        }
 
         // Start ~30fps timer for frame capture
         let target: &AnyObject = unsafe { &*(self as *const Self as *const AnyObject) };
@@ -813,6 +2705,11 @@ impl AppDelegate {
             timer.invalidate();
         }
 
+        // Stop the microphone before the audio track is closed
+        if let Some(mic) = recording.microphone.take() {
+            mic.stop();
+        }
+
         // Finish encoding
         recording.encoder.finish();
 
@@ -831,29 +2728,35 @@ impl AppDelegate {
         // Open the editor instead of showing save dialog directly
         if let Some(tmp_path) = &recording.output_path {
             self.open_editor(tmp_path, mtm);
+            // Added to the history when saved, so cancelled recordings are left out
+            let editor = self.ivars().editor_window.borrow();
+            *self.ivars().recording_thumbnail.borrow_mut() =
+                editor.as_ref().and_then(|e| e.decoder.frame_at(0)).map(CFRetained::from);
+        }
+    }
+
+    /// Make `editor` the open editor: leave editing mode when its window closes,
+    /// attach the toolbar above it, and bring back the remembered tool settings.
+    fn install_editor(&self, editor: EditorWindow) {
+        let center = objc2_foundation::NSNotificationCenter::defaultCenter();
+        let observer: &AnyObject = unsafe { &*(self as *const Self as *const AnyObject) };
+        unsafe {
+            center.addObserver_selector_name_object(
+                observer,
+                sel!(editorWindowClosed:),
+                Some(objc2_app_kit::NSWindowWillCloseNotification),
+                Some(&*editor.window),
+            );
         }
+
+        self.show_editor_toolbar(&editor);
+        *self.ivars().editor_window.borrow_mut() = Some(editor);
+        self.restore_annotation_settings(true);
     }
 
     fn open_editor(&self, video_path: &PathBuf, mtm: MainThreadMarker) {
         match EditorWindow::open(video_path, mtm) {
-            Ok(editor) => {
-                // Observe window close to exit editing mode
-                let center = objc2_foundation::NSNotificationCenter::defaultCenter();
-                let observer: &AnyObject =
-                    unsafe { &*(self as *const Self as *const AnyObject) };
-                unsafe {
-                    center.addObserver_selector_name_object(
-                        observer,
-                        sel!(editorWindowClosed:),
-                        Some(objc2_app_kit::NSWindowWillCloseNotification),
-                        Some(&*editor.window),
-                    );
-                }
-
-                // Show the toolbar attached above the editor window
-                self.show_editor_toolbar(&editor);
-                *self.ivars().editor_window.borrow_mut() = Some(editor);
-            }
+            Ok(editor) => self.install_editor(editor),
             Err(e) => {
                 eprintln!("Failed to open editor: {}", e);
                 // Fall back to save dialog for raw video
@@ -862,14 +2765,167 @@ impl AppDelegate {
         }
     }
 
+    /// Rotate or flip the editor's still image.
+    fn transform_editor_image(&self, transform: ImageTransform) {
+        self.replace_editor_image("transform image", |editor, mtm| {
+            editor.transform_image(transform, mtm)
+        });
+    }
+
+    /// Run an edit that swaps in a new, possibly resized, still image, then move the
+    /// toolbar to the resized window.
+    fn replace_editor_image(
+        &self,
+        action: &str,
+        edit: impl FnOnce(&mut EditorWindow, MainThreadMarker) -> Result<(), String>,
+    ) {
+        let mtm = MainThreadMarker::from(self);
+        // Committing a text field calls back into the delegate, so finish it first
+        if let Some(ref editor) = *self.ivars().editor_window.borrow() {
+            editor.view.commit_text_field();
+        }
+        let result = match self.ivars().editor_window.borrow_mut().as_mut() {
+            Some(editor) => edit(editor, mtm),
+            None => return,
+        };
+        if let Err(e) = result {
+            eprintln!("Failed to {}: {}", action, e);
+            return;
+        }
+        if let Some(ref editor) = *self.ivars().editor_window.borrow() {
+            self.show_editor_toolbar(editor);
+        }
+    }
+
+    /// Ask for the fill, padding, corner radius, and shadow of "Add Background".
+    fn ask_background_style(&self, mtm: MainThreadMarker) -> Option<BackgroundStyle> {
+        let alert = NSAlert::new(mtm);
+        alert.setMessageText(&NSString::from_str("Add Background"));
+        alert.setInformativeText(&NSString::from_str(
+            "Place the image on a larger canvas. Sizes are in image pixels.",
+        ));
+        alert.addButtonWithTitle(&NSString::from_str("Add"));
+        alert.addButtonWithTitle(&NSString::from_str("Cancel"));
+        let accessory = NSView::initWithFrame(
+            mtm.alloc(),
+            CGRect::new(CGPoint::ZERO, CGSize::new(260.0, 116.0)),
+        );
+        // Rows top to bottom (accessory view is not flipped)
+        let add_label = |title: &str, y: CGFloat| {
+            let label = NSTextField::labelWithString(&NSString::from_str(title), mtm);
+            label.setFrame(CGRect::new(CGPoint::new(0.0, y + 4.0), CGSize::new(100.0, 17.0)));
+            accessory.addSubview(&label);
+        };
+        let add_field = |value: &str, y: CGFloat| {
+            let frame = CGRect::new(CGPoint::new(104.0, y + 1.0), CGSize::new(60.0, 22.0));
+            let field: Retained<NSTextField> =
+                unsafe { msg_send![mtm.alloc::<NSTextField>(), initWithFrame: frame] };
+            field.setStringValue(&NSString::from_str(value));
+            accessory.addSubview(&field);
+            field
+        };
+        add_label("Background:", 90.0);
+        let popup = NSPopUpButton::initWithFrame_pullsDown(
+            mtm.alloc(),
+            CGRect::new(CGPoint::new(100.0, 88.0), CGSize::new(160.0, 26.0)),
+            false,
+        );
+        for (title, _) in BACKGROUND_FILLS {
+            popup.addItemWithTitle(&NSString::from_str(title));
+        }
+        accessory.addSubview(&popup);
+        add_label("Padding:", 60.0);
+        let padding_field = add_field("64", 60.0);
+        add_label("Corner radius:", 30.0);
+        let radius_field = add_field("12", 30.0);
+        let shadow = unsafe {
+            NSButton::checkboxWithTitle_target_action(
+                &NSString::from_str("Drop shadow"),
+                None,
+                None,
+                mtm,
+            )
+        };
+        shadow.setState(NSControlStateValueOn);
+        shadow.setFrameOrigin(CGPoint::new(102.0, 2.0));
+        accessory.addSubview(&shadow);
+        alert.setAccessoryView(Some(&accessory));
+        alert.window().setInitialFirstResponder(Some(&padding_field));
+        if alert.runModal() != NSAlertFirstButtonReturn {
+            return None;
+        }
+        let (_, fill) = *BACKGROUND_FILLS.get(popup.indexOfSelectedItem() as usize)?;
+        let padding = padding_field.stringValue().to_string().trim().parse().unwrap_or(64);
+        let corner_radius: CGFloat =
+            radius_field.stringValue().to_string().trim().parse().unwrap_or(12.0);
+        Some(BackgroundStyle {
+            padding: padding.min(2000),
+            corner_radius: corner_radius.max(0.0),
+            shadow: shadow.state() != NSControlStateValueOff,
+            fill,
+        })
+    }
+
+    /// Ask which chrome "Add Window Frame" should draw around the image.
+    fn ask_window_frame(&self, mtm: MainThreadMarker) -> Option<WindowFrame> {
+        let alert = NSAlert::new(mtm);
+        alert.setMessageText(&NSString::from_str("Add Window Frame"));
+        alert.setInformativeText(&NSString::from_str(
+            "Draw a title bar with window buttons above the image.",
+        ));
+        alert.addButtonWithTitle(&NSString::from_str("Add"));
+        alert.addButtonWithTitle(&NSString::from_str("Cancel"));
+        let popup = NSPopUpButton::initWithFrame_pullsDown(
+            mtm.alloc(),
+            CGRect::new(CGPoint::ZERO, CGSize::new(220.0, 26.0)),
+            false,
+        );
+        for frame in WindowFrame::ALL {
+            popup.addItemWithTitle(&NSString::from_str(frame.title()));
+        }
+        alert.setAccessoryView(Some(&popup));
+        if alert.runModal() != NSAlertFirstButtonReturn {
+            return None;
+        }
+        WindowFrame::ALL.get(popup.indexOfSelectedItem() as usize).copied()
+    }
+
+    /// Apply a color adjustment to the editor's still image, or remove them all with `None`.
+    fn adjust_editor_image(&self, adjustment: Option<ImageAdjustment>) {
+        let mtm = MainThreadMarker::from(self);
+        let result = match self.ivars().editor_window.borrow_mut().as_mut() {
+            Some(editor) => match adjustment {
+                Some(adjustment) => editor.adjust_image(adjustment, mtm),
+                None => editor.remove_adjustments(mtm),
+            },
+            None => return,
+        };
+        if let Err(e) = result {
+            eprintln!("Failed to adjust image: {}", e);
+        }
+    }
+
     fn show_editor_toolbar(&self, editor: &EditorWindow) {
         if let Some(toolbar) = self.ivars().toolbar.borrow().as_ref() {
-            // Position toolbar centered above the editor window with a small gap
+            // Position toolbar centered above the editor window with a small gap,
+            // or top-aligned to its left when vertical
             let editor_frame = editor.window.frame();
+            if let Some(screen) = editor.window.screen() {
+                toolbar.fit_within(screen.visibleFrame());
+            }
             let toolbar_size = toolbar.view.frame().size;
             let gap: CGFloat = 4.0;
-            let x = editor_frame.origin.x + (editor_frame.size.width - toolbar_size.width) / 2.0;
-            let y = editor_frame.origin.y + editor_frame.size.height + gap;
+            let (x, y) = if toolbar.view.is_vertical() {
+                (
+                    editor_frame.origin.x - gap - toolbar_size.width,
+                    editor_frame.origin.y + editor_frame.size.height - toolbar_size.height,
+                )
+            } else {
+                (
+                    editor_frame.origin.x + (editor_frame.size.width - toolbar_size.width) / 2.0,
+                    editor_frame.origin.y + editor_frame.size.height + gap,
+                )
+            };
             toolbar.panel.setFrameOrigin(objc2_core_foundation::CGPoint::new(x, y));
             toolbar.panel.orderFrontRegardless();
 
@@ -880,65 +2936,88 @@ impl AppDelegate {
         }
     }
 
-    fn export_editor(&self) {
+    /// Export the editor's recording (or image) through the save panel.
+    /// Returns where it was saved, or `None` if cancelled or failed.
+    fn export_editor(&self) -> Option<PathBuf> {
         let mtm = MainThreadMarker::from(self);
 
         let editor_ref = self.ivars().editor_window.borrow();
-        let Some(ref editor) = *editor_ref else {
-            return;
-        };
+        let editor = editor_ref.as_ref()?;
 
         // Single-frame mode: export as image instead of video
         let is_single_frame = editor.decoder.total_frames() == 1;
         if is_single_frame {
             drop(editor_ref);
-            self.export_editor_as_image();
-            return;
+            return self.export_editor_as_image();
         }
 
         // Commit any pending text field
         editor.view.commit_text_field();
 
+        // Ask for the destination first: the chosen format decides how to export.
+        // Cancelling returns to the editor.
+        drop(editor_ref);
+        let (dest, format) = self.run_recording_save_panel(mtm)?;
+        let editor_ref = self.ivars().editor_window.borrow();
+        let editor = editor_ref.as_ref()?;
+
         let state = editor.sessions();
         let video_path = state.video_path.clone();
         let annotations = &state.annotations;
+        let view_size = {
+            let vb = editor.view.bounds();
+            (vb.size.width, vb.size.height)
+        };
 
-        // Create temp file for export
-        let export_path = std::env::temp_dir().join(format!(
-            "clipshot_export_{}.mp4",
-            std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap_or_default()
-                .as_secs()
-        ));
-
-        // Check if there are any annotations at all
-        let has_annotations = state.has_any_annotations();
+        // Re-encode when there are annotations to burn in, a crop, or a trim to apply
+        let crop = editor
+            .view
+            .ivars()
+            .crop_rect
+            .get()
+            .map(crate::overlay::view::normalize_rect)
+            .or(state.crop());
+        let needs_export = state.has_any_annotations() || crop.is_some() || state.is_trimmed();
+        let save_raw = format == VideoFormat::Mp4 && !needs_export;
+        if !save_raw {
+            // The panel already confirmed replacing it; AVAssetWriter won't overwrite
+            let _ = std::fs::remove_file(&dest);
+        }
 
-        if has_annotations {
-            if let Err(e) =
+        let result = match format {
+            VideoFormat::Gif => crate::editor::export::export_gif(
+                &editor.decoder,
+                annotations,
+                &dest,
+                view_size,
+                crop,
+                state.trim_range(),
+            ),
+            // MP4 without annotations, crop, or trim: save the raw video directly
+            VideoFormat::Mp4 if save_raw => Ok(()),
+            VideoFormat::Mp4 | VideoFormat::WebM => {
                 crate::editor::export::export_with_annotations(
                     &editor.decoder,
                     annotations,
-                    &export_path,
-                    {
-                        let vb = editor.view.bounds();
-                        (vb.size.width, vb.size.height)
-                    },
+                    &dest,
+                    format,
+                    view_size,
+                    crop,
+                    state.trim_range(),
                 )
-            {
-                eprintln!("Export failed: {}", e);
-                drop(state);
-                drop(editor_ref);
-                // Fall back to saving raw video
-                self.close_editor_and_save_raw();
-                return;
             }
-        }
+        };
 
         drop(state);
         drop(editor_ref);
 
+        if let Err(e) = result {
+            eprintln!("Export failed: {}", e);
+            // Fall back to saving raw video
+            self.close_editor_and_save_raw();
+            return None;
+        }
+
         // Close editor
         if let Some(editor) = self.ivars().editor_window.borrow_mut().take() {
             // Remove notification observer
@@ -958,15 +3037,21 @@ impl AppDelegate {
             toolbar.hide();
         }
 
-        // Show save dialog
-        if has_annotations {
-            self.show_save_dialog_for_recording(&export_path, mtm);
+        let saved = if save_raw {
+            move_recording(&video_path, &dest)
+        } else {
+            eprintln!("Recording saved to {}", dest.display());
             // Clean up raw video
             let _ = std::fs::remove_file(&video_path);
-        } else {
-            // No annotations: save the raw video directly
-            self.show_save_dialog_for_recording(&video_path, mtm);
+            true
+        };
+        if !saved {
+            return None;
         }
+        copy_saved_recording(&dest);
+        self.add_recording_to_history(&dest);
+        announce_saved(&dest);
+        Some(dest)
     }
 
     /// Close the editor and discard everything (cancel). No save, no clipboard copy.
@@ -988,6 +3073,7 @@ impl AppDelegate {
             // Clean up temp video file
             let video_path = editor.state.borrow().video_path.clone();
             let _ = std::fs::remove_file(&video_path);
+            self.ivars().recording_thumbnail.borrow_mut().take();
 
             editor.close();
         }
@@ -1023,7 +3109,7 @@ impl AppDelegate {
                     if let Some(toolbar) = self.ivars().toolbar.borrow().as_ref() {
                         toolbar.hide();
                     }
-                    crate::actions::save_to_file(source_image, mtm);
+                    self.save_image_to_file(source_image, mtm);
                     return;
                 }
             }
@@ -1040,46 +3126,109 @@ impl AppDelegate {
     }
 
     fn show_save_dialog_for_recording(&self, tmp_path: &PathBuf, mtm: MainThreadMarker) {
+        if crate::prefs::auto_save() {
+            let dest = crate::actions::default_save_path("mp4");
+            if move_recording(tmp_path, &dest) {
+                copy_saved_recording(&dest);
+                self.add_recording_to_history(&dest);
+                announce_saved(&dest);
+            }
+            return;
+        }
+
         let panel = NSSavePanel::new(mtm);
-        panel.setNameFieldStringValue(&NSString::from_str("recording.mp4"));
+        crate::actions::prepare_save_panel(&panel, "mp4");
 
         let response = panel.runModal();
         if response == NSModalResponseOK {
             if let Some(url) = panel.URL() {
                 if let Some(path) = url.path() {
-                    let dest = path.to_string();
-                    if let Err(e) = std::fs::rename(tmp_path, &dest) {
-                        // rename may fail across filesystems, try copy
-                        if let Err(e2) = std::fs::copy(tmp_path, &dest) {
-                            eprintln!("Failed to save recording: rename={}, copy={}", e, e2);
-                        } else {
-                            let _ = std::fs::remove_file(tmp_path);
-                            eprintln!("Recording saved to {}", dest);
-                        }
-                    } else {
-                        eprintln!("Recording saved to {}", dest);
+                    let dest = PathBuf::from(path.to_string());
+                    if move_recording(tmp_path, &dest) {
+                        copy_saved_recording(&dest);
+                        self.add_recording_to_history(&dest);
+                        announce_saved(&dest);
                     }
                 }
             }
         }
         // Clean up temp file if not saved
         let _ = std::fs::remove_file(tmp_path);
+        self.ivars().recording_thumbnail.borrow_mut().take();
+    }
+
+    /// Run a save panel with a format pop-up for an edited recording.
+    /// Returns the destination (with the format's extension) and the format.
+    /// With auto-save on, skips the panel and exports MP4 to the save folder.
+    fn run_recording_save_panel(&self, mtm: MainThreadMarker) -> Option<(PathBuf, VideoFormat)> {
+        if crate::prefs::auto_save() {
+            let format = VideoFormat::Mp4;
+            return Some((crate::actions::default_save_path(format.extension()), format));
+        }
+
+        let panel = NSSavePanel::new(mtm);
+        crate::actions::prepare_save_panel(&panel, VideoFormat::Mp4.extension());
+        panel.setExtensionHidden(false);
+
+        // Format pop-up in the accessory view; recordingFormatChanged: keeps the
+        // file name's extension and the panel's allowed type in step with the selection.
+        let accessory = NSView::initWithFrame(
+            mtm.alloc(),
+            CGRect::new(CGPoint::ZERO, CGSize::new(260.0, 40.0)),
+        );
+        let label = NSTextField::labelWithString(&NSString::from_str("Format:"), mtm);
+        label.setFrame(CGRect::new(CGPoint::new(20.0, 11.0), CGSize::new(60.0, 17.0)));
+        accessory.addSubview(&label);
+        let popup = NSPopUpButton::initWithFrame_pullsDown(
+            mtm.alloc(),
+            CGRect::new(CGPoint::new(80.0, 6.0), CGSize::new(160.0, 26.0)),
+            false,
+        );
+        for format in VideoFormat::ALL {
+            popup.addItemWithTitle(&NSString::from_str(format.title()));
+        }
+        unsafe {
+            popup.setAction(Some(sel!(recordingFormatChanged:)));
+            popup.setTarget(Some(self));
+        }
+        accessory.addSubview(&popup);
+        panel.setAccessoryView(Some(&accessory));
+
+        if panel.runModal() != NSModalResponseOK {
+            return None;
+        }
+        let path = panel.URL()?.path()?.to_string();
+        let format = VideoFormat::ALL
+            .get(popup.indexOfSelectedItem() as usize)
+            .copied()
+            .unwrap_or(VideoFormat::Mp4);
+        Some((PathBuf::from(path), format))
+    }
+
+    /// Bring back the tool, color, and stroke width remembered from last time.
+    /// The tool is left alone for the overlay, which must start out selecting a region.
+    fn restore_annotation_settings(&self, with_tool: bool) {
+        if let Some(color) = crate::prefs::annotation_color() {
+            self.set_preset_color(color);
+        }
+        if let Some(index) = crate::prefs::annotation_stroke() {
+            self.set_stroke_width(index);
+        }
+        if with_tool && let Some(tool) = crate::prefs::annotation_tool() {
+            self.set_active_tool(tool);
+        }
     }
 
     fn set_active_tool(&self, tool: ActiveTool) {
         // Map ActiveTool to toolbar button index
-        let tool_index = match tool {
-            ActiveTool::Select => 0,
-            ActiveTool::Arrow => 1,
-            ActiveTool::Rectangle => 2,
-            ActiveTool::Ellipse => 3,
-            ActiveTool::Pencil => 4,
-            ActiveTool::Text => 5,
-            ActiveTool::Highlight => 6,
-            ActiveTool::Step => 7,
-            ActiveTool::Blur => 8,
-            ActiveTool::Crop => 9,
-        };
+        let tool_index = ActiveTool::ALL.iter().position(|&t| t == tool).unwrap_or(0);
+        if tool != ActiveTool::Eyedropper {
+            self.ivars().tool_before_eyedropper.set(tool);
+        }
+        // Eyedropper and Crop are one-off steps, not a tool to come back to
+        if !matches!(tool, ActiveTool::Eyedropper | ActiveTool::Crop) {
+            crate::prefs::set_annotation_tool(tool);
+        }
         // Update toolbar visual state
         if let Some(toolbar) = self.ivars().toolbar.borrow().as_ref() {
             toolbar.view.set_active_tool(tool_index);
@@ -1088,11 +3237,19 @@ impl AppDelegate {
             editor.view.commit_text_field();
             editor.view.ivars().active_tool.set(tool);
             // Auto-set crop rect to full view bounds when activating crop tool
+            // Start from the applied crop, if any, so it can be adjusted
             if tool == ActiveTool::Crop && editor.view.ivars().crop_rect.get().is_none() {
-                let bounds = editor.view.bounds();
-                editor.view.ivars().crop_rect.set(Some(bounds));
+                let start = editor.sessions().crop().unwrap_or_else(|| editor.view.bounds());
+                editor.view.set_crop_rect(Some(start));
                 editor.view.setNeedsDisplay(true);
             }
+            // Keep the view's aspect lock in step with the toolbar picker
+            if tool == ActiveTool::Crop
+                && let Some(toolbar) = self.ivars().toolbar.borrow().as_ref()
+            {
+                editor.view.set_crop_aspect(toolbar.view.crop_aspect());
+            }
+            editor.view.set_crop_size_bar_visible(tool == ActiveTool::Crop);
             return;
         }
         if let Some(overlay) = self.ivars().overlay.borrow().as_ref() {
@@ -1101,19 +3258,37 @@ impl AppDelegate {
         }
     }
 
-    fn set_stroke_width(&self, width: CGFloat, font_size: CGFloat, stroke_index: usize) {
+    fn set_stroke_width(&self, stroke_index: usize) {
+        let (width, font_size) = crate::toolbar::view::STROKE_WIDTHS[stroke_index];
+        crate::prefs::set_annotation_stroke(stroke_index);
         // Update toolbar visual state
         if let Some(toolbar) = self.ivars().toolbar.borrow().as_ref() {
             toolbar.view.set_active_stroke(stroke_index);
         }
+        // The same three buttons pick fine/medium/coarse pixelation for Blur
+        let block_size = crate::toolbar::view::BLUR_BLOCK_SIZES[stroke_index];
         if let Some(ref editor) = *self.ivars().editor_window.borrow() {
             editor.view.ivars().annotation_width.set(width);
             editor.view.ivars().annotation_font_size.set(font_size);
+            editor.view.ivars().annotation_block_size.set(block_size);
+            let mtm = MainThreadMarker::from(self);
+            editor.set_active_blur_block_size(block_size, mtm);
+            editor.set_active_stroke_width(width, font_size, mtm);
             return;
         }
         if let Some(overlay) = self.ivars().overlay.borrow().as_ref() {
             overlay.view.ivars().annotation_width.set(width);
             overlay.view.ivars().annotation_font_size.set(font_size);
+            overlay.view.ivars().annotation_block_size.set(block_size);
+            // A selected annotation takes the new width (block size, font size) too
+            if let Some(idx) = overlay.view.ivars().active_annotation_index.get()
+                && let Some(ann) = overlay.view.ivars().annotations.borrow_mut().get_mut(idx)
+                && (ann.set_block_size(block_size)
+                    || ann.set_stroke_width(width)
+                    || ann.set_font_size(font_size))
+            {
+                overlay.view.setNeedsDisplay(true);
+            }
         }
     }
 
@@ -1130,17 +3305,65 @@ impl AppDelegate {
         }
     }
 
-    fn set_annotation_color(&self, color: (f64, f64, f64)) {
+    fn set_preset_color(&self, color: (f64, f64, f64)) {
+        self.set_annotation_color(color);
+        if let Some(toolbar) = self.ivars().toolbar.borrow().as_ref() {
+            toolbar.view.set_color(color.0, color.1, color.2);
+        }
+    }
+
+    fn set_annotation_color(&self, color: (f64, f64, f64)) {
+        crate::prefs::set_annotation_color(color);
+        // A color picked while redacting replaces the default black for new bars
+        if let Some(ref editor) = *self.ivars().editor_window.borrow() {
+            editor.view.ivars().annotation_color.set(color);
+            if editor.view.ivars().active_tool.get() == ActiveTool::Redact {
+                editor.view.ivars().annotation_redact_color.set(color);
+            }
+            editor.set_active_color(color, MainThreadMarker::from(self));
+            return;
+        }
+        if let Some(overlay) = self.ivars().overlay.borrow().as_ref() {
+            overlay.view.ivars().annotation_color.set(color);
+            if overlay.view.ivars().active_tool.get() == ActiveTool::Redact {
+                overlay.view.ivars().annotation_redact_color.set(color);
+            }
+            // Recolor the selected annotation as well
+            if let Some(idx) = overlay.view.ivars().active_annotation_index.get()
+                && let Some(ann) = overlay.view.ivars().annotations.borrow_mut().get_mut(idx)
+                && ann.set_color(color)
+            {
+                overlay.view.setNeedsDisplay(true);
+            }
+        }
+    }
+
+    /// Use the toolbar's bold/italic styling for new text labels and the selected one.
+    fn apply_font_traits(&self) {
+        let Some(traits) = self.ivars().toolbar.borrow().as_ref().map(|t| t.view.font_traits())
+        else {
+            return;
+        };
         if let Some(ref editor) = *self.ivars().editor_window.borrow() {
-            editor.view.ivars().annotation_color.set(color);
+            editor.view.ivars().annotation_font_traits.set(traits);
+            editor.set_active_font_traits(traits, MainThreadMarker::from(self));
             return;
         }
         if let Some(overlay) = self.ivars().overlay.borrow().as_ref() {
-            overlay.view.ivars().annotation_color.set(color);
+            overlay.view.ivars().annotation_font_traits.set(traits);
+            if let Some(idx) = overlay.view.ivars().active_annotation_index.get()
+                && let Some(ann) = overlay.view.ivars().annotations.borrow_mut().get_mut(idx)
+                && ann.set_font_traits(traits)
+            {
+                overlay.view.setNeedsDisplay(true);
+            }
         }
     }
 
     fn dismiss_all(&self) {
+        if let Some(palette) = self.ivars().command_palette.borrow().as_ref() {
+            palette.hide();
+        }
         if let Some(overlay) = self.ivars().overlay.borrow().as_ref() {
             overlay.hide();
         }
@@ -1176,18 +3399,28 @@ impl AppDelegate {
             if let Some(selection) = overlay.view.ivars().selection.get() {
                 let norm = crate::overlay::view::normalize_rect(selection);
                 if norm.size.width > 5.0 && norm.size.height > 5.0 {
-                    // Use the overlay window's screen for toolbar positioning
-                    let screen_frame = overlay.window.frame();
-                    toolbar.show_near_selection(norm, screen_frame);
+                    // Keep the toolbar on the screen showing the selection
+                    let screen_frame = overlay.frame();
+                    let window = overlay.window_for(norm);
+                    let visible_frame =
+                        window.screen().map_or(screen_frame, |s| s.visibleFrame());
+                    if let Some(parent) = toolbar.panel.parentWindow()
+                        && parent != window
+                    {
+                        parent.removeChildWindow(&toolbar.panel);
+                    }
+                    toolbar.show_near_selection(norm, screen_frame, visible_frame);
                     let _: () = unsafe {
-                        msg_send![&*overlay.window, addChildWindow: &*toolbar.panel, ordered: 1i64]
+                        msg_send![&*window, addChildWindow: &*toolbar.panel, ordered: 1i64]
                     };
                 }
             }
         }
     }
 
-    fn handle_scroll_capture_hotkey(&self) {
+    /// Select a region for scroll capture. `manual` captures while the user
+    /// scrolls instead of scrolling automatically.
+    fn handle_scroll_capture_hotkey(&self, direction: ScrollDirection, manual: bool) {
         // Don't start scroll capture while recording or editing
         if self.ivars().recording_state.borrow().is_some() {
             eprintln!("Cannot start scroll capture while recording");
@@ -1197,9 +3430,19 @@ impl AppDelegate {
             eprintln!("Cannot start scroll capture while editing");
             return;
         }
+        if self.ivars().scroll_stitch_result.borrow().is_some() {
+            eprintln!("Cannot start scroll capture while stitching");
+            return;
+        }
+        if self.ivars().capture_countdown.borrow().is_some() {
+            eprintln!("Cannot start scroll capture while a delayed capture is pending");
+            return;
+        }
 
         // Set scroll capture mode and show overlay for region selection
         self.ivars().scroll_capture_mode.set(true);
+        self.ivars().scroll_capture_direction.set(direction);
+        self.ivars().scroll_capture_manual.set(manual);
         if let Some(toolbar) = self.ivars().toolbar.borrow().as_ref() {
             toolbar.set_non_confirm_buttons_enabled(false);
         }
@@ -1209,6 +3452,7 @@ impl AppDelegate {
 
     fn start_scroll_capture_with_selection(&self) {
         let mtm = MainThreadMarker::from(self);
+        self.remember_selection();
 
         // Read selection from overlay, and get display info from the overlay's screen
         let (selection, scale_factor, display_id, screen_frame) = {
@@ -1225,7 +3469,7 @@ impl AppDelegate {
                 }
             };
             let sf = overlay.view.ivars().scale_factor.get();
-            let sf_frame = overlay.window.frame();
+            let sf_frame = overlay.frame();
             let did = crate::screen::display_with_mouse();
             (sel, sf, did, sf_frame)
         };
@@ -1247,7 +3491,14 @@ impl AppDelegate {
             scale_factor,
             screen_origin,
             display_id,
+            self.ivars().scroll_capture_direction.get(),
+            self.ivars().scroll_capture_manual.get(),
         );
+        if !state.manual {
+            state.settle_delay = crate::prefs::scroll_settle_delay().seconds();
+        }
+        state.max_steps = crate::prefs::scroll_step_limit().steps();
+        state.set_scroll_fraction(crate::prefs::scroll_step_size().fraction());
 
         // Exclude the border window from screen captures
         if let Some(border) = self.ivars().recording_border.borrow().as_ref() {
@@ -1272,12 +3523,26 @@ impl AppDelegate {
 
         *self.ivars().scroll_capture_state.borrow_mut() = Some(state);
 
-        // Show status bar recording indicator
+        // Show status bar recording indicator, with the frame count in place of the clock
         if let Some(sb) = self.ivars().status_bar.borrow().as_ref() {
             sb.enter_recording_mode(mtm);
         }
+        self.show_scroll_capture_progress(0);
 
-        eprintln!("Scroll capture started");
+        eprintln!(
+            "Scroll capture started ({:?}{})",
+            self.ivars().scroll_capture_direction.get(),
+            if self.ivars().scroll_capture_manual.get() { ", manual" } else { "" }
+        );
+    }
+
+    /// Show the number of frames captured so far in the status bar.
+    fn show_scroll_capture_progress(&self, frame_count: usize) {
+        let mtm = MainThreadMarker::from(self);
+        if let Some(sb) = self.ivars().status_bar.borrow().as_ref() {
+            let plural = if frame_count == 1 { "" } else { "s" };
+            sb.set_recording_text(mtm, &format!("{} frame{}", frame_count, plural));
+        }
     }
 
     fn stop_scroll_capture(&self) {
@@ -1298,87 +3563,195 @@ impl AppDelegate {
             border.hide();
         }
 
-        // Exit recording mode in status bar
-        if let Some(sb) = self.ivars().status_bar.borrow().as_ref() {
-            sb.exit_recording_mode(mtm);
-        }
-
         let frame_count = state.frames.len();
         eprintln!("Scroll capture stopped: {} frames captured", frame_count);
 
         if frame_count == 0 {
             eprintln!("No frames captured");
+            if let Some(sb) = self.ivars().status_bar.borrow().as_ref() {
+                sb.exit_recording_mode(mtm);
+            }
+            return;
+        }
+
+        // Stitch on a background thread (tall captures take a while), keeping
+        // the status bar in recording mode until the editor opens
+        if let Some(sb) = self.ivars().status_bar.borrow().as_ref() {
+            sb.set_recording_text(mtm, "Stitching\u{2026}");
+        }
+        state.arrange_top_to_bottom();
+        let horizontal = state.direction.is_horizontal();
+        let frozen_top = frozen_band(crate::prefs::scroll_frozen_top(), state.scale_factor);
+        let frozen_bottom = frozen_band(crate::prefs::scroll_frozen_bottom(), state.scale_factor);
+        let frames = std::mem::take(&mut state.frames);
+        let frame_rgba = std::mem::take(&mut state.frame_rgba);
+        let (sender, receiver) = mpsc::channel();
+        *self.ivars().scroll_stitch_result.borrow_mut() = Some(receiver);
+        std::thread::spawn(move || {
+            // Stitch frames using pre-captured RGBA data for overlap detection. A panic
+            // still reports back, so the main thread always leaves "Stitching…"
+            let stitched = std::panic::catch_unwind(AssertUnwindSafe(|| {
+                if horizontal {
+                    crate::stitch::stitch_frames_horizontally(&frames, &frame_rgba)
+                } else {
+                    crate::stitch::stitch_frames(&frames, &frame_rgba, frozen_top, frozen_bottom)
+                }
+            }));
+            let stitched = match stitched {
+                Ok(Some(image)) => Ok(image),
+                Ok(None) => Err("Failed to stitch frames".to_string()),
+                Err(_) => Err("Stitching crashed".to_string()),
+            };
+            let _ = sender.send(stitched);
+            dispatch2::DispatchQueue::main().exec_async(|| {
+                let Some(mtm) = MainThreadMarker::new() else {
+                    return;
+                };
+                let app = NSApplication::sharedApplication(mtm);
+                if let Some(delegate) = app.delegate() {
+                    let _: () = unsafe {
+                        msg_send![&*delegate, scrollCaptureStitched: std::ptr::null::<AnyObject>()]
+                    };
+                }
+            });
+        });
+    }
+
+    /// Read text or codes off the editor's current frame, or else the overlay
+    /// selection, on a background thread so Vision doesn't stall the UI.
+    fn start_vision_request(&self, read: VisionRead) {
+        // One at a time; the result closes the selection it was read from
+        if self.ivars().vision_request.borrow().is_some() {
+            return;
+        }
+
+        // In the editor, read the frame on screen and leave the editor open
+        let editor_frame = self.ivars().editor_window.borrow().as_ref().map(|editor| {
+            let frame = editor.sessions().current_frame;
+            editor.decoder.frame_at(frame).map(CFRetained::from)
+        });
+        let (image, capture) = match editor_frame {
+            Some(Some(image)) => (image, None),
+            Some(None) => return,
+            None => {
+                let Some(image) = self.get_final_image() else {
+                    return;
+                };
+                (image, self.ivars().captured_image.borrow().clone())
+            }
+        };
+
+        let (sender, receiver) = mpsc::channel();
+        *self.ivars().vision_request.borrow_mut() =
+            Some(VisionRequest { result: receiver, capture });
+        std::thread::spawn(move || {
+            let result = objc2::rc::autoreleasepool(|_| match read {
+                VisionRead::Text => VisionResult::Text(crate::ocr::recognize_text(&image)),
+                VisionRead::Codes => {
+                    VisionResult::Codes(crate::barcode::detect_payloads(&image))
+                }
+            });
+            let _ = sender.send(result);
+            dispatch2::DispatchQueue::main().exec_async(|| {
+                let Some(mtm) = MainThreadMarker::new() else {
+                    return;
+                };
+                let app = NSApplication::sharedApplication(mtm);
+                if let Some(delegate) = app.delegate() {
+                    let _: () = unsafe {
+                        msg_send![&*delegate, visionRequestFinished: std::ptr::null::<AnyObject>()]
+                    };
+                }
+            });
+        });
+    }
+
+    /// Copy what the background Vision request read, closing the overlay selection
+    /// it came from once something is copied.
+    fn finish_vision_request(&self) {
+        let Some(request) = self.ivars().vision_request.borrow_mut().take() else {
             return;
+        };
+        let copied = match request.result.try_recv() {
+            Ok(VisionResult::Text(result)) => copy_recognized_text(result),
+            Ok(VisionResult::Codes(result)) => copy_barcode_payload(result),
+            Err(_) => false,
+        };
+
+        // Keep the selection up when there's nothing to copy, to adjust and retry
+        let same_capture = match (&request.capture, &*self.ivars().captured_image.borrow()) {
+            (Some(from), Some(current)) => std::ptr::eq::<CGImage>(&**from, &**current),
+            _ => false,
+        };
+        if copied && same_capture {
+            self.remember_selection();
+            self.dismiss_all();
         }
+    }
 
-        // Stitch frames using pre-captured RGBA data for overlap detection
-        let stitched = crate::stitch::stitch_frames(&state.frames, &state.frame_rgba);
-        let Some(stitched) = stitched else {
-            eprintln!("Failed to stitch frames");
+    /// Leave recording mode and open the stitched scroll capture in the editor.
+    fn open_stitched_scroll_capture(&self) {
+        let mtm = MainThreadMarker::from(self);
+
+        let Some(receiver) = self.ivars().scroll_stitch_result.borrow_mut().take() else {
             return;
         };
 
+        if let Some(sb) = self.ivars().status_bar.borrow().as_ref() {
+            sb.exit_recording_mode(mtm);
+        }
+
+        let stitched = receiver
+            .try_recv()
+            .map_err(|_| "Stitching ended without a result".to_string())
+            .and_then(|result| result);
+        let stitched = match stitched {
+            Ok(stitched) => stitched,
+            Err(e) => {
+                eprintln!("{}", e);
+                crate::notify::show("Scroll Capture Failed", &e);
+                return;
+            }
+        };
+
         let width = CGImage::width(Some(&stitched));
         let height = CGImage::height(Some(&stitched));
         eprintln!("Stitched image: {}x{}", width, height);
+        self.add_screenshot_to_history(&stitched);
 
         // Create decoder from stitched image and open editor
         let decoder = crate::editor::decoder::VideoDecoder::from_image(stitched);
 
         // Use a temporary path for the editor state
-        let tmp_path = std::env::temp_dir().join(format!(
-            "clipshot_scroll_{}.png",
-            std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap_or_default()
-                .as_secs()
-        ));
+        let tmp_path = temp_capture_path("clipshot_scroll", "png");
 
         match EditorWindow::open_with_decoder(decoder, "Edit Scroll Capture", &tmp_path, mtm) {
-            Ok(editor) => {
-                // Observe window close
-                let center = objc2_foundation::NSNotificationCenter::defaultCenter();
-                let observer: &AnyObject =
-                    unsafe { &*(self as *const Self as *const AnyObject) };
-                unsafe {
-                    center.addObserver_selector_name_object(
-                        observer,
-                        sel!(editorWindowClosed:),
-                        Some(objc2_app_kit::NSWindowWillCloseNotification),
-                        Some(&*editor.window),
-                    );
-                }
-
-                self.show_editor_toolbar(&editor);
-                *self.ivars().editor_window.borrow_mut() = Some(editor);
-            }
+            Ok(editor) => self.install_editor(editor),
             Err(e) => {
                 eprintln!("Failed to open editor for scroll capture: {}", e);
             }
         }
     }
 
-    fn export_editor_as_image(&self) {
+    fn export_editor_as_image(&self) -> Option<PathBuf> {
         let mtm = MainThreadMarker::from(self);
 
         let editor_ref = self.ivars().editor_window.borrow();
-        let Some(ref editor) = *editor_ref else {
-            return;
-        };
+        let editor = editor_ref.as_ref()?;
 
         // Commit any pending text field
         editor.view.commit_text_field();
 
         let Some(source_image) = editor.decoder.frame_at(0) else {
             drop(editor_ref);
-            return;
+            return None;
         };
 
         let state = editor.sessions();
-        let annotations: Vec<&crate::annotation::model::Annotation> = state
+        let annotations: Vec<(&crate::annotation::model::Annotation, CGFloat)> = state
             .annotations
             .iter()
-            .map(|ta| &ta.annotation)
+            .map(|ta| (&ta.annotation, 1.0))
             .collect();
 
         let width = editor.decoder.width();
@@ -1387,18 +3760,16 @@ impl AppDelegate {
         let view_size = (view_bounds.size.width, view_bounds.size.height);
         let composited = crate::editor::export::composite_frame(source_image, &annotations, width, height, view_size);
 
-        // Apply crop if present
-        let crop_rect = editor.view.ivars().crop_rect.get();
+        // Apply the crop being edited, or else the one already applied
+        let crop_rect = editor
+            .view
+            .ivars()
+            .crop_rect
+            .get()
+            .map(crate::overlay::view::normalize_rect)
+            .or(state.crop());
         let final_image = if let (Some(img), Some(crop)) = (&composited, crop_rect) {
-            let norm = crate::overlay::view::normalize_rect(crop);
-            // Scale from view coords to pixel coords
-            let view_bounds = editor.view.bounds();
-            let sx = width as CGFloat / view_bounds.size.width;
-            let sy = height as CGFloat / view_bounds.size.height;
-            let pixel_crop = objc2_core_foundation::CGRect::new(
-                CGPoint::new(norm.origin.x * sx, norm.origin.y * sy),
-                objc2_core_foundation::CGSize::new(norm.size.width * sx, norm.size.height * sy),
-            );
+            let pixel_crop = crate::editor::export::pixel_crop_rect(crop, width, height, view_size);
             objc2_core_graphics::CGImage::with_image_in_rect(Some(img), pixel_crop)
         } else {
             composited
@@ -1425,16 +3796,15 @@ impl AppDelegate {
             toolbar.hide();
         }
 
-        if let Some(image) = final_image {
-            crate::actions::save_to_file(&image, mtm);
-        }
+        let image = final_image?;
+        self.save_image_to_file(&image, mtm)
     }
 
     fn apply_crop(&self) {
         let mtm = MainThreadMarker::from(self);
 
-        let mut editor_ref = self.ivars().editor_window.borrow_mut();
-        let Some(ref mut editor) = *editor_ref else {
+        let editor_ref = self.ivars().editor_window.borrow();
+        let Some(ref editor) = *editor_ref else {
             return;
         };
 
@@ -1445,65 +3815,421 @@ impl AppDelegate {
         let Some(crop) = editor.view.ivars().crop_rect.get() else {
             return;
         };
-        let norm_crop = crate::overlay::view::normalize_rect(crop);
 
-        // Get the source image
-        let Some(source_image) = editor.decoder.frame_at(0) else {
+        // Record the crop on the editor state. The image and annotations are left
+        // untouched so Cmd+Z can bring them back; the crop is applied on export.
+        editor.apply_crop(crate::overlay::view::normalize_rect(crop), mtm);
+        editor.view.set_crop_rect(None);
+
+        // Switch tool to Select and update toolbar
+        editor.view.ivars().active_tool.set(ActiveTool::Select);
+        editor.view.set_crop_size_bar_visible(false);
+        drop(editor_ref);
+
+        if let Some(toolbar) = self.ivars().toolbar.borrow().as_ref() {
+            toolbar.view.set_active_tool(0); // 0 = Select
+        }
+    }
+
+    /// Pick a baseline and a second image, then open the editor on the baseline
+    /// with the changed pixels highlighted.
+    fn compare_images(&self) {
+        let mtm = MainThreadMarker::from(self);
+
+        if self.ivars().recording_state.borrow().is_some() {
+            eprintln!("Cannot compare images while recording");
+            return;
+        }
+        if self.ivars().editor_window.borrow().is_some() {
+            eprintln!("Cannot compare images while editing");
+            return;
+        }
+
+        #[allow(deprecated)]
+        NSApplication::sharedApplication(mtm).activateIgnoringOtherApps(true);
+
+        let Some(base_path) = crate::actions::choose_image_file("Choose the baseline image", mtm) else {
+            return;
+        };
+        let Some(other_path) = crate::actions::choose_image_file("Choose the image to compare", mtm) else {
             return;
         };
 
-        // Composite source image + all annotations
-        let state = editor.sessions();
-        let annotations: Vec<&crate::annotation::model::Annotation> = state
-            .annotations
-            .iter()
-            .map(|ta| &ta.annotation)
-            .collect();
-        let width = editor.decoder.width();
-        let height = editor.decoder.height();
-        let view_bounds = editor.view.bounds();
-        let view_size = (view_bounds.size.width, view_bounds.size.height);
-        let composited = crate::editor::export::composite_frame(source_image, &annotations, width, height, view_size);
-        drop(state);
+        let (base, other) = match (
+            crate::actions::load_image_file(&base_path),
+            crate::actions::load_image_file(&other_path),
+        ) {
+            (Ok(b), Ok(o)) => (b, o),
+            (Err(e), _) => {
+                eprintln!("Failed to load image for comparison: {}", e);
+                show_open_failed(&base_path, &e, mtm);
+                return;
+            }
+            (_, Err(e)) => {
+                eprintln!("Failed to load image for comparison: {}", e);
+                show_open_failed(&other_path, &e, mtm);
+                return;
+            }
+        };
+
+        let decoder = crate::editor::decoder::VideoDecoder::from_image(base);
+
+        // Temp path so closing the editor never touches the user's files
+        let tmp_path = temp_capture_path("clipshot_diff", "png");
+
+        match EditorWindow::open_with_decoder(decoder, "Compare Images", &tmp_path, mtm) {
+            Ok(editor) => {
+                editor.set_diff_image(other, mtm);
+                self.install_editor(editor);
+            }
+            Err(e) => {
+                eprintln!("Failed to open editor for comparison: {}", e);
+            }
+        }
+    }
+
+    /// Keep `image` in the capture history; "Recent Captures" refreshes once it is written.
+    fn add_screenshot_to_history(&self, image: &CGImage) {
+        if !crate::prefs::keep_history() {
+            return;
+        }
+        let result = crate::history::add_screenshot(CFRetained::from(image), post_history_changed);
+        if let Err(e) = result {
+            eprintln!("Failed to add screenshot to history: {}", e);
+        }
+    }
+
+    /// Save `image` through the save panel, then show the format it remembered
+    /// in the "Saving" submenu. Returns where the image was saved.
+    fn save_image_to_file(&self, image: &CGImage, mtm: MainThreadMarker) -> Option<PathBuf> {
+        let saved = crate::actions::save_to_file(image, mtm);
+        if let Some(sb) = self.ivars().status_bar.borrow().as_ref() {
+            sb.set_image_format(crate::prefs::image_format());
+        }
+        if let Some(path) = &saved {
+            copy_saved_image(image, path);
+            announce_saved(path);
+        }
+        saved
+    }
 
-        let Some(composited) = composited else {
-            eprintln!("Failed to composite frame for crop");
+    /// Keep the just-saved recording at `path` in the capture history; "Recent
+    /// Captures" refreshes once the file is in place.
+    fn add_recording_to_history(&self, path: &Path) {
+        let Some(thumbnail) = self.ivars().recording_thumbnail.borrow_mut().take() else {
             return;
         };
+        if !crate::prefs::keep_history() {
+            return;
+        }
+        let result =
+            crate::history::add_recording(path, Some(&thumbnail), post_history_changed);
+        if let Err(e) = result {
+            eprintln!("Failed to add recording to history: {}", e);
+        }
+    }
 
-        // Scale crop rect from view coords to pixel coords
-        let view_bounds = editor.view.bounds();
-        let sx = width as CGFloat / view_bounds.size.width;
-        let sy = height as CGFloat / view_bounds.size.height;
-        let pixel_crop = objc2_core_foundation::CGRect::new(
-            CGPoint::new(norm_crop.origin.x * sx, norm_crop.origin.y * sy),
-            objc2_core_foundation::CGSize::new(norm_crop.size.width * sx, norm_crop.size.height * sy),
-        );
+    fn refresh_recent_captures(&self) {
+        let mtm = MainThreadMarker::from(self);
+        if let Some(sb) = self.ivars().status_bar.borrow().as_ref() {
+            sb.set_recent_captures(mtm, &crate::history::items());
+        }
+    }
+
+    /// Open an image file in the editor for annotation.
+    fn open_image_file(&self, path: &Path) {
+        let mtm = MainThreadMarker::from(self);
 
-        // Crop the composited image
-        let Some(cropped) = objc2_core_graphics::CGImage::with_image_in_rect(Some(&composited), pixel_crop) else {
-            eprintln!("Failed to crop image");
+        if self.ivars().recording_state.borrow().is_some() {
+            eprintln!("Cannot open an image while recording");
+            return;
+        }
+        if self.ivars().editor_window.borrow().is_some() {
+            eprintln!("Cannot open an image while editing");
             return;
+        }
+
+        let image = match crate::actions::load_image_file(path) {
+            Ok(image) => image,
+            Err(e) => {
+                eprintln!("Failed to open {}: {}", path.display(), e);
+                show_open_failed(path, &e, mtm);
+                return;
+            }
         };
 
-        // Replace the decoder's image with the cropped result
-        editor.decoder.replace_image(cropped);
+        #[allow(deprecated)]
+        NSApplication::sharedApplication(mtm).activateIgnoringOtherApps(true);
+        let name = path.file_name().map(|n| n.to_string_lossy()).unwrap_or_default();
+        self.open_screenshot_editor(image, &format!("Edit {}", name), mtm);
+    }
 
-        // Clear all annotations (they're now baked into the image)
-        editor.state.borrow_mut().clear_all();
+    /// Open the image on the clipboard in the editor for annotation.
+    fn annotate_clipboard_image(&self) {
+        let mtm = MainThreadMarker::from(self);
 
-        // Clear crop rect
-        editor.view.ivars().crop_rect.set(None);
+        if self.ivars().recording_state.borrow().is_some() {
+            eprintln!("Cannot annotate the clipboard while recording");
+            return;
+        }
+        if self.ivars().editor_window.borrow().is_some() {
+            eprintln!("Cannot annotate the clipboard while editing");
+            return;
+        }
 
-        // Resize view/window for new image dimensions
-        editor.resize_for_new_image(mtm);
+        let image = match crate::actions::image_from_pasteboard() {
+            Ok(image) => image,
+            Err(e) => {
+                eprintln!("{}", e);
+                NSBeep();
+                return;
+            }
+        };
 
-        // Switch tool to Select and update toolbar
-        editor.view.ivars().active_tool.set(ActiveTool::Select);
-        drop(editor_ref);
+        #[allow(deprecated)]
+        NSApplication::sharedApplication(mtm).activateIgnoringOtherApps(true);
+        self.open_screenshot_editor(image, "Edit Clipboard Image", mtm);
+    }
 
-        if let Some(toolbar) = self.ivars().toolbar.borrow().as_ref() {
-            toolbar.view.set_active_tool(0); // 0 = Select
+    /// Finish a screenshot selection with the given action and dismiss the overlay.
+    fn finish_screenshot(&self, action: PostCaptureAction) {
+        let mtm = MainThreadMarker::from(self);
+        self.remember_selection();
+        let image = self.get_final_image();
+        self.dismiss_all();
+        let Some(image) = image else {
+            return;
+        };
+        self.deliver_screenshot(image, action, mtm);
+    }
+
+    /// Copy, save, or open a finished screenshot according to `action`.
+    fn deliver_screenshot(
+        &self,
+        image: CFRetained<CGImage>,
+        action: PostCaptureAction,
+        mtm: MainThreadMarker,
+    ) {
+        self.add_screenshot_to_history(&image);
+        match action {
+            PostCaptureAction::ShowToolbar | PostCaptureAction::Copy => {
+                if let Err(e) = crate::actions::copy_to_clipboard(&image) {
+                    eprintln!("Clipboard error: {}", e);
+                }
+            }
+            PostCaptureAction::QuickSave => match crate::actions::quick_save(&image) {
+                Ok(path) => {
+                    copy_saved_image(&image, &path);
+                    announce_saved(&path);
+                }
+                Err(e) => eprintln!("Failed to save: {}", e),
+            },
+            PostCaptureAction::OpenEditor => {
+                self.open_screenshot_editor(image, "Edit Screenshot", mtm)
+            }
+        }
+    }
+
+    fn open_screenshot_editor(
+        &self,
+        image: CFRetained<CGImage>,
+        title: &str,
+        mtm: MainThreadMarker,
+    ) {
+        let decoder = crate::editor::decoder::VideoDecoder::from_image(image);
+
+        // Use a temporary path for the editor state
+        let tmp_path = temp_capture_path("clipshot_screenshot", "png");
+
+        match EditorWindow::open_with_decoder(decoder, title, &tmp_path, mtm) {
+            Ok(editor) => self.install_editor(editor),
+            Err(e) => {
+                eprintln!("Failed to open editor for screenshot: {}", e);
+            }
+        }
+    }
+}
+
+/// A new file in the temp folder named `{prefix}_{seconds since the epoch}.{extension}`.
+fn temp_capture_path(prefix: &str, extension: &str) -> PathBuf {
+    let secs = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    std::env::temp_dir().join(format!("{}_{}.{}", prefix, secs, extension))
+}
+
+/// The image file being dragged, if it's one the editor can open.
+fn dragged_image_path(sender: &ProtocolObject<dyn NSDraggingInfo>) -> Option<PathBuf> {
+    let pasteboard = sender.draggingPasteboard();
+    let url = pasteboard.stringForType(unsafe { NSPasteboardTypeFileURL })?;
+    let path = PathBuf::from(NSURL::URLWithString(&url)?.path()?.to_string());
+    crate::actions::is_image_file(&path).then_some(path)
+}
+
+/// Move a finished recording from its temp file to `dest`.
+fn move_recording(tmp_path: &Path, dest: &Path) -> bool {
+    if let Err(e) = std::fs::rename(tmp_path, dest) {
+        // rename may fail across filesystems, try copy
+        if let Err(e2) = std::fs::copy(tmp_path, dest) {
+            eprintln!("Failed to save recording: rename={}, copy={}", e, e2);
+            return false;
+        }
+        let _ = std::fs::remove_file(tmp_path);
+    }
+    eprintln!("Recording saved to {}", dest.display());
+    true
+}
+
+/// Tell the app delegate the capture history changed, so "Recent Captures" is
+/// rebuilt. Runs on the main thread after a background history write.
+fn post_history_changed() {
+    let Some(mtm) = MainThreadMarker::new() else {
+        return;
+    };
+    let app = NSApplication::sharedApplication(mtm);
+    if let Some(delegate) = app.delegate() {
+        let _: () = unsafe { msg_send![&*delegate, historyChanged: std::ptr::null::<AnyObject>()] };
+    }
+}
+
+/// Start uploading the file at `path`, reporting setup problems with a notification.
+fn upload(path: &Path) {
+    if let Err(e) = crate::upload::upload_file(path) {
+        eprintln!("Upload failed: {}", e);
+        crate::notify::show("Upload Failed", &e);
+    }
+}
+
+/// Put recognized text on the clipboard, reporting the outcome with a notification.
+/// Returns whether any text was copied.
+fn copy_recognized_text(recognized: Result<String, String>) -> bool {
+    let text = match recognized {
+        Ok(text) if !text.trim().is_empty() => text,
+        Ok(_) => {
+            NSBeep();
+            crate::notify::show("No Text Found", "The capture has no readable text.");
+            return false;
+        }
+        Err(e) => {
+            eprintln!("Text recognition failed: {}", e);
+            crate::notify::show("Copy Text Failed", &e);
+            return false;
         }
+    };
+    if let Err(e) = arboard::Clipboard::new().and_then(|mut c| c.set_text(text.clone())) {
+        eprintln!("Failed to copy text: {}", e);
+        return false;
+    }
+    let lines = text.lines().count();
+    eprintln!("Copied {} line(s) of recognized text", lines);
+    let noun = if lines == 1 { "line" } else { "lines" };
+    let body = format!("{} {} of text copied to the clipboard.", lines, noun);
+    crate::notify::show("Text Copied", &body);
+    true
+}
+
+/// Put the payloads of decoded QR codes and barcodes on the clipboard, one per
+/// line, reporting the outcome with a notification. Returns whether anything was
+/// copied.
+fn copy_barcode_payload(decoded: Result<Vec<String>, String>) -> bool {
+    let payloads = match decoded {
+        Ok(payloads) if !payloads.is_empty() => payloads,
+        Ok(_) => {
+            NSBeep();
+            crate::notify::show("No Code Found", "The capture has no readable QR code or barcode.");
+            return false;
+        }
+        Err(e) => {
+            eprintln!("Barcode detection failed: {}", e);
+            crate::notify::show("Decode Failed", &e);
+            return false;
+        }
+    };
+    let text = payloads.join("\n");
+    if let Err(e) = arboard::Clipboard::new().and_then(|mut c| c.set_text(text.clone())) {
+        eprintln!("Failed to copy code: {}", e);
+        return false;
+    }
+    eprintln!("Decoded {} code(s)", payloads.len());
+    crate::notify::show("Code Copied", &text);
+    true
+}
+
+/// Tell the user the image at `path` couldn't be opened, and why.
+fn show_open_failed(path: &Path, error: &str, mtm: MainThreadMarker) {
+    let name = path.file_name().map(|n| n.to_string_lossy()).unwrap_or_default();
+    let alert = NSAlert::new(mtm);
+    alert.setMessageText(&NSString::from_str(&format!(
+        "\u{201C}{}\u{201D} couldn't be opened.",
+        name
+    )));
+    alert.setInformativeText(&NSString::from_str(error));
+    alert.runModal();
+}
+
+/// Ask before removing all `count` annotations. The alert is raised above the
+/// capture overlay so it isn't hidden behind it.
+fn confirm_clear_annotations(count: usize, mtm: MainThreadMarker) -> bool {
+    let alert = NSAlert::new(mtm);
+    alert.setMessageText(&NSString::from_str("Clear All Annotations?"));
+    let noun = if count == 1 { "annotation" } else { "annotations" };
+    alert.setInformativeText(&NSString::from_str(&format!(
+        "{} {} will be removed. This can't be undone.",
+        count, noun
+    )));
+    alert.addButtonWithTitle(&NSString::from_str("Clear"));
+    alert.addButtonWithTitle(&NSString::from_str("Cancel"));
+    alert.window().setLevel((kCGOverlayWindowLevel + 1) as _);
+    alert.runModal() == NSAlertFirstButtonReturn
+}
+
+/// After a screenshot is saved to `path`, put the file on the clipboard when
+/// "Copy Saved File" is on, or else the image when "Also Copy to Clipboard" is.
+fn copy_saved_image(image: &CGImage, path: &Path) {
+    let result = if crate::prefs::copy_saved_file() {
+        crate::actions::copy_file_to_clipboard(path)
+    } else if crate::prefs::copy_on_save() {
+        crate::actions::copy_to_clipboard(image)
+    } else {
+        return;
+    };
+    if let Err(e) = result {
+        eprintln!("Failed to copy saved image: {}", e);
+    }
+}
+
+/// Put a saved recording on the clipboard as a file when "Also Copy to
+/// Clipboard" or "Copy Saved File" is on.
+fn copy_saved_recording(path: &Path) {
+    if !crate::prefs::copy_on_save() && !crate::prefs::copy_saved_file() {
+        return;
+    }
+    if let Err(e) = crate::actions::copy_file_to_clipboard(path) {
+        eprintln!("Failed to copy saved recording: {}", e);
+    }
+}
+
+/// Play the screenshot sound, post a "Saved" banner, and select `path` in
+/// Finder when "Play Sound When Saved", "Notify When Saved", and "Reveal in
+/// Finder When Saved" are on.
+fn announce_saved(path: &Path) {
+    if crate::prefs::save_sound() {
+        crate::actions::play_screenshot_sound();
+    }
+    if crate::prefs::notify_on_save() {
+        crate::notify::saved(path);
+    }
+    if crate::prefs::reveal_on_save() {
+        crate::actions::reveal_in_finder(path);
+    }
+}
+
+/// Convert a frozen margin preference to a band of capture pixels.
+fn frozen_band(margin: FrozenMargin, scale_factor: CGFloat) -> crate::stitch::FrozenBand {
+    match margin {
+        FrozenMargin::None => crate::stitch::FrozenBand::None,
+        FrozenMargin::Auto => crate::stitch::FrozenBand::Auto,
+        _ => crate::stitch::FrozenBand::Rows((margin.points() * scale_factor) as usize),
     }
 }
@@ -0,0 +1,27 @@
+use objc2::msg_send;
+use objc2::rc::Retained;
+use objc2::runtime::{AnyClass, AnyObject};
+use objc2_core_graphics::CGImage;
+use objc2_foundation::NSString;
+
+/// Find QR codes and barcodes in `image` with Vision and return their payloads,
+/// in the order found. Codes without a text payload are skipped.
+pub fn detect_payloads(image: &CGImage) -> Result<Vec<String>, String> {
+    let request_class =
+        AnyClass::get(c"VNDetectBarcodesRequest").ok_or("Barcode detection is unavailable")?;
+    unsafe {
+        let request: Retained<AnyObject> = msg_send![request_class, new];
+        let mut payloads: Vec<String> = Vec::new();
+        for observation in crate::ocr::perform_request(&request, image)? {
+            let payload: Option<Retained<NSString>> =
+                msg_send![&observation, payloadStringValue];
+            // The same code can be reported once per symbology
+            if let Some(payload) = payload.map(|p| p.to_string())
+                && !payloads.contains(&payload)
+            {
+                payloads.push(payload);
+            }
+        }
+        Ok(payloads)
+    }
+}
@@ -1,7 +1,11 @@
-use objc2_core_foundation::CFRetained;
+use objc2_core_foundation::{
+    CFDictionary, CFNumber, CFRetained, CFString, CFType, CGPoint, CGRect,
+};
 use objc2_core_graphics::{
-    CGDirectDisplayID, CGDisplayBounds, CGImage, CGMainDisplayID, CGWindowID,
-    CGWindowImageOption, CGWindowListOption,
+    CGDirectDisplayID, CGDisplayBounds, CGImage, CGMainDisplayID,
+    CGRectMakeWithDictionaryRepresentation, CGRectNull, CGWindowID, CGWindowImageOption,
+    CGWindowListCopyWindowInfo, CGWindowListOption, kCGWindowAlpha, kCGWindowBounds,
+    kCGWindowLayer, kCGWindowNumber, kCGWindowOwnerPID,
 };
 #[allow(deprecated)]
 use objc2_core_graphics::CGWindowListCreateImage;
@@ -14,6 +18,38 @@ pub fn capture_full_screen() -> Option<CFRetained<CGImage>> {
     capture_display_excluding(display_id, None)
 }
 
+/// Capture everything on screen within `frame` (AppKit coordinates, e.g. the
+/// union of all screens) as one image. Areas no display covers come out empty.
+#[allow(deprecated)]
+pub fn capture_all_displays(frame: CGRect) -> Option<CFRetained<CGImage>> {
+    // AppKit frames have a bottom-left origin; CG bounds a top-left one (primary display)
+    let primary_height = CGDisplayBounds(CGMainDisplayID()).size.height;
+    let bounds = CGRect::new(
+        CGPoint::new(frame.origin.x, primary_height - (frame.origin.y + frame.size.height)),
+        frame.size,
+    );
+    let image = CGWindowListCreateImage(
+        bounds,
+        CGWindowListOption::OptionOnScreenOnly,
+        0 as CGWindowID,
+        CGWindowImageOption::BestResolution,
+    );
+
+    if let Some(ref img) = image {
+        eprintln!(
+            "All displays captured: {}x{} pixels (bounds: {},{} {}x{})",
+            CGImage::width(Some(img)),
+            CGImage::height(Some(img)),
+            bounds.origin.x, bounds.origin.y,
+            bounds.size.width, bounds.size.height,
+        );
+    } else {
+        eprintln!("Screen capture failed - check Screen Recording permission");
+    }
+
+    image
+}
+
 /// Capture a specific display, optionally excluding a specific window by its ID.
 /// When `exclude_window_id` is Some, captures everything on screen below that window,
 /// effectively excluding it from the capture.
@@ -58,6 +94,88 @@ pub fn capture_display_excluding(
     image
 }
 
+/// An on-screen application window that can be captured on its own.
+#[derive(Clone, Copy)]
+pub struct WindowInfo {
+    pub id: CGWindowID,
+    /// Window frame in global display coordinates (top-left origin), without the shadow.
+    pub bounds: CGRect,
+}
+
+/// List the visible windows of other apps, frontmost first. Menu bar, Dock,
+/// desktop, and other non-normal-level windows are left out.
+pub fn on_screen_windows() -> Vec<WindowInfo> {
+    let options = CGWindowListOption::OptionOnScreenOnly
+        | CGWindowListOption::ExcludeDesktopElements;
+    let Some(list) = CGWindowListCopyWindowInfo(options, 0 as CGWindowID) else {
+        eprintln!("Failed to list on-screen windows");
+        return Vec::new();
+    };
+    // SAFETY: Each element of the window list is a dictionary keyed by CFString.
+    let list = unsafe { list.cast_unchecked::<CFDictionary<CFString, CFType>>() };
+    let own_pid = std::process::id() as i64;
+
+    let number = |info: &CFDictionary<CFString, CFType>, key: &CFString| {
+        info.get(key)?.downcast::<CFNumber>().ok()
+    };
+    let mut windows = Vec::new();
+    for i in 0..list.len() {
+        let Some(info) = list.get(i) else {
+            continue;
+        };
+        let id = number(&info, unsafe { kCGWindowNumber }).and_then(|n| n.as_i64());
+        let layer = number(&info, unsafe { kCGWindowLayer }).and_then(|n| n.as_i64());
+        let pid = number(&info, unsafe { kCGWindowOwnerPID }).and_then(|n| n.as_i64());
+        let alpha = number(&info, unsafe { kCGWindowAlpha }).and_then(|n| n.as_f64());
+        let (Some(id), Some(0)) = (id, layer) else {
+            continue;
+        };
+        if pid == Some(own_pid) || alpha == Some(0.0) {
+            continue;
+        }
+
+        let Some(bounds_dict) = info
+            .get(unsafe { kCGWindowBounds })
+            .and_then(|b| b.downcast::<CFDictionary>().ok())
+        else {
+            continue;
+        };
+        let mut bounds = CGRect::default();
+        if !unsafe { CGRectMakeWithDictionaryRepresentation(Some(&bounds_dict), &mut bounds) } {
+            continue;
+        }
+        if bounds.size.width < 1.0 || bounds.size.height < 1.0 {
+            continue;
+        }
+        windows.push(WindowInfo { id: id as CGWindowID, bounds });
+    }
+    windows
+}
+
+/// Capture a single window, including its shadow, even where other windows cover it.
+#[allow(deprecated)]
+pub fn capture_window(window_id: CGWindowID) -> Option<CFRetained<CGImage>> {
+    let image = CGWindowListCreateImage(
+        unsafe { CGRectNull },
+        CGWindowListOption::OptionIncludingWindow,
+        window_id,
+        CGWindowImageOption::BestResolution,
+    );
+
+    if let Some(ref img) = image {
+        eprintln!(
+            "Window {} captured: {}x{} pixels",
+            window_id,
+            CGImage::width(Some(img)),
+            CGImage::height(Some(img)),
+        );
+    } else {
+        eprintln!("Window capture failed - check Screen Recording permission");
+    }
+
+    image
+}
+
 /// Check if we have screen recording permission by attempting a minimal capture.
 #[allow(deprecated)]
 pub fn has_screen_recording_permission() -> bool {
@@ -0,0 +1,92 @@
+use objc2_core_foundation::CFRetained;
+use objc2_core_graphics::CGImage;
+
+/// Default per-channel difference (0–255) above which a pixel counts as changed.
+pub const DEFAULT_THRESHOLD: u8 = 16;
+
+/// Result of comparing a base image against another capture.
+pub struct DiffResult {
+    /// Highlight image the size of the base: changed pixels tinted, the rest transparent.
+    pub overlay: CFRetained<CGImage>,
+    /// Number of base pixels that differ by more than the threshold.
+    pub changed_pixels: usize,
+}
+
+/// Compare two images and build a highlight overlay for the pixels that changed.
+///
+/// The overlay always matches the base image's dimensions. Pixels of the base that
+/// fall outside the other image (size mismatch) are treated as changed.
+pub fn diff_images(base: &CGImage, other: &CGImage, threshold: u8) -> Result<DiffResult, String> {
+    let base_w = CGImage::width(Some(base));
+    let base_h = CGImage::height(Some(base));
+    let other_w = CGImage::width(Some(other));
+    let other_h = CGImage::height(Some(other));
+
+    let base_rgba = crate::actions::cgimage_to_rgba(base)?;
+    let other_rgba = crate::actions::cgimage_to_rgba(other)?;
+
+    let (mut overlay_rgba, changed_pixels) = diff_rgba(
+        &base_rgba,
+        (base_w, base_h),
+        &other_rgba,
+        (other_w, other_h),
+        threshold,
+    );
+
+    let overlay = crate::stitch::rgba_to_cgimage(&mut overlay_rgba, base_w, base_h)
+        .ok_or("Failed to create diff overlay image")?;
+
+    Ok(DiffResult {
+        overlay,
+        changed_pixels,
+    })
+}
+
+/// Compare two RGBA buffers (as produced by `cgimage_to_rgba`) pixel by pixel.
+///
+/// A pixel is changed when any RGB channel differs by more than `threshold`.
+/// Returns a premultiplied RGBA heat overlay sized like `base` — stronger differences
+/// are drawn more opaque — and the number of changed pixels.
+pub fn diff_rgba(
+    base: &[u8],
+    (base_w, base_h): (usize, usize),
+    other: &[u8],
+    (other_w, other_h): (usize, usize),
+    threshold: u8,
+) -> (Vec<u8>, usize) {
+    let mut output = vec![0u8; base_w * base_h * 4];
+    let mut changed = 0;
+
+    for y in 0..base_h {
+        for x in 0..base_w {
+            let bi = (y * base_w + x) * 4;
+            let delta = if x < other_w && y < other_h {
+                let oi = (y * other_w + x) * 4;
+                if bi + 2 >= base.len() || oi + 2 >= other.len() {
+                    continue;
+                }
+                (0..3)
+                    .map(|c| (base[bi + c] as i32 - other[oi + c] as i32).unsigned_abs())
+                    .max()
+                    .unwrap_or(0)
+            } else {
+                255
+            };
+
+            if delta <= threshold as u32 {
+                continue;
+            }
+            changed += 1;
+
+            // Heat: alpha ramps from 110 for barely-changed pixels up to 230.
+            let alpha = 110 + delta * 120 / 255;
+            // Magenta-red tint, premultiplied by alpha.
+            output[bi] = alpha as u8;
+            output[bi + 1] = 0;
+            output[bi + 2] = (alpha * 64 / 255) as u8;
+            output[bi + 3] = alpha as u8;
+        }
+    }
+
+    (output, changed)
+}
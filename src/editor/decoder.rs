@@ -1,4 +1,4 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use objc2::rc::Retained;
 use objc2::runtime::AnyObject;
@@ -19,6 +19,8 @@ use objc2_foundation::{NSDictionary, NSNumber, NSString, NSURL};
 /// Pre-decodes all frames from an MP4 into CGImages for random access.
 pub struct VideoDecoder {
     frames: Vec<CFRetained<CGImage>>,
+    /// The video file the frames came from; `None` for a static image.
+    path: Option<PathBuf>,
     fps: f64,
     width: usize,
     height: usize,
@@ -111,6 +113,7 @@ impl VideoDecoder {
 
         Ok(VideoDecoder {
             frames,
+            path: Some(path.to_path_buf()),
             fps,
             width,
             height,
@@ -124,16 +127,29 @@ impl VideoDecoder {
         eprintln!("VideoDecoder: static image {}x{}", width, height);
         VideoDecoder {
             frames: vec![image],
+            path: None,
             fps: 1.0,
             width,
             height,
         }
     }
 
+    /// Swap in a new static image (after rotating or flipping it).
+    pub fn replace_image(&mut self, image: CFRetained<CGImage>) {
+        self.width = CGImage::width(Some(&image));
+        self.height = CGImage::height(Some(&image));
+        self.frames = vec![image];
+    }
+
     pub fn frame_at(&self, index: usize) -> Option<&CGImage> {
         self.frames.get(index).map(|f| &**f)
     }
 
+    /// The video file the frames were decoded from, if any.
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
     pub fn total_frames(&self) -> usize {
         self.frames.len()
     }
@@ -149,15 +165,6 @@ impl VideoDecoder {
     pub fn height(&self) -> usize {
         self.height
     }
-
-    /// Replace the decoder's image with a new one (used for crop).
-    pub fn replace_image(&mut self, image: CFRetained<CGImage>) {
-        let width = CGImage::width(Some(&image));
-        let height = CGImage::height(Some(&image));
-        self.frames = vec![image];
-        self.width = width;
-        self.height = height;
-    }
 }
 
 /// Convert a CVPixelBuffer (BGRA) to a CGImage via CGBitmapContext.
@@ -1,79 +1,575 @@
+use std::ops::Range;
 use std::path::Path;
 
-use objc2_core_foundation::{CGFloat, CGPoint, CGRect, CGSize};
+use objc2_core_foundation::{
+    CFDictionary, CFNumber, CFRetained, CFString, CFType, CFURL, CGAffineTransform, CGFloat,
+    CGPoint, CGRect, CGSize, Type,
+};
 use objc2_core_graphics::{
-    CGBitmapContextCreate, CGBitmapContextCreateImage, CGColorSpace, CGContext, CGImage,
-    CGImageAlphaInfo,
+    CGBitmapContextCreate, CGBitmapContextCreateImage, CGColor, CGColorSpace, CGContext,
+    CGGradient, CGGradientDrawingOptions, CGImage, CGImageAlphaInfo, CGInterpolationQuality,
+};
+use objc2_image_io::{
+    CGImageDestination, kCGImagePropertyGIFDelayTime, kCGImagePropertyGIFDictionary,
+    kCGImagePropertyGIFLoopCount, kCGImagePropertyGIFUnclampedDelayTime,
 };
 
 use super::decoder::VideoDecoder;
-use super::model::TimedAnnotation;
-use crate::encoder::VideoEncoder;
+use super::model::{
+    BackgroundFill, BackgroundStyle, ImageAdjustment, ImageTransform, TimedAnnotation,
+    WindowFrame,
+};
+use crate::annotation::renderer::add_rounded_rect_path;
+use crate::encoder::{AudioSource, VideoEncoder, WebmEncoder};
+
+/// Longest side of an exported GIF in pixels; larger frames are scaled down.
+const GIF_MAX_DIMENSION: usize = 800;
+
+/// File format a recording is saved in.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum VideoFormat {
+    Mp4,
+    WebM,
+    Gif,
+}
+
+impl VideoFormat {
+    /// All formats, in save-dialog order.
+    pub const ALL: [VideoFormat; 3] = [VideoFormat::Mp4, VideoFormat::WebM, VideoFormat::Gif];
+
+    pub fn title(self) -> &'static str {
+        match self {
+            VideoFormat::Mp4 => "MP4 Video",
+            VideoFormat::WebM => "WebM Video (VP9)",
+            VideoFormat::Gif => "Animated GIF",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            VideoFormat::Mp4 => "mp4",
+            VideoFormat::WebM => "webm",
+            VideoFormat::Gif => "gif",
+        }
+    }
+}
+
+/// Encoder backend a video export writes its frames to.
+enum FrameEncoder {
+    /// H.264 MP4 via AVAssetWriter.
+    Mp4(VideoEncoder),
+    /// VP9 WebM via ffmpeg.
+    WebM(WebmEncoder),
+}
+
+impl FrameEncoder {
+    /// `audio` is the recording to take the audio track from, if any, and the
+    /// part of it (in seconds) that matches the exported frames.
+    fn new(
+        format: VideoFormat,
+        path: &Path,
+        width: usize,
+        height: usize,
+        fps: i32,
+        audio: Option<(&Path, Range<f64>)>,
+    ) -> Result<Self, String> {
+        let quality = crate::prefs::video_quality();
+        match format {
+            VideoFormat::Mp4 => {
+                let codec = crate::prefs::video_codec();
+                let mut encoder = VideoEncoder::new(path, width, height, fps, quality, codec)?;
+                if let Some((source_path, seconds)) = audio {
+                    match AudioSource::open(source_path, seconds) {
+                        Ok(Some(source)) => encoder.add_audio_source(source)?,
+                        Ok(None) => {}
+                        Err(e) => eprintln!("Exporting without audio: {}", e),
+                    }
+                }
+                encoder.start()?;
+                Ok(FrameEncoder::Mp4(encoder))
+            }
+            VideoFormat::WebM => {
+                let encoder = WebmEncoder::new(path, width, height, fps, quality, audio)?;
+                Ok(FrameEncoder::WebM(encoder))
+            }
+            VideoFormat::Gif => Err("GIFs are written by export_gif".to_string()),
+        }
+    }
+
+    fn append_frame(&mut self, frame: &CGImage) -> bool {
+        match self {
+            FrameEncoder::Mp4(encoder) => encoder.append_frame(frame),
+            FrameEncoder::WebM(encoder) => encoder.append_frame(frame),
+        }
+    }
+
+    fn finish(self) -> Result<(), String> {
+        match self {
+            FrameEncoder::Mp4(mut encoder) => {
+                encoder.finish();
+                Ok(())
+            }
+            FrameEncoder::WebM(encoder) => encoder.finish(),
+        }
+    }
+}
 
 /// Export the video with timed annotations composited onto frames.
 /// `view_size` is the editor view's bounds size (in points) — annotations are stored
 /// in this coordinate space and must be scaled to the video's pixel dimensions.
+/// `crop` (view coordinates) trims every frame; the output is trimmed to even
+/// dimensions as H.264 and VP9 require. Only the source frames in `frames` are
+/// encoded, along with the matching stretch of the source video's audio track.
+/// `format` picks the encoder: H.264 MP4, or VP9 WebM through ffmpeg.
 pub fn export_with_annotations(
     decoder: &VideoDecoder,
     annotations: &[TimedAnnotation],
     output_path: &Path,
+    format: VideoFormat,
     view_size: (CGFloat, CGFloat),
+    crop: Option<CGRect>,
+    frames: Range<usize>,
 ) -> Result<(), String> {
     let width = decoder.width();
     let height = decoder.height();
     let total_frames = decoder.total_frames();
     let fps = decoder.fps().round() as i32;
 
-    if total_frames == 0 {
+    let frames = frames.start..frames.end.min(total_frames);
+    if frames.is_empty() {
         return Err("No frames to export".to_string());
     }
 
-    let mut encoder = VideoEncoder::new(output_path, width, height, fps)?;
-    encoder.start()?;
+    let pixel_crop = crop.map(|crop| even_pixel_crop(crop, width, height, view_size));
+    let (out_width, out_height) = match pixel_crop {
+        Some(rect) => (rect.size.width as usize, rect.size.height as usize),
+        None => (width, height),
+    };
+    if out_width == 0 || out_height == 0 {
+        return Err("Crop region is too small to export".to_string());
+    }
 
-    for frame_idx in 0..total_frames {
-        let Some(source_image) = decoder.frame_at(frame_idx) else {
+    let source_fps = decoder.fps().max(1.0);
+    let seconds = frames.start as f64 / source_fps..frames.end as f64 / source_fps;
+    let audio = decoder.path().map(|path| (path, seconds));
+    let mut encoder =
+        FrameEncoder::new(format, output_path, out_width, out_height, fps, audio)?;
+
+    // Annotation ranges stay in source frame indices; the encoder numbers its
+    // output frames from zero, so a trimmed export starts at `frames.start`.
+    for frame_idx in frames.clone() {
+        if let Some(frame) = render_frame(decoder, annotations, frame_idx, view_size, pixel_crop) {
+            encoder.append_frame(&frame);
+        }
+    }
+
+    encoder.finish()?;
+    eprintln!(
+        "Export complete: {} frames ({}x{}) -> {:?}",
+        frames.len(), out_width, out_height, output_path
+    );
+    Ok(())
+}
+
+/// Export the video as an animated GIF that loops forever, with the same
+/// annotations, crop and trim handling as `export_with_annotations`. Each frame
+/// is shown for 1/fps seconds, and frames are scaled down so neither side
+/// exceeds `GIF_MAX_DIMENSION`.
+pub fn export_gif(
+    decoder: &VideoDecoder,
+    annotations: &[TimedAnnotation],
+    output_path: &Path,
+    view_size: (CGFloat, CGFloat),
+    crop: Option<CGRect>,
+    frames: Range<usize>,
+) -> Result<(), String> {
+    let width = decoder.width();
+    let height = decoder.height();
+    let fps = decoder.fps().max(1.0);
+
+    let frames = frames.start..frames.end.min(decoder.total_frames());
+    if frames.is_empty() {
+        return Err("No frames to export".to_string());
+    }
+
+    let pixel_crop = crop.map(|crop| clamped_pixel_crop(crop, width, height, view_size));
+    let (crop_width, crop_height) = match pixel_crop {
+        Some(rect) => (rect.size.width as usize, rect.size.height as usize),
+        None => (width, height),
+    };
+    if crop_width == 0 || crop_height == 0 {
+        return Err("Crop region is too small to export".to_string());
+    }
+    let scale = (GIF_MAX_DIMENSION as CGFloat / crop_width.max(crop_height) as CGFloat).min(1.0);
+    let out_width = ((crop_width as CGFloat * scale).round() as usize).max(1);
+    let out_height = ((crop_height as CGFloat * scale).round() as usize).max(1);
+
+    let url = CFURL::from_file_path(output_path).ok_or("Invalid path")?;
+    let gif_type = CFString::from_str("com.compuserve.gif");
+    let destination =
+        unsafe { CGImageDestination::with_url(&url, &gif_type, frames.len(), None) }
+            .ok_or("Failed to create GIF destination")?;
+
+    // Loop forever
+    let loop_count = CFNumber::new_i32(0);
+    let file_properties =
+        gif_properties(&[unsafe { kCGImagePropertyGIFLoopCount }], &[&loop_count]);
+    unsafe { destination.set_properties(Some(file_properties.as_opaque())) };
+
+    let delay = CFNumber::new_f64(1.0 / fps);
+    let frame_properties = gif_properties(
+        &[
+            unsafe { kCGImagePropertyGIFDelayTime },
+            unsafe { kCGImagePropertyGIFUnclampedDelayTime },
+        ],
+        &[&delay, &delay],
+    );
+
+    let mut written = 0;
+    for frame_idx in frames.clone() {
+        let Some(frame) = render_frame(decoder, annotations, frame_idx, view_size, pixel_crop)
+        else {
             continue;
         };
-
-        // Collect annotations visible at this frame
-        let visible_annotations: Vec<_> = annotations
-            .iter()
-            .filter(|ta| {
-                frame_idx >= ta.start_frame
-                    && ta.end_frame.map_or(true, |end| frame_idx < end)
-            })
-            .map(|ta| &ta.annotation)
-            .collect();
-
-        if visible_annotations.is_empty() {
-            // No annotations: encode the source frame directly
-            encoder.append_frame(source_image);
-        } else {
-            // Composite annotations onto the frame
-            let composited = composite_frame(source_image, &visible_annotations, width, height, view_size);
-            match composited {
-                Some(ref img) => {
-                    encoder.append_frame(img);
-                }
+        let frame = if scale < 1.0 {
+            match scale_image(&frame, out_width, out_height, CGInterpolationQuality::High) {
+                Some(scaled) => scaled,
                 None => {
-                    // Fallback: encode without annotations
-                    encoder.append_frame(source_image);
+                    eprintln!("Failed to scale frame {}", frame_idx);
+                    continue;
                 }
             }
-        }
+        } else {
+            frame
+        };
+        unsafe { destination.add_image(&frame, Some(frame_properties.as_opaque())) };
+        written += 1;
     }
 
-    encoder.finish();
-    eprintln!("Export complete: {} frames -> {:?}", total_frames, output_path);
+    if written == 0 || !unsafe { destination.finalize() } {
+        return Err("Failed to write GIF".to_string());
+    }
+    eprintln!(
+        "GIF export complete: {} frames ({}x{}) -> {:?}",
+        written, out_width, out_height, output_path
+    );
     Ok(())
 }
 
-/// Draw annotations onto a source frame, producing a new CGImage.
+/// Wrap GIF-specific properties in a `kCGImagePropertyGIFDictionary` entry.
+fn gif_properties(
+    keys: &[&CFString],
+    values: &[&CFType],
+) -> CFRetained<CFDictionary<CFString, CFType>> {
+    let gif = CFDictionary::<CFString, CFType>::from_slices(keys, values);
+    CFDictionary::from_slices(&[unsafe { kCGImagePropertyGIFDictionary }], &[&gif])
+}
+
+/// The source frame at `frame_idx` with its visible annotations composited
+/// and `pixel_crop` applied.
+fn render_frame(
+    decoder: &VideoDecoder,
+    annotations: &[TimedAnnotation],
+    frame_idx: usize,
+    view_size: (CGFloat, CGFloat),
+    pixel_crop: Option<CGRect>,
+) -> Option<CFRetained<CGImage>> {
+    let source_image = decoder.frame_at(frame_idx)?;
+
+    // Collect annotations visible at this frame, with their fade opacity
+    let visible_annotations: Vec<_> = annotations
+        .iter()
+        .filter(|ta| {
+            frame_idx >= ta.start_frame && ta.end_frame.map_or(true, |end| frame_idx < end)
+        })
+        .map(|ta| (&ta.annotation, ta.opacity_at(frame_idx, decoder.total_frames())))
+        .collect();
+
+    // Composite annotations onto the frame (falling back to the plain
+    // source frame when there are none or compositing fails)
+    let composited = if visible_annotations.is_empty() {
+        None
+    } else {
+        composite_frame(
+            source_image,
+            &visible_annotations,
+            decoder.width(),
+            decoder.height(),
+            view_size,
+        )
+    };
+    let frame: &CGImage = composited.as_deref().unwrap_or(source_image);
+
+    match pixel_crop {
+        Some(rect) => {
+            let cropped = CGImage::with_image_in_rect(Some(frame), rect);
+            if cropped.is_none() {
+                eprintln!("Failed to crop frame {}", frame_idx);
+            }
+            cropped
+        }
+        None => Some(frame.retain()),
+    }
+}
+
+/// Redraw `image` at `width`×`height` pixels, resampled at `quality`.
+pub(crate) fn scale_image(
+    image: &CGImage,
+    width: usize,
+    height: usize,
+    quality: CGInterpolationQuality,
+) -> Option<CFRetained<CGImage>> {
+    let color_space = CGColorSpace::new_device_rgb()?;
+    let ctx = unsafe {
+        CGBitmapContextCreate(
+            std::ptr::null_mut(),
+            width,
+            height,
+            8,
+            width * 4,
+            Some(&color_space),
+            CGImageAlphaInfo::PremultipliedLast.0,
+        )
+    }?;
+    CGContext::set_interpolation_quality(Some(&ctx), quality);
+    let draw_rect = CGRect::new(
+        CGPoint::ZERO,
+        CGSize::new(width as CGFloat, height as CGFloat),
+    );
+    CGContext::draw_image(Some(&ctx), draw_rect, Some(image));
+    CGBitmapContextCreateImage(Some(&ctx))
+}
+
+/// Redraw `image` turned or mirrored by `transform`.
+pub(crate) fn transform_image(
+    image: &CGImage,
+    transform: ImageTransform,
+) -> Option<CFRetained<CGImage>> {
+    let width = CGImage::width(Some(image));
+    let height = CGImage::height(Some(image));
+    let (out_w, out_h) = transform.output_size(width, height);
+    let color_space = CGColorSpace::new_device_rgb()?;
+    let ctx = unsafe {
+        CGBitmapContextCreate(
+            std::ptr::null_mut(),
+            out_w,
+            out_h,
+            8,
+            out_w * 4,
+            Some(&color_space),
+            CGImageAlphaInfo::PremultipliedLast.0,
+        )
+    }?;
+    // Bitmap contexts have a bottom-left origin, so the turns run the other way
+    let (w, h) = (width as CGFloat, height as CGFloat);
+    let (a, b, c, d, tx, ty) = match transform {
+        ImageTransform::RotateLeft => (0.0, 1.0, -1.0, 0.0, h, 0.0),
+        ImageTransform::RotateRight => (0.0, -1.0, 1.0, 0.0, 0.0, w),
+        ImageTransform::FlipHorizontal => (-1.0, 0.0, 0.0, 1.0, w, 0.0),
+        ImageTransform::FlipVertical => (1.0, 0.0, 0.0, -1.0, 0.0, h),
+    };
+    CGContext::concat_ctm(Some(&ctx), CGAffineTransform { a, b, c, d, tx, ty });
+    CGContext::draw_image(Some(&ctx), CGRect::new(CGPoint::ZERO, CGSize::new(w, h)), Some(image));
+    CGBitmapContextCreateImage(Some(&ctx))
+}
+
+/// Draw `image` centered on a canvas `style.padding` pixels larger on every side.
+pub(crate) fn add_background(
+    image: &CGImage,
+    style: &BackgroundStyle,
+) -> Option<CFRetained<CGImage>> {
+    let width = CGImage::width(Some(image));
+    let height = CGImage::height(Some(image));
+    let (out_w, out_h) = (width + style.padding * 2, height + style.padding * 2);
+    let color_space = CGColorSpace::new_device_rgb()?;
+    let ctx = unsafe {
+        CGBitmapContextCreate(
+            std::ptr::null_mut(),
+            out_w,
+            out_h,
+            8,
+            out_w * 4,
+            Some(&color_space),
+            CGImageAlphaInfo::PremultipliedLast.0,
+        )
+    }?;
+    let canvas = CGRect::new(CGPoint::ZERO, CGSize::new(out_w as CGFloat, out_h as CGFloat));
+    match style.fill {
+        BackgroundFill::Solid((r, g, b)) => {
+            CGContext::set_rgb_fill_color(Some(&ctx), r, g, b, 1.0);
+            CGContext::fill_rect(Some(&ctx), canvas);
+        }
+        BackgroundFill::Gradient(from, to) => {
+            let components = [from.0, from.1, from.2, 1.0, to.0, to.1, to.2, 1.0];
+            let locations: [CGFloat; 2] = [0.0, 1.0];
+            let gradient = unsafe {
+                CGGradient::with_color_components(
+                    Some(&color_space),
+                    components.as_ptr(),
+                    locations.as_ptr(),
+                    2,
+                )
+            }?;
+            // Bottom-left origin: start at the top-left corner
+            CGContext::draw_linear_gradient(
+                Some(&ctx),
+                Some(&gradient),
+                CGPoint::new(0.0, canvas.size.height),
+                CGPoint::new(canvas.size.width, 0.0),
+                CGGradientDrawingOptions::empty(),
+            );
+        }
+    }
+
+    let padding = style.padding as CGFloat;
+    let image_rect = CGRect::new(
+        CGPoint::new(padding, padding),
+        CGSize::new(width as CGFloat, height as CGFloat),
+    );
+    if style.shadow {
+        // Shadow scales with the image so it reads the same on Retina captures
+        let blur = (width.max(height) as CGFloat * 0.03).clamp(8.0, 60.0);
+        CGContext::save_g_state(Some(&ctx));
+        let shadow_color = CGColor::new_srgb(0.0, 0.0, 0.0, 0.45);
+        CGContext::set_shadow_with_color(
+            Some(&ctx),
+            CGSize::new(0.0, -blur / 3.0),
+            blur,
+            Some(&shadow_color),
+        );
+        CGContext::set_rgb_fill_color(Some(&ctx), 0.0, 0.0, 0.0, 1.0);
+        add_rounded_rect_path(&ctx, image_rect, style.corner_radius);
+        CGContext::fill_path(Some(&ctx));
+        CGContext::restore_g_state(Some(&ctx));
+    }
+    CGContext::save_g_state(Some(&ctx));
+    add_rounded_rect_path(&ctx, image_rect, style.corner_radius);
+    CGContext::clip(Some(&ctx));
+    CGContext::draw_image(Some(&ctx), image_rect, Some(image));
+    CGContext::restore_g_state(Some(&ctx));
+    CGBitmapContextCreateImage(Some(&ctx))
+}
+
+/// Draw `frame`'s chrome above `image`, sized for a capture at `scale` pixels per point.
+/// Returns the framed image and the chrome's height in pixels.
+pub(crate) fn add_window_frame(
+    image: &CGImage,
+    frame: WindowFrame,
+    scale: CGFloat,
+) -> Option<(CFRetained<CGImage>, usize)> {
+    let width = CGImage::width(Some(image));
+    let height = CGImage::height(Some(image));
+    let bar = (frame.bar_height() * scale).round() as usize;
+    let (out_w, out_h) = (width, height + bar);
+    let color_space = CGColorSpace::new_device_rgb()?;
+    let ctx = unsafe {
+        CGBitmapContextCreate(
+            std::ptr::null_mut(),
+            out_w,
+            out_h,
+            8,
+            out_w * 4,
+            Some(&color_space),
+            CGImageAlphaInfo::PremultipliedLast.0,
+        )
+    }?;
+    let canvas = CGRect::new(CGPoint::ZERO, CGSize::new(out_w as CGFloat, out_h as CGFloat));
+    // Round the window's corners; the pixels outside stay transparent
+    add_rounded_rect_path(&ctx, canvas, 10.0 * scale);
+    CGContext::clip(Some(&ctx));
+
+    let bar_rect = CGRect::new(
+        CGPoint::new(0.0, height as CGFloat),
+        CGSize::new(out_w as CGFloat, bar as CGFloat),
+    );
+    let (bar_color, line_color) = match frame {
+        WindowFrame::MacDark => (0.22, 0.1),
+        WindowFrame::MacLight | WindowFrame::Browser => (0.93, 0.8),
+    };
+    CGContext::set_rgb_fill_color(Some(&ctx), bar_color, bar_color, bar_color, 1.0);
+    CGContext::fill_rect(Some(&ctx), bar_rect);
+    CGContext::draw_image(
+        Some(&ctx),
+        CGRect::new(CGPoint::ZERO, CGSize::new(width as CGFloat, height as CGFloat)),
+        Some(image),
+    );
+    // Hairline between the chrome and the content
+    CGContext::set_rgb_fill_color(Some(&ctx), line_color, line_color, line_color, 1.0);
+    CGContext::fill_rect(
+        Some(&ctx),
+        CGRect::new(
+            CGPoint::new(0.0, height as CGFloat),
+            CGSize::new(out_w as CGFloat, scale.max(1.0)),
+        ),
+    );
+
+    // Traffic lights: close, minimize, zoom
+    let center_y = height as CGFloat + bar as CGFloat / 2.0;
+    let diameter = 12.0 * scale;
+    let lights = [(1.0, 0.37, 0.34), (1.0, 0.74, 0.18), (0.16, 0.78, 0.25)];
+    for (i, (r, g, b)) in lights.into_iter().enumerate() {
+        let center_x = (20.0 + 20.0 * i as CGFloat) * scale;
+        CGContext::set_rgb_fill_color(Some(&ctx), r, g, b, 1.0);
+        CGContext::fill_ellipse_in_rect(
+            Some(&ctx),
+            CGRect::new(
+                CGPoint::new(center_x - diameter / 2.0, center_y - diameter / 2.0),
+                CGSize::new(diameter, diameter),
+            ),
+        );
+    }
+
+    if frame == WindowFrame::Browser {
+        // Empty address bar to the right of the traffic lights
+        let field_x = 90.0 * scale;
+        let field_w = out_w as CGFloat - field_x - 20.0 * scale;
+        if field_w > 0.0 {
+            let field_h = 24.0 * scale;
+            let field = CGRect::new(
+                CGPoint::new(field_x, center_y - field_h / 2.0),
+                CGSize::new(field_w, field_h),
+            );
+            CGContext::set_rgb_fill_color(Some(&ctx), 1.0, 1.0, 1.0, 1.0);
+            add_rounded_rect_path(&ctx, field, 6.0 * scale);
+            CGContext::fill_path(Some(&ctx));
+        }
+    }
+    Some((CGBitmapContextCreateImage(Some(&ctx))?, bar))
+}
+
+/// Run `adjustment` over every pixel of `image`.
+pub(crate) fn adjust_image(
+    image: &CGImage,
+    adjustment: ImageAdjustment,
+) -> Result<CFRetained<CGImage>, String> {
+    let width = CGImage::width(Some(image));
+    let height = CGImage::height(Some(image));
+    let mut rgba = crate::actions::cgimage_to_rgba(image)?;
+    // The buffer is premultiplied, so each channel runs from 0 to the pixel's alpha
+    for px in rgba.chunks_exact_mut(4) {
+        match adjustment {
+            ImageAdjustment::Grayscale => {
+                let luma = 0.2126 * px[0] as f64 + 0.7152 * px[1] as f64 + 0.0722 * px[2] as f64;
+                let luma = luma.round().min(px[3] as f64) as u8;
+                px[..3].fill(luma);
+            }
+            ImageAdjustment::Invert => {
+                let alpha = px[3];
+                for c in px[..3].iter_mut() {
+                    *c = alpha.saturating_sub(*c);
+                }
+            }
+        }
+    }
+    crate::stitch::rgba_to_cgimage(&mut rgba, width, height)
+        .ok_or("Failed to create image".to_string())
+}
+
+/// Draw annotations (each with its opacity) onto a source frame, producing a new CGImage.
 /// `view_size` is the editor view's bounds size — annotations use this coordinate space.
 pub(crate) fn composite_frame(
     source: &CGImage,
-    annotations: &[&crate::annotation::model::Annotation],
+    annotations: &[(&crate::annotation::model::Annotation, CGFloat)],
     width: usize,
     height: usize,
     view_size: (CGFloat, CGFloat),
@@ -113,9 +609,53 @@ pub(crate) fn composite_frame(
     let sy = height as CGFloat / view_size.1;
     CGContext::scale_ctm(Some(&ctx), sx, sy);
 
-    for ann in annotations {
-        crate::annotation::renderer::draw_annotation(&ctx, ann, Some(source));
+    for &(ann, opacity) in annotations {
+        crate::annotation::renderer::draw_annotation_with_opacity(&ctx, ann, Some(source), opacity);
     }
 
     CGBitmapContextCreateImage(Some(&ctx))
 }
+
+/// Scale a crop rect from editor view coordinates to whole pixels of a
+/// `width`×`height` frame.
+pub(crate) fn pixel_crop_rect(
+    crop: CGRect,
+    width: usize,
+    height: usize,
+    view_size: (CGFloat, CGFloat),
+) -> CGRect {
+    let sx = width as CGFloat / view_size.0;
+    let sy = height as CGFloat / view_size.1;
+    CGRect::new(
+        CGPoint::new((crop.origin.x * sx).round(), (crop.origin.y * sy).round()),
+        CGSize::new((crop.size.width * sx).round(), (crop.size.height * sy).round()),
+    )
+}
+
+/// `pixel_crop_rect` kept inside the frame.
+fn clamped_pixel_crop(
+    crop: CGRect,
+    width: usize,
+    height: usize,
+    view_size: (CGFloat, CGFloat),
+) -> CGRect {
+    let rect = pixel_crop_rect(crop, width, height, view_size);
+    let x0 = rect.origin.x.clamp(0.0, width as CGFloat);
+    let y0 = rect.origin.y.clamp(0.0, height as CGFloat);
+    let x1 = (rect.origin.x + rect.size.width).clamp(x0, width as CGFloat);
+    let y1 = (rect.origin.y + rect.size.height).clamp(y0, height as CGFloat);
+    CGRect::new(CGPoint::new(x0, y0), CGSize::new(x1 - x0, y1 - y0))
+}
+
+/// `clamped_pixel_crop` trimmed to even dimensions.
+fn even_pixel_crop(
+    crop: CGRect,
+    width: usize,
+    height: usize,
+    view_size: (CGFloat, CGFloat),
+) -> CGRect {
+    let mut rect = clamped_pixel_crop(crop, width, height, view_size);
+    rect.size.width = (rect.size.width as usize & !1) as CGFloat;
+    rect.size.height = (rect.size.height as usize & !1) as CGFloat;
+    rect
+}
@@ -1,12 +1,14 @@
-use std::cell::Cell;
+use std::cell::{Cell, RefCell};
 use std::ffi::CString;
 
 use objc2::rc::Retained;
 use objc2::{define_class, msg_send, DefinedClass, MainThreadOnly};
-use objc2_app_kit::{NSEvent, NSView};
+use objc2_app_kit::{NSEvent, NSFont, NSTextField, NSView};
 use objc2_core_foundation::{CGAffineTransform, CGFloat, CGPoint, CGSize};
 use objc2_core_graphics::CGContext;
-use objc2_foundation::{MainThreadMarker, NSRect};
+use objc2_foundation::{MainThreadMarker, NSRect, NSString};
+
+use crate::prefs::TimelineUnit;
 
 /// What the user is currently dragging on the mini bar.
 #[derive(Clone, Copy, PartialEq)]
@@ -22,9 +24,17 @@ pub struct MiniBarViewIvars {
     /// None = annotation persists to end of video.
     end_frame: Cell<Option<usize>>,
     current_frame: Cell<usize>,
+    /// Fade-in and fade-out lengths in frames, shaded at the ends of the range.
+    fades: Cell<(usize, usize)>,
     dragging: Cell<MiniBarDragTarget>,
     /// Frame the delegate should seek to (set during drag).
     pending_seek_frame: Cell<Option<usize>>,
+    /// Unit and frame rate for the range label under the track.
+    unit: Cell<TimelineUnit>,
+    fps: Cell<f64>,
+    /// Handle whose position is being typed into `entry_field` (None = not editing).
+    entry_target: Cell<MiniBarDragTarget>,
+    entry_field: RefCell<Option<Retained<NSTextField>>>,
 }
 
 const HANDLE_WIDTH: CGFloat = 6.0;
@@ -32,9 +42,16 @@ const TRACK_HEIGHT: CGFloat = 10.0;
 const TRACK_Y_OFFSET: CGFloat = 5.0;
 const BUTTON_WIDTH: CGFloat = 42.0;
 const BUTTON_GAP: CGFloat = 4.0;
-
-/// Total height of the mini bar view (track + padding).
-pub const MINI_BAR_HEIGHT: CGFloat = 20.0;
+const BUTTON_HEIGHT: CGFloat = 16.0;
+const LABEL_FONT_SIZE: CGFloat = 9.0;
+/// Baseline of the range label (flipped coordinates).
+const LABEL_BASELINE: CGFloat = 28.0;
+/// Size of the field for typing a handle's position (replaces the range label).
+const ENTRY_FIELD_WIDTH: CGFloat = 80.0;
+const ENTRY_FIELD_HEIGHT: CGFloat = 15.0;
+
+/// Total height of the mini bar view (track + range label + padding).
+pub const MINI_BAR_HEIGHT: CGFloat = 32.0;
 /// Width of the mini bar view (track + button).
 pub const MINI_BAR_WIDTH: CGFloat = 220.0;
 /// Vertical gap between annotation bounding rect and the mini bar.
@@ -64,7 +81,7 @@ define_class!(
             // Draw semi-transparent background pill
             CGContext::save_g_state(Some(&ctx));
             CGContext::set_rgb_fill_color(Some(&ctx), 0.0, 0.0, 0.0, 0.75);
-            fill_rounded_rect(&ctx, bounds, bounds.size.height / 2.0);
+            fill_rounded_rect(&ctx, bounds, BUTTON_HEIGHT / 2.0 + 2.0);
 
             let total = self.ivars().total_frames.get();
             if total == 0 {
@@ -105,6 +122,23 @@ define_class!(
                 );
                 CGContext::set_rgb_fill_color(Some(&ctx), 0.3, 0.6, 1.0, 0.7);
                 fill_rounded_rect(&ctx, range_rect, corner_radius);
+
+                // Shade the fade-in and fade-out frames at either end
+                let (fade_in, fade_out) = self.ivars().fades.get();
+                let fade_in_x = self.x_for_frame((start + fade_in).min(end)).min(end_x);
+                let fade_out_x = self.x_for_frame(end.saturating_sub(fade_out)).max(start_x);
+                CGContext::set_rgb_fill_color(Some(&ctx), 0.0, 0.0, 0.0, 0.35);
+                for (from, to) in [(start_x, fade_in_x), (fade_out_x, end_x)] {
+                    if to > from {
+                        CGContext::fill_rect(
+                            Some(&ctx),
+                            NSRect::new(
+                                CGPoint::new(from, TRACK_Y_OFFSET),
+                                CGSize::new(to - from, TRACK_HEIGHT),
+                            ),
+                        );
+                    }
+                }
             }
 
             // Draw start handle
@@ -136,10 +170,10 @@ define_class!(
             let button_x = bounds.size.width - BUTTON_WIDTH - 2.0;
             let button_rect = NSRect::new(
                 CGPoint::new(button_x, 2.0),
-                CGSize::new(BUTTON_WIDTH, bounds.size.height - 4.0),
+                CGSize::new(BUTTON_WIDTH, BUTTON_HEIGHT),
             );
             CGContext::set_rgb_fill_color(Some(&ctx), 0.3, 0.6, 1.0, 1.0);
-            fill_rounded_rect(&ctx, button_rect, (bounds.size.height - 4.0) / 2.0);
+            fill_rounded_rect(&ctx, button_rect, BUTTON_HEIGHT / 2.0);
 
             // Draw "Done" text — flipped coordinate system, so use negative d in text matrix
             CGContext::set_rgb_fill_color(Some(&ctx), 1.0, 1.0, 1.0, 1.0);
@@ -161,7 +195,7 @@ define_class!(
             let label = CString::new("Done").unwrap();
             let text_w: CGFloat = 30.0; // approximate width of "Done" at 11pt
             let text_x = button_x + (BUTTON_WIDTH - text_w) / 2.0;
-            let text_y = bounds.size.height / 2.0 + font_size * 0.35;
+            let text_y = 2.0 + BUTTON_HEIGHT / 2.0 + font_size * 0.35;
             #[allow(deprecated)]
             unsafe {
                 CGContext::show_text_at_point(
@@ -173,6 +207,45 @@ define_class!(
                 );
             }
 
+            if self.ivars().entry_target.get() != MiniBarDragTarget::None {
+                CGContext::restore_g_state(Some(&ctx));
+                return;
+            }
+
+            // Range label under the track: first to last visible frame, or
+            // the time the annotation appears to the time it disappears
+            let unit = self.ivars().unit.get();
+            let fps = self.ivars().fps.get();
+            let end_label = match unit {
+                TimelineUnit::Frames => end.saturating_sub(1),
+                TimelineUnit::Time => end,
+            };
+            let range_label = CString::new(format!(
+                "{} - {}",
+                unit.format(start, fps),
+                unit.format(end_label, fps)
+            ))
+            .unwrap();
+            let label_len = range_label.as_bytes().len();
+            let label_font = CString::new("Helvetica").unwrap();
+            CGContext::set_rgb_fill_color(Some(&ctx), 0.85, 0.85, 0.85, 1.0);
+            #[allow(deprecated)]
+            unsafe {
+                CGContext::select_font(
+                    Some(&ctx),
+                    label_font.as_ptr(),
+                    LABEL_FONT_SIZE,
+                    objc2_core_graphics::CGTextEncoding::EncodingMacRoman,
+                );
+                CGContext::show_text_at_point(
+                    Some(&ctx),
+                    track_left,
+                    LABEL_BASELINE,
+                    range_label.as_ptr(),
+                    label_len,
+                );
+            }
+
             CGContext::restore_g_state(Some(&ctx));
         }
 
@@ -201,10 +274,17 @@ define_class!(
             let hit_tolerance = HANDLE_WIDTH + 4.0;
 
             // Check end handle first (takes priority if handles overlap)
-            if (point.x - end_x).abs() <= hit_tolerance {
-                self.ivars().dragging.set(MiniBarDragTarget::EndHandle);
+            let target = if (point.x - end_x).abs() <= hit_tolerance {
+                MiniBarDragTarget::EndHandle
             } else if (point.x - start_x).abs() <= hit_tolerance {
-                self.ivars().dragging.set(MiniBarDragTarget::StartHandle);
+                MiniBarDragTarget::StartHandle
+            } else {
+                return;
+            };
+            if event.clickCount() >= 2 {
+                self.begin_entry(target);
+            } else {
+                self.ivars().dragging.set(target);
             }
         }
 
@@ -244,6 +324,18 @@ define_class!(
             self.notify_delegate_changed();
         }
 
+        /// Enter in the position field.
+        #[unsafe(method(frameEntryEntered:))]
+        fn frame_entry_entered(&self, _sender: &NSTextField) {
+            self.apply_entry();
+        }
+
+        /// Esc while typing a position (forwarded up from the field editor).
+        #[unsafe(method(cancelOperation:))]
+        fn cancel_operation(&self, _sender: Option<&objc2::runtime::AnyObject>) {
+            self.end_entry();
+        }
+
         #[unsafe(method(mouseUp:))]
         fn mouse_up(&self, _event: &NSEvent) {
             let was_dragging = self.ivars().dragging.get();
@@ -263,28 +355,160 @@ impl MiniBarView {
             start_frame: Cell::new(0),
             end_frame: Cell::new(None),
             current_frame: Cell::new(0),
+            fades: Cell::new((0, 0)),
             dragging: Cell::new(MiniBarDragTarget::None),
             pending_seek_frame: Cell::new(None),
+            unit: Cell::new(TimelineUnit::Frames),
+            fps: Cell::new(30.0),
+            entry_target: Cell::new(MiniBarDragTarget::None),
+            entry_field: RefCell::new(None),
         });
         let view: Retained<Self> = unsafe { msg_send![super(this), initWithFrame: frame] };
+        view.create_entry_field(mtm);
         view
     }
 
+    /// Build the (hidden) field for typing a handle's position.
+    fn create_entry_field(&self, mtm: MainThreadMarker) {
+        let frame = NSRect::new(
+            CGPoint::ZERO,
+            CGSize::new(ENTRY_FIELD_WIDTH, ENTRY_FIELD_HEIGHT),
+        );
+        let field: Retained<NSTextField> =
+            unsafe { msg_send![mtm.alloc::<NSTextField>(), initWithFrame: frame] };
+        field.setFont(Some(&NSFont::monospacedDigitSystemFontOfSize_weight(10.0, 0.0)));
+        unsafe {
+            field.setAction(Some(objc2::sel!(frameEntryEntered:)));
+            field.setTarget(Some(self));
+        }
+        field.setHidden(true);
+        self.addSubview(&field);
+        *self.ivars().entry_field.borrow_mut() = Some(field);
+    }
+
+    /// The position shown for a handle, in the current unit: the first or
+    /// last visible frame, or the time the annotation appears or disappears.
+    fn handle_label(&self, target: MiniBarDragTarget) -> String {
+        let unit = self.ivars().unit.get();
+        let fps = self.ivars().fps.get();
+        let total = self.ivars().total_frames.get();
+        let frame = match target {
+            MiniBarDragTarget::EndHandle => {
+                let end = self.ivars().end_frame.get().unwrap_or(total);
+                match unit {
+                    TimelineUnit::Frames => end.saturating_sub(1),
+                    TimelineUnit::Time => end,
+                }
+            }
+            _ => self.ivars().start_frame.get(),
+        };
+        unit.format(frame, fps)
+    }
+
+    /// Show the position field under a handle, filled with its current value.
+    fn begin_entry(&self, target: MiniBarDragTarget) {
+        let Some(field) = self.ivars().entry_field.borrow().clone() else {
+            return;
+        };
+        let bounds = self.bounds();
+        let track_left = HANDLE_WIDTH + 4.0;
+        let track_right = bounds.size.width - BUTTON_WIDTH - BUTTON_GAP;
+        let x = match target {
+            MiniBarDragTarget::EndHandle => track_right - ENTRY_FIELD_WIDTH,
+            _ => track_left,
+        };
+        field.setFrameOrigin(CGPoint::new(x, bounds.size.height - ENTRY_FIELD_HEIGHT - 1.0));
+        field.setStringValue(&NSString::from_str(&self.handle_label(target)));
+        field.setHidden(false);
+        self.ivars().entry_target.set(target);
+        self.setNeedsDisplay(true);
+        if let Some(window) = self.window() {
+            let _ = window.makeFirstResponder(Some(&field));
+        }
+    }
+
+    /// Move the edited handle to the typed position, then report it like the
+    /// end of a drag. Invalid input leaves the range unchanged.
+    fn apply_entry(&self) {
+        let target = self.ivars().entry_target.get();
+        let text = self
+            .ivars()
+            .entry_field
+            .borrow()
+            .as_ref()
+            .map(|f| f.stringValue().to_string())
+            .unwrap_or_default();
+        self.end_entry();
+
+        let unit = self.ivars().unit.get();
+        let Some(frame) = unit.parse(&text, self.ivars().fps.get()) else {
+            eprintln!("Invalid position: {:?}", text);
+            return;
+        };
+        let total = self.ivars().total_frames.get();
+        match target {
+            MiniBarDragTarget::StartHandle => {
+                let end = self.ivars().end_frame.get().unwrap_or(total);
+                let clamped = frame.min(end.saturating_sub(1));
+                self.ivars().start_frame.set(clamped);
+                self.ivars().pending_seek_frame.set(Some(clamped));
+            }
+            MiniBarDragTarget::EndHandle => {
+                // Frames name the last visible frame; times name when it disappears
+                let end = match unit {
+                    TimelineUnit::Frames => frame + 1,
+                    TimelineUnit::Time => frame,
+                };
+                let start = self.ivars().start_frame.get();
+                let clamped = end.max(start + 1).min(total);
+                self.ivars().end_frame.set(Some(clamped));
+                self.ivars().pending_seek_frame.set(Some(clamped.saturating_sub(1)));
+            }
+            MiniBarDragTarget::None => return,
+        }
+        self.setNeedsDisplay(true);
+        self.notify_delegate_changed();
+        self.notify_delegate_drag_ended();
+    }
+
+    /// Hide the position field and hand keys back to the editor view.
+    pub fn end_entry(&self) {
+        if self.ivars().entry_target.replace(MiniBarDragTarget::None) == MiniBarDragTarget::None {
+            return;
+        }
+        if let Some(ref field) = *self.ivars().entry_field.borrow() {
+            field.setHidden(true);
+        }
+        self.setNeedsDisplay(true);
+        if let (Some(window), Some(editor_view)) = (self.window(), unsafe { self.superview() }) {
+            let _ = window.makeFirstResponder(Some(&editor_view));
+        }
+    }
+
     /// Update the mini bar's state from external source.
     pub fn update_state(
         &self,
         start: usize,
         end: Option<usize>,
+        fades: (usize, usize),
         current: usize,
         total: usize,
     ) {
         self.ivars().start_frame.set(start);
         self.ivars().end_frame.set(end);
+        self.ivars().fades.set(fades);
         self.ivars().current_frame.set(current);
         self.ivars().total_frames.set(total);
         self.setNeedsDisplay(true);
     }
 
+    /// Set how the range label shows frame positions.
+    pub fn set_timeline_unit(&self, unit: TimelineUnit, fps: f64) {
+        self.ivars().unit.set(unit);
+        self.ivars().fps.set(fps);
+        self.setNeedsDisplay(true);
+    }
+
     /// Take the pending seek frame (consumed by delegate).
     pub fn take_pending_seek_frame(&self) -> Option<usize> {
         self.ivars().pending_seek_frame.take()
@@ -357,21 +581,6 @@ impl MiniBarView {
 
 /// Fill a rounded rectangle path.
 fn fill_rounded_rect(ctx: &CGContext, rect: NSRect, radius: CGFloat) {
-    let min_x = rect.origin.x;
-    let min_y = rect.origin.y;
-    let max_x = min_x + rect.size.width;
-    let max_y = min_y + rect.size.height;
-    let r = radius.min(rect.size.width / 2.0).min(rect.size.height / 2.0);
-
-    CGContext::move_to_point(Some(ctx), min_x + r, min_y);
-    CGContext::add_line_to_point(Some(ctx), max_x - r, min_y);
-    CGContext::add_arc_to_point(Some(ctx), max_x, min_y, max_x, min_y + r, r);
-    CGContext::add_line_to_point(Some(ctx), max_x, max_y - r);
-    CGContext::add_arc_to_point(Some(ctx), max_x, max_y, max_x - r, max_y, r);
-    CGContext::add_line_to_point(Some(ctx), min_x + r, max_y);
-    CGContext::add_arc_to_point(Some(ctx), min_x, max_y, min_x, max_y - r, r);
-    CGContext::add_line_to_point(Some(ctx), min_x, min_y + r);
-    CGContext::add_arc_to_point(Some(ctx), min_x, min_y, min_x + r, min_y, r);
-    CGContext::close_path(Some(ctx));
+    crate::annotation::renderer::add_rounded_rect_path(ctx, rect, radius);
     CGContext::fill_path(Some(ctx));
 }
@@ -2,5 +2,6 @@ pub mod model;
 pub mod decoder;
 pub mod view;
 pub mod minibar;
+pub mod thumbnails;
 pub mod window;
 pub mod export;
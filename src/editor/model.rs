@@ -1,8 +1,8 @@
 use std::path::PathBuf;
 
-use objc2_core_foundation::CGPoint;
+use objc2_core_foundation::{CGFloat, CGPoint, CGRect, CGSize};
 
-use crate::annotation::model::Annotation;
+use crate::annotation::model::{Annotation, TransformHistory};
 
 /// A single annotation with its own lifespan (frame range).
 #[derive(Clone)]
@@ -11,6 +11,130 @@ pub struct TimedAnnotation {
     pub start_frame: usize,
     /// None means the annotation persists until the end of the video.
     pub end_frame: Option<usize>,
+    /// Frames over which the annotation fades in after `start_frame` (0 = pops in).
+    pub fade_in_frames: usize,
+    /// Frames over which the annotation fades out before it ends (0 = pops out).
+    pub fade_out_frames: usize,
+}
+
+/// Fade lengths offered when cycling an annotation's fade, in seconds.
+const FADE_STEPS: [f64; 4] = [0.0, 0.25, 0.5, 1.0];
+
+impl TimedAnnotation {
+    /// Opacity at `frame` from the fades (1.0 outside them). Annotations that
+    /// persist to the end fade out before the video's last frame.
+    pub fn opacity_at(&self, frame: usize, total_frames: usize) -> CGFloat {
+        let end = self.end_frame.unwrap_or(total_frames);
+        let ramp = |offset: usize, frames: usize| {
+            if frames == 0 {
+                1.0
+            } else {
+                ((offset + 1) as CGFloat / (frames + 1) as CGFloat).min(1.0)
+            }
+        };
+        let fade_in = ramp(frame.saturating_sub(self.start_frame), self.fade_in_frames);
+        let fade_out = ramp(end.saturating_sub(frame + 1), self.fade_out_frames);
+        fade_in.min(fade_out)
+    }
+}
+
+/// A quarter turn or mirror applied to a still image in the editor.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ImageTransform {
+    /// 90° counterclockwise.
+    RotateLeft,
+    /// 90° clockwise.
+    RotateRight,
+    /// Mirrored left to right.
+    FlipHorizontal,
+    /// Mirrored top to bottom.
+    FlipVertical,
+}
+
+impl ImageTransform {
+    /// Pixel size of a `width`×`height` image after the transform.
+    pub fn output_size(self, width: usize, height: usize) -> (usize, usize) {
+        match self {
+            ImageTransform::RotateLeft | ImageTransform::RotateRight => (height, width),
+            ImageTransform::FlipHorizontal | ImageTransform::FlipVertical => (width, height),
+        }
+    }
+
+    /// Where a point of a `width`×`height` image (top-left origin) ends up.
+    pub fn map_point(self, p: CGPoint, width: CGFloat, height: CGFloat) -> CGPoint {
+        match self {
+            ImageTransform::RotateLeft => CGPoint::new(p.y, width - p.x),
+            ImageTransform::RotateRight => CGPoint::new(height - p.y, p.x),
+            ImageTransform::FlipHorizontal => CGPoint::new(width - p.x, p.y),
+            ImageTransform::FlipVertical => CGPoint::new(p.x, height - p.y),
+        }
+    }
+}
+
+/// A color change applied to every pixel of a still image in the editor.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ImageAdjustment {
+    Grayscale,
+    Invert,
+}
+
+/// What fills the canvas around a still image given a background.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum BackgroundFill {
+    Solid((CGFloat, CGFloat, CGFloat)),
+    /// Blends from the first color at the top-left to the second at the bottom-right.
+    Gradient((CGFloat, CGFloat, CGFloat), (CGFloat, CGFloat, CGFloat)),
+}
+
+/// Fills offered by "Add Background", besides the current annotation color.
+pub const BACKGROUND_FILLS: [(&str, BackgroundFill); 6] = [
+    ("Sunset", BackgroundFill::Gradient((1.0, 0.62, 0.36), (0.86, 0.26, 0.52))),
+    ("Ocean", BackgroundFill::Gradient((0.27, 0.68, 0.96), (0.36, 0.27, 0.82))),
+    ("Mint", BackgroundFill::Gradient((0.62, 0.93, 0.76), (0.18, 0.62, 0.66))),
+    ("White", BackgroundFill::Solid((1.0, 1.0, 1.0))),
+    ("Light Gray", BackgroundFill::Solid((0.92, 0.92, 0.93))),
+    ("Charcoal", BackgroundFill::Solid((0.16, 0.17, 0.19))),
+];
+
+/// How "Add Background" sets a still image on a larger canvas.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct BackgroundStyle {
+    /// Space around the image on every side, in image pixels.
+    pub padding: usize,
+    /// Corner radius of the image, in image pixels (0 keeps square corners).
+    pub corner_radius: CGFloat,
+    /// Cast a soft shadow from the image onto the background.
+    pub shadow: bool,
+    pub fill: BackgroundFill,
+}
+
+/// Window chrome that "Add Window Frame" draws around a still image.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum WindowFrame {
+    MacLight,
+    MacDark,
+    Browser,
+}
+
+impl WindowFrame {
+    pub const ALL: [WindowFrame; 3] =
+        [WindowFrame::MacLight, WindowFrame::MacDark, WindowFrame::Browser];
+
+    pub fn title(self) -> &'static str {
+        match self {
+            WindowFrame::MacLight => "macOS Window (Light)",
+            WindowFrame::MacDark => "macOS Window (Dark)",
+            WindowFrame::Browser => "Browser Window",
+        }
+    }
+
+    /// Height of the chrome above the image, in points.
+    pub fn bar_height(self) -> CGFloat {
+        match self {
+            WindowFrame::MacLight | WindowFrame::MacDark => 28.0,
+            WindowFrame::Browser => 40.0,
+        }
+    }
 }
 
 /// State for the post-recording video editor.
@@ -23,8 +147,22 @@ pub struct EditorState {
     pub annotations: Vec<TimedAnnotation>,
     /// Index into annotations for the annotation currently being edited.
     pub active_annotation: Option<usize>,
+    /// Annotations selected together by a marquee drag (`active_annotation` is None
+    /// while this is non-empty).
+    pub selected: Vec<usize>,
     /// Redo stack for undone annotations.
     pub redo_stack: Vec<TimedAnnotation>,
+    /// Applied crops (view coordinates), newest last, each with the number of
+    /// annotations that existed when it was applied. Only the newest is in effect.
+    pub crops: Vec<(CGRect, usize)>,
+    /// Undo and redo for annotation moves and resizes.
+    pub transforms: TransformHistory<Annotation>,
+    /// Indices and shapes of the annotations being moved or resized, from before the drag.
+    transform_before: Vec<(usize, Annotation)>,
+    /// First frame kept on export.
+    pub trim_start: usize,
+    /// Frame after the last one kept on export. None = end of the video.
+    pub trim_end: Option<usize>,
 }
 
 impl EditorState {
@@ -37,7 +175,13 @@ impl EditorState {
             is_playing: false,
             annotations: Vec::new(),
             active_annotation: None,
+            selected: Vec::new(),
             redo_stack: Vec::new(),
+            crops: Vec::new(),
+            transforms: TransformHistory::new(),
+            transform_before: Vec::new(),
+            trim_start: 0,
+            trim_end: None,
         }
     }
 
@@ -50,37 +194,65 @@ impl EditorState {
     /// Default end frame is 1 second after start (capped at total frames).
     pub fn add_annotation(&mut self, annotation: Annotation, frame: usize) -> usize {
         self.redo_stack.clear();
+        self.transforms.clear_redo();
         let one_second = (self.fps.round() as usize).max(1);
         let end = (frame + one_second).min(self.total_frames);
         let timed = TimedAnnotation {
             annotation,
             start_frame: frame,
             end_frame: Some(end),
+            fade_in_frames: 0,
+            fade_out_frames: 0,
         };
         self.annotations.push(timed);
         let idx = self.annotations.len() - 1;
         self.active_annotation = Some(idx);
+        self.selected.clear();
         idx
     }
 
     /// Remove the active annotation, or pop the last annotation if none is active.
-    /// Pushes the removed annotation to the redo stack.
+    /// Pushes the removed annotation to the redo stack. A move or resize made after
+    /// the last annotation was added, or else a crop applied after it, is undone first.
     pub fn undo_annotation(&mut self) {
+        if let Some(indices) = self.transforms.undo(&mut self.annotations, |ta| &mut ta.annotation)
+        {
+            self.select_annotations(indices);
+            return;
+        }
+        if self.active_annotation.is_none()
+            && let Some(&(_, count)) = self.crops.last()
+            && self.annotations.len() <= count
+        {
+            self.crops.pop();
+            return;
+        }
         if let Some(idx) = self.active_annotation.take() {
             if idx < self.annotations.len() {
                 let removed = self.annotations.remove(idx);
                 self.redo_stack.push(removed);
+                if idx < self.annotations.len() {
+                    // Later annotations shifted down, so recorded indices are stale
+                    self.transforms.clear();
+                }
             }
         } else if let Some(ann) = self.annotations.pop() {
             self.redo_stack.push(ann);
         }
+        let count = self.annotations.len();
+        self.selected.retain(|&i| i < count);
     }
 
-    /// Redo the last undone annotation. Returns true if an annotation was restored.
+    /// Redo the last undone move, resize, or annotation. Returns true if anything was restored.
     pub fn redo_annotation(&mut self) -> bool {
-        if let Some(ann) = self.redo_stack.pop() {
+        if let Some(indices) = self.transforms.redo(&mut self.annotations, |ta| &mut ta.annotation)
+        {
+            self.select_annotations(indices);
+            true
+        } else if let Some(ann) = self.redo_stack.pop() {
             self.annotations.push(ann);
             self.active_annotation = Some(self.annotations.len() - 1);
+            self.selected.clear();
             true
         } else {
             false
@@ -93,6 +265,8 @@ impl EditorState {
             return;
         }
         self.annotations.remove(idx);
+        self.transforms.clear();
+        self.selected.clear();
         // Adjust or clear active_annotation
         match self.active_annotation {
             Some(active) if active == idx => {
@@ -105,6 +279,95 @@ impl EditorState {
         }
     }
 
+    /// Append a copy of an annotation, offset by (dx, dy), with the same frame range.
+    /// Selects the copy and returns its index.
+    pub fn duplicate_annotation(&mut self, idx: usize, dx: CGFloat, dy: CGFloat) -> Option<usize> {
+        let mut copy = self.annotations.get(idx)?.clone();
+        copy.annotation.translate(dx, dy);
+        self.redo_stack.clear();
+        self.transforms.clear_redo();
+        self.annotations.push(copy);
+        let new_idx = self.annotations.len() - 1;
+        self.active_annotation = Some(new_idx);
+        self.selected.clear();
+        Some(new_idx)
+    }
+
+    /// Move an annotation to the front (drawn last) or back (drawn first) of the paint order.
+    /// Its frame range moves with it; the active index is remapped. Returns the new index.
+    pub fn reorder_annotation(&mut self, idx: usize, to_front: bool) -> Option<usize> {
+        let new_idx = crate::annotation::model::reorder(&mut self.annotations, idx, to_front)?;
+        self.transforms.clear();
+        self.selected.clear();
+        self.active_annotation = self.active_annotation.map(|active| {
+            if active == idx {
+                new_idx
+            } else if to_front && active > idx {
+                active - 1
+            } else if !to_front && active < idx {
+                active + 1
+            } else {
+                active
+            }
+        });
+        Some(new_idx)
+    }
+
+    /// Remove every annotation and forget their undo history.
+    pub fn clear_annotations(&mut self) {
+        self.annotations.clear();
+        self.redo_stack.clear();
+        self.transforms.clear();
+        self.active_annotation = None;
+        self.selected.clear();
+    }
+
+    /// Delete several annotations at once (a marquee selection) and clear the selection.
+    pub fn delete_annotations(&mut self, indices: &[usize]) {
+        crate::annotation::model::remove_indices(&mut self.annotations, indices);
+        self.transforms.clear();
+        self.active_annotation = None;
+        self.selected.clear();
+    }
+
+    /// Annotations that moves, nudges, and Delete act on: the marquee group, or else
+    /// the active one.
+    pub fn selected_indices(&self) -> Vec<usize> {
+        if self.selected.is_empty() {
+            self.active_annotation.into_iter().collect()
+        } else {
+            self.selected.clone()
+        }
+    }
+
+    /// Swap in a changed copy of the annotation at `idx` (its text edited), keeping
+    /// its frame range, fades, and paint order. Undo restores the original like a move.
+    pub fn replace_annotation(&mut self, idx: usize, annotation: Annotation) {
+        let Some(ta) = self.annotations.get_mut(idx) else {
+            return;
+        };
+        let before = std::mem::replace(&mut ta.annotation, annotation);
+        self.transforms.record(vec![(idx, before)], self.annotations.len());
+        self.select_annotation(idx);
+    }
+
+    /// Remember the annotations at `indices` as they are before a move or resize
+    /// (once per drag).
+    pub fn begin_transform(&mut self, indices: &[usize]) {
+        if self.transform_before.is_empty() {
+            self.transform_before = indices
+                .iter()
+                .filter_map(|&i| self.annotations.get(i).map(|ta| (i, ta.annotation.clone())))
+                .collect();
+        }
+    }
+
+    /// Record the move or resize started by `begin_transform` for undo.
+    pub fn finish_transform(&mut self) {
+        let before = std::mem::take(&mut self.transform_before);
+        self.transforms.record(before, self.annotations.len());
+    }
+
     /// Collect all annotations visible at a given frame index, with their indices.
     pub fn annotations_at_frame(&self, frame: usize) -> Vec<(usize, &Annotation)> {
         self.annotations
@@ -143,23 +406,104 @@ impl EditorState {
         }
     }
 
+    /// Step an annotation's fade in (or out) to the next length in `FADE_STEPS`,
+    /// wrapping back to none. Returns the new length in frames.
+    pub fn cycle_annotation_fade(&mut self, idx: usize, fade_out: bool) -> Option<usize> {
+        let fps = self.fps;
+        let ta = self.annotations.get_mut(idx)?;
+        let frames = if fade_out { &mut ta.fade_out_frames } else { &mut ta.fade_in_frames };
+        let mut steps: Vec<usize> =
+            FADE_STEPS.iter().map(|s| (s * fps).round() as usize).collect();
+        steps.dedup();
+        let next = steps
+            .iter()
+            .position(|&step| step == *frames)
+            .map_or(1, |i| (i + 1) % steps.len());
+        *frames = steps[next];
+        Some(*frames)
+    }
+
     /// Select an annotation by index for editing.
     pub fn select_annotation(&mut self, idx: usize) {
         if idx < self.annotations.len() {
             self.active_annotation = Some(idx);
+            self.selected.clear();
+        }
+    }
+
+    /// Select several annotations at once (a marquee drag). A single one becomes the
+    /// active annotation instead.
+    pub fn select_annotations(&mut self, mut indices: Vec<usize>) {
+        let count = self.annotations.len();
+        indices.retain(|&i| i < count);
+        if indices.len() == 1 {
+            self.active_annotation = indices.pop();
+            self.selected.clear();
+        } else {
+            self.active_annotation = None;
+            self.selected = indices;
         }
     }
 
     /// Deselect the active annotation.
     pub fn deselect_annotation(&mut self) {
         self.active_annotation = None;
+        self.selected.clear();
     }
 
-    /// Clear all annotations, redo stack, and active annotation (used after crop bakes them in).
-    pub fn clear_all(&mut self) {
-        self.annotations.clear();
-        self.redo_stack.clear();
+    /// Crop the output to `rect` (view coordinates). Undoable with `undo_annotation`.
+    pub fn apply_crop(&mut self, rect: CGRect) {
         self.active_annotation = None;
+        self.selected.clear();
+        // Earlier moves and resizes would otherwise be undone ahead of the crop
+        self.transforms.clear();
+        self.crops.push((rect, self.annotations.len()));
+    }
+
+    /// Move every annotation and crop (including undone ones) through `map`, scaling
+    /// their line widths and text by `scale`. Pending move and resize undos are dropped.
+    pub fn map_geometry(&mut self, map: impl Fn(CGPoint) -> CGPoint, scale: CGFloat) {
+        for ta in self.annotations.iter_mut().chain(self.redo_stack.iter_mut()) {
+            ta.annotation.map_geometry(&map, scale);
+        }
+        for (rect, _) in self.crops.iter_mut() {
+            *rect = map_rect(*rect, &map);
+        }
+        self.transforms.clear();
+        self.transform_before.clear();
+    }
+
+    /// The crop currently in effect, if any.
+    pub fn crop(&self) -> Option<CGRect> {
+        self.crops.last().map(|(rect, _)| *rect)
+    }
+
+    /// Start the exported video at `frame` (kept before the trim end).
+    pub fn set_trim_start(&mut self, frame: usize) {
+        let end = self.trim_range().end;
+        self.trim_start = frame.min(end.saturating_sub(1));
+    }
+
+    /// End the exported video after `frame` (kept after the trim start).
+    pub fn set_trim_end(&mut self, frame: usize) {
+        let end = (frame + 1).max(self.trim_start + 1).min(self.total_frames);
+        self.trim_end = if end >= self.total_frames { None } else { Some(end) };
+    }
+
+    /// Export the whole video again.
+    pub fn clear_trim(&mut self) {
+        self.trim_start = 0;
+        self.trim_end = None;
+    }
+
+    /// Frames kept on export, as `start..end`.
+    pub fn trim_range(&self) -> std::ops::Range<usize> {
+        self.trim_start..self.trim_end.unwrap_or(self.total_frames)
+    }
+
+    /// Returns true if the start or end of the video is trimmed off.
+    pub fn is_trimmed(&self) -> bool {
+        self.trim_start > 0 || self.trim_end.is_some()
     }
 
     /// Hit-test annotations at a point for the given frame. Returns the topmost match.
@@ -185,4 +529,21 @@ impl EditorState {
             self.annotations.get(idx).map(|ta| (ta.start_frame, ta.end_frame))
         })
     }
+
+    /// Get the active annotation's fade-in and fade-out lengths in frames.
+    pub fn active_annotation_fades(&self) -> (usize, usize) {
+        self.active_annotation
+            .and_then(|idx| self.annotations.get(idx))
+            .map_or((0, 0), |ta| (ta.fade_in_frames, ta.fade_out_frames))
+    }
+}
+
+/// The upright rect covering `rect` after its corners go through `map`.
+pub fn map_rect(rect: CGRect, map: impl Fn(CGPoint) -> CGPoint) -> CGRect {
+    let a = map(rect.origin);
+    let b = map(CGPoint::new(rect.origin.x + rect.size.width, rect.origin.y + rect.size.height));
+    CGRect::new(
+        CGPoint::new(a.x.min(b.x), a.y.min(b.y)),
+        CGSize::new((b.x - a.x).abs(), (b.y - a.y).abs()),
+    )
 }
@@ -0,0 +1,140 @@
+use std::cell::{Cell, RefCell};
+
+use objc2::rc::Retained;
+use objc2::{define_class, msg_send, DefinedClass, MainThreadOnly};
+use objc2_app_kit::{NSEvent, NSView};
+use objc2_core_foundation::{CFRetained, CGFloat, CGPoint, CGSize};
+use objc2_core_graphics::{CGContext, CGImage, CGInterpolationQuality};
+use objc2_foundation::{MainThreadMarker, NSRect};
+
+use super::decoder::VideoDecoder;
+use super::export::scale_image;
+
+/// Thumbnails are rendered at this multiple of their point size (Retina).
+const THUMBNAIL_PIXEL_SCALE: CGFloat = 2.0;
+
+pub struct ThumbnailStripViewIvars {
+    /// Cached thumbnails, left to right. Drawn scaled to the current bounds.
+    thumbnails: RefCell<Vec<CFRetained<CGImage>>>,
+    total_frames: Cell<usize>,
+    /// Frame the delegate should seek to (set while scrubbing).
+    pending_seek_frame: Cell<Option<usize>>,
+}
+
+define_class!(
+    #[unsafe(super(NSView))]
+    #[thread_kind = MainThreadOnly]
+    #[name = "ThumbnailStripView"]
+    #[ivars = ThumbnailStripViewIvars]
+    pub struct ThumbnailStripView;
+
+    impl ThumbnailStripView {
+        #[unsafe(method(drawRect:))]
+        fn draw_rect(&self, _dirty_rect: NSRect) {
+            let Some(context) = objc2_app_kit::NSGraphicsContext::currentContext() else {
+                return;
+            };
+            let ctx = context.CGContext();
+            let bounds = self.bounds();
+
+            CGContext::set_rgb_fill_color(Some(&ctx), 0.1, 0.1, 0.1, 1.0);
+            CGContext::fill_rect(Some(&ctx), bounds);
+
+            let thumbnails = self.ivars().thumbnails.borrow();
+            if thumbnails.is_empty() {
+                return;
+            }
+            let cell_w = bounds.size.width / thumbnails.len() as CGFloat;
+            CGContext::set_interpolation_quality(Some(&ctx), CGInterpolationQuality::High);
+            for (i, thumbnail) in thumbnails.iter().enumerate() {
+                let cell = NSRect::new(
+                    CGPoint::new(i as CGFloat * cell_w, 0.0),
+                    CGSize::new(cell_w, bounds.size.height),
+                );
+                CGContext::draw_image(Some(&ctx), cell, Some(thumbnail));
+            }
+
+            // Thin separators between thumbnails
+            CGContext::set_rgb_fill_color(Some(&ctx), 0.1, 0.1, 0.1, 1.0);
+            for i in 1..thumbnails.len() {
+                let separator = NSRect::new(
+                    CGPoint::new(i as CGFloat * cell_w - 0.5, 0.0),
+                    CGSize::new(1.0, bounds.size.height),
+                );
+                CGContext::fill_rect(Some(&ctx), separator);
+            }
+        }
+
+        #[unsafe(method(mouseDown:))]
+        fn mouse_down(&self, event: &NSEvent) {
+            self.scrub_to_event(event);
+        }
+
+        #[unsafe(method(mouseDragged:))]
+        fn mouse_dragged(&self, event: &NSEvent) {
+            self.scrub_to_event(event);
+        }
+    }
+);
+
+impl ThumbnailStripView {
+    pub fn new(mtm: MainThreadMarker, frame: NSRect) -> Retained<Self> {
+        let this = mtm.alloc().set_ivars(ThumbnailStripViewIvars {
+            thumbnails: RefCell::new(Vec::new()),
+            total_frames: Cell::new(0),
+            pending_seek_frame: Cell::new(None),
+        });
+        let view: Retained<Self> = unsafe { msg_send![super(this), initWithFrame: frame] };
+        view
+    }
+
+    /// Sample evenly spaced frames from `decoder` and cache them as thumbnails
+    /// sized to fill the strip's current height.
+    pub fn generate(&self, decoder: &VideoDecoder) {
+        let total = decoder.total_frames();
+        self.ivars().total_frames.set(total);
+
+        let bounds = self.bounds();
+        let aspect = decoder.width() as CGFloat / decoder.height().max(1) as CGFloat;
+        let thumb_w = (bounds.size.height * aspect).max(1.0);
+        let count = ((bounds.size.width / thumb_w).ceil() as usize).clamp(1, total.max(1));
+
+        let pixel_w = (bounds.size.width / count as CGFloat * THUMBNAIL_PIXEL_SCALE) as usize;
+        let pixel_h = (bounds.size.height * THUMBNAIL_PIXEL_SCALE) as usize;
+
+        let thumbnails = (0..count)
+            .filter_map(|i| {
+                // Sample the middle of each thumbnail's span of frames
+                let frame = ((i as f64 + 0.5) / count as f64 * total as f64) as usize;
+                let image = decoder.frame_at(frame.min(total.saturating_sub(1)))?;
+                let (width, height) = (pixel_w.max(1), pixel_h.max(1));
+                scale_image(image, width, height, CGInterpolationQuality::Medium)
+            })
+            .collect();
+        *self.ivars().thumbnails.borrow_mut() = thumbnails;
+        self.setNeedsDisplay(true);
+    }
+
+    /// Take the pending seek frame (consumed by delegate).
+    pub fn take_pending_seek_frame(&self) -> Option<usize> {
+        self.ivars().pending_seek_frame.take()
+    }
+
+    fn scrub_to_event(&self, event: &NSEvent) {
+        let total = self.ivars().total_frames.get();
+        let width = self.bounds().size.width;
+        if total == 0 || width <= 0.0 {
+            return;
+        }
+        let point = self.convertPoint_fromView(event.locationInWindow(), None);
+        let fraction = (point.x / width).clamp(0.0, 1.0);
+        let frame = (fraction * total.saturating_sub(1) as CGFloat).round() as usize;
+        self.ivars().pending_seek_frame.set(Some(frame));
+
+        let mtm = MainThreadMarker::from(self);
+        let app = objc2_app_kit::NSApplication::sharedApplication(mtm);
+        if let Some(delegate) = app.delegate() {
+            let _: () = unsafe { msg_send![&*delegate, editorThumbnailScrubbed: self] };
+        }
+    }
+}
@@ -3,15 +3,69 @@ use std::cell::{Cell, RefCell};
 use objc2::rc::Retained;
 use objc2::{define_class, msg_send, DefinedClass, MainThreadOnly};
 use objc2_app_kit::{
-    NSEvent, NSGraphicsContext, NSImage, NSTrackingArea,
-    NSTrackingAreaOptions, NSView,
+    NSEvent, NSFont, NSFontDescriptorSymbolicTraits, NSGraphicsContext, NSImage, NSMenu,
+    NSTextField, NSTrackingArea, NSTrackingAreaOptions, NSView,
 };
 use objc2_core_foundation::{CFRetained, CGFloat, CGPoint, CGRect, CGSize};
 use objc2_core_graphics::{CGContext, CGImage};
-use objc2_foundation::{MainThreadMarker, NSRect};
+use objc2_foundation::{MainThreadMarker, NSRect, NSString};
 
-use crate::annotation::model::{Annotation, HandleKind, update_annotation};
-use crate::overlay::view::{ActiveTool, SelectDragMode, stroke_for_key, tool_for_key};
+use crate::annotation::model::{
+    AlignmentGuide, Annotation, BlurStyle, HandleKind, SpotlightShape, StepShape, StrokeStyle,
+    CALLOUT_PADDING, HIGHLIGHTER_WIDTH_SCALE, snap_to_guides, text_background_for, text_font,
+    update_annotation,
+};
+use crate::overlay::view::{
+    ActiveTool, SelectDragMode, context_menu, draw_alignment_guides, draw_grid, grid_offset,
+    nudge_for_key, snap_to_grid, stroke_for_key, tool_for_key,
+};
+
+/// Width of each crop size field.
+const CROP_FIELD_W: CGFloat = 56.0;
+/// Height of the crop size fields.
+const CROP_FIELD_H: CGFloat = 22.0;
+/// Width of the "×" label between the crop size fields.
+const CROP_TIMES_W: CGFloat = 14.0;
+/// Inset around the crop size fields.
+const CROP_BAR_PADDING: CGFloat = 4.0;
+
+/// Aspect ratio the Crop tool is locked to (ratios are in image pixels).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CropAspect {
+    Free,
+    Square,
+    FourThree,
+    SixteenNine,
+}
+
+impl CropAspect {
+    /// All choices, in the order shown in the toolbar.
+    pub const ALL: [CropAspect; 4] = [
+        CropAspect::Free,
+        CropAspect::Square,
+        CropAspect::FourThree,
+        CropAspect::SixteenNine,
+    ];
+
+    /// Width / height, or None when unconstrained.
+    pub fn ratio(self) -> Option<CGFloat> {
+        match self {
+            CropAspect::Free => None,
+            CropAspect::Square => Some(1.0),
+            CropAspect::FourThree => Some(4.0 / 3.0),
+            CropAspect::SixteenNine => Some(16.0 / 9.0),
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            CropAspect::Free => "Free",
+            CropAspect::Square => "1:1",
+            CropAspect::FourThree => "4:3",
+            CropAspect::SixteenNine => "16:9",
+        }
+    }
+}
 
 /// Tracks the current drag operation for the Crop tool.
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -29,34 +83,98 @@ pub struct EditorViewIvars {
     pub current_annotation: RefCell<Option<Annotation>>,
     /// Annotations to draw on the current frame, with their indices in EditorState.
     pub annotations_to_draw: RefCell<Vec<(usize, Annotation)>>,
+    /// Opacity of annotations fading in or out at this frame, by index (others are opaque).
+    pub faded_annotations: RefCell<Vec<(usize, CGFloat)>>,
     /// Completed annotation waiting to be picked up by the editor window.
     pub pending_annotation: RefCell<Option<Annotation>>,
+    /// Re-texted annotation and its index, waiting to replace the original in the editor window.
+    pub pending_text_edit: RefCell<Option<(usize, Annotation)>>,
     pub tracking_area: RefCell<Option<Retained<NSTrackingArea>>>,
     pub text_container: RefCell<Option<Retained<NSView>>>,
     pub text_view: RefCell<Option<Retained<NSView>>>,
     pub text_position: Cell<CGPoint>,
+    /// Callout or Step whose text/number is being edited in the text field, with its
+    /// index once added (a new Callout has none). It isn't drawn while edited.
+    pub text_edit_target: RefCell<Option<(Option<usize>, Annotation)>>,
     /// Click point stored when Select tool is used, for the delegate to read.
     pub selection_click_point: Cell<Option<CGPoint>>,
     /// Index of the currently selected/active annotation (for visual highlight).
     pub active_annotation_index: Cell<Option<usize>>,
+    /// Annotations selected together by a marquee drag (mirrors EditorState while idle;
+    /// updated live during the drag and read by the delegate when it ends).
+    pub selected_annotations: RefCell<Vec<usize>>,
+    /// Rectangle being dragged out by a marquee selection.
+    pub marquee_rect: Cell<Option<CGRect>>,
     /// What the Select tool is currently dragging.
     pub select_drag_mode: Cell<SelectDragMode>,
     /// Mouse position at the start of a select drag.
     pub select_drag_start: Cell<CGPoint>,
+    /// How far the mouse has moved past annotations held by an alignment guide.
+    pub move_residual: Cell<CGPoint>,
+    /// Guides shown while moving annotations that line up with others.
+    pub alignment_guides: RefCell<Vec<AlignmentGuide>>,
+    /// Spacing of the grid that annotations snap to, if any.
+    pub snap_grid: Cell<Option<CGFloat>>,
     /// Current stroke width for annotations.
     pub annotation_width: Cell<CGFloat>,
     /// Current font size for text annotations.
     pub annotation_font_size: Cell<CGFloat>,
+    /// Whether new rectangles and ellipses are filled.
+    pub annotation_fill: Cell<bool>,
+    /// Whether new arrows, lines, shapes, and pencil strokes get a drop shadow.
+    pub annotation_shadow: Cell<bool>,
+    /// Whether new pencil strokes are smoothed into curves.
+    pub annotation_smooth: Cell<bool>,
+    /// Whether new text labels get a contrasting background box.
+    pub annotation_text_background: Cell<bool>,
+    /// Whether new text labels are outlined in a contrasting color.
+    pub annotation_text_outline: Cell<bool>,
+    /// Font for new text labels (see `TEXT_FONTS`); None is the system font.
+    pub annotation_font_name: RefCell<Option<String>>,
+    /// Bold/italic styling for new text labels.
+    pub annotation_font_traits: Cell<NSFontDescriptorSymbolicTraits>,
+    /// Corner radius for new rectangles (0 = square corners).
+    pub annotation_corner_radius: Cell<CGFloat>,
+    /// Outline pattern for new rectangles, ellipses, and lines.
+    pub annotation_stroke_style: Cell<StrokeStyle>,
+    /// Opacity for new Highlight annotations.
+    pub annotation_highlight_opacity: Cell<CGFloat>,
+    /// Color for new Redact bars (black unless picked while the Redact tool is active).
+    pub annotation_redact_color: Cell<(CGFloat, CGFloat, CGFloat)>,
+    /// Undimmed region shape for new Spotlight annotations.
+    pub annotation_spotlight_shape: Cell<SpotlightShape>,
+    /// Pixelation block size for new Blur annotations.
+    pub annotation_block_size: Cell<usize>,
+    /// Pixelate or Gaussian for new Blur annotations.
+    pub annotation_blur_style: Cell<BlurStyle>,
+    /// Mouse-down point of the annotation being drawn.
+    pub annotation_anchor: Cell<CGPoint>,
     /// Next step number for the Step tool.
     pub next_step_number: Cell<u32>,
+    /// Marker shape for new Step annotations.
+    pub annotation_step_shape: Cell<StepShape>,
     /// Current frame as CGImage (for blur annotation rendering).
     pub current_cgimage: RefCell<Option<CFRetained<CGImage>>>,
     /// Crop rectangle (in view coordinates). None = no crop.
     pub crop_rect: Cell<Option<CGRect>>,
+    /// Crop already applied to the output, shown dimmed when not editing the crop.
+    pub applied_crop: Cell<Option<CGRect>>,
     /// Current crop drag operation.
     pub crop_drag_mode: Cell<CropDragMode>,
     /// Mouse position at the start of a crop drag.
     pub crop_drag_start: Cell<CGPoint>,
+    /// Aspect ratio the crop rect is locked to.
+    pub crop_aspect: Cell<CropAspect>,
+    /// Width/height entry shown in the corner while the Crop tool is active.
+    pub crop_size_bar: RefCell<Option<Retained<NSView>>>,
+    pub crop_width_field: RefCell<Option<Retained<NSTextField>>>,
+    pub crop_height_field: RefCell<Option<Retained<NSTextField>>>,
+    /// Pixel size of the displayed image, for converting between view and pixel coordinates.
+    pub image_pixel_size: Cell<CGSize>,
+    /// Changed-pixel highlight drawn over the image when comparing captures.
+    pub diff_overlay: RefCell<Option<Retained<NSImage>>>,
+    /// Editing a still image rather than a recording (enables rotate, flip, and adjustments).
+    pub single_frame: Cell<bool>,
 }
 
 define_class!(
@@ -100,17 +218,40 @@ define_class!(
                 image.drawInRect(bounds);
             }
 
+            // Draw the diff highlight between the image and the annotations
+            if let Some(ref overlay) = *self.ivars().diff_overlay.borrow() {
+                overlay.drawInRect(bounds);
+            }
+
+            // Show the grid while drawing or moving annotations
+            let dragging = self.ivars().current_annotation.borrow().is_some()
+                || self.ivars().select_drag_mode.get() != SelectDragMode::None;
+            if let (true, Some(spacing)) = (dragging, self.ivars().snap_grid.get()) {
+                draw_grid(&cg, bounds, spacing);
+            }
+
             // Draw all annotations visible at this frame
             let active_idx = self.ivars().active_annotation_index.get();
+            let group = self.ivars().selected_annotations.borrow();
             let cgimage_ref = self.ivars().current_cgimage.borrow();
             let screenshot = cgimage_ref.as_ref().map(|img| &**img);
+            let faded = self.ivars().faded_annotations.borrow();
+            let editing = self.ivars().text_edit_target.borrow().as_ref().and_then(|(i, _)| *i);
             for (idx, ann) in self.ivars().annotations_to_draw.borrow().iter() {
-                crate::annotation::renderer::draw_annotation(&cg, ann, screenshot);
+                if editing == Some(*idx) {
+                    continue;
+                }
+                let opacity = faded.iter().find(|(i, _)| i == idx).map_or(1.0, |&(_, o)| o);
+                crate::annotation::renderer::draw_annotation_with_opacity(
+                    &cg, ann, screenshot, opacity,
+                );
 
                 // Draw highlight and handles around the active/selected annotation
                 if active_idx == Some(*idx) {
                     draw_selection_highlight(&cg, ann);
                     draw_annotation_handles(&cg, ann);
+                } else if group.contains(idx) {
+                    draw_selection_highlight(&cg, ann);
                 }
             }
 
@@ -118,12 +259,20 @@ define_class!(
             if let Some(ref ann) = *self.ivars().current_annotation.borrow() {
                 crate::annotation::renderer::draw_annotation(&cg, ann, screenshot);
             }
+            if let Some(marquee) = self.ivars().marquee_rect.get() {
+                crate::overlay::view::draw_marquee(&cg, marquee);
+            }
+            draw_alignment_guides(&cg, &self.ivars().alignment_guides.borrow());
+            drop(group);
+            drop(faded);
             drop(cgimage_ref);
 
             // Draw crop overlay if present
             if let Some(crop) = self.ivars().crop_rect.get() {
                 let norm_crop = crate::overlay::view::normalize_rect(crop);
                 draw_crop_overlay(&cg, bounds, norm_crop, self.ivars().active_tool.get() == ActiveTool::Crop);
+            } else if let Some(applied) = self.ivars().applied_crop.get() {
+                draw_crop_overlay(&cg, bounds, applied, false);
             }
         }
 
@@ -135,6 +284,11 @@ define_class!(
             let point = self.convert_event_point(event);
             let active_tool = self.ivars().active_tool.get();
 
+            if active_tool == ActiveTool::Eyedropper {
+                self.notify_sample_color(point);
+                return;
+            }
+
             if active_tool == ActiveTool::Crop {
                 // Crop tool: draw a new crop rect or interact with existing one
                 if let Some(crop) = self.ivars().crop_rect.get() {
@@ -157,7 +311,7 @@ define_class!(
                     }
                 }
                 // Start drawing a new crop rect
-                self.ivars().crop_rect.set(Some(CGRect::new(point, CGSize::ZERO)));
+                self.set_crop_rect(Some(CGRect::new(point, CGSize::ZERO)));
                 self.ivars().crop_drag_mode.set(CropDragMode::Drawing);
                 self.ivars().crop_drag_start.set(point);
                 return;
@@ -190,15 +344,37 @@ define_class!(
                 drop(annotations);
 
                 if let Some(idx) = hit_idx {
+                    // Dragging any member of a marquee selection moves the whole group
+                    if event.clickCount() < 2
+                        && self.ivars().selected_annotations.borrow().contains(&idx)
+                    {
+                        self.ivars().select_drag_mode.set(SelectDragMode::MovingAnnotation);
+                        self.ivars().select_drag_start.set(point);
+                        return;
+                    }
                     // Double-click on Text annotation: re-edit it
                     if event.clickCount() >= 2 {
                         let annotations = self.ivars().annotations_to_draw.borrow();
                         if let Some((_, ann)) = annotations.iter().find(|(i, _)| *i == idx) {
-                            if let Annotation::Text { position, text, color, font_size } = ann {
+                            if let Annotation::Text {
+                                position,
+                                text,
+                                color,
+                                font_size,
+                                background,
+                                outline,
+                                font_name,
+                                traits,
+                            } = ann
+                            {
                                 let pos = *position;
                                 let txt = text.clone();
                                 let clr = *color;
                                 let fs = *font_size;
+                                let has_background = background.is_some();
+                                let has_outline = *outline;
+                                let name = font_name.clone();
+                                let font_traits = *traits;
                                 drop(annotations);
                                 // Select and delete the annotation via delegate
                                 self.ivars().selection_click_point.set(Some(point));
@@ -207,7 +383,21 @@ define_class!(
                                 // Show text field pre-filled
                                 self.ivars().annotation_color.set(clr);
                                 self.ivars().annotation_font_size.set(fs);
-                                self.show_text_field_with_text(pos, &txt, fs);
+                                self.ivars().annotation_text_background.set(has_background);
+                                self.ivars().annotation_text_outline.set(has_outline);
+                                self.ivars().annotation_font_traits.set(font_traits);
+                                let font = text_font(name.as_deref(), fs, font_traits);
+                                *self.ivars().annotation_font_name.borrow_mut() = name;
+                                self.show_text_field_with_text(pos, &txt, &font);
+                                return;
+                            }
+                            // Double-click on a Callout or Step: edit its text/number in place
+                            if matches!(ann, Annotation::Callout { .. } | Annotation::Step { .. }) {
+                                let target = ann.clone();
+                                drop(annotations);
+                                self.ivars().selection_click_point.set(Some(point));
+                                self.notify_delegate_selection_click();
+                                self.edit_annotation_text(target, Some(idx));
                                 return;
                             }
                         }
@@ -220,15 +410,23 @@ define_class!(
                     self.ivars().selection_click_point.set(Some(point));
                     self.notify_delegate_selection_click();
                 } else {
-                    // Clicked empty space — deselect
-                    self.ivars().select_drag_mode.set(SelectDragMode::None);
+                    // Clicked empty space — deselect and start a marquee
                     self.ivars().selection_click_point.set(Some(point));
                     self.notify_delegate_selection_click();
+                    self.ivars().select_drag_mode.set(SelectDragMode::Marquee);
+                    self.ivars().select_drag_start.set(point);
+                    self.ivars().marquee_rect.set(Some(CGRect::new(point, CGSize::ZERO)));
                 }
                 return;
             }
 
-            self.start_annotation(point);
+            // Cmd+click with the Step tool restarts the sequence at 1
+            if active_tool == ActiveTool::Step
+                && event.modifierFlags().contains(objc2_app_kit::NSEventModifierFlags::Command)
+            {
+                self.ivars().next_step_number.set(1);
+            }
+            self.start_annotation(self.grid_point(point));
         }
 
         #[unsafe(method(mouseDragged:))]
@@ -240,11 +438,15 @@ define_class!(
             match crop_mode {
                 CropDragMode::Drawing => {
                     let start = self.ivars().crop_drag_start.get();
+                    let point = match self.crop_view_ratio() {
+                        Some(ratio) => constrain_to_aspect(start, point, ratio),
+                        None => point,
+                    };
                     let new_rect = CGRect::new(
                         start,
                         CGSize::new(point.x - start.x, point.y - start.y),
                     );
-                    self.ivars().crop_rect.set(Some(new_rect));
+                    self.set_crop_rect(Some(new_rect));
                     self.setNeedsDisplay(true);
                     return;
                 }
@@ -256,15 +458,15 @@ define_class!(
                     if let Some(mut crop) = self.ivars().crop_rect.get() {
                         crop.origin.x += dx;
                         crop.origin.y += dy;
-                        self.ivars().crop_rect.set(Some(crop));
+                        self.set_crop_rect(Some(crop));
                     }
                     self.setNeedsDisplay(true);
                     return;
                 }
                 CropDragMode::Resizing(handle) => {
                     if let Some(crop) = self.ivars().crop_rect.get() {
-                        let new_crop = apply_crop_resize(crop, handle, point);
-                        self.ivars().crop_rect.set(Some(new_crop));
+                        let new_crop = apply_crop_resize(crop, handle, point, self.crop_view_ratio());
+                        self.set_crop_rect(Some(new_crop));
                     }
                     self.setNeedsDisplay(true);
                     return;
@@ -277,15 +479,33 @@ define_class!(
             match drag_mode {
                 SelectDragMode::MovingAnnotation => {
                     let drag_start = self.ivars().select_drag_start.get();
-                    let dx = point.x - drag_start.x;
-                    let dy = point.y - drag_start.y;
+                    // Snap to the grid or other annotations unless Cmd is held
+                    let flags = event.modifierFlags();
+                    let free = flags.contains(objc2_app_kit::NSEventModifierFlags::Command);
+                    let (dx, dy) =
+                        self.snapped_move(point.x - drag_start.x, point.y - drag_start.y, free);
                     self.ivars().select_drag_start.set(point);
                     self.notify_delegate_move_annotation(dx, dy);
                     self.setNeedsDisplay(true);
                     return;
                 }
                 SelectDragMode::ResizingHandle(handle) => {
-                    self.notify_delegate_resize_annotation(handle, point);
+                    self.notify_delegate_resize_annotation(handle, self.grid_point(point));
+                    self.setNeedsDisplay(true);
+                    return;
+                }
+                SelectDragMode::Marquee => {
+                    let drag_start = self.ivars().select_drag_start.get();
+                    let marquee = CGRect::new(
+                        drag_start,
+                        CGSize::new(point.x - drag_start.x, point.y - drag_start.y),
+                    );
+                    self.ivars().marquee_rect.set(Some(marquee));
+                    let hits = crate::annotation::model::annotations_in_rect(
+                        self.ivars().annotations_to_draw.borrow().iter().map(|(i, a)| (*i, a)),
+                        marquee,
+                    );
+                    *self.ivars().selected_annotations.borrow_mut() = hits;
                     self.setNeedsDisplay(true);
                     return;
                 }
@@ -293,18 +513,110 @@ define_class!(
             }
 
             if let Some(ref mut ann) = *self.ivars().current_annotation.borrow_mut() {
-                update_annotation(ann, point);
+                let flags = event.modifierFlags();
+                let constrain = flags.contains(objc2_app_kit::NSEventModifierFlags::Shift);
+                let from_center = flags.contains(objc2_app_kit::NSEventModifierFlags::Option);
+                let anchor = self.ivars().annotation_anchor.get();
+                update_annotation(ann, anchor, self.grid_point(point), constrain, from_center);
                 self.setNeedsDisplay(true);
             }
         }
 
+        /// Right-click opens a menu for the annotation under the cursor, or for the canvas.
+        #[unsafe(method(rightMouseDown:))]
+        fn right_mouse_down(&self, event: &NSEvent) {
+            self.commit_text_field();
+            let point = self.convert_event_point(event);
+            let annotations = self.ivars().annotations_to_draw.borrow();
+            let hit = annotations
+                .iter()
+                .rev()
+                .find(|(_, ann)| ann.hit_test(point))
+                .map(|(idx, _)| *idx);
+            let has_annotations = !annotations.is_empty();
+            drop(annotations);
+
+            let mtm = MainThreadMarker::from(self);
+            let app = objc2_app_kit::NSApplication::sharedApplication(mtm);
+            let Some(delegate) = app.delegate() else {
+                return;
+            };
+            let items = if let Some(idx) = hit {
+                // Keep a marquee group when right-clicking one of its members
+                let in_group = self.ivars().selected_annotations.borrow().contains(&idx);
+                if !in_group {
+                    self.ivars().selection_click_point.set(Some(point));
+                    self.notify_delegate_selection_click();
+                }
+                let mut items = vec![("Delete", "editorDeleteAnnotation:")];
+                if !in_group {
+                    items.extend([
+                        ("Duplicate", "editorDuplicateAnnotation:"),
+                        ("Bring to Front", "editorBringToFront:"),
+                        ("Send to Back", "editorSendToBack:"),
+                    ]);
+                }
+                items
+            } else {
+                let mut items = vec![
+                    ("Copy Text", "actionCopyText:"),
+                    ("Decode QR Code", "actionDecodeBarcode:"),
+                ];
+                if has_annotations {
+                    items.extend([
+                        ("Select All", "actionSelectAllAnnotations:"),
+                        ("Clear All\u{2026}", "actionClearAnnotations:"),
+                    ]);
+                }
+                if self.ivars().single_frame.get() {
+                    items.extend([
+                        ("Rotate Left", "editorRotateLeft:"),
+                        ("Rotate Right", "editorRotateRight:"),
+                        ("Flip Horizontal", "editorFlipHorizontal:"),
+                        ("Flip Vertical", "editorFlipVertical:"),
+                        ("Grayscale", "editorGrayscale:"),
+                        ("Invert Colors", "editorInvertColors:"),
+                        ("Add Background\u{2026}", "editorAddBackground:"),
+                        ("Add Window Frame\u{2026}", "editorAddWindowFrame:"),
+                    ]);
+                }
+                items
+            };
+            let menu = context_menu(mtm, &items, (*delegate).as_ref());
+            NSMenu::popUpContextMenu_withEvent_forView(&menu, event, self);
+        }
+
+        #[unsafe(method(cropSizeEntered:))]
+        fn crop_size_entered(&self, _sender: &objc2::runtime::AnyObject) {
+            self.apply_crop_size_fields();
+        }
+
         #[unsafe(method(mouseUp:))]
         fn mouse_up(&self, _event: &NSEvent) {
             self.ivars().crop_drag_mode.set(CropDragMode::None);
-            self.ivars().select_drag_mode.set(SelectDragMode::None);
+            self.ivars().move_residual.set(CGPoint::ZERO);
+            if !self.ivars().alignment_guides.borrow().is_empty() {
+                self.ivars().alignment_guides.borrow_mut().clear();
+                self.setNeedsDisplay(true);
+            }
+            match self.ivars().select_drag_mode.replace(SelectDragMode::None) {
+                SelectDragMode::None => {}
+                SelectDragMode::Marquee => {
+                    self.ivars().marquee_rect.set(None);
+                    self.notify_delegate_marquee_selection();
+                }
+                _ => self.notify_delegate_transform_ended(),
+            }
 
-            if let Some(ann) = self.ivars().current_annotation.borrow_mut().take() {
-                self.finish_annotation(ann);
+            let finished = self.ivars().current_annotation.borrow_mut().take();
+            if let Some(ann) = finished {
+                if matches!(ann, Annotation::Callout { .. }) {
+                    // The callout is added once its text is committed
+                    self.edit_annotation_text(ann, None);
+                    self.setNeedsDisplay(true);
+                } else {
+                    self.finish_annotation(ann);
+                }
             }
         }
 
@@ -329,6 +641,84 @@ define_class!(
                     self.notify_stroke_changed(sel_name);
                     return;
                 }
+                // Stroke style shortcut: D cycles solid/dashed/dotted
+                if key_code == 2 {
+                    self.notify_stroke_changed("cycleStrokeStyle:");
+                    return;
+                }
+                // Diff threshold: [ = lower (more sensitive), ] = higher
+                if self.ivars().diff_overlay.borrow().is_some() && (key_code == 33 || key_code == 30) {
+                    self.notify_delegate_diff_threshold(key_code == 30);
+                    return;
+                }
+                // Otherwise [ / ] trim the recording to start / end at the current frame
+                if key_code == 33 || key_code == 30 {
+                    self.notify_delegate_trim(key_code == 33);
+                    return;
+                }
+            }
+
+            // Cmd+K = command palette (keyCode 40 = K)
+            if key_code == 40
+                && flags.contains(objc2_app_kit::NSEventModifierFlags::Command)
+                && self.ivars().text_view.borrow().is_none()
+            {
+                self.notify_show_command_palette();
+                return;
+            }
+
+            // Cmd+A = select all annotations with the Select tool (keyCode 0 = A)
+            if key_code == 0
+                && flags.contains(objc2_app_kit::NSEventModifierFlags::Command)
+                && self.ivars().text_view.borrow().is_none()
+            {
+                if self.ivars().active_tool.get() == ActiveTool::Select {
+                    self.notify_delegate_select_all();
+                }
+                return;
+            }
+
+            // Cmd+Shift+Delete = clear all annotations (the delegate asks first)
+            if (key_code == 51 || key_code == 117)
+                && flags.contains(objc2_app_kit::NSEventModifierFlags::Command)
+                && flags.contains(objc2_app_kit::NSEventModifierFlags::Shift)
+            {
+                self.notify_delegate_clear_annotations();
+                return;
+            }
+
+            // Cmd+D = duplicate selected annotation (keyCode 2 = D)
+            if key_code == 2
+                && flags.contains(objc2_app_kit::NSEventModifierFlags::Command)
+                && self.ivars().text_view.borrow().is_none()
+            {
+                if self.ivars().active_annotation_index.get().is_some() {
+                    self.notify_delegate_duplicate_annotation();
+                }
+                return;
+            }
+
+            // Cmd+L / Cmd+R = rotate a still image left / right (keyCodes 37 / 15)
+            if (key_code == 37 || key_code == 15)
+                && flags.contains(objc2_app_kit::NSEventModifierFlags::Command)
+                && !flags.contains(objc2_app_kit::NSEventModifierFlags::Shift)
+                && self.ivars().text_view.borrow().is_none()
+            {
+                if self.ivars().single_frame.get() {
+                    self.notify_delegate_rotate(key_code == 15);
+                }
+                return;
+            }
+
+            // Cmd+Shift+] = bring to front, Cmd+Shift+[ = send to back (keyCodes 30 / 33)
+            if (key_code == 30 || key_code == 33)
+                && flags.contains(objc2_app_kit::NSEventModifierFlags::Command)
+                && flags.contains(objc2_app_kit::NSEventModifierFlags::Shift)
+            {
+                if self.ivars().active_annotation_index.get().is_some() {
+                    self.notify_delegate_reorder_annotation(key_code == 30);
+                }
+                return;
             }
 
             // Enter = 36 -> apply crop if crop tool is active with a crop rect
@@ -355,12 +745,51 @@ define_class!(
 
             // Delete (backspace=51, forward delete=117) -> delete selected annotation
             if key_code == 51 || key_code == 117 {
-                if self.ivars().active_annotation_index.get().is_some() {
+                if self.has_selection() {
                     self.notify_delegate_delete_annotation();
                     return;
                 }
             }
 
+            // Arrow keys with the Crop tool -> move the crop rect (Shift = 10 points).
+            // With Option they resize it instead: Right/Down grow, Left/Up shrink.
+            if self.ivars().active_tool.get() == ActiveTool::Crop
+                && !flags.contains(objc2_app_kit::NSEventModifierFlags::Command)
+                && !flags.contains(objc2_app_kit::NSEventModifierFlags::Control)
+                && let Some(crop) = self.ivars().crop_rect.get()
+            {
+                let shift = flags.contains(objc2_app_kit::NSEventModifierFlags::Shift);
+                if let Some((dx, dy)) = nudge_for_key(key_code, shift) {
+                    let crop = crate::overlay::view::normalize_rect(crop);
+                    let moved = if flags.contains(objc2_app_kit::NSEventModifierFlags::Option) {
+                        let corner = CGPoint::new(
+                            crop.origin.x + (crop.size.width + dx).max(1.0),
+                            crop.origin.y + (crop.size.height + dy).max(1.0),
+                        );
+                        apply_crop_resize(crop, HandleKind::BottomRight, corner, self.crop_view_ratio())
+                    } else {
+                        CGRect::new(CGPoint::new(crop.origin.x + dx, crop.origin.y + dy), crop.size)
+                    };
+                    self.set_crop_rect(Some(clamp_rect_to_bounds(moved, self.bounds())));
+                    self.setNeedsDisplay(true);
+                    return;
+                }
+            }
+
+            // Arrow keys -> nudge selected annotation (Shift = 10 points),
+            // through the same delegate path as mouse moves
+            if !has_modifiers && self.ivars().text_view.borrow().is_none() {
+                let shift = flags.contains(objc2_app_kit::NSEventModifierFlags::Shift);
+                if let Some((dx, dy)) = nudge_for_key(key_code, shift) {
+                    if self.has_selection() {
+                        self.notify_delegate_move_annotation(dx, dy);
+                        self.notify_delegate_transform_ended();
+                        self.setNeedsDisplay(true);
+                    }
+                    return;
+                }
+            }
+
             // Cmd+Shift+Z = redo
             if key_code == 6
                 && flags.contains(objc2_app_kit::NSEventModifierFlags::Command)
@@ -375,7 +804,7 @@ define_class!(
                 && flags.contains(objc2_app_kit::NSEventModifierFlags::Command)
             {
                 if self.ivars().crop_rect.get().is_some() {
-                    self.ivars().crop_rect.set(None);
+                    self.set_crop_rect(None);
                     // Switch back to Select tool and notify delegate
                     self.ivars().active_tool.set(ActiveTool::Select);
                     self.notify_tool_changed();
@@ -397,6 +826,8 @@ define_class!(
         fn cancel_text_input_action(&self) {
             let container = self.ivars().text_container.borrow_mut().take();
             let _view = self.ivars().text_view.borrow_mut().take();
+            // An edited Callout or Step keeps its original text
+            self.ivars().text_edit_target.borrow_mut().take();
             if let Some(container) = container {
                 container.removeFromSuperview();
             }
@@ -438,32 +869,248 @@ impl EditorView {
             annotation_color: Cell::new((1.0, 0.0, 0.0)),
             current_annotation: RefCell::new(None),
             annotations_to_draw: RefCell::new(Vec::new()),
+            faded_annotations: RefCell::new(Vec::new()),
             pending_annotation: RefCell::new(None),
+            pending_text_edit: RefCell::new(None),
             tracking_area: RefCell::new(None),
             text_container: RefCell::new(None),
             text_view: RefCell::new(None),
             text_position: Cell::new(CGPoint::ZERO),
+            text_edit_target: RefCell::new(None),
             selection_click_point: Cell::new(None),
             active_annotation_index: Cell::new(None),
+            selected_annotations: RefCell::new(Vec::new()),
+            marquee_rect: Cell::new(None),
             select_drag_mode: Cell::new(SelectDragMode::None),
             select_drag_start: Cell::new(CGPoint::ZERO),
+            move_residual: Cell::new(CGPoint::ZERO),
+            alignment_guides: RefCell::new(Vec::new()),
+            snap_grid: Cell::new(None),
             annotation_width: Cell::new(3.0),
             annotation_font_size: Cell::new(18.0),
+            annotation_fill: Cell::new(false),
+            annotation_shadow: Cell::new(false),
+            annotation_smooth: Cell::new(true),
+            annotation_text_background: Cell::new(false),
+            annotation_text_outline: Cell::new(false),
+            annotation_font_name: RefCell::new(None),
+            annotation_font_traits: Cell::new(NSFontDescriptorSymbolicTraits::empty()),
+            annotation_corner_radius: Cell::new(0.0),
+            annotation_stroke_style: Cell::new(StrokeStyle::Solid),
+            annotation_highlight_opacity: Cell::new(0.35),
+            annotation_redact_color: Cell::new((0.0, 0.0, 0.0)),
+            annotation_spotlight_shape: Cell::new(SpotlightShape::Rect),
+            annotation_block_size: Cell::new(10),
+            annotation_blur_style: Cell::new(BlurStyle::Pixelate),
+            annotation_anchor: Cell::new(CGPoint::ZERO),
             next_step_number: Cell::new(1),
+            annotation_step_shape: Cell::new(StepShape::Circle),
             current_cgimage: RefCell::new(None),
             crop_rect: Cell::new(None),
+            applied_crop: Cell::new(None),
             crop_drag_mode: Cell::new(CropDragMode::None),
             crop_drag_start: Cell::new(CGPoint::ZERO),
+            crop_aspect: Cell::new(CropAspect::Free),
+            crop_size_bar: RefCell::new(None),
+            crop_width_field: RefCell::new(None),
+            crop_height_field: RefCell::new(None),
+            image_pixel_size: Cell::new(frame.size),
+            diff_overlay: RefCell::new(None),
+            single_frame: Cell::new(false),
         });
         let view: Retained<Self> = unsafe { msg_send![super(this), initWithFrame: frame] };
+        view.create_crop_size_bar(mtm);
         view
     }
 
-    /// Set the current frame image and the indexed annotations visible at this frame.
-    pub fn display_frame(&self, image: Retained<NSImage>, cgimage: &CGImage, annotations: Vec<(usize, Annotation)>) {
+    /// Build the (hidden) W × H pixel entry in the top-left corner.
+    fn create_crop_size_bar(&self, mtm: MainThreadMarker) {
+        let bar_w = CROP_BAR_PADDING * 2.0 + CROP_FIELD_W * 2.0 + CROP_TIMES_W;
+        let bar_h = CROP_FIELD_H + CROP_BAR_PADDING * 2.0;
+        let bar_frame = NSRect::new(CGPoint::new(8.0, 8.0), CGSize::new(bar_w, bar_h));
+        let bar: Retained<NSView> = unsafe { msg_send![mtm.alloc::<NSView>(), initWithFrame: bar_frame] };
+        bar.setWantsLayer(true);
+        if let Some(layer) = bar.layer() {
+            unsafe {
+                let bg = objc2_core_graphics::CGColor::new_srgb(0.1, 0.1, 0.1, 0.75);
+                let _: () = msg_send![&*layer, setBackgroundColor: &*bg];
+                let _: () = msg_send![&*layer, setCornerRadius: 6.0f64];
+            }
+        }
+
+        let make_field = |x: CGFloat, tooltip: &str| -> Retained<NSTextField> {
+            let frame = NSRect::new(
+                CGPoint::new(x, CROP_BAR_PADDING),
+                CGSize::new(CROP_FIELD_W, CROP_FIELD_H),
+            );
+            let field: Retained<NSTextField> =
+                unsafe { msg_send![mtm.alloc::<NSTextField>(), initWithFrame: frame] };
+            field.setFont(Some(&NSFont::monospacedDigitSystemFontOfSize_weight(11.0, 0.0)));
+            field.setToolTip(Some(&NSString::from_str(tooltip)));
+            unsafe {
+                field.setAction(Some(objc2::sel!(cropSizeEntered:)));
+                field.setTarget(Some(self));
+            }
+            bar.addSubview(&field);
+            field
+        };
+        let width_field = make_field(CROP_BAR_PADDING, "Crop Width (pixels)");
+        let height_field = make_field(CROP_BAR_PADDING + CROP_FIELD_W + CROP_TIMES_W, "Crop Height (pixels)");
+
+        let times = NSTextField::labelWithString(&NSString::from_str("\u{00D7}"), mtm);
+        times.setFrame(NSRect::new(
+            CGPoint::new(CROP_BAR_PADDING + CROP_FIELD_W, CROP_BAR_PADDING + 3.0),
+            CGSize::new(CROP_TIMES_W, CROP_FIELD_H - 6.0),
+        ));
+        times.setAlignment(objc2_app_kit::NSTextAlignment::Center);
+        times.setTextColor(Some(&objc2_app_kit::NSColor::whiteColor()));
+        bar.addSubview(&times);
+
+        bar.setHidden(true);
+        self.addSubview(&bar);
+        *self.ivars().crop_width_field.borrow_mut() = Some(width_field);
+        *self.ivars().crop_height_field.borrow_mut() = Some(height_field);
+        *self.ivars().crop_size_bar.borrow_mut() = Some(bar);
+    }
+
+    /// Show or hide the crop size entry (shown while the Crop tool is active).
+    pub fn set_crop_size_bar_visible(&self, visible: bool) {
+        if let Some(ref bar) = *self.ivars().crop_size_bar.borrow() {
+            bar.setHidden(!visible);
+        }
+        if visible {
+            self.sync_crop_size_fields();
+        }
+    }
+
+    /// Set the crop rectangle (view coordinates) and refresh the size fields.
+    pub fn set_crop_rect(&self, rect: Option<CGRect>) {
+        self.ivars().crop_rect.set(rect);
+        self.sync_crop_size_fields();
+    }
+
+    /// Record the pixel size of the image shown in the view.
+    pub fn set_image_pixel_size(&self, width: usize, height: usize) {
+        self.ivars()
+            .image_pixel_size
+            .set(CGSize::new(width as CGFloat, height as CGFloat));
+        self.sync_crop_size_fields();
+    }
+
+    /// Image pixels per view point, horizontally and vertically.
+    pub fn pixel_scale(&self) -> (CGFloat, CGFloat) {
+        let pixels = self.ivars().image_pixel_size.get();
+        let bounds = self.bounds();
+        if bounds.size.width <= 0.0 || bounds.size.height <= 0.0 {
+            return (1.0, 1.0);
+        }
+        (pixels.width / bounds.size.width, pixels.height / bounds.size.height)
+    }
+
+    /// Lock the crop rect to an aspect ratio, reshaping the current rect to match.
+    pub fn set_crop_aspect(&self, aspect: CropAspect) {
+        self.ivars().crop_aspect.set(aspect);
+        let (Some(ratio), Some(crop)) = (self.crop_view_ratio(), self.ivars().crop_rect.get()) else {
+            return;
+        };
+        // Keep the width (and top-left corner), shrinking both sides if the height won't fit
+        let bounds = self.bounds();
+        let crop = crate::overlay::view::normalize_rect(crop);
+        let mut width = crop.size.width.max(1.0);
+        let mut height = width / ratio;
+        if height > bounds.size.height {
+            height = bounds.size.height;
+            width = height * ratio;
+        }
+        let rect = CGRect::new(crop.origin, CGSize::new(width, height));
+        self.set_crop_rect(Some(clamp_rect_to_bounds(rect, bounds)));
+        self.setNeedsDisplay(true);
+    }
+
+    /// The locked crop ratio converted to view coordinates (None = free).
+    fn crop_view_ratio(&self) -> Option<CGFloat> {
+        let (sx, sy) = self.pixel_scale();
+        self.ivars().crop_aspect.get().ratio().map(|ratio| ratio * sy / sx)
+    }
+
+    /// Show the crop rect's size in pixels (empty when there is no crop rect).
+    fn sync_crop_size_fields(&self) {
+        let (sx, sy) = self.pixel_scale();
+        let size = self
+            .ivars()
+            .crop_rect
+            .get()
+            .map(|crop| crate::overlay::view::normalize_rect(crop).size);
+        let format = |pixels: Option<CGFloat>| {
+            NSString::from_str(&pixels.map(|p| format!("{}", p.round() as i64)).unwrap_or_default())
+        };
+        if let Some(ref field) = *self.ivars().crop_width_field.borrow() {
+            field.setStringValue(&format(size.map(|s| s.width * sx)));
+        }
+        if let Some(ref field) = *self.ivars().crop_height_field.borrow() {
+            field.setStringValue(&format(size.map(|s| s.height * sy)));
+        }
+    }
+
+    /// Resize the crop rect to the typed pixel size, keeping its top-left corner.
+    /// Invalid input puts the current size back in the fields.
+    fn apply_crop_size_fields(&self) {
+        let parse = |field: &RefCell<Option<Retained<NSTextField>>>| {
+            field
+                .borrow()
+                .as_ref()
+                .and_then(|f| f.stringValue().to_string().trim().parse::<u32>().ok())
+                .filter(|v| *v > 0)
+        };
+        let (Some(width), Some(height)) = (
+            parse(&self.ivars().crop_width_field),
+            parse(&self.ivars().crop_height_field),
+        ) else {
+            eprintln!("Invalid crop size");
+            self.sync_crop_size_fields();
+            return;
+        };
+
+        // A locked aspect ratio derives the height from the typed width
+        let height = match self.ivars().crop_aspect.get().ratio() {
+            Some(ratio) => ((width as CGFloat / ratio).round() as u32).max(1),
+            None => height,
+        };
+
+        let (sx, sy) = self.pixel_scale();
+        let bounds = self.bounds();
+        let origin = self
+            .ivars()
+            .crop_rect
+            .get()
+            .map(|crop| crate::overlay::view::normalize_rect(crop).origin)
+            .unwrap_or(bounds.origin);
+        let rect = CGRect::new(
+            origin,
+            CGSize::new(width as CGFloat / sx, height as CGFloat / sy),
+        );
+        self.set_crop_rect(Some(clamp_rect_to_bounds(rect, bounds)));
+        self.setNeedsDisplay(true);
+
+        // Hand keys back to the view so Enter applies the crop
+        if let Some(window) = self.window() {
+            let _ = window.makeFirstResponder(Some(self));
+        }
+    }
+
+    /// Set the current frame image and the indexed annotations visible at this frame,
+    /// along with the opacity of any that are mid-fade.
+    pub fn display_frame(
+        &self,
+        image: Retained<NSImage>,
+        cgimage: &CGImage,
+        annotations: Vec<(usize, Annotation)>,
+        faded: Vec<(usize, CGFloat)>,
+    ) {
         *self.ivars().current_image.borrow_mut() = Some(image);
         *self.ivars().current_cgimage.borrow_mut() = Some(unsafe { CFRetained::retain(cgimage.into()) });
         *self.ivars().annotations_to_draw.borrow_mut() = annotations;
+        *self.ivars().faded_annotations.borrow_mut() = faded;
         self.setNeedsDisplay(true);
     }
 
@@ -473,6 +1120,12 @@ impl EditorView {
         self.ivars().pending_annotation.borrow_mut().take()
     }
 
+    /// Take the re-texted annotation and its index (if any). Called by the app
+    /// delegate after receiving editorAnnotationTextEdited:.
+    pub fn take_pending_text_edit(&self) -> Option<(usize, Annotation)> {
+        self.ivars().pending_text_edit.borrow_mut().take()
+    }
+
     /// Take the selection click point. Called by the app delegate after
     /// receiving editorSelectionClick: notification.
     pub fn take_selection_click_point(&self) -> Option<CGPoint> {
@@ -485,6 +1138,36 @@ impl EditorView {
         self.setNeedsDisplay(true);
     }
 
+    /// Set the marquee-selected annotations for visual highlight.
+    pub fn set_selected_annotations(&self, indices: Vec<usize>) {
+        *self.ivars().selected_annotations.borrow_mut() = indices;
+        self.setNeedsDisplay(true);
+    }
+
+    /// The annotations caught by the last marquee drag. Called by the app delegate
+    /// after receiving editorMarqueeSelection: notification.
+    pub fn marquee_selection(&self) -> Vec<usize> {
+        self.ivars().selected_annotations.borrow().clone()
+    }
+
+    /// Whether an annotation (or a marquee group) is selected.
+    fn has_selection(&self) -> bool {
+        self.ivars().active_annotation_index.get().is_some()
+            || !self.ivars().selected_annotations.borrow().is_empty()
+    }
+
+    /// Set (or clear) the applied crop shown outside crop editing.
+    pub fn set_applied_crop(&self, crop: Option<CGRect>) {
+        self.ivars().applied_crop.set(crop);
+        self.setNeedsDisplay(true);
+    }
+
+    /// Set (or clear) the diff highlight drawn over the image.
+    pub fn set_diff_overlay(&self, overlay: Option<Retained<NSImage>>) {
+        *self.ivars().diff_overlay.borrow_mut() = overlay;
+        self.setNeedsDisplay(true);
+    }
+
     fn convert_event_point(&self, event: &NSEvent) -> CGPoint {
         let window_point = event.locationInWindow();
         self.convertPoint_fromView(window_point, None)
@@ -494,6 +1177,10 @@ impl EditorView {
         let color = self.ivars().annotation_color.get();
         let tool = self.ivars().active_tool.get();
         let width = self.ivars().annotation_width.get();
+        let fill = self.ivars().annotation_fill.get();
+        let style = self.ivars().annotation_stroke_style.get();
+        let shadow = self.ivars().annotation_shadow.get();
+        self.ivars().annotation_anchor.set(point);
 
         let ann = match tool {
             ActiveTool::Arrow => Annotation::Arrow {
@@ -501,34 +1188,80 @@ impl EditorView {
                 end: point,
                 color,
                 width,
+                shadow,
+            },
+            ActiveTool::Line => Annotation::Line {
+                start: point,
+                end: point,
+                color,
+                width,
+                style,
+                shadow,
             },
             ActiveTool::Rectangle => Annotation::Rect {
                 origin: point,
                 size: CGSize::ZERO,
                 color,
                 width,
+                fill,
+                corner_radius: self.ivars().annotation_corner_radius.get(),
+                style,
+                shadow,
             },
             ActiveTool::Ellipse => Annotation::Ellipse {
                 origin: point,
                 size: CGSize::ZERO,
                 color,
                 width,
+                fill,
+                style,
+                shadow,
             },
             ActiveTool::Pencil => Annotation::Pencil {
                 points: vec![point],
                 color,
                 width,
+                shadow,
+                smooth: self.ivars().annotation_smooth.get(),
+                highlighter: false,
+            },
+            ActiveTool::Highlighter => Annotation::Pencil {
+                points: vec![point],
+                color,
+                width: width * HIGHLIGHTER_WIDTH_SCALE,
+                shadow: false,
+                smooth: self.ivars().annotation_smooth.get(),
+                highlighter: true,
             },
             ActiveTool::Highlight => Annotation::Highlight {
                 origin: point,
                 size: CGSize::ZERO,
                 color,
-                opacity: 0.35,
+                opacity: self.ivars().annotation_highlight_opacity.get(),
             },
             ActiveTool::Blur => Annotation::Blur {
                 origin: point,
                 size: CGSize::ZERO,
-                block_size: 10,
+                block_size: self.ivars().annotation_block_size.get(),
+                style: self.ivars().annotation_blur_style.get(),
+            },
+            ActiveTool::Redact => Annotation::Redact {
+                origin: point,
+                size: CGSize::ZERO,
+                color: self.ivars().annotation_redact_color.get(),
+            },
+            ActiveTool::Spotlight => Annotation::Spotlight {
+                origin: point,
+                size: CGSize::ZERO,
+                shape: self.ivars().annotation_spotlight_shape.get(),
+                dim: 0.6,
+            },
+            ActiveTool::Callout => Annotation::Callout {
+                rect: CGRect::new(point, CGSize::ZERO),
+                tail_tip: point,
+                text: String::new(),
+                color,
+                font_size: self.ivars().annotation_font_size.get(),
             },
             ActiveTool::Step => {
                 let number = self.ivars().next_step_number.get();
@@ -538,6 +1271,7 @@ impl EditorView {
                     number,
                     color,
                     radius: 14.0,
+                    shape: self.ivars().annotation_step_shape.get(),
                 };
                 self.finish_annotation(ann);
                 return;
@@ -546,7 +1280,7 @@ impl EditorView {
                 self.show_text_field(point);
                 return;
             }
-            ActiveTool::Select | ActiveTool::Crop => return,
+            ActiveTool::Select | ActiveTool::Crop | ActiveTool::Eyedropper => return,
         };
 
         *self.ivars().current_annotation.borrow_mut() = Some(ann);
@@ -563,16 +1297,43 @@ impl EditorView {
     }
 
     fn show_text_field(&self, point: CGPoint) {
-        self.show_text_field_with_text(point, "", self.ivars().annotation_font_size.get());
+        let font = text_font(
+            self.ivars().annotation_font_name.borrow().as_deref(),
+            self.ivars().annotation_font_size.get(),
+            self.ivars().annotation_font_traits.get(),
+        );
+        self.show_text_field_with_text(point, "", &font);
     }
 
-    fn show_text_field_with_text(&self, point: CGPoint, initial_text: &str, font_size: CGFloat) {
+    /// Open the text field to edit a Callout's text or a Step's number in place.
+    /// Committing stores the result back on the annotation at `index` as one
+    /// undoable change, or adds it when it is new.
+    fn edit_annotation_text(&self, ann: Annotation, index: Option<usize>) {
+        let (point, text, font_size) = match &ann {
+            Annotation::Callout { rect, text, font_size, .. } => {
+                let r = crate::overlay::view::normalize_rect(*rect);
+                let point = CGPoint::new(r.origin.x + CALLOUT_PADDING, r.origin.y + CALLOUT_PADDING);
+                (point, text.clone(), *font_size)
+            }
+            Annotation::Step { center, number, radius, .. } => {
+                let point = CGPoint::new(center.x - radius, center.y - radius);
+                (point, number.to_string(), self.ivars().annotation_font_size.get())
+            }
+            _ => return,
+        };
+        // Callouts and steps are drawn in the system font
+        self.show_text_field_with_text(point, &text, &NSFont::systemFontOfSize(font_size));
+        *self.ivars().text_edit_target.borrow_mut() = Some((index, ann));
+        self.setNeedsDisplay(true);
+    }
+
+    fn show_text_field_with_text(&self, point: CGPoint, initial_text: &str, font: &NSFont) {
         self.commit_text_field();
 
         let mtm = MainThreadMarker::from(self);
         let color = self.ivars().annotation_color.get();
         let (container, text_view) =
-            crate::annotation::text_input::create_text_input(mtm, point, initial_text, font_size, color);
+            crate::annotation::text_input::create_text_input(mtm, point, initial_text, font, color);
 
         self.addSubview(&container);
         if let Some(w) = self.window() {
@@ -587,16 +1348,36 @@ impl EditorView {
     pub fn commit_text_field(&self) {
         let container = self.ivars().text_container.borrow_mut().take();
         let text_view = self.ivars().text_view.borrow_mut().take();
+        let target = self.ivars().text_edit_target.borrow_mut().take();
         if let (Some(container), Some(text_view)) = (container, text_view) {
-            if let Some(text) = crate::annotation::text_input::commit_text_input(&container, &text_view) {
+            let text = crate::annotation::text_input::commit_text_input(&container, &text_view);
+            if let Some((index, mut target)) = target {
+                target.apply_edited_text(text.unwrap_or_default());
+                if let Annotation::Step { number, .. } = target {
+                    // Continue the sequence from the edited number
+                    self.ivars().next_step_number.set(number + 1);
+                }
+                match index {
+                    Some(idx) => {
+                        *self.ivars().pending_text_edit.borrow_mut() = Some((idx, target));
+                        self.notify_delegate_text_edited();
+                    }
+                    None => self.finish_annotation(target),
+                }
+            } else if let Some(text) = text {
                 let color = self.ivars().annotation_color.get();
                 let position = self.ivars().text_position.get();
                 let font_size = self.ivars().annotation_font_size.get();
+                let background = self.ivars().annotation_text_background.get();
                 let ann = Annotation::Text {
                     position,
                     text,
                     color,
                     font_size,
+                    background: background.then(|| text_background_for(color)),
+                    outline: self.ivars().annotation_text_outline.get(),
+                    font_name: self.ivars().annotation_font_name.borrow().clone(),
+                    traits: self.ivars().annotation_font_traits.get(),
                 };
                 self.finish_annotation(ann);
             }
@@ -647,6 +1428,14 @@ impl EditorView {
         }
     }
 
+    fn notify_delegate_text_edited(&self) {
+        let mtm = MainThreadMarker::from(self);
+        let app = objc2_app_kit::NSApplication::sharedApplication(mtm);
+        if let Some(delegate) = app.delegate() {
+            let _: () = unsafe { msg_send![&*delegate, editorAnnotationTextEdited: self] };
+        }
+    }
+
     fn notify_delegate_selection_click(&self) {
         let mtm = MainThreadMarker::from(self);
         let app = objc2_app_kit::NSApplication::sharedApplication(mtm);
@@ -655,6 +1444,30 @@ impl EditorView {
         }
     }
 
+    fn notify_delegate_marquee_selection(&self) {
+        let mtm = MainThreadMarker::from(self);
+        let app = objc2_app_kit::NSApplication::sharedApplication(mtm);
+        if let Some(delegate) = app.delegate() {
+            let _: () = unsafe { msg_send![&*delegate, editorMarqueeSelection: self] };
+        }
+    }
+
+    fn notify_delegate_select_all(&self) {
+        let mtm = MainThreadMarker::from(self);
+        let app = objc2_app_kit::NSApplication::sharedApplication(mtm);
+        if let Some(delegate) = app.delegate() {
+            let _: () = unsafe { msg_send![&*delegate, actionSelectAllAnnotations: self] };
+        }
+    }
+
+    fn notify_delegate_clear_annotations(&self) {
+        let mtm = MainThreadMarker::from(self);
+        let app = objc2_app_kit::NSApplication::sharedApplication(mtm);
+        if let Some(delegate) = app.delegate() {
+            let _: () = unsafe { msg_send![&*delegate, actionClearAnnotations: self] };
+        }
+    }
+
     fn notify_delegate_delete_annotation(&self) {
         let mtm = MainThreadMarker::from(self);
         let app = objc2_app_kit::NSApplication::sharedApplication(mtm);
@@ -663,6 +1476,67 @@ impl EditorView {
         }
     }
 
+    fn notify_delegate_duplicate_annotation(&self) {
+        let mtm = MainThreadMarker::from(self);
+        let app = objc2_app_kit::NSApplication::sharedApplication(mtm);
+        if let Some(delegate) = app.delegate() {
+            let _: () = unsafe { msg_send![&*delegate, editorDuplicateAnnotation: self] };
+        }
+    }
+
+    fn notify_delegate_reorder_annotation(&self, to_front: bool) {
+        let mtm = MainThreadMarker::from(self);
+        let app = objc2_app_kit::NSApplication::sharedApplication(mtm);
+        if let Some(delegate) = app.delegate() {
+            if to_front {
+                let _: () = unsafe { msg_send![&*delegate, editorBringToFront: self] };
+            } else {
+                let _: () = unsafe { msg_send![&*delegate, editorSendToBack: self] };
+            }
+        }
+    }
+
+    /// `point` on the grid, when one is on and the tool isn't freehand.
+    fn grid_point(&self, point: CGPoint) -> CGPoint {
+        let freehand = matches!(
+            self.ivars().active_tool.get(),
+            ActiveTool::Pencil | ActiveTool::Highlighter
+        );
+        match self.ivars().snap_grid.get() {
+            Some(spacing) if !freehand => snap_to_grid(point, spacing),
+            _ => point,
+        }
+    }
+
+    /// Adjust a move of the selected annotations by (dx, dy) so they step along the
+    /// grid, or else line up with the other annotations on screen and the (cropped)
+    /// canvas, unless `free`. The gap left by annotations held in place carries over.
+    fn snapped_move(&self, dx: CGFloat, dy: CGFloat, free: bool) -> (CGFloat, CGFloat) {
+        let residual = self.ivars().move_residual.get();
+        let (dx, dy) = (dx + residual.x, dy + residual.y);
+        let (offset, guides) = match (free, self.ivars().snap_grid.get()) {
+            (true, _) => (CGPoint::ZERO, Vec::new()),
+            (false, Some(spacing)) => (grid_offset(dx, dy, spacing), Vec::new()),
+            (false, None) => {
+                let mut moving = self.ivars().selected_annotations.borrow().clone();
+                if moving.is_empty() {
+                    moving.extend(self.ivars().active_annotation_index.get());
+                }
+                let canvas = self.ivars().applied_crop.get().unwrap_or(self.bounds());
+                snap_to_guides(
+                    self.ivars().annotations_to_draw.borrow().iter().map(|(i, a)| (*i, a)),
+                    &moving,
+                    dx,
+                    dy,
+                    canvas,
+                )
+            }
+        };
+        self.ivars().move_residual.set(CGPoint::new(-offset.x, -offset.y));
+        *self.ivars().alignment_guides.borrow_mut() = guides;
+        (dx + offset.x, dy + offset.y)
+    }
+
     fn notify_delegate_move_annotation(&self, dx: CGFloat, dy: CGFloat) {
         let mtm = MainThreadMarker::from(self);
         let app = objc2_app_kit::NSApplication::sharedApplication(mtm);
@@ -681,6 +1555,15 @@ impl EditorView {
         }
     }
 
+    /// A move or resize (drag or nudge) is over; lets it be undone as one step.
+    fn notify_delegate_transform_ended(&self) {
+        let mtm = MainThreadMarker::from(self);
+        let app = objc2_app_kit::NSApplication::sharedApplication(mtm);
+        if let Some(delegate) = app.delegate() {
+            let _: () = unsafe { msg_send![&*delegate, editorTransformEnded: self] };
+        }
+    }
+
     fn notify_delegate_apply_crop(&self) {
         let mtm = MainThreadMarker::from(self);
         let app = objc2_app_kit::NSApplication::sharedApplication(mtm);
@@ -689,6 +1572,51 @@ impl EditorView {
         }
     }
 
+    /// Ask the app delegate to toggle the Cmd+K command palette.
+    fn notify_show_command_palette(&self) {
+        let mtm = MainThreadMarker::from(self);
+        let app = objc2_app_kit::NSApplication::sharedApplication(mtm);
+        if let Some(delegate) = app.delegate() {
+            let _: () = unsafe { msg_send![&*delegate, showCommandPalette: self] };
+        }
+    }
+
+    fn notify_delegate_trim(&self, start: bool) {
+        let mtm = MainThreadMarker::from(self);
+        let app = objc2_app_kit::NSApplication::sharedApplication(mtm);
+        if let Some(delegate) = app.delegate() {
+            if start {
+                let _: () = unsafe { msg_send![&*delegate, editorSetTrimStart: self] };
+            } else {
+                let _: () = unsafe { msg_send![&*delegate, editorSetTrimEnd: self] };
+            }
+        }
+    }
+
+    fn notify_delegate_rotate(&self, clockwise: bool) {
+        let mtm = MainThreadMarker::from(self);
+        let app = objc2_app_kit::NSApplication::sharedApplication(mtm);
+        if let Some(delegate) = app.delegate() {
+            if clockwise {
+                let _: () = unsafe { msg_send![&*delegate, editorRotateRight: self] };
+            } else {
+                let _: () = unsafe { msg_send![&*delegate, editorRotateLeft: self] };
+            }
+        }
+    }
+
+    fn notify_delegate_diff_threshold(&self, increase: bool) {
+        let mtm = MainThreadMarker::from(self);
+        let app = objc2_app_kit::NSApplication::sharedApplication(mtm);
+        if let Some(delegate) = app.delegate() {
+            if increase {
+                let _: () = unsafe { msg_send![&*delegate, editorDiffThresholdUp: self] };
+            } else {
+                let _: () = unsafe { msg_send![&*delegate, editorDiffThresholdDown: self] };
+            }
+        }
+    }
+
     /// Notify the app delegate that the tool changed (from keyboard shortcut).
     fn notify_tool_changed(&self) {
         let tool = self.ivars().active_tool.get();
@@ -699,18 +1627,33 @@ impl EditorView {
             match tool {
                 ActiveTool::Select => { let _: () = unsafe { msg_send![d, toolSelect: self] }; }
                 ActiveTool::Arrow => { let _: () = unsafe { msg_send![d, toolArrow: self] }; }
+                ActiveTool::Line => { let _: () = unsafe { msg_send![d, toolLine: self] }; }
                 ActiveTool::Rectangle => { let _: () = unsafe { msg_send![d, toolRect: self] }; }
                 ActiveTool::Ellipse => { let _: () = unsafe { msg_send![d, toolEllipse: self] }; }
                 ActiveTool::Pencil => { let _: () = unsafe { msg_send![d, toolPencil: self] }; }
                 ActiveTool::Text => { let _: () = unsafe { msg_send![d, toolText: self] }; }
                 ActiveTool::Highlight => { let _: () = unsafe { msg_send![d, toolHighlight: self] }; }
+                ActiveTool::Highlighter => { let _: () = unsafe { msg_send![d, toolHighlighter: self] }; }
                 ActiveTool::Step => { let _: () = unsafe { msg_send![d, toolStep: self] }; }
                 ActiveTool::Blur => { let _: () = unsafe { msg_send![d, toolBlur: self] }; }
+                ActiveTool::Redact => { let _: () = unsafe { msg_send![d, toolRedact: self] }; }
+                ActiveTool::Spotlight => { let _: () = unsafe { msg_send![d, toolSpotlight: self] }; }
+                ActiveTool::Callout => { let _: () = unsafe { msg_send![d, toolCallout: self] }; }
                 ActiveTool::Crop => { let _: () = unsafe { msg_send![d, toolCrop: self] }; }
+                ActiveTool::Eyedropper => { let _: () = unsafe { msg_send![d, toolEyedropper: self] }; }
             }
         }
     }
 
+    /// Ask the app delegate to sample the image color under `point` (eyedropper).
+    fn notify_sample_color(&self, point: CGPoint) {
+        let mtm = MainThreadMarker::from(self);
+        let app = objc2_app_kit::NSApplication::sharedApplication(mtm);
+        if let Some(delegate) = app.delegate() {
+            let _: () = unsafe { msg_send![&*delegate, sampleColorAtX: point.x, y: point.y] };
+        }
+    }
+
     /// Notify the app delegate that the stroke width changed (from keyboard shortcut).
     fn notify_stroke_changed(&self, sel_name: &str) {
         let mtm = MainThreadMarker::from(self);
@@ -721,6 +1664,7 @@ impl EditorView {
                 "strokeThin:" => { let _: () = unsafe { msg_send![d, strokeThin: self] }; }
                 "strokeMedium:" => { let _: () = unsafe { msg_send![d, strokeMedium: self] }; }
                 "strokeThick:" => { let _: () = unsafe { msg_send![d, strokeThick: self] }; }
+                "cycleStrokeStyle:" => { let _: () = unsafe { msg_send![d, cycleStrokeStyle: self] }; }
                 _ => {}
             }
         }
@@ -879,8 +1823,63 @@ fn hit_test_crop_handle(crop: CGRect, point: CGPoint) -> Option<HandleKind> {
     None
 }
 
+/// Fit a normalized rect inside `bounds`, shrinking it only if it is larger than the bounds.
+pub fn clamp_rect_to_bounds(rect: CGRect, bounds: CGRect) -> CGRect {
+    let w = rect.size.width.min(bounds.size.width);
+    let h = rect.size.height.min(bounds.size.height);
+    let x = rect.origin.x.clamp(bounds.origin.x, bounds.origin.x + bounds.size.width - w);
+    let y = rect.origin.y.clamp(bounds.origin.y, bounds.origin.y + bounds.size.height - h);
+    CGRect::new(CGPoint::new(x, y), CGSize::new(w, h))
+}
+
+/// Move `point` so the rect spanned from `anchor` has the given width/height
+/// ratio, following whichever dimension was dragged further.
+pub fn constrain_to_aspect(anchor: CGPoint, point: CGPoint, ratio: CGFloat) -> CGPoint {
+    let dx = point.x - anchor.x;
+    let dy = point.y - anchor.y;
+    let sign = |v: CGFloat| if v < 0.0 { -1.0 } else { 1.0 };
+    if dx.abs() / ratio >= dy.abs() {
+        CGPoint::new(point.x, anchor.y + sign(dy) * dx.abs() / ratio)
+    } else {
+        CGPoint::new(anchor.x + sign(dx) * dy.abs() * ratio, point.y)
+    }
+}
+
 /// Apply a resize operation to the crop rect based on which handle is being dragged.
-fn apply_crop_resize(crop: CGRect, handle: HandleKind, point: CGPoint) -> CGRect {
+/// With a locked `ratio`, corners keep the opposite corner fixed and edges resize
+/// the other dimension around its center.
+pub fn apply_crop_resize(crop: CGRect, handle: HandleKind, point: CGPoint, ratio: Option<CGFloat>) -> CGRect {
+    if let Some(ratio) = ratio {
+        let crop = crate::overlay::view::normalize_rect(crop);
+        let x0 = crop.origin.x;
+        let y0 = crop.origin.y;
+        let x1 = crop.origin.x + crop.size.width;
+        let y1 = crop.origin.y + crop.size.height;
+        let from_anchor = |anchor: CGPoint| {
+            let p = constrain_to_aspect(anchor, point, ratio);
+            CGRect::new(anchor, CGSize::new(p.x - anchor.x, p.y - anchor.y))
+        };
+        return match handle {
+            HandleKind::TopLeft => from_anchor(CGPoint::new(x1, y1)),
+            HandleKind::TopRight => from_anchor(CGPoint::new(x0, y1)),
+            HandleKind::BottomLeft => from_anchor(CGPoint::new(x1, y0)),
+            HandleKind::BottomRight => from_anchor(CGPoint::new(x0, y0)),
+            HandleKind::Left | HandleKind::Right => {
+                let w = if handle == HandleKind::Left { x1 - point.x } else { point.x - x0 }.max(1.0);
+                let h = w / ratio;
+                let x = if handle == HandleKind::Left { x1 - w } else { x0 };
+                CGRect::new(CGPoint::new(x, (y0 + y1 - h) / 2.0), CGSize::new(w, h))
+            }
+            HandleKind::Top | HandleKind::Bottom => {
+                let h = if handle == HandleKind::Top { y1 - point.y } else { point.y - y0 }.max(1.0);
+                let w = h * ratio;
+                let y = if handle == HandleKind::Top { y1 - h } else { y0 };
+                CGRect::new(CGPoint::new((x0 + x1 - w) / 2.0, y), CGSize::new(w, h))
+            }
+            _ => crop,
+        };
+    }
+
     let mut x0 = crop.origin.x;
     let mut y0 = crop.origin.y;
     let mut x1 = crop.origin.x + crop.size.width;
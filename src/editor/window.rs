@@ -3,35 +3,71 @@ use std::path::Path;
 
 use objc2::rc::Retained;
 use objc2::runtime::{AnyObject, Sel};
-use objc2::{msg_send, sel};
+use objc2::{msg_send, sel, DefinedClass};
 use objc2_app_kit::{
-    NSApplication, NSBackingStoreType, NSImage, NSSlider, NSWindow, NSWindowStyleMask,
+    NSApplication, NSBackingStoreType, NSFont, NSFontDescriptorSymbolicTraits, NSImage,
+    NSSegmentSwitchTracking, NSSegmentedControl, NSSlider, NSView, NSWindow, NSWindowStyleMask,
 };
-use objc2_core_foundation::{CGFloat, CGPoint, CGRect, CGSize};
+use objc2_core_foundation::{CFRetained, CGFloat, CGPoint, CGRect, CGSize, Type};
+use objc2_core_graphics::CGImage;
 use objc2_foundation::{MainThreadMarker, NSRect, NSSize, NSString, NSTimer};
 
 use super::decoder::VideoDecoder;
 use super::minibar::{MiniBarView, MINI_BAR_GAP, MINI_BAR_HEIGHT, MINI_BAR_WIDTH};
-use super::model::EditorState;
+use super::model::{
+    BackgroundStyle, EditorState, ImageAdjustment, ImageTransform, WindowFrame,
+};
+use super::thumbnails::ThumbnailStripView;
 use super::view::EditorView;
 use crate::annotation::model::Annotation;
+use crate::prefs::TimelineUnit;
 
 const SLIDER_HEIGHT: CGFloat = 24.0;
 const SLIDER_PADDING: CGFloat = 8.0;
-const PROGRESS_BAR_HEIGHT: CGFloat = SLIDER_HEIGHT + SLIDER_PADDING * 2.0;
+/// Height of the frame thumbnail strip above the slider.
+const THUMBNAIL_STRIP_HEIGHT: CGFloat = 36.0;
+const THUMBNAIL_STRIP_GAP: CGFloat = 2.0;
+const PROGRESS_BAR_HEIGHT: CGFloat =
+    SLIDER_HEIGHT + THUMBNAIL_STRIP_GAP + THUMBNAIL_STRIP_HEIGHT + SLIDER_PADDING * 2.0;
+/// Horizontal inset from the slider's edges to the knob center at min/max.
+const SLIDER_KNOB_INSET: CGFloat = 10.0;
+/// Height of the bar under the slider marking the frames kept on export.
+const TRIM_BAR_HEIGHT: CGFloat = 3.0;
+/// Width of the playback speed picker to the right of the slider.
+const SPEED_CONTROL_WIDTH: CGFloat = 120.0;
+
+/// Preview playback speeds offered by the speed picker (1× is the default).
+const PLAYBACK_SPEEDS: [(f64, &str); 3] =
+    [(0.5, "0.5\u{00D7}"), (1.0, "1\u{00D7}"), (2.0, "2\u{00D7}")];
 
 pub struct EditorWindow {
     pub window: Retained<NSWindow>,
     pub view: Retained<EditorView>,
     pub slider: Retained<NSSlider>,
+    /// Marks the trimmed range under the slider (hidden until the video is trimmed).
+    pub trim_bar: Retained<NSView>,
+    /// Frame thumbnails above the slider, aligned with its track.
+    pub thumbnail_strip: Retained<ThumbnailStripView>,
+    /// Playback speed picker next to the slider.
+    pub speed_control: Retained<NSSegmentedControl>,
     pub minibar_view: Retained<MiniBarView>,
     pub state: RefCell<EditorState>,
     pub decoder: VideoDecoder,
     pub timer: RefCell<Option<Retained<NSTimer>>>,
     /// True when playing in reverse direction.
     pub reversing: Cell<bool>,
+    /// Preview playback speed multiplier (export timing is unaffected).
+    pub playback_speed: Cell<f64>,
+    /// Whether the slider tooltip and mini bar show frames or timestamps.
+    pub timeline_unit: Cell<TimelineUnit>,
     /// True for single-frame images (screenshots), false for video recordings.
     pub is_single_frame: bool,
+    /// Image the current frame is compared against (visual diff mode).
+    pub diff_image: RefCell<Option<CFRetained<CGImage>>>,
+    /// Per-channel difference above which a pixel is highlighted as changed.
+    pub diff_threshold: Cell<u8>,
+    /// The still image as it was before the first grayscale or invert, if any.
+    unadjusted_image: Option<CFRetained<CGImage>>,
 }
 
 impl EditorWindow {
@@ -59,28 +95,7 @@ impl EditorWindow {
 
         let is_single_frame = total_frames == 1;
 
-        // Scale up if video height is less than 800px
-        const MIN_HEIGHT: CGFloat = 800.0;
-        let native_w = dec_width as CGFloat;
-        let native_h = dec_height as CGFloat;
-        let (view_w, view_h) = if native_h < MIN_HEIGHT {
-            let scale = MIN_HEIGHT / native_h;
-            ((native_w * scale).round(), MIN_HEIGHT)
-        } else {
-            (native_w, native_h)
-        };
-
-        // Cap view size at 80% of screen dimensions, preserving aspect ratio
-        let screen = crate::screen::screen_with_mouse(mtm);
-        let screen_size = screen.frame().size;
-        let max_view_w = (screen_size.width * 0.8).round();
-        let max_view_h = (screen_size.height * 0.8).round();
-        let (view_w, view_h) = if view_w > max_view_w || view_h > max_view_h {
-            let scale = (max_view_w / view_w).min(max_view_h / view_h);
-            ((view_w * scale).round(), (view_h * scale).round())
-        } else {
-            (view_w, view_h)
-        };
+        let (view_w, view_h) = fit_view_size(dec_width, dec_height, mtm);
 
         // Window: video + progress slider (slider hidden in single-frame mode)
         let progress_height = if is_single_frame { 0.0 } else { PROGRESS_BAR_HEIGHT };
@@ -127,6 +142,7 @@ impl EditorWindow {
 
         // Non-flipped layout (origin bottom-left):
         // y=SLIDER_PADDING: main slider
+        // above the slider: thumbnail strip
         // y=PROGRESS_BAR_HEIGHT: video view
 
         // Create the editor view (video area)
@@ -135,11 +151,16 @@ impl EditorWindow {
             CGSize::new(view_w, view_h),
         );
         let view = EditorView::new(mtm, view_frame);
+        view.set_image_pixel_size(dec_width, dec_height);
+        view.ivars().single_frame.set(is_single_frame);
 
-        // Create the progress slider at the bottom
+        // Create the progress slider at the bottom, leaving room for the speed picker
         let slider_frame = NSRect::new(
             CGPoint::new(SLIDER_PADDING, SLIDER_PADDING),
-            CGSize::new(window_w - SLIDER_PADDING * 2.0, SLIDER_HEIGHT),
+            CGSize::new(
+                window_w - SLIDER_PADDING * 3.0 - SPEED_CONTROL_WIDTH,
+                SLIDER_HEIGHT,
+            ),
         );
         let slider: Retained<NSSlider> =
             unsafe { msg_send![mtm.alloc(), initWithFrame: slider_frame] };
@@ -159,6 +180,57 @@ impl EditorWindow {
             slider.setHidden(true);
         }
 
+        // Thumbnail strip above the slider, spanning the knob's travel so each
+        // thumbnail sits over the frames it shows
+        let strip_frame = NSRect::new(
+            CGPoint::new(
+                slider_frame.origin.x + SLIDER_KNOB_INSET,
+                SLIDER_PADDING + SLIDER_HEIGHT + THUMBNAIL_STRIP_GAP,
+            ),
+            CGSize::new(
+                slider_frame.size.width - SLIDER_KNOB_INSET * 2.0,
+                THUMBNAIL_STRIP_HEIGHT,
+            ),
+        );
+        let thumbnail_strip = ThumbnailStripView::new(mtm, strip_frame);
+        if !is_single_frame {
+            thumbnail_strip.generate(&decoder);
+        }
+
+        // Playback speed picker, right of the slider
+        let speed_frame = NSRect::new(
+            CGPoint::new(window_w - SLIDER_PADDING - SPEED_CONTROL_WIDTH, SLIDER_PADDING),
+            CGSize::new(SPEED_CONTROL_WIDTH, SLIDER_HEIGHT),
+        );
+        let speed_control: Retained<NSSegmentedControl> =
+            unsafe { msg_send![mtm.alloc::<NSSegmentedControl>(), initWithFrame: speed_frame] };
+        speed_control.setSegmentCount(PLAYBACK_SPEEDS.len() as isize);
+        let segment_w = (SPEED_CONTROL_WIDTH - 8.0) / PLAYBACK_SPEEDS.len() as CGFloat;
+        for (i, (_, label)) in PLAYBACK_SPEEDS.iter().enumerate() {
+            speed_control.setLabel_forSegment(&NSString::from_str(label), i as isize);
+            speed_control.setWidth_forSegment(segment_w, i as isize);
+        }
+        speed_control.setTrackingMode(NSSegmentSwitchTracking::SelectOne);
+        speed_control.setSelectedSegment(1);
+        speed_control.setFont(Some(&NSFont::systemFontOfSize(11.0)));
+        unsafe {
+            speed_control.setAction(Some(Sel::register(c"editorPlaybackSpeedChanged:")));
+            speed_control.setTarget(None);
+        }
+        speed_control.setToolTip(Some(&NSString::from_str("Playback Speed (preview only)")));
+
+        // Trim range marker, just below the slider track
+        let trim_bar: Retained<NSView> =
+            unsafe { msg_send![mtm.alloc::<NSView>(), initWithFrame: NSRect::ZERO] };
+        trim_bar.setWantsLayer(true);
+        if let Some(layer) = trim_bar.layer() {
+            unsafe {
+                let color = objc2_core_graphics::CGColor::new_srgb(1.0, 0.75, 0.0, 0.9);
+                let _: () = msg_send![&*layer, setBackgroundColor: &*color];
+            }
+        }
+        trim_bar.setHidden(true);
+
         // Create the floating mini bar (starts hidden, is a subview of the editor view)
         let minibar_frame = NSRect::new(
             CGPoint::ZERO,
@@ -166,6 +238,9 @@ impl EditorWindow {
         );
         let minibar_view = MiniBarView::new(mtm, minibar_frame);
         minibar_view.setHidden(true);
+        let timeline_unit = crate::prefs::timeline_unit();
+        minibar_view.set_timeline_unit(timeline_unit, fps);
+        view.ivars().snap_grid.set(crate::prefs::snap_grid().spacing());
         view.addSubview(&minibar_view);
 
         // Add views to the window's content view
@@ -173,6 +248,9 @@ impl EditorWindow {
             content_view.addSubview(&view);
             if !is_single_frame {
                 content_view.addSubview(&slider);
+                content_view.addSubview(&thumbnail_strip);
+                content_view.addSubview(&speed_control);
+                content_view.addSubview(&trim_bar);
             }
         }
 
@@ -180,12 +258,20 @@ impl EditorWindow {
             window,
             view,
             slider,
+            trim_bar,
+            thumbnail_strip,
+            speed_control,
             minibar_view,
             state: RefCell::new(state),
             decoder,
             timer: RefCell::new(None),
             reversing: Cell::new(false),
+            playback_speed: Cell::new(1.0),
+            timeline_unit: Cell::new(timeline_unit),
             is_single_frame,
+            diff_image: RefCell::new(None),
+            diff_threshold: Cell::new(crate::diff::DEFAULT_THRESHOLD),
+            unadjusted_image: None,
         };
 
         // Display the first frame
@@ -227,14 +313,24 @@ impl EditorWindow {
             .into_iter()
             .map(|(idx, ann)| (idx, ann.clone()))
             .collect();
+        let faded: Vec<(usize, CGFloat)> = visible
+            .iter()
+            .map(|&(idx, _)| {
+                (idx, state.annotations[idx].opacity_at(frame_idx, state.total_frames))
+            })
+            .filter(|&(_, opacity)| opacity < 1.0)
+            .collect();
 
         // Update active annotation highlight on the view
         self.view.set_active_annotation_index(state.active_annotation);
+        self.view.set_selected_annotations(state.selected.clone());
+        self.view.set_applied_crop(state.crop());
 
-        self.view.display_frame(ns_image, cg_image, visible);
+        self.view.display_frame(ns_image, cg_image, visible, faded);
 
         // Update slider position
         self.slider.setDoubleValue(frame_idx as f64);
+        self.update_slider_tooltip(frame_idx, state.total_frames, state.fps);
 
         // Update mini bar state and position if there's an active annotation
         if let Some((active_idx, range)) = state.active_annotation.and_then(|idx| {
@@ -243,6 +339,7 @@ impl EditorWindow {
             self.minibar_view.update_state(
                 range.0,
                 range.1,
+                state.active_annotation_fades(),
                 frame_idx,
                 state.total_frames,
             );
@@ -255,6 +352,27 @@ impl EditorWindow {
         }
     }
 
+    /// Show the current position on the slider's tooltip, in the timeline unit.
+    fn update_slider_tooltip(&self, frame: usize, total_frames: usize, fps: f64) {
+        let unit = self.timeline_unit.get();
+        let tooltip = match unit {
+            TimelineUnit::Frames => format!("Frame {} of {}", frame, total_frames),
+            TimelineUnit::Time => format!(
+                "{} / {}",
+                unit.format(frame, fps),
+                unit.format(total_frames, fps)
+            ),
+        };
+        self.slider.setToolTip(Some(&NSString::from_str(&tooltip)));
+    }
+
+    /// Switch the slider tooltip and mini bar between frames and timestamps.
+    pub fn set_timeline_unit(&self, unit: TimelineUnit, mtm: MainThreadMarker) {
+        self.timeline_unit.set(unit);
+        self.minibar_view.set_timeline_unit(unit, self.state.borrow().fps);
+        self.display_current_frame(mtm);
+    }
+
     /// Position the mini bar centered below an annotation's bounding rect,
     /// clamped to stay within the editor view bounds.
     fn position_mini_bar_under(&self, ann_rect: CGRect) {
@@ -311,13 +429,28 @@ impl EditorWindow {
         }
     }
 
+    /// Apply the speed picked in the speed control. Restarts the timer if
+    /// playing so the new interval takes effect right away.
+    pub fn playback_speed_changed(&self, timer_target: &AnyObject) {
+        let index = self.speed_control.selectedSegment();
+        let Some(&(speed, _)) = PLAYBACK_SPEEDS.get(index as usize) else {
+            return;
+        };
+        self.playback_speed.set(speed);
+        if self.timer.borrow().is_some() {
+            self.stop_timer();
+            self.start_play(timer_target);
+        }
+        eprintln!("Editor: playback speed {}x", speed);
+    }
+
     /// Start playback timer (direction determined by `self.reversing`).
     fn start_play(&self, timer_target: &AnyObject) {
         self.view.commit_text_field();
         self.state.borrow_mut().play();
 
         let fps = self.state.borrow().fps;
-        let interval = 1.0 / fps;
+        let interval = 1.0 / (fps * self.playback_speed.get());
 
         let timer = unsafe {
             NSTimer::scheduledTimerWithTimeInterval_target_selector_userInfo_repeats(
@@ -390,16 +523,38 @@ impl EditorWindow {
         self.display_current_frame(mtm);
     }
 
-    /// Remove the last/active annotation.
+    /// Replace the annotation at `idx` with its re-texted copy, undoably.
+    pub fn replace_annotation_text(
+        &self,
+        idx: usize,
+        annotation: Annotation,
+        mtm: MainThreadMarker,
+    ) {
+        self.state.borrow_mut().replace_annotation(idx, annotation);
+        self.show_mini_bar(mtm);
+        self.display_current_frame(mtm);
+    }
+
+    /// Undo the last move or resize, or remove the last/active annotation.
     pub fn undo_annotation(&self, mtm: MainThreadMarker) {
         let had_active = self.state.borrow().active_annotation.is_some();
         self.state.borrow_mut().undo_annotation();
-        if had_active {
+        // Undoing a move or resize keeps (or makes) that annotation active
+        if self.state.borrow().active_annotation.is_some() {
+            self.show_mini_bar(mtm);
+        } else if had_active {
             self.hide_mini_bar();
         }
         self.display_current_frame(mtm);
     }
 
+    /// Crop the output to `rect` (view coordinates), keeping the image and annotations.
+    pub fn apply_crop(&self, rect: CGRect, mtm: MainThreadMarker) {
+        self.state.borrow_mut().apply_crop(rect);
+        self.hide_mini_bar();
+        self.display_current_frame(mtm);
+    }
+
     /// Redo the last undone annotation.
     pub fn redo_annotation(&self, mtm: MainThreadMarker) {
         if self.state.borrow_mut().redo_annotation() {
@@ -425,13 +580,143 @@ impl EditorWindow {
         self.display_current_frame(mtm);
     }
 
-    /// Delete the active (selected) annotation.
+    /// Select the annotations caught by a marquee drag; a single one gets the mini bar.
+    pub fn select_annotations(&self, indices: Vec<usize>, mtm: MainThreadMarker) {
+        self.state.borrow_mut().select_annotations(indices);
+        if self.state.borrow().active_annotation.is_some() {
+            self.show_mini_bar(mtm);
+        } else {
+            self.hide_mini_bar();
+        }
+        self.display_current_frame(mtm);
+    }
+
+    /// Select every annotation.
+    pub fn select_all_annotations(&self, mtm: MainThreadMarker) {
+        let count = self.state.borrow().annotations.len();
+        self.select_annotations((0..count).collect(), mtm);
+    }
+
+    /// Remove every annotation. Not undoable, so callers confirm first.
+    pub fn clear_all_annotations(&self, mtm: MainThreadMarker) {
+        self.state.borrow_mut().clear_annotations();
+        self.hide_mini_bar();
+        self.view.set_active_annotation_index(None);
+        self.display_current_frame(mtm);
+    }
+
+    /// Delete the active (selected) annotation, or every annotation in a marquee selection.
     pub fn delete_active_annotation(&self, mtm: MainThreadMarker) {
+        let indices = self.state.borrow().selected_indices();
+        match indices.as_slice() {
+            [] => return,
+            [idx] => self.state.borrow_mut().delete_annotation(*idx),
+            _ => self.state.borrow_mut().delete_annotations(&indices),
+        }
+        self.hide_mini_bar();
+        self.view.set_active_annotation_index(None);
+        self.display_current_frame(mtm);
+    }
+
+    /// Duplicate the active annotation (offset slightly) and select the copy.
+    pub fn duplicate_active_annotation(&self, mtm: MainThreadMarker) {
         let active = self.state.borrow().active_annotation;
-        if let Some(idx) = active {
-            self.state.borrow_mut().delete_annotation(idx);
-            self.hide_mini_bar();
-            self.view.set_active_annotation_index(None);
+        let Some(idx) = active else {
+            return;
+        };
+        let offset = crate::annotation::model::DUPLICATE_OFFSET;
+        let new_idx = self.state.borrow_mut().duplicate_annotation(idx, offset, offset);
+        if let Some(new_idx) = new_idx {
+            self.view.set_active_annotation_index(Some(new_idx));
+            self.show_mini_bar(mtm);
+            self.display_current_frame(mtm);
+        }
+    }
+
+    /// Apply `change` to the active annotation, re-rendering if it reports a change.
+    fn change_active_annotation(
+        &self,
+        mtm: MainThreadMarker,
+        change: impl FnOnce(&mut Annotation) -> bool,
+    ) {
+        let changed = {
+            let mut state = self.state.borrow_mut();
+            let Some(idx) = state.active_annotation else {
+                return;
+            };
+            state.annotations.get_mut(idx).is_some_and(|ta| change(&mut ta.annotation))
+        };
+        if changed {
+            self.display_current_frame(mtm);
+        }
+    }
+
+    /// Change the block size of the active annotation if it is a Blur, and re-render.
+    pub fn set_active_blur_block_size(&self, block_size: usize, mtm: MainThreadMarker) {
+        self.change_active_annotation(mtm, |ann| ann.set_block_size(block_size));
+    }
+
+    /// Change the opacity of the active annotation if it is a Highlight, and re-render.
+    pub fn set_active_highlight_opacity(&self, opacity: CGFloat, mtm: MainThreadMarker) {
+        self.change_active_annotation(mtm, |ann| ann.set_opacity(opacity));
+    }
+
+    /// Recolor the active annotation, and re-render.
+    pub fn set_active_color(&self, color: (CGFloat, CGFloat, CGFloat), mtm: MainThreadMarker) {
+        self.change_active_annotation(mtm, |ann| ann.set_color(color));
+    }
+
+    /// Restyle the active annotation's text as bold/italic, and re-render.
+    pub fn set_active_font_traits(
+        &self,
+        traits: NSFontDescriptorSymbolicTraits,
+        mtm: MainThreadMarker,
+    ) {
+        self.change_active_annotation(mtm, |ann| ann.set_font_traits(traits));
+    }
+
+    /// Change the active annotation's stroke width, or its font size if it is Text, and re-render.
+    pub fn set_active_stroke_width(
+        &self,
+        width: CGFloat,
+        font_size: CGFloat,
+        mtm: MainThreadMarker,
+    ) {
+        self.change_active_annotation(mtm, |ann| {
+            ann.set_stroke_width(width) || ann.set_font_size(font_size)
+        });
+    }
+
+    /// Step the active annotation's fade in (or out) to the next length, and re-render.
+    /// Skipped for single-frame images (nothing to fade over).
+    pub fn cycle_active_fade(&self, fade_out: bool, mtm: MainThreadMarker) {
+        if self.is_single_frame {
+            return;
+        }
+        let frames = {
+            let mut state = self.state.borrow_mut();
+            let Some(idx) = state.active_annotation else {
+                return;
+            };
+            state.cycle_annotation_fade(idx, fade_out)
+        };
+        if let Some(frames) = frames {
+            let fps = self.state.borrow().fps;
+            let kind = if fade_out { "out" } else { "in" };
+            eprintln!("Fade {}: {} frames ({:.2}s)", kind, frames, frames as f64 / fps);
+            self.display_current_frame(mtm);
+        }
+    }
+
+    /// Bring the active annotation to the front or send it to the back, keeping it selected.
+    pub fn reorder_active_annotation(&self, to_front: bool, mtm: MainThreadMarker) {
+        let active = self.state.borrow().active_annotation;
+        let Some(idx) = active else {
+            return;
+        };
+        let new_idx = self.state.borrow_mut().reorder_annotation(idx, to_front);
+        if let Some(new_idx) = new_idx {
+            self.view.set_active_annotation_index(Some(new_idx));
             self.display_current_frame(mtm);
         }
     }
@@ -454,6 +739,7 @@ impl EditorWindow {
             self.minibar_view.update_state(
                 range.0,
                 range.1,
+                state.active_annotation_fades(),
                 state.current_frame,
                 state.total_frames,
             );
@@ -474,53 +760,207 @@ impl EditorWindow {
 
     /// Hide the mini bar.
     fn hide_mini_bar(&self) {
+        self.minibar_view.end_entry();
         self.minibar_view.setHidden(true);
     }
 
-    /// Recalculate and resize the view/window based on current decoder dimensions.
-    /// Used after crop to fit the new image size.
-    pub fn resize_for_new_image(&self, mtm: MainThreadMarker) {
-        let dec_width = self.decoder.width();
-        let dec_height = self.decoder.height();
+    /// Rotate or flip a still image, carrying its annotations and crop along.
+    /// Text stays readable: labels move with the image but aren't mirrored.
+    pub fn transform_image(
+        &mut self,
+        transform: ImageTransform,
+        mtm: MainThreadMarker,
+    ) -> Result<(), String> {
+        self.ensure_still_image("rotate or flip")?;
+        let image = self.decoder.frame_at(0).ok_or("No image to transform")?;
+        let transformed =
+            super::export::transform_image(image, transform).ok_or("Failed to draw image")?;
+        if let Some(original) = self.unadjusted_image.as_mut() {
+            *original = super::export::transform_image(original, transform)
+                .ok_or("Failed to draw image")?;
+        }
+        let (width, height) = (self.decoder.width() as CGFloat, self.decoder.height() as CGFloat);
+        self.replace_image_mapped(transformed, |p| transform.map_point(p, width, height), mtm);
+        eprintln!("Editor image {:?}", transform);
+        Ok(())
+    }
 
-        // Scale up if image height is less than 800px
-        const MIN_HEIGHT: CGFloat = 800.0;
-        let native_w = dec_width as CGFloat;
-        let native_h = dec_height as CGFloat;
-        let (view_w, view_h) = if native_h < MIN_HEIGHT {
-            let scale = MIN_HEIGHT / native_h;
-            ((native_w * scale).round(), MIN_HEIGHT)
-        } else {
-            (native_w, native_h)
-        };
+    /// Place a still image on a padded canvas with a fill, rounded corners, and a shadow.
+    pub fn add_background(
+        &mut self,
+        style: &BackgroundStyle,
+        mtm: MainThreadMarker,
+    ) -> Result<(), String> {
+        self.ensure_still_image("add a background to")?;
+        let image = self.decoder.frame_at(0).ok_or("No image to frame")?;
+        let framed =
+            super::export::add_background(image, style).ok_or("Failed to draw background")?;
+        if let Some(original) = self.unadjusted_image.as_mut() {
+            *original =
+                super::export::add_background(original, style).ok_or("Failed to draw background")?;
+        }
+        let padding = style.padding as CGFloat;
+        self.replace_image_mapped(framed, |p| CGPoint::new(p.x + padding, p.y + padding), mtm);
+        eprintln!("Editor image background added: {:?}", style);
+        Ok(())
+    }
 
-        // Cap view size at 80% of screen dimensions, preserving aspect ratio
-        let screen = crate::screen::screen_with_mouse(mtm);
-        let screen_size = screen.frame().size;
-        let max_view_w = (screen_size.width * 0.8).round();
-        let max_view_h = (screen_size.height * 0.8).round();
-        let (view_w, view_h) = if view_w > max_view_w || view_h > max_view_h {
-            let scale = (max_view_w / view_w).min(max_view_h / view_h);
-            ((view_w * scale).round(), (view_h * scale).round())
-        } else {
-            (view_w, view_h)
-        };
+    /// Draw window chrome above a still image, growing it by the chrome's height.
+    pub fn add_window_frame(
+        &mut self,
+        frame: WindowFrame,
+        mtm: MainThreadMarker,
+    ) -> Result<(), String> {
+        self.ensure_still_image("frame")?;
+        // Captures are taken at the screen's pixel density; size the chrome to match
+        let scale = crate::screen::screen_with_mouse(mtm).backingScaleFactor();
+        let image = self.decoder.frame_at(0).ok_or("No image to frame")?;
+        let (framed, bar) = super::export::add_window_frame(image, frame, scale)
+            .ok_or("Failed to draw window frame")?;
+        if let Some(original) = self.unadjusted_image.as_mut() {
+            *original = super::export::add_window_frame(original, frame, scale)
+                .ok_or("Failed to draw window frame")?
+                .0;
+        }
+        let bar = bar as CGFloat;
+        self.replace_image_mapped(framed, |p| CGPoint::new(p.x, p.y + bar), mtm);
+        eprintln!("Editor image framed: {}", frame.title());
+        Ok(())
+    }
 
-        let progress_height = if self.is_single_frame { 0.0 } else { PROGRESS_BAR_HEIGHT };
+    /// Refuse image edits on recordings and while comparing images.
+    fn ensure_still_image(&self, action: &str) -> Result<(), String> {
+        if !self.is_single_frame {
+            return Err(format!("Can only {} still images", action));
+        }
+        if self.diff_image.borrow().is_some() {
+            return Err(format!("Can't {} images being compared", action));
+        }
+        Ok(())
+    }
 
-        // Resize the editor view
-        self.view.setFrame(NSRect::new(
-            CGPoint::new(0.0, progress_height),
-            CGSize::new(view_w, view_h),
-        ));
+    /// Swap in a new still image whose pixels relate to the old one through `map`
+    /// (image pixels, top-left origin), resizing the window to fit and carrying
+    /// annotations and the crop along.
+    fn replace_image_mapped(
+        &mut self,
+        image: CFRetained<CGImage>,
+        map: impl Fn(CGPoint) -> CGPoint,
+        mtm: MainThreadMarker,
+    ) {
+        let (old_w, old_h) = (self.decoder.width() as CGFloat, self.decoder.height() as CGFloat);
+        let old_view = self.view.bounds().size;
+        self.decoder.replace_image(image);
+        self.resize_for_new_image(mtm);
+        let new_view = self.view.bounds().size;
+
+        // View points -> image pixels -> new image pixels -> new view points
+        let (to_px_x, to_px_y) = (old_w / old_view.width, old_h / old_view.height);
+        let (from_px_x, from_px_y) = (
+            new_view.width / self.decoder.width() as CGFloat,
+            new_view.height / self.decoder.height() as CGFloat,
+        );
+        let map_view = |p: CGPoint| {
+            let q = map(CGPoint::new(p.x * to_px_x, p.y * to_px_y));
+            CGPoint::new(q.x * from_px_x, q.y * from_px_y)
+        };
+        let scale = to_px_x * from_px_x;
+        self.state.borrow_mut().map_geometry(map_view, scale);
+        if let Some(crop) = self.view.ivars().crop_rect.get() {
+            let crop = crate::overlay::view::normalize_rect(crop);
+            self.view.set_crop_rect(Some(super::model::map_rect(crop, map_view)));
+        }
+        self.hide_mini_bar();
+        self.display_current_frame(mtm);
+    }
 
-        // Resize the window content
-        let window_size = NSSize::new(view_w, view_h + progress_height);
-        self.window.setContentSize(window_size);
-        self.window.center();
+    /// Turn a still image grayscale or invert its colors. Annotations are left as they are.
+    pub fn adjust_image(
+        &mut self,
+        adjustment: ImageAdjustment,
+        mtm: MainThreadMarker,
+    ) -> Result<(), String> {
+        self.ensure_still_image("adjust the colors of")?;
+        let image = self.decoder.frame_at(0).ok_or("No image to adjust")?;
+        let adjusted = super::export::adjust_image(image, adjustment)?;
+        if self.unadjusted_image.is_none() {
+            self.unadjusted_image = Some(image.retain());
+        }
+        self.decoder.replace_image(adjusted);
+        self.display_current_frame(mtm);
+        eprintln!("Editor image adjusted: {:?}", adjustment);
+        Ok(())
+    }
 
-        // Refresh display
+    /// Undo every grayscale and invert, keeping rotations and flips made since.
+    pub fn remove_adjustments(&mut self, mtm: MainThreadMarker) -> Result<(), String> {
+        let original = self.unadjusted_image.take().ok_or("No adjustments to remove")?;
+        self.decoder.replace_image(original);
         self.display_current_frame(mtm);
+        Ok(())
+    }
+
+    /// Resize the window to fit the decoder's image after its dimensions changed,
+    /// keeping the window's top-left corner in place. Still images only.
+    fn resize_for_new_image(&self, mtm: MainThreadMarker) {
+        let (width, height) = (self.decoder.width(), self.decoder.height());
+        let (view_w, view_h) = fit_view_size(width, height, mtm);
+        let frame = self.window.frame();
+        let top_left = CGPoint::new(frame.origin.x, frame.origin.y + frame.size.height);
+        self.window.setContentSize(CGSize::new(view_w, view_h));
+        self.window.setFrameTopLeftPoint(top_left);
+        self.view.setFrame(NSRect::new(CGPoint::ZERO, CGSize::new(view_w, view_h)));
+        self.view.set_image_pixel_size(width, height);
+    }
+
+    /// Compare the current image against `other` and show the diff overlay.
+    pub fn set_diff_image(&self, other: CFRetained<CGImage>, mtm: MainThreadMarker) {
+        *self.diff_image.borrow_mut() = Some(other);
+        self.refresh_diff_overlay(mtm);
+    }
+
+    /// Raise or lower the diff threshold and recompute the overlay.
+    pub fn adjust_diff_threshold(&self, delta: i16, mtm: MainThreadMarker) {
+        let threshold = (self.diff_threshold.get() as i16 + delta).clamp(0, 255) as u8;
+        self.diff_threshold.set(threshold);
+        self.refresh_diff_overlay(mtm);
+    }
+
+    /// Recompute the diff overlay for the current frame and threshold.
+    fn refresh_diff_overlay(&self, mtm: MainThreadMarker) {
+        let diff_ref = self.diff_image.borrow();
+        let Some(other) = diff_ref.as_ref() else {
+            return;
+        };
+        let frame_idx = self.state.borrow().current_frame;
+        let Some(base) = self.decoder.frame_at(frame_idx) else {
+            return;
+        };
+
+        let threshold = self.diff_threshold.get();
+        let result = match crate::diff::diff_images(base, other, threshold) {
+            Ok(r) => r,
+            Err(e) => {
+                eprintln!("Diff failed: {}", e);
+                return;
+            }
+        };
+
+        let ns_image = NSImage::initWithCGImage_size(
+            mtm.alloc(),
+            &result.overlay,
+            NSSize::new(
+                self.decoder.width() as CGFloat,
+                self.decoder.height() as CGFloat,
+            ),
+        );
+        self.view.set_diff_overlay(Some(ns_image));
+
+        self.window.setTitle(&NSString::from_str(&format!(
+            "Compare Images \u{2014} {} changed pixels (threshold {}, [ / ] to adjust)",
+            result.changed_pixels, threshold
+        )));
+        eprintln!("Diff: {} changed pixels at threshold {}", result.changed_pixels, threshold);
     }
 
     /// Close the editor window and clean up.
@@ -531,8 +971,89 @@ impl EditorWindow {
         self.window.orderOut(None);
     }
 
+    /// Start the exported video at the current frame.
+    pub fn set_trim_start_at_current(&self) {
+        if self.is_single_frame {
+            return;
+        }
+        let mut state = self.state.borrow_mut();
+        let frame = state.current_frame;
+        state.set_trim_start(frame);
+        drop(state);
+        self.update_trim_bar();
+    }
+
+    /// End the exported video at the current frame.
+    pub fn set_trim_end_at_current(&self) {
+        if self.is_single_frame {
+            return;
+        }
+        let mut state = self.state.borrow_mut();
+        let frame = state.current_frame;
+        state.set_trim_end(frame);
+        drop(state);
+        self.update_trim_bar();
+    }
+
+    /// Export the whole video again.
+    pub fn clear_trim(&self) {
+        self.state.borrow_mut().clear_trim();
+        self.update_trim_bar();
+    }
+
+    /// Position the trim marker under the slider to cover the kept frames.
+    fn update_trim_bar(&self) {
+        let state = self.state.borrow();
+        let range = state.trim_range();
+        eprintln!("Trim: frames {}..{} of {}", range.start, range.end, state.total_frames);
+        if !state.is_trimmed() {
+            self.trim_bar.setHidden(true);
+            return;
+        }
+        let last = state.total_frames.saturating_sub(1).max(1) as CGFloat;
+        drop(state);
+
+        let slider_frame = self.slider.frame();
+        let track_x = slider_frame.origin.x + SLIDER_KNOB_INSET;
+        let track_w = slider_frame.size.width - SLIDER_KNOB_INSET * 2.0;
+        let x0 = track_x + track_w * range.start as CGFloat / last;
+        let x1 = track_x + track_w * (range.end - 1) as CGFloat / last;
+        self.trim_bar.setFrame(NSRect::new(
+            CGPoint::new(x0, slider_frame.origin.y - TRIM_BAR_HEIGHT),
+            CGSize::new((x1 - x0).max(2.0), TRIM_BAR_HEIGHT),
+        ));
+        self.trim_bar.setHidden(false);
+    }
+
     /// Get a reference to the editor state for export.
     pub fn sessions(&self) -> std::cell::Ref<'_, EditorState> {
         self.state.borrow()
     }
 }
+
+/// Editor view size for a `width`×`height` image: small images are scaled up to
+/// 800 points tall, and the result is capped at 80% of the screen, preserving aspect ratio.
+fn fit_view_size(width: usize, height: usize, mtm: MainThreadMarker) -> (CGFloat, CGFloat) {
+    // Scale up if video height is less than 800px
+    const MIN_HEIGHT: CGFloat = 800.0;
+    let native_w = width as CGFloat;
+    let native_h = height as CGFloat;
+    let (view_w, view_h) = if native_h < MIN_HEIGHT {
+        let scale = MIN_HEIGHT / native_h;
+        ((native_w * scale).round(), MIN_HEIGHT)
+    } else {
+        (native_w, native_h)
+    };
+
+    // Cap view size at 80% of screen dimensions, preserving aspect ratio
+    let screen = crate::screen::screen_with_mouse(mtm);
+    let screen_size = screen.frame().size;
+    let max_view_w = (screen_size.width * 0.8).round();
+    let max_view_h = (screen_size.height * 0.8).round();
+    if view_w > max_view_w || view_h > max_view_h {
+        let scale = (max_view_w / view_w).min(max_view_h / view_h);
+        ((view_w * scale).round(), (view_h * scale).round())
+    } else {
+        (view_w, view_h)
+    }
+}
@@ -1,16 +1,26 @@
 use std::ffi::c_void;
-use std::path::Path;
+use std::io::Write;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, Command, Stdio};
 use std::ptr::NonNull;
 
 use objc2::rc::Retained;
 use objc2::runtime::AnyObject;
 use objc2_av_foundation::{
-    AVAssetWriter, AVAssetWriterInput, AVAssetWriterInputPixelBufferAdaptor, AVFileTypeMPEG4,
-    AVMediaTypeVideo, AVVideoCodecKey, AVVideoCodecTypeH264, AVVideoHeightKey, AVVideoWidthKey,
+    AVAssetReader, AVAssetReaderTrackOutput, AVAssetWriter, AVAssetWriterInput,
+    AVAssetWriterInputPixelBufferAdaptor, AVFileTypeMPEG4, AVMediaTypeAudio, AVMediaTypeVideo,
+    AVURLAsset, AVVideoAverageBitRateKey, AVVideoCodecKey,
+    AVVideoCodecTypeH264, AVVideoCodecTypeHEVC, AVVideoCompressionPropertiesKey, AVVideoHeightKey,
+    AVVideoWidthKey,
 };
+use objc2_avf_audio::{AVEncoderBitRateKey, AVFormatIDKey, AVNumberOfChannelsKey, AVSampleRateKey};
 use objc2_core_foundation::{CGFloat, CGPoint, CGRect, CGSize};
 use objc2_core_graphics::{CGBitmapContextCreate, CGColorSpace, CGContext, CGImage};
-use objc2_core_media::CMTime;
+use objc2_core_media::{
+    CMClock, CMItemCount, CMSampleBuffer, CMSampleTimingInfo, CMTime, CMTimeRange,
+    kCMTimeInvalid,
+};
 use objc2_core_video::{
     CVPixelBuffer, CVPixelBufferCreate, CVPixelBufferGetBaseAddress, CVPixelBufferGetBytesPerRow,
     CVPixelBufferLockBaseAddress, CVPixelBufferLockFlags, CVPixelBufferUnlockBaseAddress,
@@ -18,22 +28,51 @@ use objc2_core_video::{
 };
 use objc2_foundation::{NSDictionary, NSNumber, NSString, NSURL};
 
+use crate::prefs::{VideoCodec, VideoQuality};
+
 unsafe extern "C" {
     fn CFRelease(cf: *const c_void);
 }
 
+/// kAudioFormatMPEG4AAC ('aac ').
+const AUDIO_FORMAT_AAC: u32 = 0x6161_6320;
+
+/// kAudioFormatLinearPCM ('lpcm').
+const AUDIO_FORMAT_LINEAR_PCM: u32 = 0x6c70_636d;
+
+/// Longest an export waits for the writer to accept more audio.
+const AUDIO_READY_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(2);
+
 pub struct VideoEncoder {
     writer: Retained<AVAssetWriter>,
     input: Retained<AVAssetWriterInput>,
     adaptor: Retained<AVAssetWriterInputPixelBufferAdaptor>,
+    path: PathBuf,
     width: usize,
     height: usize,
     frame_count: u64,
     fps: i32,
+    quality: VideoQuality,
+    codec: VideoCodec,
+    /// AAC track fed from the microphone, if audio is being recorded, or
+    /// from `audio_source` when exporting.
+    audio_input: Option<Retained<AVAssetWriterInput>>,
+    /// Audio of the recording being exported, appended as the frames reach it.
+    audio_source: Option<AudioSource>,
+    /// Host-clock time when writing started. Set only with an audio track:
+    /// frames are then stamped in real time so they stay in sync with the audio.
+    clock_start: Option<CMTime>,
 }
 
 impl VideoEncoder {
-    pub fn new(path: &Path, width: usize, height: usize, fps: i32) -> Result<Self, String> {
+    pub fn new(
+        path: &Path,
+        width: usize,
+        height: usize,
+        fps: i32,
+        quality: VideoQuality,
+        codec: VideoCodec,
+    ) -> Result<Self, String> {
         let url = {
             let path_str = path.to_str().ok_or("Invalid path")?;
             let ns_path = NSString::from_str(path_str);
@@ -47,11 +86,22 @@ impl VideoEncoder {
                 .map_err(|e| format!("Failed to create AVAssetWriter: {}", e))?
         };
 
-        let output_settings = build_video_settings(width, height)?;
-
         let media_type =
             unsafe { AVMediaTypeVideo.ok_or("AVMediaTypeVideo not available")? };
 
+        // Fall back to H.264 when this Mac can't encode HEVC
+        let mut codec = codec;
+        let mut output_settings = build_video_settings(width, height, fps, quality, codec)?;
+        if codec != VideoCodec::H264
+            && !unsafe {
+                writer.canApplyOutputSettings_forMediaType(Some(&output_settings), media_type)
+            }
+        {
+            eprintln!("VideoEncoder: {} unavailable, using H.264", codec.title());
+            codec = VideoCodec::H264;
+            output_settings = build_video_settings(width, height, fps, quality, codec)?;
+        }
+
         let input = unsafe {
             AVAssetWriterInput::assetWriterInputWithMediaType_outputSettings(
                 media_type,
@@ -73,24 +123,109 @@ impl VideoEncoder {
             writer,
             input,
             adaptor,
+            path: path.to_path_buf(),
             width,
             height,
             frame_count: 0,
             fps,
+            quality,
+            codec,
+            audio_input: None,
+            audio_source: None,
+            clock_start: None,
         })
     }
 
-    pub fn start(&self) -> Result<(), String> {
+    /// Add an AAC audio track fed live from the microphone. Must be called before `start`.
+    pub fn add_audio_input(&mut self) -> Result<(), String> {
+        self.audio_input = Some(self.new_audio_input(true)?);
+        Ok(())
+    }
+
+    /// Add an AAC audio track re-encoded from `source`, kept in step with the
+    /// appended frames. Must be called before `start`.
+    pub fn add_audio_source(&mut self, source: AudioSource) -> Result<(), String> {
+        self.audio_input = Some(self.new_audio_input(false)?);
+        self.audio_source = Some(source);
+        Ok(())
+    }
+
+    fn new_audio_input(&self, real_time: bool) -> Result<Retained<AVAssetWriterInput>, String> {
+        let media_type =
+            unsafe { AVMediaTypeAudio.ok_or("AVMediaTypeAudio not available")? };
+        let output_settings = build_audio_settings()?;
+
+        let input = unsafe {
+            AVAssetWriterInput::assetWriterInputWithMediaType_outputSettings(
+                media_type,
+                Some(&output_settings),
+            )
+        };
+        unsafe { input.setExpectsMediaDataInRealTime(real_time) };
+
+        if !unsafe { self.writer.canAddInput(&input) } {
+            return Err("Cannot add audio input".into());
+        }
+        unsafe { self.writer.addInput(&input) };
+        Ok(input)
+    }
+
+    /// The audio track and the host-clock time its samples are offset by,
+    /// once writing has started with an audio track.
+    pub fn audio_track(&self) -> Option<(Retained<AVAssetWriterInput>, CMTime)> {
+        Some((self.audio_input.clone()?, self.clock_start?))
+    }
+
+    /// Number of frames appended so far.
+    pub fn frame_count(&self) -> u64 {
+        self.frame_count
+    }
+
+    /// Frames per second of the output video.
+    pub fn fps(&self) -> i32 {
+        self.fps
+    }
+
+    pub fn start(&mut self) -> Result<(), String> {
         let ok = unsafe { self.writer.startWriting() };
         if !ok {
             let err = unsafe { self.writer.error() };
-            return Err(format!(
-                "startWriting failed: {}",
-                err.map(|e| e.to_string()).unwrap_or_default()
-            ));
+            let err = err.map(|e| e.to_string()).unwrap_or_default();
+
+            // The HEVC encoder can still fail here (e.g. no hardware encoder for
+            // this size); retry with a fresh H.264 writer.
+            if self.codec != VideoCodec::H264 {
+                eprintln!(
+                    "VideoEncoder: {} failed to start ({}), retrying with H.264",
+                    self.codec.title(),
+                    err
+                );
+                let _ = std::fs::remove_file(&self.path);
+                let mut fallback = VideoEncoder::new(
+                    &self.path,
+                    self.width,
+                    self.height,
+                    self.fps,
+                    self.quality,
+                    VideoCodec::H264,
+                )?;
+                if let Some(source) = self.audio_source.take() {
+                    fallback.add_audio_source(source)?;
+                } else if self.audio_input.is_some() {
+                    fallback.add_audio_input()?;
+                }
+                *self = fallback;
+                return self.start();
+            }
+
+            return Err(format!("startWriting failed: {}", err));
         }
         let zero = unsafe { CMTime::new(0, self.fps) };
         unsafe { self.writer.startSessionAtSourceTime(zero) };
+        // Live audio is stamped by the host clock, so frames must be too
+        if self.audio_input.is_some() && self.audio_source.is_none() {
+            self.clock_start = Some(unsafe { CMClock::host_time_clock().time() });
+        }
         eprintln!("VideoEncoder: started writing");
         Ok(())
     }
@@ -130,7 +265,10 @@ impl VideoEncoder {
             return false;
         }
 
-        let presentation_time = unsafe { CMTime::new(self.frame_count as i64, self.fps) };
+        let presentation_time = match self.clock_start {
+            Some(start) => unsafe { CMClock::host_time_clock().time().subtract(start) },
+            None => unsafe { CMTime::new(self.frame_count as i64, self.fps) },
+        };
 
         let ok = unsafe {
             self.adaptor
@@ -146,12 +284,24 @@ impl VideoEncoder {
             eprintln!("VideoEncoder: appendPixelBuffer failed");
         }
 
+        // Keep the exported audio level with the video so the writer can interleave them
+        if let (Some(source), Some(audio_input)) = (&mut self.audio_source, &self.audio_input) {
+            let video_time = self.frame_count as f64 / self.fps as f64;
+            source.append_until(audio_input, Some(video_time));
+        }
+
         ok
     }
 
     /// Finish writing synchronously.
-    pub fn finish(&self) {
+    pub fn finish(&mut self) {
         unsafe { self.input.markAsFinished() };
+        if let Some(audio_input) = &self.audio_input {
+            if let Some(source) = &mut self.audio_source {
+                source.append_until(audio_input, None);
+            }
+            unsafe { audio_input.markAsFinished() };
+        }
 
         #[allow(deprecated)]
         let ok = unsafe { self.writer.finishWriting() };
@@ -231,15 +381,337 @@ impl VideoEncoder {
     }
 }
 
-/// Build an NSDictionary with video codec, width and height settings.
+/// The audio track of a recording, read back as PCM so an export can
+/// re-encode the part that matches its exported frames.
+pub struct AudioSource {
+    /// Kept alive while `output` is read.
+    _reader: Retained<AVAssetReader>,
+    output: Retained<AVAssetReaderTrackOutput>,
+    /// Source time that becomes zero in the export.
+    start: CMTime,
+    /// Sample read ahead of the video, appended once the video catches up.
+    pending: Option<Retained<CMSampleBuffer>>,
+}
+
+impl AudioSource {
+    /// Open the audio in `path` between `seconds.start` and `seconds.end`.
+    /// Returns `Ok(None)` when the file has no audio track.
+    pub fn open(path: &Path, seconds: Range<f64>) -> Result<Option<Self>, String> {
+        let path_str = path.to_str().ok_or("Invalid path")?;
+        let url = NSURL::fileURLWithPath(&NSString::from_str(path_str));
+        let asset = unsafe { AVURLAsset::URLAssetWithURL_options(&url, None) };
+
+        let audio_type = unsafe { AVMediaTypeAudio.ok_or("AVMediaTypeAudio not available")? };
+        #[allow(deprecated)]
+        let tracks = unsafe { asset.tracksWithMediaType(audio_type) };
+        let Some(track) = tracks.firstObject() else {
+            return Ok(None);
+        };
+
+        let output_settings = build_pcm_settings()?;
+        let output = unsafe {
+            AVAssetReaderTrackOutput::assetReaderTrackOutputWithTrack_outputSettings(
+                &track,
+                Some(&output_settings),
+            )
+        };
+        let reader = unsafe {
+            AVAssetReader::assetReaderWithAsset_error(&asset)
+                .map_err(|e| format!("Failed to create AVAssetReader: {}", e))?
+        };
+        let start = unsafe { CMTime::with_seconds(seconds.start, 600) };
+        let duration = unsafe { CMTime::with_seconds(seconds.end - seconds.start, 600) };
+        unsafe {
+            reader.setTimeRange(CMTimeRange { start, duration });
+            reader.addOutput(&output);
+        }
+        if !unsafe { reader.startReading() } {
+            let err = unsafe { reader.error() };
+            return Err(format!(
+                "Failed to read audio: {}",
+                err.map(|e| e.to_string()).unwrap_or_default()
+            ));
+        }
+
+        Ok(Some(AudioSource {
+            _reader: reader,
+            output,
+            start,
+            pending: None,
+        }))
+    }
+
+    /// Append audio to `input` up to `until` seconds into the export, or all
+    /// that is left when `until` is `None`. Waits briefly while the writer is busy.
+    fn append_until(&mut self, input: &AVAssetWriterInput, until: Option<f64>) {
+        loop {
+            let Some(sample) = self
+                .pending
+                .take()
+                .or_else(|| unsafe { self.output.copyNextSampleBuffer() })
+            else {
+                return;
+            };
+            let time = unsafe { sample.presentation_time_stamp().subtract(self.start).seconds() };
+            if until.is_some_and(|until| time >= until) {
+                self.pending = Some(sample);
+                return;
+            }
+            if !wait_until_ready(input) {
+                eprintln!("VideoEncoder: audio input stalled, dropping the rest of the audio");
+                return;
+            }
+            append_audio(input, &sample, self.start);
+        }
+    }
+}
+
+/// Wait up to `AUDIO_READY_TIMEOUT` for `input` to accept more samples.
+fn wait_until_ready(input: &AVAssetWriterInput) -> bool {
+    let deadline = std::time::Instant::now() + AUDIO_READY_TIMEOUT;
+    while !unsafe { input.isReadyForMoreMediaData() } {
+        if std::time::Instant::now() >= deadline {
+            return false;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(2));
+    }
+    true
+}
+
+/// Where ffmpeg is looked for besides PATH (apps launched from Finder don't
+/// inherit the shell's PATH, so Homebrew installs would otherwise be missed).
+const FFMPEG_PATHS: [&str; 2] = ["/opt/homebrew/bin/ffmpeg", "/usr/local/bin/ffmpeg"];
+
+/// VP9 WebM encoder backed by an `ffmpeg` subprocess: frames are piped to
+/// its stdin as raw RGBA. Audio, if any, is read by ffmpeg from the source
+/// recording and encoded as Opus.
+pub struct WebmEncoder {
+    child: Child,
+    stdin: Option<ChildStdin>,
+    width: usize,
+    height: usize,
+    frame_count: u64,
+}
+
+impl WebmEncoder {
+    pub fn new(
+        path: &Path,
+        width: usize,
+        height: usize,
+        fps: i32,
+        quality: VideoQuality,
+        audio: Option<(&Path, Range<f64>)>,
+    ) -> Result<Self, String> {
+        let ffmpeg = find_ffmpeg()
+            .ok_or("ffmpeg not found; install it (e.g. `brew install ffmpeg`) to export WebM")?;
+
+        let mut command = Command::new(ffmpeg);
+        command
+            .args(["-y", "-loglevel", "error", "-f", "rawvideo", "-pix_fmt", "rgba"])
+            .args(["-s", &format!("{}x{}", width, height)])
+            .args(["-framerate", &fps.to_string(), "-i", "-"]);
+        if let Some((source, seconds)) = audio {
+            // The `?` keeps a recording without an audio track exportable
+            command
+                .args(["-ss", &format!("{:.3}", seconds.start)])
+                .args(["-t", &format!("{:.3}", seconds.end - seconds.start)])
+                .arg("-i")
+                .arg(source)
+                .args(["-map", "0:v", "-map", "1:a?", "-c:a", "libopus", "-b:a", "96k"]);
+        }
+        let mut child = command
+            .args(["-c:v", "libvpx-vp9", "-b:v", "0", "-row-mt", "1"])
+            .args(["-crf", &quality.vp9_crf().to_string()])
+            .args(["-pix_fmt", "yuv420p"])
+            .arg(path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .spawn()
+            .map_err(|e| format!("Failed to start ffmpeg: {}", e))?;
+        let stdin = child.stdin.take();
+
+        eprintln!("WebmEncoder: started ffmpeg");
+        Ok(WebmEncoder {
+            child,
+            stdin,
+            width,
+            height,
+            frame_count: 0,
+        })
+    }
+
+    /// Append a CGImage as a frame. It must match the encoder's dimensions.
+    pub fn append_frame(&mut self, image: &CGImage) -> bool {
+        let size = (CGImage::width(Some(image)), CGImage::height(Some(image)));
+        if size != (self.width, self.height) {
+            eprintln!(
+                "WebmEncoder: frame is {}x{}, expected {}x{}",
+                size.0, size.1, self.width, self.height
+            );
+            return false;
+        }
+        let Some(stdin) = self.stdin.as_mut() else {
+            return false;
+        };
+        let rgba = match crate::actions::cgimage_to_rgba(image) {
+            Ok(rgba) => rgba,
+            Err(e) => {
+                eprintln!("WebmEncoder: {}", e);
+                return false;
+            }
+        };
+        if let Err(e) = stdin.write_all(&rgba) {
+            eprintln!("WebmEncoder: writing to ffmpeg failed: {}", e);
+            self.stdin = None;
+            return false;
+        }
+        self.frame_count += 1;
+        true
+    }
+
+    /// Close ffmpeg's input and wait for it to finish the file.
+    pub fn finish(mut self) -> Result<(), String> {
+        drop(self.stdin.take());
+        let status = self
+            .child
+            .wait()
+            .map_err(|e| format!("Failed to wait for ffmpeg: {}", e))?;
+        if !status.success() {
+            return Err(format!("ffmpeg exited with {}", status));
+        }
+        eprintln!("WebmEncoder: finished writing ({} frames)", self.frame_count);
+        Ok(())
+    }
+}
+
+/// Locate the ffmpeg binary on PATH or in the usual Homebrew locations.
+fn find_ffmpeg() -> Option<PathBuf> {
+    let on_path = std::env::var_os("PATH")
+        .into_iter()
+        .flat_map(|paths| std::env::split_paths(&paths).collect::<Vec<_>>())
+        .map(|dir| dir.join("ffmpeg"));
+    on_path
+        .chain(FFMPEG_PATHS.iter().map(PathBuf::from))
+        .find(|path| path.is_file())
+}
+
+/// Append a captured audio sample buffer to `input`, shifting its timestamps
+/// so that `clock_start` (host-clock time) becomes zero. Audio captured
+/// before writing started is dropped.
+pub fn append_audio(
+    input: &AVAssetWriterInput,
+    sample_buffer: &CMSampleBuffer,
+    clock_start: CMTime,
+) -> bool {
+    if !unsafe { input.isReadyForMoreMediaData() } {
+        return false;
+    }
+
+    let mut count: CMItemCount = 0;
+    let status =
+        unsafe { sample_buffer.sample_timing_info_array(0, std::ptr::null_mut(), &mut count) };
+    if status != 0 || count <= 0 {
+        return false;
+    }
+
+    let invalid = unsafe { kCMTimeInvalid };
+    let mut timing = vec![
+        CMSampleTimingInfo {
+            duration: invalid,
+            presentationTimeStamp: invalid,
+            decodeTimeStamp: invalid,
+        };
+        count as usize
+    ];
+    let status = unsafe {
+        sample_buffer.sample_timing_info_array(count, timing.as_mut_ptr(), &mut count)
+    };
+    if status != 0 {
+        return false;
+    }
+
+    for info in &mut timing {
+        info.presentationTimeStamp = unsafe { info.presentationTimeStamp.subtract(clock_start) };
+        info.decodeTimeStamp = invalid;
+    }
+    let first_value = timing[0].presentationTimeStamp.value;
+    if first_value < 0 {
+        return false;
+    }
+
+    let mut copy_ptr: *mut CMSampleBuffer = std::ptr::null_mut();
+    let status = unsafe {
+        CMSampleBuffer::create_copy_with_new_timing(
+            None,
+            sample_buffer,
+            count,
+            timing.as_ptr(),
+            NonNull::new(&mut copy_ptr).unwrap(),
+        )
+    };
+    if status != 0 || copy_ptr.is_null() {
+        eprintln!("VideoEncoder: retiming audio failed: {}", status);
+        return false;
+    }
+
+    // SAFETY: the copy succeeded and we own its only reference.
+    let ok = unsafe { input.appendSampleBuffer(&*copy_ptr) };
+    unsafe { CFRelease(copy_ptr as *const c_void) };
+    ok
+}
+
+/// Build an NSDictionary asking an asset reader for linear PCM audio.
+fn build_pcm_settings() -> Result<Retained<NSDictionary<NSString, AnyObject>>, String> {
+    let format_key = unsafe { AVFormatIDKey.ok_or("AVFormatIDKey not available")? };
+    let format_num = NSNumber::numberWithUnsignedInt(AUDIO_FORMAT_LINEAR_PCM);
+    let format_obj: &AnyObject =
+        unsafe { &*(&*format_num as *const NSNumber as *const AnyObject) };
+    Ok(NSDictionary::from_slices(&[format_key], &[format_obj]))
+}
+
+/// Build an NSDictionary with AAC format, sample rate and channel settings.
+fn build_audio_settings() -> Result<Retained<NSDictionary<NSString, AnyObject>>, String> {
+    let format_key = unsafe { AVFormatIDKey.ok_or("AVFormatIDKey not available")? };
+    let rate_key = unsafe { AVSampleRateKey.ok_or("AVSampleRateKey not available")? };
+    let channels_key =
+        unsafe { AVNumberOfChannelsKey.ok_or("AVNumberOfChannelsKey not available")? };
+    let bit_rate_key =
+        unsafe { AVEncoderBitRateKey.ok_or("AVEncoderBitRateKey not available")? };
+
+    let format_num = NSNumber::numberWithUnsignedInt(AUDIO_FORMAT_AAC);
+    let rate_num = NSNumber::numberWithDouble(44_100.0);
+    let channels_num = NSNumber::numberWithInt(1);
+    let bit_rate_num = NSNumber::numberWithInt(96_000);
+
+    let keys: &[&NSString] = &[format_key, rate_key, channels_key, bit_rate_key];
+    let objects: &[&AnyObject] = &[
+        unsafe { &*(&*format_num as *const NSNumber as *const AnyObject) },
+        unsafe { &*(&*rate_num as *const NSNumber as *const AnyObject) },
+        unsafe { &*(&*channels_num as *const NSNumber as *const AnyObject) },
+        unsafe { &*(&*bit_rate_num as *const NSNumber as *const AnyObject) },
+    ];
+
+    Ok(NSDictionary::from_slices(keys, objects))
+}
+
+/// Build an NSDictionary with video codec, width, height and bitrate settings.
 fn build_video_settings(
     width: usize,
     height: usize,
+    fps: i32,
+    quality: VideoQuality,
+    codec: VideoCodec,
 ) -> Result<Retained<NSDictionary<NSString, AnyObject>>, String> {
     let codec_key =
         unsafe { AVVideoCodecKey.ok_or("AVVideoCodecKey not available")? };
-    let codec_value =
-        unsafe { AVVideoCodecTypeH264.ok_or("AVVideoCodecTypeH264 not available")? };
+    let codec_value = match codec {
+        VideoCodec::H264 => unsafe {
+            AVVideoCodecTypeH264.ok_or("AVVideoCodecTypeH264 not available")?
+        },
+        VideoCodec::Hevc => unsafe {
+            AVVideoCodecTypeHEVC.ok_or("AVVideoCodecTypeHEVC not available")?
+        },
+    };
     let width_key =
         unsafe { AVVideoWidthKey.ok_or("AVVideoWidthKey not available")? };
     let height_key =
@@ -255,8 +727,27 @@ fn build_video_settings(
     let height_obj: &AnyObject =
         unsafe { &*(&*height_num as *const NSNumber as *const AnyObject) };
 
-    let keys: &[&NSString] = &[codec_key, width_key, height_key];
-    let objects: &[&AnyObject] = &[codec_obj, width_obj, height_obj];
+    // Average bitrate scaled to the frame size and rate
+    let compression_key = unsafe {
+        AVVideoCompressionPropertiesKey.ok_or("AVVideoCompressionPropertiesKey not available")?
+    };
+    let bit_rate_key =
+        unsafe { AVVideoAverageBitRateKey.ok_or("AVVideoAverageBitRateKey not available")? };
+    let bit_rate = (width * height) as f64
+        * fps.max(1) as f64
+        * quality.bits_per_pixel()
+        * codec.bit_rate_factor();
+    let bit_rate_num = NSNumber::numberWithInteger(bit_rate as isize);
+    let bit_rate_obj: &AnyObject =
+        unsafe { &*(&*bit_rate_num as *const NSNumber as *const AnyObject) };
+    let compression: Retained<NSDictionary<NSString, AnyObject>> =
+        NSDictionary::from_slices(&[bit_rate_key], &[bit_rate_obj]);
+    let compression_obj: &AnyObject = unsafe {
+        &*(&*compression as *const NSDictionary<NSString, AnyObject> as *const AnyObject)
+    };
+
+    let keys: &[&NSString] = &[codec_key, width_key, height_key, compression_key];
+    let objects: &[&AnyObject] = &[codec_obj, width_obj, height_obj, compression_obj];
 
     Ok(NSDictionary::from_slices(keys, objects))
 }
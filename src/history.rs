@@ -0,0 +1,172 @@
+use std::path::{Path, PathBuf};
+
+use dispatch2::DispatchQueue;
+use objc2_core_foundation::{CFRetained, CGFloat};
+use objc2_core_graphics::{CGImage, CGInterpolationQuality};
+use objc2_foundation::{NSDate, NSDateFormatter, NSDateFormatterStyle};
+
+/// Number of captures kept; the oldest is deleted when a new one arrives.
+const MAX_ITEMS: usize = 10;
+/// Longest side of a thumbnail in pixels (shown at half size in the menu, for Retina).
+const THUMBNAIL_PIXELS: CGFloat = 96.0;
+/// Suffix of the preview stored next to each capture.
+const THUMBNAIL_SUFFIX: &str = ".thumb.png";
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum HistoryKind {
+    Screenshot,
+    Recording,
+}
+
+/// One past capture stored in the history folder.
+pub struct HistoryItem {
+    pub kind: HistoryKind,
+    /// The capture itself (PNG or video file).
+    pub path: PathBuf,
+    /// Small PNG preview for the menu (may be missing).
+    pub thumbnail_path: PathBuf,
+    /// Milliseconds since the Unix epoch when it was captured (also its file name).
+    timestamp_ms: u64,
+}
+
+impl HistoryItem {
+    /// Menu title, e.g. "Screenshot — Today at 2:32 PM".
+    pub fn title(&self) -> String {
+        let kind = match self.kind {
+            HistoryKind::Screenshot => "Screenshot",
+            HistoryKind::Recording => "Recording",
+        };
+        let formatter = NSDateFormatter::new();
+        formatter.setDateStyle(NSDateFormatterStyle::MediumStyle);
+        formatter.setTimeStyle(NSDateFormatterStyle::ShortStyle);
+        formatter.setDoesRelativeDateFormatting(true);
+        let date = NSDate::dateWithTimeIntervalSince1970(self.timestamp_ms as f64 / 1000.0);
+        format!("{} \u{2014} {}", kind, formatter.stringFromDate(&date))
+    }
+}
+
+/// `~/Library/Application Support/ClipShot/History`, created if needed.
+fn history_dir() -> Result<PathBuf, String> {
+    let home = std::env::var_os("HOME").ok_or("HOME is not set")?;
+    let dir = PathBuf::from(home)
+        .join("Library")
+        .join("Application Support")
+        .join("ClipShot")
+        .join("History");
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir)
+}
+
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+/// Store a finished screenshot as the newest history item. It is encoded on a
+/// background thread (tall scroll captures take a while); `done` runs on the
+/// main thread once it is written.
+pub fn add_screenshot(
+    image: CFRetained<CGImage>,
+    done: impl FnOnce() + Send + 'static,
+) -> Result<(), String> {
+    let dir = history_dir()?;
+    let stem = format!("{:013}", now_ms());
+    std::thread::spawn(move || {
+        match crate::actions::save_png(&image, &dir.join(format!("{}.png", stem))) {
+            Ok(()) => write_thumbnail(&image, &dir.join(format!("{}{}", stem, THUMBNAIL_SUFFIX))),
+            Err(e) => eprintln!("Failed to add screenshot to history: {}", e),
+        }
+        prune();
+        DispatchQueue::main().exec_async(done);
+    });
+    Ok(())
+}
+
+/// Store a recording as the newest history item, previewed by `first_frame`.
+/// The file is hard-linked, or copied on a background thread when it lives on
+/// another volume; `done` runs on the main thread once it is in place.
+pub fn add_recording(
+    video_path: &Path,
+    first_frame: Option<&CGImage>,
+    done: impl FnOnce() + Send + 'static,
+) -> Result<(), String> {
+    let dir = history_dir()?;
+    let stem = format!("{:013}", now_ms());
+    let ext = video_path.extension().and_then(|e| e.to_str()).unwrap_or("mp4");
+    if let Some(frame) = first_frame {
+        write_thumbnail(frame, &dir.join(format!("{}{}", stem, THUMBNAIL_SUFFIX)));
+    }
+    let source = video_path.to_path_buf();
+    let dest = dir.join(format!("{}.{}", stem, ext));
+    std::thread::spawn(move || {
+        let result = std::fs::hard_link(&source, &dest)
+            .or_else(|_| std::fs::copy(&source, &dest).map(|_| ()));
+        if let Err(e) = result {
+            eprintln!("Failed to add recording to history: {}", e);
+        }
+        prune();
+        DispatchQueue::main().exec_async(done);
+    });
+    Ok(())
+}
+
+/// Delete every stored capture.
+pub fn clear() -> Result<(), String> {
+    let dir = history_dir()?;
+    std::fs::remove_dir_all(&dir).map_err(|e| e.to_string())
+}
+
+/// Stored captures, newest first.
+pub fn items() -> Vec<HistoryItem> {
+    let Ok(dir) = history_dir() else {
+        return Vec::new();
+    };
+    let Ok(entries) = std::fs::read_dir(&dir) else {
+        return Vec::new();
+    };
+    let mut items: Vec<HistoryItem> = entries
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            let name = path.file_name()?.to_str()?;
+            if name.ends_with(THUMBNAIL_SUFFIX) {
+                return None;
+            }
+            let (stem, ext) = name.split_once('.')?;
+            let timestamp_ms = stem.parse().ok()?;
+            let kind = if ext == "png" {
+                HistoryKind::Screenshot
+            } else {
+                HistoryKind::Recording
+            };
+            let thumbnail_path = dir.join(format!("{}{}", stem, THUMBNAIL_SUFFIX));
+            Some(HistoryItem { kind, path, thumbnail_path, timestamp_ms })
+        })
+        .collect();
+    items.sort_by_key(|item| std::cmp::Reverse(item.timestamp_ms));
+    items
+}
+
+/// Delete everything past the newest `MAX_ITEMS`.
+fn prune() {
+    for item in items().into_iter().skip(MAX_ITEMS) {
+        let _ = std::fs::remove_file(&item.path);
+        let _ = std::fs::remove_file(&item.thumbnail_path);
+    }
+}
+
+fn write_thumbnail(image: &CGImage, path: &Path) {
+    let width = CGImage::width(Some(image)) as CGFloat;
+    let height = CGImage::height(Some(image)) as CGFloat;
+    let scale = (THUMBNAIL_PIXELS / width.max(height)).min(1.0);
+    let thumb_w = ((width * scale).round() as usize).max(1);
+    let thumb_h = ((height * scale).round() as usize).max(1);
+    let quality = CGInterpolationQuality::Medium;
+    let result = crate::editor::export::scale_image(image, thumb_w, thumb_h, quality)
+        .ok_or_else(|| "Failed to scale image".to_string())
+        .and_then(|thumb| crate::actions::save_png(&thumb, path));
+    if let Err(e) = result {
+        eprintln!("Failed to write history thumbnail: {}", e);
+    }
+}
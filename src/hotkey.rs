@@ -1,56 +1,354 @@
 use global_hotkey::hotkey::{Code, HotKey, Modifiers};
 use global_hotkey::GlobalHotKeyManager;
+use objc2_app_kit::NSEventModifierFlags;
+
+/// A global hotkey the user can remap.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum HotkeyAction {
+    Capture,
+    Record,
+    ScrollCapture,
+    AnnotateClipboard,
+}
+
+impl HotkeyAction {
+    /// All actions, in display order. The index doubles as the recorder tag.
+    pub const ALL: [HotkeyAction; 4] = [
+        HotkeyAction::Capture,
+        HotkeyAction::Record,
+        HotkeyAction::ScrollCapture,
+        HotkeyAction::AnnotateClipboard,
+    ];
+
+    pub fn title(self) -> &'static str {
+        match self {
+            HotkeyAction::Capture => "Capture Screenshot",
+            HotkeyAction::Record => "Start/Stop Recording",
+            HotkeyAction::ScrollCapture => "Start/Stop Scroll Capture",
+            HotkeyAction::AnnotateClipboard => "Annotate Clipboard Image",
+        }
+    }
+
+    /// The built-in combo: Ctrl+Cmd+A, Ctrl+Cmd+Z, Ctrl+Cmd+S, or Ctrl+Cmd+V.
+    pub fn default_hotkey(self) -> HotKey {
+        let code = match self {
+            HotkeyAction::Capture => Code::KeyA,
+            HotkeyAction::Record => Code::KeyZ,
+            HotkeyAction::ScrollCapture => Code::KeyS,
+            HotkeyAction::AnnotateClipboard => Code::KeyV,
+        };
+        HotKey::new(Some(Modifiers::CONTROL | Modifiers::SUPER), code)
+    }
+
+    fn index(self) -> usize {
+        Self::ALL.iter().position(|&a| a == self).unwrap_or(0)
+    }
+}
 
 pub struct HotkeyManager {
-    _manager: GlobalHotKeyManager,
-    pub capture_hotkey_id: u32,
-    pub record_hotkey_id: u32,
-    pub scroll_capture_hotkey_id: u32,
+    manager: GlobalHotKeyManager,
+    /// Current combo for each action, indexed like `HotkeyAction::ALL`.
+    hotkeys: [HotKey; 4],
+    /// Whether each combo is registered. Disabled actions stay available from
+    /// the menu bar; only the global hotkey is off.
+    enabled: [bool; 4],
 }
 
 impl HotkeyManager {
+    /// Register each enabled action's saved combo (or its default).
     pub fn new() -> Self {
         let manager = GlobalHotKeyManager::new().expect("failed to create hotkey manager");
 
-        // Register Ctrl+Cmd+A for screenshot
-        let capture_hotkey = HotKey::new(
-            Some(Modifiers::CONTROL | Modifiers::META),
-            Code::KeyA,
-        );
-        manager
-            .register(capture_hotkey)
-            .expect("failed to register capture hotkey");
-
-        // Register Ctrl+Cmd+Z for screen recording
-        let record_hotkey = HotKey::new(
-            Some(Modifiers::CONTROL | Modifiers::META),
-            Code::KeyZ,
-        );
-        manager
-            .register(record_hotkey)
-            .expect("failed to register record hotkey");
-
-        // Register Ctrl+Cmd+S for scrolling capture
-        let scroll_capture_hotkey = HotKey::new(
-            Some(Modifiers::CONTROL | Modifiers::META),
-            Code::KeyS,
-        );
-        manager
-            .register(scroll_capture_hotkey)
-            .expect("failed to register scroll capture hotkey");
-
-        eprintln!(
-            "Global hotkeys registered: Ctrl+Cmd+A (id={}), Ctrl+Cmd+Z (id={}), Ctrl+Cmd+S (id={})",
-            capture_hotkey.id(),
-            record_hotkey.id(),
-            scroll_capture_hotkey.id(),
-        );
+        let enabled = HotkeyAction::ALL.map(crate::prefs::hotkey_enabled);
+        let hotkeys = HotkeyAction::ALL.map(|action| {
+            let default = action.default_hotkey();
+            let hotkey = crate::prefs::hotkey(action).map_or(default, normalized);
+            if !enabled[action.index()] {
+                return hotkey;
+            }
+            if manager.register(hotkey).is_ok() {
+                return hotkey;
+            }
+            // A saved combo taken by another app since it was chosen
+            eprintln!(
+                "Failed to register {} hotkey {}, using {}",
+                action.title(),
+                describe(&hotkey),
+                describe(&default)
+            );
+            if let Err(e) = manager.register(default) {
+                eprintln!("Failed to register {} hotkey: {}", action.title(), e);
+            }
+            default
+        });
+
+        for action in HotkeyAction::ALL {
+            let hotkey = hotkeys[action.index()];
+            if enabled[action.index()] {
+                eprintln!("{} hotkey: {} (id={})", action.title(), describe(&hotkey), hotkey.id());
+            } else {
+                eprintln!("{} hotkey: {} (disabled)", action.title(), describe(&hotkey));
+            }
+        }
 
         HotkeyManager {
-            _manager: manager,
-            capture_hotkey_id: capture_hotkey.id(),
-            record_hotkey_id: record_hotkey.id(),
-            scroll_capture_hotkey_id: scroll_capture_hotkey.id(),
+            manager,
+            hotkeys,
+            enabled,
+        }
+    }
+
+    /// The combo currently bound to `action`.
+    pub fn hotkey(&self, action: HotkeyAction) -> HotKey {
+        self.hotkeys[action.index()]
+    }
+
+    pub fn is_enabled(&self, action: HotkeyAction) -> bool {
+        self.enabled[action.index()]
+    }
+
+    /// The enabled action bound to a hotkey event's id.
+    pub fn action_for(&self, id: u32) -> Option<HotkeyAction> {
+        HotkeyAction::ALL
+            .into_iter()
+            .find(|&action| self.is_enabled(action) && self.hotkey(action).id() == id)
+    }
+
+    /// Register or unregister `action`'s combo. Enabling fails if another app
+    /// took the combo in the meantime.
+    pub fn set_enabled(&mut self, action: HotkeyAction, enabled: bool) -> Result<(), String> {
+        let index = action.index();
+        if self.enabled[index] == enabled {
+            return Ok(());
+        }
+        let hotkey = self.hotkeys[index];
+        if enabled {
+            self.manager.register(hotkey).map_err(|_| {
+                format!("{} is already in use by macOS or another app.", describe(&hotkey))
+            })?;
+        } else if let Err(e) = self.manager.unregister(hotkey) {
+            eprintln!("Failed to unregister hotkey {}: {}", describe(&hotkey), e);
+        }
+        self.enabled[index] = enabled;
+        Ok(())
+    }
+
+    /// Rebind `action` to `hotkey`. On conflict the old combo stays registered
+    /// and the error explains what the combo clashes with. A disabled action
+    /// just remembers the combo until it's enabled.
+    pub fn set_hotkey(&mut self, action: HotkeyAction, hotkey: HotKey) -> Result<(), String> {
+        let hotkey = normalized(hotkey);
+        let old = self.hotkey(action);
+        if hotkey == old {
+            return Ok(());
+        }
+        if let Some(other) = HotkeyAction::ALL
+            .into_iter()
+            .find(|&a| a != action && self.hotkey(a) == hotkey)
+        {
+            return Err(format!("{} is already used for {}.", describe(&hotkey), other.title()));
+        }
+        if let Some(reason) = system_conflict(&hotkey) {
+            return Err(format!("{} {}.", describe(&hotkey), reason));
+        }
+
+        if !self.is_enabled(action) {
+            self.hotkeys[action.index()] = hotkey;
+            return Ok(());
+        }
+        let _ = self.manager.unregister(old);
+        if self.manager.register(hotkey).is_err() {
+            let _ = self.manager.register(old);
+            return Err(format!(
+                "{} is already in use by macOS or another app.",
+                describe(&hotkey)
+            ));
         }
+        self.hotkeys[action.index()] = hotkey;
+        eprintln!("{} hotkey: {} (id={})", action.title(), describe(&hotkey), hotkey.id());
+        Ok(())
+    }
+
+    /// Unregister every hotkey so the combos reach the key window (while a
+    /// new shortcut is being typed).
+    pub fn suspend(&self) {
+        for hotkey in self.enabled_hotkeys() {
+            if let Err(e) = self.manager.unregister(hotkey) {
+                eprintln!("Failed to suspend hotkey {}: {}", describe(&hotkey), e);
+            }
+        }
+    }
+
+    /// Register every enabled hotkey again after `suspend`.
+    pub fn resume(&self) {
+        for hotkey in self.enabled_hotkeys() {
+            if let Err(e) = self.manager.register(hotkey) {
+                eprintln!("Failed to register hotkey {}: {}", describe(&hotkey), e);
+            }
+        }
+    }
+
+    fn enabled_hotkeys(&self) -> impl Iterator<Item = HotKey> + '_ {
+        HotkeyAction::ALL
+            .into_iter()
+            .filter(|&action| self.is_enabled(action))
+            .map(|action| self.hotkey(action))
+    }
+}
+
+/// `hotkey` with Meta folded into Super. Both are Cmd on macOS, but only Super
+/// is shown, put on menu items, and matched against other combos.
+pub fn normalized(hotkey: HotKey) -> HotKey {
+    if !hotkey.mods.contains(Modifiers::META) {
+        return hotkey;
+    }
+    let mods = (hotkey.mods - Modifiers::META) | Modifiers::SUPER;
+    HotKey::new(Some(mods), hotkey.key)
+}
+
+/// Why `hotkey` can't be used globally, if it clashes with a macOS shortcut or
+/// would hijack an everyday one in every app.
+fn system_conflict(hotkey: &HotKey) -> Option<&'static str> {
+    let mods = hotkey.mods;
+    let cmd = Modifiers::SUPER;
+    let shift = Modifiers::SHIFT;
+    let reason = match (mods, hotkey.key) {
+        (m, Code::Space) if m == cmd => "opens Spotlight",
+        (m, Code::Space) if m == Modifiers::CONTROL => "switches input sources",
+        (m, Code::Space) if m == Modifiers::CONTROL | cmd => "opens Emoji & Symbols",
+        (m, Code::Tab) if m.contains(cmd) => "switches apps",
+        (m, Code::Digit3 | Code::Digit4 | Code::Digit5) if m.contains(cmd | shift) => {
+            "is a macOS screenshot shortcut"
+        }
+        (m, Code::KeyQ) if m == Modifiers::CONTROL | cmd => "locks the screen",
+        (m, Code::Escape) if m == Modifiers::ALT | cmd => "opens Force Quit",
+        (m, Code::KeyD) if m == Modifiers::ALT | cmd => "shows or hides the Dock",
+        (m, _) if !m.intersects(Modifiers::CONTROL | Modifiers::ALT | cmd) => {
+            "needs Ctrl, Option, or Cmd"
+        }
+        (m, _) if !m.intersects(Modifiers::CONTROL | Modifiers::ALT) => {
+            "would replace that shortcut in every app; add Ctrl or Option"
+        }
+        _ => return None,
+    };
+    Some(reason)
+}
+
+const MODIFIER_FLAGS: [(NSEventModifierFlags, Modifiers); 4] = [
+    (NSEventModifierFlags::Shift, Modifiers::SHIFT),
+    (NSEventModifierFlags::Control, Modifiers::CONTROL),
+    (NSEventModifierFlags::Option, Modifiers::ALT),
+    (NSEventModifierFlags::Command, Modifiers::SUPER),
+];
+
+/// macOS virtual key codes, hotkey codes, and display labels for the keys a
+/// shortcut can use.
+const KEYS: [(u16, Code, &str); 60] = [
+    (0x00, Code::KeyA, "A"),
+    (0x0b, Code::KeyB, "B"),
+    (0x08, Code::KeyC, "C"),
+    (0x02, Code::KeyD, "D"),
+    (0x0e, Code::KeyE, "E"),
+    (0x03, Code::KeyF, "F"),
+    (0x05, Code::KeyG, "G"),
+    (0x04, Code::KeyH, "H"),
+    (0x22, Code::KeyI, "I"),
+    (0x26, Code::KeyJ, "J"),
+    (0x28, Code::KeyK, "K"),
+    (0x25, Code::KeyL, "L"),
+    (0x2e, Code::KeyM, "M"),
+    (0x2d, Code::KeyN, "N"),
+    (0x1f, Code::KeyO, "O"),
+    (0x23, Code::KeyP, "P"),
+    (0x0c, Code::KeyQ, "Q"),
+    (0x0f, Code::KeyR, "R"),
+    (0x01, Code::KeyS, "S"),
+    (0x11, Code::KeyT, "T"),
+    (0x20, Code::KeyU, "U"),
+    (0x09, Code::KeyV, "V"),
+    (0x0d, Code::KeyW, "W"),
+    (0x07, Code::KeyX, "X"),
+    (0x10, Code::KeyY, "Y"),
+    (0x06, Code::KeyZ, "Z"),
+    (0x1d, Code::Digit0, "0"),
+    (0x12, Code::Digit1, "1"),
+    (0x13, Code::Digit2, "2"),
+    (0x14, Code::Digit3, "3"),
+    (0x15, Code::Digit4, "4"),
+    (0x17, Code::Digit5, "5"),
+    (0x16, Code::Digit6, "6"),
+    (0x1a, Code::Digit7, "7"),
+    (0x1c, Code::Digit8, "8"),
+    (0x19, Code::Digit9, "9"),
+    (0x1b, Code::Minus, "-"),
+    (0x18, Code::Equal, "="),
+    (0x21, Code::BracketLeft, "["),
+    (0x1e, Code::BracketRight, "]"),
+    (0x2a, Code::Backslash, "\\"),
+    (0x29, Code::Semicolon, ";"),
+    (0x27, Code::Quote, "'"),
+    (0x2b, Code::Comma, ","),
+    (0x2f, Code::Period, "."),
+    (0x2c, Code::Slash, "/"),
+    (0x32, Code::Backquote, "`"),
+    (0x31, Code::Space, "Space"),
+    (0x30, Code::Tab, "Tab"),
+    (0x35, Code::Escape, "Esc"),
+    (0x7a, Code::F1, "F1"),
+    (0x78, Code::F2, "F2"),
+    (0x63, Code::F3, "F3"),
+    (0x76, Code::F4, "F4"),
+    (0x60, Code::F5, "F5"),
+    (0x61, Code::F6, "F6"),
+    (0x62, Code::F7, "F7"),
+    (0x64, Code::F8, "F8"),
+    (0x65, Code::F9, "F9"),
+    (0x6d, Code::F10, "F10"),
+];
+
+/// Build a hotkey from a key event's virtual key code and modifier flags.
+/// Returns None for keys that can't be used as a shortcut.
+pub fn hotkey_from_event(key_code: u16, flags: NSEventModifierFlags) -> Option<HotKey> {
+    let (_, code, _) = KEYS.iter().find(|(k, _, _)| *k == key_code)?;
+    let mut mods = Modifiers::empty();
+    for (flag, modifier) in MODIFIER_FLAGS {
+        mods.set(modifier, flags.contains(flag));
+    }
+    Some(HotKey::new(Some(mods), *code))
+}
+
+/// Menu modifier mask matching `hotkey`'s modifiers.
+pub fn modifier_flags(hotkey: &HotKey) -> NSEventModifierFlags {
+    MODIFIER_FLAGS
+        .into_iter()
+        .filter(|(_, modifier)| hotkey.mods.contains(*modifier))
+        .fold(NSEventModifierFlags::empty(), |flags, (flag, _)| flags.union(flag))
+}
+
+/// Human-readable combo in the README's style, e.g. "Ctrl+Cmd+A".
+pub fn describe(hotkey: &HotKey) -> String {
+    let mut text = String::new();
+    for (modifier, name) in [
+        (Modifiers::SHIFT, "Shift+"),
+        (Modifiers::ALT, "Option+"),
+        (Modifiers::CONTROL, "Ctrl+"),
+        (Modifiers::SUPER, "Cmd+"),
+    ] {
+        if hotkey.mods.contains(modifier) {
+            text.push_str(name);
+        }
+    }
+    match KEYS.iter().find(|(_, code, _)| *code == hotkey.key) {
+        Some((_, _, label)) => text.push_str(label),
+        None => text.push_str(&hotkey.key.to_string()),
+    }
+    text
+}
+
+/// Menu key equivalent for `hotkey` (empty for keys a menu can't show).
+pub fn key_equivalent(hotkey: &HotKey) -> String {
+    match KEYS.iter().find(|(_, code, _)| *code == hotkey.key) {
+        Some((_, _, label)) if label.chars().count() == 1 => label.to_lowercase(),
+        _ => String::new(),
     }
 }
@@ -0,0 +1,90 @@
+use objc2_core_foundation::{CFBoolean, CFData, CFDictionary, CFRetained, CFString, CFType};
+
+// Security has no objc2 bindings here; the keychain calls are declared directly.
+#[link(name = "Security", kind = "framework")]
+unsafe extern "C" {
+    static kSecClass: &'static CFString;
+    static kSecClassGenericPassword: &'static CFString;
+    static kSecAttrService: &'static CFString;
+    static kSecAttrAccount: &'static CFString;
+    static kSecValueData: &'static CFString;
+    static kSecReturnData: &'static CFString;
+
+    fn SecItemAdd(attributes: &CFDictionary, result: *mut *const CFType) -> i32;
+    fn SecItemCopyMatching(query: &CFDictionary, result: *mut *const CFType) -> i32;
+    fn SecItemUpdate(query: &CFDictionary, attributes: &CFDictionary) -> i32;
+    fn SecItemDelete(query: &CFDictionary) -> i32;
+}
+
+/// Service name of the app's keychain items.
+const SERVICE: &str = "ClipShot";
+
+const ERR_SEC_SUCCESS: i32 = 0;
+const ERR_SEC_ITEM_NOT_FOUND: i32 = -25300;
+
+/// The secret stored under `account`, if any.
+pub fn password(account: &str) -> Option<String> {
+    let mut query = item_query(account);
+    query.push((unsafe { kSecReturnData }, CFBoolean::new(true).into()));
+    let mut result: *const CFType = std::ptr::null();
+    let status = unsafe { SecItemCopyMatching(dictionary(&query).as_opaque(), &mut result) };
+    if status != ERR_SEC_SUCCESS || result.is_null() {
+        return None;
+    }
+    // SAFETY: a successful copy returns a +1 reference, CFData for kSecReturnData.
+    let data = unsafe { CFRetained::from_raw(std::ptr::NonNull::new(result.cast_mut())?) };
+    let data = data.downcast::<CFData>().ok()?;
+    String::from_utf8(data.to_vec()).ok()
+}
+
+/// Store `secret` under `account`, replacing what was there.
+pub fn set_password(account: &str, secret: &str) -> Result<(), String> {
+    let query = item_query(account);
+    let data = CFData::from_bytes(secret.as_bytes());
+    let value = [(unsafe { kSecValueData }, CFRetained::<CFType>::from(&**data))];
+    let status = unsafe {
+        SecItemUpdate(dictionary(&query).as_opaque(), dictionary(&value).as_opaque())
+    };
+    let status = if status == ERR_SEC_ITEM_NOT_FOUND {
+        let mut attributes = query;
+        attributes.extend(value);
+        unsafe { SecItemAdd(dictionary(&attributes).as_opaque(), std::ptr::null_mut()) }
+    } else {
+        status
+    };
+    check(status)
+}
+
+/// Remove the secret stored under `account` (nothing to do if there is none).
+pub fn delete_password(account: &str) -> Result<(), String> {
+    let status = unsafe { SecItemDelete(dictionary(&item_query(account)).as_opaque()) };
+    if status == ERR_SEC_ITEM_NOT_FOUND {
+        return Ok(());
+    }
+    check(status)
+}
+
+/// Keys identifying the generic password for `account`.
+fn item_query(account: &str) -> Vec<(&'static CFString, CFRetained<CFType>)> {
+    vec![
+        (unsafe { kSecClass }, unsafe { kSecClassGenericPassword }.into()),
+        (unsafe { kSecAttrService }, CFString::from_str(SERVICE).into()),
+        (unsafe { kSecAttrAccount }, CFString::from_str(account).into()),
+    ]
+}
+
+fn dictionary(
+    pairs: &[(&'static CFString, CFRetained<CFType>)],
+) -> CFRetained<CFDictionary<CFString, CFType>> {
+    let keys: Vec<&CFString> = pairs.iter().map(|(key, _)| *key).collect();
+    let values: Vec<&CFType> = pairs.iter().map(|(_, value)| &**value).collect();
+    CFDictionary::from_slices(&keys, &values)
+}
+
+fn check(status: i32) -> Result<(), String> {
+    if status == ERR_SEC_SUCCESS {
+        Ok(())
+    } else {
+        Err(format!("Keychain error {}", status))
+    }
+}
@@ -9,11 +9,23 @@ mod actions;
 mod border;
 mod editor;
 mod encoder;
+mod microphone;
 mod recording;
 mod screen;
 mod scroll;
 mod scroll_capture;
 mod stitch;
+mod diff;
+mod palette;
+mod prefs;
+mod shortcuts;
+mod pin;
+mod history;
+mod notify;
+mod upload;
+mod ocr;
+mod barcode;
+mod keychain;
 
 use objc2::runtime::ProtocolObject;
 use objc2_app_kit::{NSApplication, NSApplicationActivationPolicy, NSApplicationDelegate};
@@ -0,0 +1,129 @@
+use block2::RcBlock;
+use dispatch2::{DispatchQueue, DispatchRetained};
+use objc2::rc::Retained;
+use objc2::runtime::{Bool, NSObject, ProtocolObject};
+use objc2::{AllocAnyThread, DefinedClass, define_class, msg_send};
+use objc2_av_foundation::{
+    AVAssetWriterInput, AVAuthorizationStatus, AVCaptureAudioDataOutput,
+    AVCaptureAudioDataOutputSampleBufferDelegate, AVCaptureConnection, AVCaptureDevice,
+    AVCaptureDeviceInput, AVCaptureOutput, AVCaptureSession, AVMediaTypeAudio,
+};
+use objc2_core_media::{CMSampleBuffer, CMTime};
+use objc2_foundation::NSObjectProtocol;
+
+// --- Sample buffer delegate (called on the capture queue) ---
+
+pub struct MicrophoneDelegateIvars {
+    input: Retained<AVAssetWriterInput>,
+    clock_start: CMTime,
+}
+
+define_class!(
+    #[unsafe(super(NSObject))]
+    #[name = "MicrophoneDelegate"]
+    #[ivars = MicrophoneDelegateIvars]
+    pub struct MicrophoneDelegate;
+
+    unsafe impl NSObjectProtocol for MicrophoneDelegate {}
+
+    unsafe impl AVCaptureAudioDataOutputSampleBufferDelegate for MicrophoneDelegate {
+        #[unsafe(method(captureOutput:didOutputSampleBuffer:fromConnection:))]
+        fn capture_output(
+            &self,
+            _output: &AVCaptureOutput,
+            sample_buffer: &CMSampleBuffer,
+            _connection: &AVCaptureConnection,
+        ) {
+            let ivars = self.ivars();
+            crate::encoder::append_audio(&ivars.input, sample_buffer, ivars.clock_start);
+        }
+    }
+);
+
+impl MicrophoneDelegate {
+    fn new(input: Retained<AVAssetWriterInput>, clock_start: CMTime) -> Retained<Self> {
+        let this = Self::alloc().set_ivars(MicrophoneDelegateIvars { input, clock_start });
+        unsafe { msg_send![super(this), init] }
+    }
+}
+
+// --- Microphone capture session ---
+
+/// Feeds the default microphone into a recording's audio track.
+pub struct MicrophoneCapture {
+    session: Retained<AVCaptureSession>,
+    queue: DispatchRetained<DispatchQueue>,
+    _delegate: Retained<MicrophoneDelegate>,
+}
+
+impl MicrophoneCapture {
+    /// Start capturing into `input`, with timestamps offset by `clock_start`.
+    pub fn start(input: Retained<AVAssetWriterInput>, clock_start: CMTime) -> Result<Self, String> {
+        let media_type = unsafe { AVMediaTypeAudio.ok_or("AVMediaTypeAudio not available")? };
+        let device = unsafe { AVCaptureDevice::defaultDeviceWithMediaType(media_type) }
+            .ok_or("No microphone found")?;
+        let device_input = unsafe { AVCaptureDeviceInput::deviceInputWithDevice_error(&device) }
+            .map_err(|e| format!("Failed to open microphone: {}", e))?;
+
+        let session = unsafe { AVCaptureSession::new() };
+        if !unsafe { session.canAddInput(&device_input) } {
+            return Err("Cannot add microphone input".into());
+        }
+        unsafe { session.addInput(&device_input) };
+
+        let output = unsafe { AVCaptureAudioDataOutput::new() };
+        let delegate = MicrophoneDelegate::new(input, clock_start);
+        let queue = DispatchQueue::new("com.clipshot.microphone", None);
+        unsafe {
+            output.setSampleBufferDelegate_queue(
+                Some(ProtocolObject::from_ref(&*delegate)),
+                Some(&queue),
+            )
+        };
+        if !unsafe { session.canAddOutput(&output) } {
+            return Err("Cannot add audio output".into());
+        }
+        unsafe { session.addOutput(&output) };
+
+        unsafe { session.startRunning() };
+        eprintln!("Microphone capture started");
+
+        Ok(MicrophoneCapture {
+            session,
+            queue,
+            _delegate: delegate,
+        })
+    }
+
+    /// Stop capturing and wait for buffers already queued, so none are
+    /// appended after the encoder finishes.
+    pub fn stop(&self) {
+        unsafe { self.session.stopRunning() };
+        self.queue.exec_sync(|| {});
+        eprintln!("Microphone capture stopped");
+    }
+}
+
+/// Returns true if the user has granted microphone access.
+pub fn is_authorized() -> bool {
+    let Some(media_type) = (unsafe { AVMediaTypeAudio }) else {
+        return false;
+    };
+    let status = unsafe { AVCaptureDevice::authorizationStatusForMediaType(media_type) };
+    status == AVAuthorizationStatus::Authorized
+}
+
+/// Show the system microphone prompt if the user hasn't been asked yet.
+pub fn request_access() {
+    let Some(media_type) = (unsafe { AVMediaTypeAudio }) else {
+        return;
+    };
+    let status = unsafe { AVCaptureDevice::authorizationStatusForMediaType(media_type) };
+    if status != AVAuthorizationStatus::NotDetermined {
+        return;
+    }
+    let handler = RcBlock::new(|granted: Bool| {
+        eprintln!("Microphone access granted: {}", granted.as_bool());
+    });
+    unsafe { AVCaptureDevice::requestAccessForMediaType_completionHandler(media_type, &handler) };
+}
@@ -0,0 +1,47 @@
+// NSUserNotification is deprecated, but unlike UNUserNotificationCenter it
+// needs no authorization prompt and works from an unsigned build.
+#![allow(deprecated)]
+
+use std::path::{Path, PathBuf};
+
+use objc2::runtime::AnyObject;
+use objc2_foundation::{
+    ns_string, NSDictionary, NSString, NSUserNotification, NSUserNotificationCenter,
+};
+
+/// userInfo key holding the file a "Saved" banner is about.
+const PATH_KEY: &str = "path";
+
+/// Post a banner to Notification Center.
+pub fn show(title: &str, body: &str) {
+    let notification = NSUserNotification::new();
+    notification.setTitle(Some(&NSString::from_str(title)));
+    notification.setInformativeText(Some(&NSString::from_str(body)));
+    NSUserNotificationCenter::defaultUserNotificationCenter().deliverNotification(&notification);
+}
+
+/// Post a "Saved" banner for `path`. Clicking it reveals the file in Finder.
+pub fn saved(path: &Path) {
+    let name = path.file_name().map_or_else(
+        || path.display().to_string(),
+        |name| name.to_string_lossy().into_owned(),
+    );
+    let notification = NSUserNotification::new();
+    notification.setTitle(Some(ns_string!("Saved")));
+    notification.setInformativeText(Some(&NSString::from_str(&format!("Saved to {}", name))));
+    let path = NSString::from_str(&path.to_string_lossy());
+    let info = NSDictionary::<NSString, AnyObject>::from_slices(
+        &[&*NSString::from_str(PATH_KEY)],
+        &[&*path as &AnyObject],
+    );
+    unsafe { notification.setUserInfo(Some(&info)) };
+    NSUserNotificationCenter::defaultUserNotificationCenter().deliverNotification(&notification);
+}
+
+/// The file a banner posted by [`saved`] is about.
+pub fn saved_path(notification: &NSUserNotification) -> Option<PathBuf> {
+    let info = notification.userInfo()?;
+    let path = info.objectForKey(&NSString::from_str(PATH_KEY))?;
+    let path = path.downcast::<NSString>().ok()?;
+    Some(PathBuf::from(path.to_string()))
+}
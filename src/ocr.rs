@@ -0,0 +1,61 @@
+use objc2::msg_send;
+use objc2::rc::{Allocated, Retained};
+use objc2::runtime::{AnyClass, AnyObject};
+use objc2_core_graphics::CGImage;
+use objc2_foundation::{NSArray, NSDictionary, NSError, NSString};
+
+// Vision has no objc2 bindings here; its classes are looked up at runtime.
+#[link(name = "Vision", kind = "framework")]
+unsafe extern "C" {}
+
+/// VNRequestTextRecognitionLevelAccurate
+const RECOGNITION_LEVEL_ACCURATE: isize = 0;
+
+/// Recognize the text in `image` with Vision, one line per recognized line of text.
+/// Returns an empty string when the image has no text.
+pub fn recognize_text(image: &CGImage) -> Result<String, String> {
+    let request_class =
+        AnyClass::get(c"VNRecognizeTextRequest").ok_or("Text recognition is unavailable")?;
+    unsafe {
+        let request: Retained<AnyObject> = msg_send![request_class, new];
+        let _: () = msg_send![&request, setRecognitionLevel: RECOGNITION_LEVEL_ACCURATE];
+        let _: () = msg_send![&request, setUsesLanguageCorrection: true];
+
+        // Observations come back in reading order
+        let mut lines = Vec::new();
+        for observation in perform_request(&request, image)? {
+            let candidates: Retained<NSArray<AnyObject>> =
+                msg_send![&observation, topCandidates: 1usize];
+            if let Some(candidate) = candidates.firstObject() {
+                let text: Retained<NSString> = msg_send![&candidate, string];
+                lines.push(text.to_string());
+            }
+        }
+        Ok(lines.join("\n"))
+    }
+}
+
+/// Run a Vision `request` on `image`, returning its observations.
+///
+/// # Safety
+/// `request` must be a `VNImageBasedRequest`.
+pub(crate) unsafe fn perform_request(
+    request: &AnyObject,
+    image: &CGImage,
+) -> Result<Retained<NSArray<AnyObject>>, String> {
+    let handler_class =
+        AnyClass::get(c"VNImageRequestHandler").ok_or("The Vision framework is unavailable")?;
+    unsafe {
+        let options = NSDictionary::<NSString, AnyObject>::new();
+        let handler: Allocated<AnyObject> = msg_send![handler_class, alloc];
+        let handler: Option<Retained<AnyObject>> =
+            msg_send![handler, initWithCGImage: image, options: &*options];
+        let handler = handler.ok_or("Failed to read image")?;
+        let requests = NSArray::from_slice(&[request]);
+        let performed: Result<(), Retained<NSError>> =
+            msg_send![&handler, performRequests: &*requests, error: _];
+        performed.map_err(|e| e.localizedDescription().to_string())?;
+        let results: Option<Retained<NSArray<AnyObject>>> = msg_send![request, results];
+        Ok(results.unwrap_or_default())
+    }
+}
@@ -0,0 +1,146 @@
+use std::cell::RefCell;
+
+use objc2::rc::Retained;
+use objc2::{define_class, msg_send, DefinedClass, MainThreadOnly, Message};
+use objc2_app_kit::{
+    NSBackingStoreType, NSColor, NSCursor, NSEvent, NSTrackingArea, NSTrackingAreaOptions, NSView,
+    NSWindow, NSWindowStyleMask,
+};
+use objc2_core_foundation::{CGPoint, CGRect};
+use objc2_core_graphics::kCGOverlayWindowLevel;
+use objc2_foundation::{MainThreadMarker, NSRect};
+
+use super::view::OverlayView;
+
+pub struct OverlayMirrorViewIvars {
+    /// The overlay whose state this mirror shows and whose handlers receive its events.
+    source: Retained<OverlayView>,
+    tracking_area: RefCell<Option<Retained<NSTrackingArea>>>,
+}
+
+// Shows part of an overlay on another screen. Displays with separate Spaces only
+// show a window on one screen, so an overlay across all displays puts one of these
+// on each remaining screen. It draws and forwards mouse events to the source view,
+// which keeps the single selection in global coordinates.
+define_class!(
+    #[unsafe(super(NSView))]
+    #[thread_kind = MainThreadOnly]
+    #[name = "OverlayMirrorView"]
+    #[ivars = OverlayMirrorViewIvars]
+    pub struct OverlayMirrorView;
+
+    impl OverlayMirrorView {
+        #[unsafe(method(isFlipped))]
+        fn is_flipped(&self) -> bool {
+            true
+        }
+
+        #[unsafe(method(acceptsFirstMouse:))]
+        fn accepts_first_mouse(&self, _event: Option<&NSEvent>) -> bool {
+            true
+        }
+
+        #[unsafe(method(drawRect:))]
+        fn draw_rect(&self, dirty_rect: NSRect) {
+            // Same bounds as the source, so its drawing lands in place
+            let _: () = unsafe { msg_send![&*self.ivars().source, drawRect: dirty_rect] };
+        }
+
+        #[unsafe(method(mouseDown:))]
+        fn mouse_down(&self, event: &NSEvent) {
+            self.ivars().source.mouseDown(event);
+        }
+
+        #[unsafe(method(mouseDragged:))]
+        fn mouse_dragged(&self, event: &NSEvent) {
+            self.ivars().source.mouseDragged(event);
+        }
+
+        #[unsafe(method(mouseUp:))]
+        fn mouse_up(&self, event: &NSEvent) {
+            self.ivars().source.mouseUp(event);
+        }
+
+        #[unsafe(method(mouseMoved:))]
+        fn mouse_moved(&self, event: &NSEvent) {
+            self.ivars().source.mouseMoved(event);
+        }
+
+        #[unsafe(method(rightMouseDown:))]
+        fn right_mouse_down(&self, event: &NSEvent) {
+            self.ivars().source.rightMouseDown(event);
+        }
+
+        #[unsafe(method(resetCursorRects))]
+        fn reset_cursor_rects(&self) {
+            self.addCursorRect_cursor(self.bounds(), &NSCursor::crosshairCursor());
+        }
+
+        #[unsafe(method(updateTrackingAreas))]
+        fn update_tracking_areas(&self) {
+            if let Some(old_area) = self.ivars().tracking_area.borrow_mut().take() {
+                self.removeTrackingArea(&old_area);
+            }
+
+            let area = unsafe {
+                NSTrackingArea::initWithRect_options_owner_userInfo(
+                    MainThreadMarker::from(self).alloc(),
+                    self.bounds(),
+                    NSTrackingAreaOptions::MouseMoved | NSTrackingAreaOptions::ActiveAlways,
+                    Some(self),
+                    None,
+                )
+            };
+            self.addTrackingArea(&area);
+            *self.ivars().tracking_area.borrow_mut() = Some(area);
+        }
+    }
+);
+
+impl OverlayMirrorView {
+    /// Open a window over `screen_frame` that mirrors `source`, which spans
+    /// `overlay_frame` (both AppKit coordinates).
+    pub fn show_on_screen(
+        source: &OverlayView,
+        screen_frame: CGRect,
+        overlay_frame: CGRect,
+        mtm: MainThreadMarker,
+    ) -> (Retained<NSWindow>, Retained<Self>) {
+        // Borderless windows can't become key, so keys keep going to the source
+        let window = unsafe {
+            NSWindow::initWithContentRect_styleMask_backing_defer(
+                mtm.alloc(),
+                screen_frame,
+                NSWindowStyleMask::Borderless,
+                NSBackingStoreType::Buffered,
+                false,
+            )
+        };
+        window.setLevel(kCGOverlayWindowLevel as _);
+        window.setOpaque(false);
+        window.setBackgroundColor(Some(&NSColor::clearColor()));
+        window.setHasShadow(false);
+        unsafe { window.setReleasedWhenClosed(false) };
+
+        let this = mtm.alloc().set_ivars(OverlayMirrorViewIvars {
+            source: source.retain(),
+            tracking_area: RefCell::new(None),
+        });
+        let frame = offset_within(overlay_frame, screen_frame);
+        let view: Retained<Self> = unsafe { msg_send![super(this), initWithFrame: frame] };
+        if let Some(content) = window.contentView() {
+            content.addSubview(&view);
+        }
+        window.orderFront(None);
+        (window, view)
+    }
+}
+
+/// `frame` in the coordinates of a window whose frame is `window_frame`.
+pub fn offset_within(frame: CGRect, window_frame: CGRect) -> CGRect {
+    let origin = CGPoint::new(
+        frame.origin.x - window_frame.origin.x,
+        frame.origin.y - window_frame.origin.y,
+    );
+    CGRect::new(origin, frame.size)
+}
@@ -1,3 +1,4 @@
+pub mod mirror;
 pub mod view;
 pub mod window;
 
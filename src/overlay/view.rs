@@ -1,16 +1,38 @@
 use std::cell::{Cell, RefCell};
 
 use objc2::rc::Retained;
+use objc2::runtime::{AnyObject, Sel};
 use objc2::{define_class, msg_send, DefinedClass, MainThreadOnly};
 use objc2_app_kit::{
-    NSCursor, NSEvent, NSGraphicsContext, NSImage, NSTrackingArea,
-    NSTrackingAreaOptions, NSView,
+    NSCursor, NSEvent, NSFont, NSFontDescriptorSymbolicTraits, NSGraphicsContext, NSImage,
+    NSImageInterpolation, NSMenu, NSMenuItem, NSTrackingArea, NSTrackingAreaOptions, NSView,
+    NSWindow,
 };
 use objc2_core_foundation::{CGFloat, CGPoint, CGRect, CGSize};
-use objc2_core_graphics::CGContext;
-use objc2_foundation::{MainThreadMarker, NSRect};
+use objc2_core_graphics::{kCGPopUpMenuWindowLevel, CGContext};
+use objc2_foundation::{MainThreadMarker, NSRect, NSString};
 
-use crate::annotation::model::{Annotation, HandleKind};
+use crate::annotation::model::{
+    AlignmentGuide, Annotation, BlurStyle, HandleKind, SpotlightShape, StepShape, StrokeStyle,
+    TransformHistory, CALLOUT_PADDING, HIGHLIGHTER_WIDTH_SCALE, snap_to_guides,
+    text_background_for, text_font, union_rect,
+};
+use super::mirror::OverlayMirrorView;
+use crate::editor::view::{
+    apply_crop_resize, clamp_rect_to_bounds, constrain_to_aspect, CropAspect,
+};
+
+/// How close (in points) a dragged selection edge must come to a window edge to snap to it.
+const SNAP_DISTANCE: CGFloat = 8.0;
+
+/// Side length of the magnifier loupe, in points.
+const LOUPE_SIZE: CGFloat = 120.0;
+/// Magnification of the loupe (view points per screen point).
+const LOUPE_ZOOM: CGFloat = 8.0;
+/// Gap between the cursor and the loupe.
+const LOUPE_OFFSET: CGFloat = 24.0;
+/// Virtual key code of M, which toggles the loupe.
+const MAGNIFIER_KEY_CODE: u16 = 46;
 
 /// Tracks which part of the selection the user is interacting with.
 #[derive(Clone, Copy, PartialEq)]
@@ -29,18 +51,48 @@ pub enum DragMode {
 }
 
 /// Which annotation tool is currently active.
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, Copy, PartialEq, Debug)]
 pub enum ActiveTool {
     Select,
     Arrow,
+    Line,
     Rectangle,
     Ellipse,
     Pencil,
     Text,
     Highlight,
+    /// Freehand, translucent strokes multiplied onto the image.
+    Highlighter,
     Step,
     Blur,
+    Redact,
+    Spotlight,
+    Callout,
     Crop,
+    /// Samples a color from the image, then returns to the previous tool.
+    Eyedropper,
+}
+
+impl ActiveTool {
+    /// Every tool, in toolbar button order.
+    pub const ALL: [ActiveTool; 16] = [
+        ActiveTool::Select,
+        ActiveTool::Arrow,
+        ActiveTool::Line,
+        ActiveTool::Rectangle,
+        ActiveTool::Ellipse,
+        ActiveTool::Pencil,
+        ActiveTool::Text,
+        ActiveTool::Highlight,
+        ActiveTool::Highlighter,
+        ActiveTool::Step,
+        ActiveTool::Blur,
+        ActiveTool::Redact,
+        ActiveTool::Spotlight,
+        ActiveTool::Callout,
+        ActiveTool::Crop,
+        ActiveTool::Eyedropper,
+    ];
 }
 
 /// Tracks what the Select tool is currently dragging.
@@ -49,6 +101,8 @@ pub enum SelectDragMode {
     None,
     MovingAnnotation,
     ResizingHandle(HandleKind),
+    /// Dragging out a rectangle that selects every annotation it touches.
+    Marquee,
 }
 
 pub struct OverlayViewIvars {
@@ -69,20 +123,94 @@ pub struct OverlayViewIvars {
     pub text_view: RefCell<Option<Retained<NSView>>>,
     /// Position where the text tool was clicked
     pub text_position: Cell<CGPoint>,
+    /// Callout or Step whose text/number is being edited in the text field, with its
+    /// index once added (a new Callout has none). It isn't drawn while edited.
+    pub text_edit_target: RefCell<Option<(Option<usize>, Annotation)>>,
     /// Index of the currently selected annotation (for highlight + delete).
     pub active_annotation_index: Cell<Option<usize>>,
+    /// Annotations selected together by a marquee drag; moves, nudges, and Delete act on
+    /// all of them. `active_annotation_index` is None while this is non-empty.
+    pub selected_annotations: RefCell<Vec<usize>>,
+    /// Rectangle being dragged out by a marquee selection.
+    pub marquee_rect: Cell<Option<CGRect>>,
     /// What the Select tool is currently dragging.
     pub select_drag_mode: Cell<SelectDragMode>,
     /// Mouse position at the start of a select drag.
     pub select_drag_start: Cell<CGPoint>,
+    /// How far the mouse has moved past annotations held by an alignment guide.
+    pub move_residual: Cell<CGPoint>,
+    /// Guides shown while moving annotations that line up with others.
+    pub alignment_guides: RefCell<Vec<AlignmentGuide>>,
+    /// Spacing of the grid that annotations and the selection snap to, if any.
+    pub snap_grid: Cell<Option<CGFloat>>,
+    /// Whether annotations are held inside the selection while drawn and moved.
+    pub keep_inside_selection: Cell<bool>,
     /// Current stroke width for annotations.
     pub annotation_width: Cell<CGFloat>,
     /// Current font size for text annotations.
     pub annotation_font_size: Cell<CGFloat>,
+    /// Whether new rectangles and ellipses are filled.
+    pub annotation_fill: Cell<bool>,
+    /// Whether new arrows, lines, shapes, and pencil strokes get a drop shadow.
+    pub annotation_shadow: Cell<bool>,
+    /// Whether new pencil strokes are smoothed into curves.
+    pub annotation_smooth: Cell<bool>,
+    /// Whether new text labels get a contrasting background box.
+    pub annotation_text_background: Cell<bool>,
+    /// Whether new text labels are outlined in a contrasting color.
+    pub annotation_text_outline: Cell<bool>,
+    /// Font for new text labels (see `TEXT_FONTS`); None is the system font.
+    pub annotation_font_name: RefCell<Option<String>>,
+    /// Bold/italic styling for new text labels.
+    pub annotation_font_traits: Cell<NSFontDescriptorSymbolicTraits>,
+    /// Corner radius for new rectangles (0 = square corners).
+    pub annotation_corner_radius: Cell<CGFloat>,
+    /// Outline pattern for new rectangles, ellipses, and lines.
+    pub annotation_stroke_style: Cell<StrokeStyle>,
+    /// Opacity for new Highlight annotations.
+    pub annotation_highlight_opacity: Cell<CGFloat>,
+    /// Color for new Redact bars (black unless picked while the Redact tool is active).
+    pub annotation_redact_color: Cell<(CGFloat, CGFloat, CGFloat)>,
+    /// Undimmed region shape for new Spotlight annotations.
+    pub annotation_spotlight_shape: Cell<SpotlightShape>,
+    /// Pixelation block size for new Blur annotations.
+    pub annotation_block_size: Cell<usize>,
+    /// Pixelate or Gaussian for new Blur annotations.
+    pub annotation_blur_style: Cell<BlurStyle>,
+    /// Mouse-down point of the annotation being drawn.
+    pub annotation_anchor: Cell<CGPoint>,
     /// Next step number for the Step tool.
     pub next_step_number: Cell<u32>,
+    /// Marker shape for new Step annotations.
+    pub annotation_step_shape: Cell<StepShape>,
     /// Redo stack for undone annotations.
     pub redo_stack: RefCell<Vec<Annotation>>,
+    /// Undo and redo for annotation moves and resizes.
+    pub transform_history: RefCell<TransformHistory<Annotation>>,
+    /// Indices and shapes of the annotations being moved or resized, from before the drag.
+    pub transform_before: RefCell<Vec<(usize, Annotation)>>,
+    /// On-screen windows when the overlay was shown (window ID, frame in view
+    /// coordinates), frontmost first. Used for window picking and edge snapping.
+    pub window_frames: RefCell<Vec<(u32, CGRect)>>,
+    /// True while clicking a window captures it (instead of drawing a region).
+    pub picking_window: Cell<bool>,
+    /// Aspect ratio the selection is locked to while drawing and resizing.
+    pub selection_aspect: Cell<CropAspect>,
+    /// Whether annotation tools can draw before a selection exists, making the
+    /// whole screen the selection (screenshots that show the toolbar).
+    pub full_screen_canvas: Cell<bool>,
+    /// Last confirmed selection on this display (Cmd+R restores it).
+    pub last_selection: Cell<Option<CGRect>>,
+    /// Last known cursor position (None until the mouse is seen).
+    pub cursor_point: Cell<Option<CGPoint>>,
+    /// Whether the magnifier loupe follows the cursor while selecting (M toggles).
+    pub show_magnifier: Cell<bool>,
+    /// Whether full-screen guide lines cross at the cursor before a selection starts.
+    pub show_crosshair: Cell<bool>,
+    /// Window under the cursor while picking a window.
+    pub hovered_window: Cell<Option<(u32, CGRect)>>,
+    /// Views showing this overlay on the other screens it spans.
+    pub mirrors: RefCell<Vec<(Retained<NSWindow>, Retained<OverlayMirrorView>)>>,
 }
 
 define_class!(
@@ -113,6 +241,14 @@ define_class!(
             true
         }
 
+        #[unsafe(method(setNeedsDisplay:))]
+        fn set_needs_display(&self, needs_display: bool) {
+            let _: () = unsafe { msg_send![super(self), setNeedsDisplay: needs_display] };
+            for (_, mirror) in self.ivars().mirrors.borrow().iter() {
+                mirror.setNeedsDisplay(needs_display);
+            }
+        }
+
         #[unsafe(method(drawRect:))]
         fn draw_rect(&self, _dirty_rect: NSRect) {
             let Some(context) = NSGraphicsContext::currentContext() else {
@@ -130,10 +266,31 @@ define_class!(
             CGContext::set_rgb_fill_color(Some(&cg), 0.0, 0.0, 0.0, 0.5);
             CGContext::fill_rect(Some(&cg), bounds);
 
+            // Picking a window: undim and outline the window under the cursor
+            if self.ivars().picking_window.get() {
+                if let Some((_, frame)) = self.ivars().hovered_window.get() {
+                    CGContext::save_g_state(Some(&cg));
+                    CGContext::clip_to_rect(Some(&cg), frame);
+                    if let Some(ref screenshot) = *self.ivars().screenshot.borrow() {
+                        screenshot.drawInRect(bounds);
+                    }
+                    CGContext::restore_g_state(Some(&cg));
+
+                    CGContext::save_g_state(Some(&cg));
+                    CGContext::set_rgb_stroke_color(Some(&cg), 0.2, 0.6, 1.0, 1.0);
+                    CGContext::set_line_width(Some(&cg), 3.0);
+                    CGContext::stroke_rect(Some(&cg), frame);
+                    CGContext::restore_g_state(Some(&cg));
+                }
+                return;
+            }
+
             // If there's a selection, clear the overlay within it to show the bright image
             if let Some(sel_rect) = self.ivars().selection.get() {
                 let norm = normalize_rect(sel_rect);
                 if norm.size.width < 1.0 || norm.size.height < 1.0 {
+                    self.draw_crosshair_guides(&cg);
+                    self.draw_loupe(&context, &cg);
                     return;
                 }
 
@@ -154,21 +311,38 @@ define_class!(
                 }
                 CGContext::restore_g_state(Some(&cg));
 
+                if let (true, Some(spacing)) = (self.is_dragging(), self.ivars().snap_grid.get()) {
+                    draw_grid(&cg, norm, spacing);
+                }
+
                 // Draw annotations within the selection area
                 CGContext::save_g_state(Some(&cg));
                 CGContext::clip_to_rect(Some(&cg), norm);
                 let active_idx = self.ivars().active_annotation_index.get();
+                let group = self.ivars().selected_annotations.borrow();
+                let editing =
+                    self.ivars().text_edit_target.borrow().as_ref().and_then(|(i, _)| *i);
                 for (i, ann) in self.ivars().annotations.borrow().iter().enumerate() {
+                    if editing == Some(i) {
+                        continue;
+                    }
                     crate::annotation::renderer::draw_annotation(&cg, ann, None);
                     if active_idx == Some(i) {
                         draw_annotation_highlight(&cg, ann);
                         draw_annotation_handles(&cg, ann);
+                    } else if group.contains(&i) {
+                        draw_annotation_highlight(&cg, ann);
                     }
                 }
+                drop(group);
                 if let Some(ref ann) = *self.ivars().current_annotation.borrow() {
                     crate::annotation::renderer::draw_annotation(&cg, ann, None);
                 }
+                if let Some(marquee) = self.ivars().marquee_rect.get() {
+                    draw_marquee(&cg, marquee);
+                }
                 CGContext::restore_g_state(Some(&cg));
+                draw_alignment_guides(&cg, &self.ivars().alignment_guides.borrow());
 
                 // Draw selection border (dashed blue line)
                 CGContext::save_g_state(Some(&cg));
@@ -189,16 +363,36 @@ define_class!(
                 // Draw resize handles
                 self.draw_resize_handles(&cg, norm);
             }
+
+            self.draw_crosshair_guides(&cg);
+            self.draw_loupe(&context, &cg);
         }
 
         #[unsafe(method(mouseDown:))]
         fn mouse_down(&self, event: &NSEvent) {
             let point = self.convert_event_point(event);
+            self.ivars().cursor_point.set(Some(point));
+
+            // Picking a window: a click captures the window under the cursor
+            if self.ivars().picking_window.get() {
+                self.update_hovered_window(point);
+                if self.ivars().hovered_window.get().is_some() {
+                    self.notify_window_picked();
+                }
+                return;
+            }
+
             self.ivars().drag_start.set(point);
             self.commit_text_field();
 
             let active_tool = self.ivars().active_tool.get();
 
+            // Eyedropper: sample anywhere on the screenshot
+            if active_tool == ActiveTool::Eyedropper {
+                self.notify_sample_color(point);
+                return;
+            }
+
             // Select tool with existing selection: handle resize, move, or select/deselect
             if active_tool == ActiveTool::Select {
                 if self.ivars().selection.get().is_some() {
@@ -227,23 +421,61 @@ define_class!(
                     drop(annotations);
 
                     if let Some(idx) = hit_idx {
+                        // Dragging any member of a marquee selection moves the whole group
+                        if event.clickCount() < 2
+                            && self.ivars().selected_annotations.borrow().contains(&idx)
+                        {
+                            self.ivars().select_drag_mode.set(SelectDragMode::MovingAnnotation);
+                            self.ivars().select_drag_start.set(point);
+                            return;
+                        }
+                        self.ivars().selected_annotations.borrow_mut().clear();
                         // Double-click on Text annotation: re-edit it
                         if event.clickCount() >= 2 {
                             let annotations = self.ivars().annotations.borrow();
                             if let Some(ann) = annotations.get(idx) {
-                                if let Annotation::Text { position, text, color, font_size } = ann {
+                                if let Annotation::Text {
+                                    position,
+                                    text,
+                                    color,
+                                    font_size,
+                                    background,
+                                    outline,
+                                    font_name,
+                                    traits,
+                                } = ann
+                                {
                                     let pos = *position;
                                     let txt = text.clone();
                                     let clr = *color;
                                     let fs = *font_size;
+                                    let has_background = background.is_some();
+                                    let has_outline = *outline;
+                                    let name = font_name.clone();
+                                    let font_traits = *traits;
                                     drop(annotations);
                                     // Remove the annotation
                                     self.ivars().annotations.borrow_mut().remove(idx);
+                                    self.ivars().transform_history.borrow_mut().clear();
                                     self.ivars().active_annotation_index.set(None);
                                     // Show text field pre-filled
                                     self.ivars().annotation_color.set(clr);
                                     self.ivars().annotation_font_size.set(fs);
-                                    self.show_text_field_with_text(pos, &txt, fs);
+                                    self.ivars().annotation_text_background.set(has_background);
+                                    self.ivars().annotation_text_outline.set(has_outline);
+                                    self.ivars().annotation_font_traits.set(font_traits);
+                                    let font = text_font(name.as_deref(), fs, font_traits);
+                                    *self.ivars().annotation_font_name.borrow_mut() = name;
+                                    self.show_text_field_with_text(pos, &txt, &font);
+                                    self.setNeedsDisplay(true);
+                                    return;
+                                }
+                                // Double-click on a Callout or Step: edit its text/number in place
+                                if matches!(ann, Annotation::Callout { .. } | Annotation::Step { .. }) {
+                                    let target = ann.clone();
+                                    drop(annotations);
+                                    self.select_annotations(vec![idx]);
+                                    self.edit_annotation_text(target, Some(idx));
                                     self.setNeedsDisplay(true);
                                     return;
                                 }
@@ -254,9 +486,12 @@ define_class!(
                         self.ivars().select_drag_mode.set(SelectDragMode::MovingAnnotation);
                         self.ivars().select_drag_start.set(point);
                     } else {
-                        // Clicked empty space — deselect
+                        // Clicked empty space — deselect and start a marquee
                         self.ivars().active_annotation_index.set(None);
-                        self.ivars().select_drag_mode.set(SelectDragMode::None);
+                        self.ivars().selected_annotations.borrow_mut().clear();
+                        self.ivars().select_drag_mode.set(SelectDragMode::Marquee);
+                        self.ivars().select_drag_start.set(point);
+                        self.ivars().marquee_rect.set(Some(CGRect::new(point, CGSize::ZERO)));
                     }
                     self.setNeedsDisplay(true);
                     return;
@@ -265,10 +500,23 @@ define_class!(
 
             // Annotation tools: draw inside the selection
             if active_tool != ActiveTool::Select {
+                // No selection yet: the whole screen becomes the canvas
+                if self.ivars().full_screen_canvas.get()
+                    && active_tool != ActiveTool::Crop
+                    && self.ivars().selection.get().is_none()
+                {
+                    self.ivars().selection.set(Some(self.bounds()));
+                }
                 if let Some(sel_rect) = self.ivars().selection.get() {
                     let norm = normalize_rect(sel_rect);
                     if rect_contains(norm, point) {
-                        self.start_annotation(point);
+                        // Cmd+click with the Step tool restarts the sequence at 1
+                        if active_tool == ActiveTool::Step
+                            && event.modifierFlags().contains(objc2_app_kit::NSEventModifierFlags::Command)
+                        {
+                            self.ivars().next_step_number.set(1);
+                        }
+                        self.start_annotation(self.annotation_point(point));
                         return;
                     }
                 }
@@ -297,6 +545,10 @@ define_class!(
         #[unsafe(method(mouseDragged:))]
         fn mouse_dragged(&self, event: &NSEvent) {
             let point = self.convert_event_point(event);
+            self.ivars().cursor_point.set(Some(point));
+            if self.ivars().picking_window.get() {
+                return;
+            }
             let start = self.ivars().drag_start.get();
 
             let active_tool = self.ivars().active_tool.get();
@@ -306,13 +558,25 @@ define_class!(
                 let drag_mode = self.ivars().select_drag_mode.get();
                 match drag_mode {
                     SelectDragMode::MovingAnnotation => {
-                        if let Some(idx) = self.ivars().active_annotation_index.get() {
+                        let indices = self.selected_indices();
+                        if !indices.is_empty() {
+                            self.begin_transform(&indices);
                             let drag_start = self.ivars().select_drag_start.get();
-                            let dx = point.x - drag_start.x;
-                            let dy = point.y - drag_start.y;
+                            // Snap to the grid or other annotations unless Cmd is held
+                            let free = event
+                                .modifierFlags()
+                                .contains(objc2_app_kit::NSEventModifierFlags::Command);
+                            let (dx, dy) = self.snapped_move(
+                                &indices,
+                                point.x - drag_start.x,
+                                point.y - drag_start.y,
+                                free,
+                            );
                             let mut annotations = self.ivars().annotations.borrow_mut();
-                            if let Some(ann) = annotations.get_mut(idx) {
-                                ann.translate(dx, dy);
+                            for idx in indices {
+                                if let Some(ann) = annotations.get_mut(idx) {
+                                    ann.translate(dx, dy);
+                                }
                             }
                             drop(annotations);
                             self.ivars().select_drag_start.set(point);
@@ -322,29 +586,63 @@ define_class!(
                     }
                     SelectDragMode::ResizingHandle(handle) => {
                         if let Some(idx) = self.ivars().active_annotation_index.get() {
+                            self.begin_transform(&[idx]);
                             let mut annotations = self.ivars().annotations.borrow_mut();
                             if let Some(ann) = annotations.get_mut(idx) {
-                                ann.apply_resize(handle, point);
+                                ann.apply_resize(handle, self.annotation_point(point));
                             }
                             drop(annotations);
                             self.setNeedsDisplay(true);
                         }
                         return;
                     }
+                    SelectDragMode::Marquee => {
+                        let drag_start = self.ivars().select_drag_start.get();
+                        let marquee = CGRect::new(
+                            drag_start,
+                            CGSize::new(point.x - drag_start.x, point.y - drag_start.y),
+                        );
+                        self.ivars().marquee_rect.set(Some(marquee));
+                        let hits = crate::annotation::model::annotations_in_rect(
+                            self.ivars().annotations.borrow().iter().enumerate(),
+                            marquee,
+                        );
+                        *self.ivars().selected_annotations.borrow_mut() = hits;
+                        self.setNeedsDisplay(true);
+                        return;
+                    }
                     SelectDragMode::None => {}
                 }
             }
 
             if active_tool != ActiveTool::Select {
                 if let Some(ref mut ann) = *self.ivars().current_annotation.borrow_mut() {
-                    crate::annotation::model::update_annotation(ann, point);
+                    let flags = event.modifierFlags();
+                    let constrain = flags.contains(objc2_app_kit::NSEventModifierFlags::Shift);
+                    let from_center = flags.contains(objc2_app_kit::NSEventModifierFlags::Option);
+                    let anchor = self.ivars().annotation_anchor.get();
+                    let point = self.annotation_point(point);
+                    crate::annotation::model::update_annotation(ann, anchor, point, constrain, from_center);
                     self.setNeedsDisplay(true);
                     return;
                 }
             }
 
+            // Snap moving selection edges to window edges unless Cmd is held
+            let snap = !event
+                .modifierFlags()
+                .contains(objc2_app_kit::NSEventModifierFlags::Command);
+
             match self.ivars().drag_mode.get() {
                 DragMode::Creating => {
+                    let (start, mut point) = if snap {
+                        (self.snap_point(start), self.snap_point(point))
+                    } else {
+                        (start, point)
+                    };
+                    if let Some(ratio) = self.ivars().selection_aspect.get().ratio() {
+                        point = constrain_to_aspect(start, point, ratio);
+                    }
                     let rect = CGRect::new(
                         CGPoint::new(start.x.min(point.x), start.y.min(point.y)),
                         CGSize::new((point.x - start.x).abs(), (point.y - start.y).abs()),
@@ -355,16 +653,32 @@ define_class!(
                     if let Some(orig) = self.ivars().original_selection.get() {
                         let dx = point.x - start.x;
                         let dy = point.y - start.y;
-                        let moved = CGRect::new(
-                            CGPoint::new(orig.origin.x + dx, orig.origin.y + dy),
-                            orig.size,
-                        );
+                        let mut origin = CGPoint::new(orig.origin.x + dx, orig.origin.y + dy);
+                        if let (true, Some(spacing)) = (snap, self.ivars().snap_grid.get()) {
+                            origin = snap_to_grid(origin, spacing);
+                        }
+                        let moved = CGRect::new(origin, orig.size);
                         self.ivars().selection.set(Some(moved));
                     }
                 }
                 mode => {
                     if let Some(orig) = self.ivars().original_selection.get() {
-                        let resized = resize_rect(orig, mode, start, point);
+                        let ratio = self.ivars().selection_aspect.get().ratio();
+                        let resized = match (ratio, handle_for_drag_mode(mode)) {
+                            // Locked ratio: the opposite corner (or the edge's center) stays put
+                            (Some(ratio), Some(handle)) => {
+                                let point = if snap { self.snap_point(point) } else { point };
+                                apply_crop_resize(orig, handle, point, Some(ratio))
+                            }
+                            _ => {
+                                let resized = resize_rect(orig, mode, start, point);
+                                if snap {
+                                    self.snap_resized_edges(resized, mode)
+                                } else {
+                                    resized
+                                }
+                            }
+                        };
                         self.ivars().selection.set(Some(resized));
                     }
                 }
@@ -375,17 +689,33 @@ define_class!(
 
         #[unsafe(method(mouseUp:))]
         fn mouse_up(&self, _event: &NSEvent) {
+            if self.ivars().picking_window.get() {
+                return;
+            }
             let active_tool = self.ivars().active_tool.get();
 
-            // Reset select drag mode
+            // Reset select drag mode, keeping a finished move or resize for undo
             if active_tool == ActiveTool::Select {
-                self.ivars().select_drag_mode.set(SelectDragMode::None);
+                let drag_mode = self.ivars().select_drag_mode.replace(SelectDragMode::None);
+                if drag_mode == SelectDragMode::Marquee {
+                    self.finish_marquee();
+                }
+                self.ivars().move_residual.set(CGPoint::ZERO);
+                self.ivars().alignment_guides.borrow_mut().clear();
+                self.finish_transform();
             }
 
             if active_tool != ActiveTool::Select {
-                if let Some(ann) = self.ivars().current_annotation.borrow_mut().take() {
-                    self.ivars().redo_stack.borrow_mut().clear();
-                    self.ivars().annotations.borrow_mut().push(ann);
+                let finished = self.ivars().current_annotation.borrow_mut().take();
+                if let Some(ann) = finished {
+                    if matches!(ann, Annotation::Callout { .. }) {
+                        // The callout is added once its text is committed
+                        self.edit_annotation_text(ann, None);
+                    } else {
+                        self.ivars().redo_stack.borrow_mut().clear();
+                        self.ivars().transform_history.borrow_mut().clear_redo();
+                        self.ivars().annotations.borrow_mut().push(ann);
+                    }
                     self.setNeedsDisplay(true);
                 }
             }
@@ -397,10 +727,23 @@ define_class!(
 
             self.ivars().drag_mode.set(DragMode::None);
             self.ivars().original_selection.set(None);
+            // Hide the loupe now that the selection is made
+            self.setNeedsDisplay(true);
 
             self.notify_selection_changed();
         }
 
+        #[unsafe(method(mouseMoved:))]
+        fn mouse_moved(&self, event: &NSEvent) {
+            let point = self.convert_event_point(event);
+            self.ivars().cursor_point.set(Some(point));
+            if self.ivars().picking_window.get() {
+                self.update_hovered_window(point);
+            } else if self.loupe_visible() || self.crosshair_visible() {
+                self.setNeedsDisplay(true);
+            }
+        }
+
         #[unsafe(method(keyDown:))]
         fn key_down(&self, event: &NSEvent) {
             let key_code = event.keyCode();
@@ -411,6 +754,11 @@ define_class!(
 
             // Tool shortcuts (only when no text field active and no modifiers)
             if !has_modifiers && self.ivars().text_view.borrow().is_none() {
+                if key_code == MAGNIFIER_KEY_CODE {
+                    self.ivars().show_magnifier.set(!self.ivars().show_magnifier.get());
+                    self.setNeedsDisplay(true);
+                    return;
+                }
                 if let Some(tool) = tool_for_key(key_code) {
                     self.commit_text_field();
                     self.ivars().active_tool.set(tool);
@@ -422,6 +770,38 @@ define_class!(
                     self.notify_stroke_changed(sel_name);
                     return;
                 }
+                // Stroke style shortcut: D cycles solid/dashed/dotted
+                if key_code == 2 {
+                    self.notify_stroke_changed("cycleStrokeStyle:");
+                    return;
+                }
+            }
+
+            // Cmd+K = command palette (keyCode 40 = K)
+            if key_code == 40
+                && flags.contains(objc2_app_kit::NSEventModifierFlags::Command)
+                && self.ivars().text_view.borrow().is_none()
+            {
+                self.notify_show_command_palette();
+                return;
+            }
+
+            // Cmd+D = duplicate selected annotation (keyCode 2 = D)
+            if key_code == 2
+                && flags.contains(objc2_app_kit::NSEventModifierFlags::Command)
+                && self.ivars().text_view.borrow().is_none()
+            {
+                self.duplicate_selected();
+                return;
+            }
+
+            // Cmd+Shift+] = bring to front, Cmd+Shift+[ = send to back (keyCodes 30 / 33)
+            if (key_code == 30 || key_code == 33)
+                && flags.contains(objc2_app_kit::NSEventModifierFlags::Command)
+                && flags.contains(objc2_app_kit::NSEventModifierFlags::Shift)
+            {
+                self.reorder_selected(key_code == 30);
+                return;
             }
 
             // Escape = 53
@@ -430,16 +810,84 @@ define_class!(
                 return;
             }
 
-            // Delete (backspace=51, forward delete=117) -> delete selected annotation
-            if key_code == 51 || key_code == 117 {
-                if let Some(idx) = self.ivars().active_annotation_index.get() {
-                    let mut annotations = self.ivars().annotations.borrow_mut();
-                    if idx < annotations.len() {
-                        annotations.remove(idx);
-                    }
-                    drop(annotations);
-                    self.ivars().active_annotation_index.set(None);
+            // Return / keypad Enter = confirm the selection, like the toolbar's checkmark
+            if (key_code == 36 || key_code == 76) && self.ivars().text_view.borrow().is_none() {
+                let has_valid_selection = self.ivars().selection.get().is_some_and(|sel| {
+                    let norm = normalize_rect(sel);
+                    norm.size.width > 5.0 && norm.size.height > 5.0
+                });
+                if has_valid_selection {
+                    self.notify_confirm();
+                }
+                return;
+            }
+
+            // Cmd+A = select all annotations with the Select tool, or else the whole screen
+            // (keyCode 0 = A)
+            if key_code == 0
+                && flags.contains(objc2_app_kit::NSEventModifierFlags::Command)
+                && self.ivars().text_view.borrow().is_none()
+            {
+                if self.ivars().active_tool.get() == ActiveTool::Select
+                    && self.ivars().selection.get().is_some()
+                    && !self.ivars().annotations.borrow().is_empty()
+                {
+                    self.select_all_annotations();
+                    return;
+                }
+                self.ivars().picking_window.set(false);
+                self.ivars().selection.set(Some(self.bounds()));
+                self.setNeedsDisplay(true);
+                self.notify_selection_changed();
+                return;
+            }
+
+            // Cmd+R = restore the last confirmed selection (keyCode 15 = R)
+            if key_code == 15
+                && flags.contains(objc2_app_kit::NSEventModifierFlags::Command)
+                && self.ivars().text_view.borrow().is_none()
+            {
+                if let Some(rect) = self.ivars().last_selection.get() {
+                    self.ivars().picking_window.set(false);
+                    self.ivars().selection.set(Some(clamp_rect_to_bounds(rect, self.bounds())));
                     self.setNeedsDisplay(true);
+                    self.notify_selection_changed();
+                }
+                return;
+            }
+
+            // Cmd+Shift+Delete = clear all annotations (the delegate asks first)
+            if (key_code == 51 || key_code == 117)
+                && flags.contains(objc2_app_kit::NSEventModifierFlags::Command)
+                && flags.contains(objc2_app_kit::NSEventModifierFlags::Shift)
+            {
+                self.notify_clear_annotations();
+                return;
+            }
+
+            // Delete (backspace=51, forward delete=117) -> delete selected annotations
+            if (key_code == 51 || key_code == 117) && self.delete_selected() {
+                return;
+            }
+
+            // Arrow keys -> nudge selected annotations (Shift = 10 points)
+            if !has_modifiers && self.ivars().text_view.borrow().is_none() {
+                let shift = flags.contains(objc2_app_kit::NSEventModifierFlags::Shift);
+                if let Some((dx, dy)) = nudge_for_key(key_code, shift) {
+                    let indices = self.selected_indices();
+                    if !indices.is_empty() {
+                        let (dx, dy) = self.clamp_move(&indices, dx, dy);
+                        self.begin_transform(&indices);
+                        let mut annotations = self.ivars().annotations.borrow_mut();
+                        for idx in indices {
+                            if let Some(ann) = annotations.get_mut(idx) {
+                                ann.translate(dx, dy);
+                            }
+                        }
+                        drop(annotations);
+                        self.finish_transform();
+                        self.setNeedsDisplay(true);
+                    }
                     return;
                 }
             }
@@ -449,10 +897,7 @@ define_class!(
                 && flags.contains(objc2_app_kit::NSEventModifierFlags::Command)
                 && flags.contains(objc2_app_kit::NSEventModifierFlags::Shift)
             {
-                if let Some(ann) = self.ivars().redo_stack.borrow_mut().pop() {
-                    self.ivars().annotations.borrow_mut().push(ann);
-                    self.setNeedsDisplay(true);
-                }
+                self.redo();
                 return;
             }
 
@@ -460,13 +905,91 @@ define_class!(
             if key_code == 6
                 && flags.contains(objc2_app_kit::NSEventModifierFlags::Command)
             {
-                if let Some(ann) = self.ivars().annotations.borrow_mut().pop() {
-                    self.ivars().redo_stack.borrow_mut().push(ann);
-                }
-                self.setNeedsDisplay(true);
+                self.undo();
             }
         }
 
+        /// Right-click opens a menu for the annotation under the cursor, or for the canvas.
+        #[unsafe(method(rightMouseDown:))]
+        fn right_mouse_down(&self, event: &NSEvent) {
+            if self.ivars().selection.get().is_none() || self.ivars().picking_window.get() {
+                return;
+            }
+            self.commit_text_field();
+            let point = self.convert_event_point(event);
+            let hit = self.ivars().annotations.borrow().iter().rposition(|ann| ann.hit_test(point));
+            let has_annotations = !self.ivars().annotations.borrow().is_empty();
+            let mtm = MainThreadMarker::from(self);
+
+            let menu = if let Some(idx) = hit {
+                // Keep a marquee group when right-clicking one of its members
+                if !self.selected_indices().contains(&idx) {
+                    self.select_annotations(vec![idx]);
+                }
+                let mut items = vec![("Delete", "deleteSelectedAnnotations:")];
+                if self.selected_indices().len() == 1 {
+                    items.extend([
+                        ("Duplicate", "duplicateSelectedAnnotation:"),
+                        ("Bring to Front", "bringSelectedToFront:"),
+                        ("Send to Back", "sendSelectedToBack:"),
+                    ]);
+                }
+                context_menu(mtm, &items, self)
+            } else {
+                let app = objc2_app_kit::NSApplication::sharedApplication(mtm);
+                let Some(delegate) = app.delegate() else {
+                    return;
+                };
+                let mut items = vec![
+                    ("Copy Text", "actionCopyText:"),
+                    ("Decode QR Code", "actionDecodeBarcode:"),
+                ];
+                if has_annotations {
+                    items.extend([
+                        ("Select All", "actionSelectAllAnnotations:"),
+                        ("Clear All\u{2026}", "actionClearAnnotations:"),
+                    ]);
+                }
+                context_menu(mtm, &items, (*delegate).as_ref())
+            };
+
+            // Open in the window that was clicked, which may be a mirror's
+            let mirror = self.ivars().mirrors.borrow().iter().find_map(|(window, view)| {
+                (event.window(mtm).as_deref() == Some(&**window)).then(|| view.clone())
+            });
+            let anchor: &NSView = mirror.as_deref().map_or(self, |view| view);
+
+            // Menus open at the pop-up menu level, below this overlay; drop beneath
+            // them until the menu closes (popUpContextMenu returns then)
+            let Some(window) = anchor.window() else {
+                return;
+            };
+            let level = window.level();
+            window.setLevel(kCGPopUpMenuWindowLevel as isize - 1);
+            NSMenu::popUpContextMenu_withEvent_forView(&menu, event, anchor);
+            window.setLevel(level);
+        }
+
+        #[unsafe(method(deleteSelectedAnnotations:))]
+        fn delete_selected_action(&self, _sender: &AnyObject) {
+            self.delete_selected();
+        }
+
+        #[unsafe(method(duplicateSelectedAnnotation:))]
+        fn duplicate_selected_action(&self, _sender: &AnyObject) {
+            self.duplicate_selected();
+        }
+
+        #[unsafe(method(bringSelectedToFront:))]
+        fn bring_selected_to_front(&self, _sender: &AnyObject) {
+            self.reorder_selected(true);
+        }
+
+        #[unsafe(method(sendSelectedToBack:))]
+        fn send_selected_to_back(&self, _sender: &AnyObject) {
+            self.reorder_selected(false);
+        }
+
         /// Called by AnnotationTextView when Enter is pressed.
         #[unsafe(method(commitTextInput))]
         fn commit_text_input_action(&self) {
@@ -476,9 +999,11 @@ define_class!(
         /// Called by AnnotationTextView when Escape is pressed.
         #[unsafe(method(cancelTextInput))]
         fn cancel_text_input_action(&self) {
-            // Discard text input without committing
+            // Discard text input without committing; an edited Callout or Step keeps
+            // its original text
             let container = self.ivars().text_container.borrow_mut().take();
             let _view = self.ivars().text_view.borrow_mut().take();
+            self.ivars().text_edit_target.borrow_mut().take();
             if let Some(container) = container {
                 container.removeFromSuperview();
             }
@@ -535,13 +1060,48 @@ impl OverlayView {
             text_container: RefCell::new(None),
             text_view: RefCell::new(None),
             text_position: Cell::new(CGPoint::ZERO),
+            text_edit_target: RefCell::new(None),
             active_annotation_index: Cell::new(None),
+            selected_annotations: RefCell::new(Vec::new()),
+            marquee_rect: Cell::new(None),
             select_drag_mode: Cell::new(SelectDragMode::None),
             select_drag_start: Cell::new(CGPoint::ZERO),
+            move_residual: Cell::new(CGPoint::ZERO),
+            alignment_guides: RefCell::new(Vec::new()),
+            snap_grid: Cell::new(None),
+            keep_inside_selection: Cell::new(false),
             annotation_width: Cell::new(3.0),
             annotation_font_size: Cell::new(18.0),
+            annotation_fill: Cell::new(false),
+            annotation_shadow: Cell::new(false),
+            annotation_smooth: Cell::new(true),
+            annotation_text_background: Cell::new(false),
+            annotation_text_outline: Cell::new(false),
+            annotation_font_name: RefCell::new(None),
+            annotation_font_traits: Cell::new(NSFontDescriptorSymbolicTraits::empty()),
+            annotation_corner_radius: Cell::new(0.0),
+            annotation_stroke_style: Cell::new(StrokeStyle::Solid),
+            annotation_highlight_opacity: Cell::new(0.35),
+            annotation_redact_color: Cell::new((0.0, 0.0, 0.0)),
+            annotation_spotlight_shape: Cell::new(SpotlightShape::Rect),
+            annotation_block_size: Cell::new(10),
+            annotation_blur_style: Cell::new(BlurStyle::Pixelate),
+            annotation_anchor: Cell::new(CGPoint::ZERO),
             next_step_number: Cell::new(1),
+            annotation_step_shape: Cell::new(StepShape::Circle),
             redo_stack: RefCell::new(Vec::new()),
+            transform_history: RefCell::new(TransformHistory::new()),
+            transform_before: RefCell::new(Vec::new()),
+            window_frames: RefCell::new(Vec::new()),
+            picking_window: Cell::new(false),
+            selection_aspect: Cell::new(CropAspect::Free),
+            full_screen_canvas: Cell::new(false),
+            last_selection: Cell::new(None),
+            cursor_point: Cell::new(None),
+            show_magnifier: Cell::new(true),
+            show_crosshair: Cell::new(false),
+            hovered_window: Cell::new(None),
+            mirrors: RefCell::new(Vec::new()),
         });
         let view: Retained<Self> = unsafe { msg_send![super(this), initWithFrame: frame] };
         view
@@ -558,15 +1118,463 @@ impl OverlayView {
         self.ivars().original_selection.set(None);
         self.ivars().active_tool.set(ActiveTool::Select);
         self.ivars().annotations.borrow_mut().clear();
+        self.ivars().transform_history.borrow_mut().clear();
         *self.ivars().current_annotation.borrow_mut() = None;
         self.ivars().active_annotation_index.set(None);
+        self.ivars().selected_annotations.borrow_mut().clear();
+        self.ivars().marquee_rect.set(None);
         self.ivars().select_drag_mode.set(SelectDragMode::None);
+        self.ivars().picking_window.set(false);
+        self.ivars().hovered_window.set(None);
         self.commit_text_field();
         self.setNeedsDisplay(true);
     }
 
+    /// Undo the newest move or resize, or else remove the newest annotation.
+    pub fn undo(&self) {
+        let mut annotations = self.ivars().annotations.borrow_mut();
+        let reverted = self.ivars().transform_history.borrow_mut().undo(&mut annotations, |a| a);
+        if reverted.is_none()
+            && let Some(ann) = annotations.pop()
+        {
+            self.ivars().redo_stack.borrow_mut().push(ann);
+        }
+        self.ivars().selected_annotations.borrow_mut().retain(|&i| i < annotations.len());
+        drop(annotations);
+        self.setNeedsDisplay(true);
+    }
+
+    /// Redo the newest undone move or resize, or else restore the last removed annotation.
+    pub fn redo(&self) {
+        let mut annotations = self.ivars().annotations.borrow_mut();
+        let restored = self.ivars().transform_history.borrow_mut().redo(&mut annotations, |a| a);
+        if restored.is_none()
+            && let Some(ann) = self.ivars().redo_stack.borrow_mut().pop()
+        {
+            annotations.push(ann);
+        }
+        drop(annotations);
+        self.setNeedsDisplay(true);
+    }
+
+    /// Annotations the Select tool acts on: the marquee group, or else the active one.
+    fn selected_indices(&self) -> Vec<usize> {
+        let group = self.ivars().selected_annotations.borrow();
+        if group.is_empty() {
+            self.ivars().active_annotation_index.get().into_iter().collect()
+        } else {
+            group.clone()
+        }
+    }
+
+    /// End a marquee drag, selecting what it caught.
+    fn finish_marquee(&self) {
+        self.ivars().marquee_rect.set(None);
+        let caught = self.ivars().selected_annotations.take();
+        self.select_annotations(caught);
+    }
+
+    /// Select several annotations at once; a single one becomes the active annotation.
+    fn select_annotations(&self, mut indices: Vec<usize>) {
+        if indices.len() == 1 {
+            self.ivars().active_annotation_index.set(indices.pop());
+        } else {
+            self.ivars().active_annotation_index.set(None);
+        }
+        *self.ivars().selected_annotations.borrow_mut() = indices;
+        self.setNeedsDisplay(true);
+    }
+
+    /// Delete the selected annotations. Returns false if nothing was selected.
+    fn delete_selected(&self) -> bool {
+        let indices = self.selected_indices();
+        if indices.is_empty() {
+            return false;
+        }
+        let mut annotations = self.ivars().annotations.borrow_mut();
+        crate::annotation::model::remove_indices(&mut annotations, &indices);
+        drop(annotations);
+        self.ivars().transform_history.borrow_mut().clear();
+        self.ivars().active_annotation_index.set(None);
+        self.ivars().selected_annotations.borrow_mut().clear();
+        self.setNeedsDisplay(true);
+        true
+    }
+
+    /// Copy the selected annotation, offset slightly, and select the copy.
+    fn duplicate_selected(&self) {
+        let Some(idx) = self.ivars().active_annotation_index.get() else {
+            return;
+        };
+        let copy = self.ivars().annotations.borrow().get(idx).cloned();
+        if let Some(mut copy) = copy {
+            let offset = crate::annotation::model::DUPLICATE_OFFSET;
+            copy.translate(offset, offset);
+            let mut annotations = self.ivars().annotations.borrow_mut();
+            annotations.push(copy);
+            self.ivars().active_annotation_index.set(Some(annotations.len() - 1));
+            drop(annotations);
+            self.ivars().redo_stack.borrow_mut().clear();
+            self.ivars().transform_history.borrow_mut().clear_redo();
+            self.setNeedsDisplay(true);
+        }
+    }
+
+    /// Move the selected annotation to the front (drawn last) or to the back.
+    fn reorder_selected(&self, to_front: bool) {
+        let Some(idx) = self.ivars().active_annotation_index.get() else {
+            return;
+        };
+        let mut annotations = self.ivars().annotations.borrow_mut();
+        let new_idx = crate::annotation::model::reorder(&mut annotations, idx, to_front);
+        drop(annotations);
+        if new_idx.is_some() {
+            self.ivars().transform_history.borrow_mut().clear();
+            self.ivars().active_annotation_index.set(new_idx);
+            self.setNeedsDisplay(true);
+        }
+    }
+
+    /// Select every annotation.
+    pub fn select_all_annotations(&self) {
+        let count = self.ivars().annotations.borrow().len();
+        self.select_annotations((0..count).collect());
+    }
+
+    /// Remove every annotation. Not undoable, so callers confirm first.
+    pub fn clear_annotations(&self) {
+        self.ivars().annotations.borrow_mut().clear();
+        self.ivars().redo_stack.borrow_mut().clear();
+        self.ivars().transform_history.borrow_mut().clear();
+        self.ivars().active_annotation_index.set(None);
+        self.ivars().selected_annotations.borrow_mut().clear();
+        self.setNeedsDisplay(true);
+    }
+
+    /// Remember the annotations at `indices` as they are before a move or resize
+    /// (once per drag).
+    fn begin_transform(&self, indices: &[usize]) {
+        let mut before = self.ivars().transform_before.borrow_mut();
+        if before.is_empty() {
+            let annotations = self.ivars().annotations.borrow();
+            *before = indices
+                .iter()
+                .filter_map(|&i| annotations.get(i).map(|a| (i, a.clone())))
+                .collect();
+        }
+    }
+
+    /// Record the move or resize started by `begin_transform` for undo.
+    fn finish_transform(&self) {
+        let before = self.ivars().transform_before.take();
+        let count = self.ivars().annotations.borrow().len();
+        self.ivars().transform_history.borrow_mut().record(before, count);
+    }
+
+    /// Cache the on-screen windows (ID, frame in view coordinates, frontmost first).
+    pub fn set_window_frames(&self, windows: Vec<(u32, CGRect)>) {
+        *self.ivars().window_frames.borrow_mut() = windows;
+    }
+
+    /// Switch to picking a window instead of drawing a region.
+    pub fn pick_window(&self) {
+        self.ivars().picking_window.set(true);
+        // Highlight the window under the cursor before the mouse moves
+        if let Some(point) = self.ivars().cursor_point.get() {
+            self.update_hovered_window(point);
+        }
+    }
+
+    /// Read the cursor position from the window (before any mouse event arrives).
+    pub fn sync_cursor_point(&self) {
+        if let Some(window) = self.window() {
+            let location = window.mouseLocationOutsideOfEventStream();
+            self.ivars().cursor_point.set(Some(self.convertPoint_fromView(location, None)));
+            self.setNeedsDisplay(true);
+        }
+    }
+
+    /// The loupe shows while a selection is being drawn or resized (or before
+    /// there is one), unless turned off.
+    fn loupe_visible(&self) -> bool {
+        if !self.ivars().show_magnifier.get() || self.ivars().picking_window.get() {
+            return false;
+        }
+        match self.ivars().drag_mode.get() {
+            DragMode::None => self.ivars().selection.get().is_none(),
+            DragMode::Moving => false,
+            _ => true,
+        }
+    }
+
+    /// Guide lines show while positioning the start of a selection, if turned on.
+    fn crosshair_visible(&self) -> bool {
+        self.ivars().show_crosshair.get()
+            && !self.ivars().picking_window.get()
+            && self.ivars().drag_mode.get() == DragMode::None
+            && self.ivars().selection.get().is_none()
+    }
+
+    /// Draw thin lines across the whole overlay through the cursor.
+    fn draw_crosshair_guides(&self, cg: &CGContext) {
+        if !self.crosshair_visible() {
+            return;
+        }
+        let Some(cursor) = self.ivars().cursor_point.get() else {
+            return;
+        };
+        let bounds = self.bounds();
+        // Centered on a point so the 1-point lines stay crisp
+        let x = cursor.x.floor() + 0.5;
+        let y = cursor.y.floor() + 0.5;
+        CGContext::save_g_state(Some(cg));
+        CGContext::set_rgb_stroke_color(Some(cg), 0.2, 0.6, 1.0, 0.8);
+        CGContext::set_line_width(Some(cg), 1.0);
+        CGContext::move_to_point(Some(cg), bounds.origin.x, y);
+        CGContext::add_line_to_point(Some(cg), bounds.origin.x + bounds.size.width, y);
+        CGContext::move_to_point(Some(cg), x, bounds.origin.y);
+        CGContext::add_line_to_point(Some(cg), x, bounds.origin.y + bounds.size.height);
+        CGContext::stroke_path(Some(cg));
+        CGContext::restore_g_state(Some(cg));
+    }
+
+    /// Draw the magnifier loupe next to the cursor: the screenshot pixels around
+    /// it, enlarged without smoothing, with the pixel under the cursor outlined.
+    fn draw_loupe(&self, context: &NSGraphicsContext, cg: &CGContext) {
+        if !self.loupe_visible() {
+            return;
+        }
+        let Some(cursor) = self.ivars().cursor_point.get() else {
+            return;
+        };
+        let screenshot = self.ivars().screenshot.borrow();
+        let Some(screenshot) = screenshot.as_ref() else {
+            return;
+        };
+        let bounds = self.bounds();
+
+        // Below-right of the cursor, flipped to the other side near the screen edges
+        let mut x = cursor.x + LOUPE_OFFSET;
+        if x + LOUPE_SIZE > bounds.size.width {
+            x = cursor.x - LOUPE_OFFSET - LOUPE_SIZE;
+        }
+        let mut y = cursor.y + LOUPE_OFFSET;
+        if y + LOUPE_SIZE > bounds.size.height {
+            y = cursor.y - LOUPE_OFFSET - LOUPE_SIZE;
+        }
+        let loupe = CGRect::new(CGPoint::new(x, y), CGSize::new(LOUPE_SIZE, LOUPE_SIZE));
+        let center = CGPoint::new(x + LOUPE_SIZE / 2.0, y + LOUPE_SIZE / 2.0);
+
+        // Center the loupe on the middle of the screen pixel under the cursor
+        let scale = self.ivars().scale_factor.get().max(1.0);
+        let pixel_center = CGPoint::new(
+            ((cursor.x * scale).floor() + 0.5) / scale,
+            ((cursor.y * scale).floor() + 0.5) / scale,
+        );
+
+        CGContext::save_g_state(Some(cg));
+        CGContext::clip_to_rect(Some(cg), loupe);
+        CGContext::set_rgb_fill_color(Some(cg), 0.0, 0.0, 0.0, 1.0);
+        CGContext::fill_rect(Some(cg), loupe);
+        CGContext::translate_ctm(Some(cg), center.x, center.y);
+        CGContext::scale_ctm(Some(cg), LOUPE_ZOOM, LOUPE_ZOOM);
+        CGContext::translate_ctm(Some(cg), -pixel_center.x, -pixel_center.y);
+        let interpolation = context.imageInterpolation();
+        context.setImageInterpolation(NSImageInterpolation::None);
+        screenshot.drawInRect(bounds);
+        context.setImageInterpolation(interpolation);
+        CGContext::restore_g_state(Some(cg));
+
+        // Crosshair through the center, boxing the pixel under the cursor
+        let pixel = LOUPE_ZOOM / scale;
+        let pixel_box = CGRect::new(
+            CGPoint::new(center.x - pixel / 2.0, center.y - pixel / 2.0),
+            CGSize::new(pixel, pixel),
+        );
+        CGContext::save_g_state(Some(cg));
+        CGContext::clip_to_rect(Some(cg), loupe);
+        CGContext::set_rgb_stroke_color(Some(cg), 0.2, 0.6, 1.0, 0.6);
+        CGContext::set_line_width(Some(cg), 1.0);
+        CGContext::move_to_point(Some(cg), x, center.y);
+        CGContext::add_line_to_point(Some(cg), pixel_box.origin.x, center.y);
+        CGContext::move_to_point(Some(cg), pixel_box.origin.x + pixel, center.y);
+        CGContext::add_line_to_point(Some(cg), x + LOUPE_SIZE, center.y);
+        CGContext::move_to_point(Some(cg), center.x, y);
+        CGContext::add_line_to_point(Some(cg), center.x, pixel_box.origin.y);
+        CGContext::move_to_point(Some(cg), center.x, pixel_box.origin.y + pixel);
+        CGContext::add_line_to_point(Some(cg), center.x, y + LOUPE_SIZE);
+        CGContext::stroke_path(Some(cg));
+        CGContext::set_rgb_stroke_color(Some(cg), 0.2, 0.6, 1.0, 1.0);
+        CGContext::stroke_rect(Some(cg), pixel_box);
+        CGContext::restore_g_state(Some(cg));
+
+        // Border
+        CGContext::save_g_state(Some(cg));
+        CGContext::set_rgb_stroke_color(Some(cg), 1.0, 1.0, 1.0, 1.0);
+        CGContext::set_line_width(Some(cg), 2.0);
+        CGContext::stroke_rect(Some(cg), loupe);
+        CGContext::restore_g_state(Some(cg));
+    }
+
+    /// Highlight the frontmost window containing `point`.
+    fn update_hovered_window(&self, point: CGPoint) {
+        let hovered = self
+            .ivars()
+            .window_frames
+            .borrow()
+            .iter()
+            .copied()
+            .find(|&(_, frame)| rect_contains(frame, point));
+        if hovered.map(|(id, _)| id) != self.ivars().hovered_window.get().map(|(id, _)| id) {
+            self.ivars().hovered_window.set(hovered);
+            self.setNeedsDisplay(true);
+        }
+    }
+
+    /// Snap `point` to the grid when one is on, or else to the nearest window edge
+    /// within `SNAP_DISTANCE`, per axis.
+    fn snap_point(&self, point: CGPoint) -> CGPoint {
+        if let Some(spacing) = self.ivars().snap_grid.get() {
+            return snap_to_grid(point, spacing);
+        }
+        let frames = self.ivars().window_frames.borrow();
+        let x_edges = frames.iter().flat_map(|(_, f)| [f.origin.x, f.origin.x + f.size.width]);
+        let y_edges = frames.iter().flat_map(|(_, f)| [f.origin.y, f.origin.y + f.size.height]);
+        CGPoint::new(snap_to_edges(point.x, x_edges), snap_to_edges(point.y, y_edges))
+    }
+
+    /// Snap the edges of `rect` that the resize `mode` moves to nearby window edges.
+    fn snap_resized_edges(&self, rect: CGRect, mode: DragMode) -> CGRect {
+        let (left, top, right, bottom) = match mode {
+            DragMode::ResizeTopLeft => (true, true, false, false),
+            DragMode::ResizeTopRight => (false, true, true, false),
+            DragMode::ResizeBottomLeft => (true, false, false, true),
+            DragMode::ResizeBottomRight => (false, false, true, true),
+            DragMode::ResizeTop => (false, true, false, false),
+            DragMode::ResizeBottom => (false, false, false, true),
+            DragMode::ResizeLeft => (true, false, false, false),
+            DragMode::ResizeRight => (false, false, true, false),
+            _ => return rect,
+        };
+        let mut x0 = rect.origin.x;
+        let mut y0 = rect.origin.y;
+        let mut x1 = x0 + rect.size.width;
+        let mut y1 = y0 + rect.size.height;
+        let corner = CGPoint::new(if left { x0 } else { x1 }, if top { y0 } else { y1 });
+        let snapped = self.snap_point(corner);
+        if left {
+            x0 = snapped.x;
+        } else if right {
+            x1 = snapped.x;
+        }
+        if top {
+            y0 = snapped.y;
+        } else if bottom {
+            y1 = snapped.y;
+        }
+        CGRect::new(CGPoint::new(x0, y0), CGSize::new(x1 - x0, y1 - y0))
+    }
+
+    /// Whether the mouse is drawing or moving an annotation or the selection.
+    fn is_dragging(&self) -> bool {
+        self.ivars().current_annotation.borrow().is_some()
+            || self.ivars().select_drag_mode.get() != SelectDragMode::None
+            || self.ivars().drag_mode.get() != DragMode::None
+    }
+
+    /// `point` on the grid, when one is on and the tool isn't freehand.
+    fn grid_point(&self, point: CGPoint) -> CGPoint {
+        let freehand = matches!(
+            self.ivars().active_tool.get(),
+            ActiveTool::Pencil | ActiveTool::Highlighter
+        );
+        match self.ivars().snap_grid.get() {
+            Some(spacing) if !freehand => snap_to_grid(point, spacing),
+            _ => point,
+        }
+    }
+
+    /// Adjust a move of the annotations at `indices` by (dx, dy) so they step along
+    /// the grid, or else line up with other annotations, unless `free`. The mouse
+    /// can run ahead of annotations held in place; that gap carries to the next move.
+    fn snapped_move(
+        &self,
+        indices: &[usize],
+        dx: CGFloat,
+        dy: CGFloat,
+        free: bool,
+    ) -> (CGFloat, CGFloat) {
+        let residual = self.ivars().move_residual.get();
+        let (dx, dy) = (dx + residual.x, dy + residual.y);
+        let canvas = self.ivars().selection.get().map(normalize_rect);
+        let (offset, guides) = match (free, self.ivars().snap_grid.get(), canvas) {
+            (true, _, _) | (false, None, None) => (CGPoint::ZERO, Vec::new()),
+            (false, Some(spacing), _) => (grid_offset(dx, dy, spacing), Vec::new()),
+            (false, None, Some(canvas)) => snap_to_guides(
+                self.ivars().annotations.borrow().iter().enumerate(),
+                indices,
+                dx,
+                dy,
+                canvas,
+            ),
+        };
+        let (moved_x, moved_y) = self.clamp_move(indices, dx + offset.x, dy + offset.y);
+        self.ivars().move_residual.set(CGPoint::new(dx - moved_x, dy - moved_y));
+        *self.ivars().alignment_guides.borrow_mut() = guides;
+        (moved_x, moved_y)
+    }
+
+    /// The selection annotations are kept inside, when that's turned on.
+    fn annotation_bounds(&self) -> Option<CGRect> {
+        if !self.ivars().keep_inside_selection.get() {
+            return None;
+        }
+        self.ivars().selection.get().map(normalize_rect)
+    }
+
+    /// `point` on the grid (see `grid_point`) and, when annotations are kept
+    /// inside the selection, pulled back into it.
+    fn annotation_point(&self, point: CGPoint) -> CGPoint {
+        let point = self.grid_point(point);
+        match self.annotation_bounds() {
+            Some(bounds) => CGPoint::new(
+                point.x.clamp(bounds.origin.x, bounds.origin.x + bounds.size.width),
+                point.y.clamp(bounds.origin.y, bounds.origin.y + bounds.size.height),
+            ),
+            None => point,
+        }
+    }
+
+    /// Shorten a move of the annotations at `indices` by (dx, dy) so they stay
+    /// inside the selection, when annotations are kept inside it.
+    fn clamp_move(&self, indices: &[usize], dx: CGFloat, dy: CGFloat) -> (CGFloat, CGFloat) {
+        let Some(bounds) = self.annotation_bounds() else {
+            return (dx, dy);
+        };
+        let annotations = self.ivars().annotations.borrow();
+        let Some(rect) = indices
+            .iter()
+            .filter_map(|&i| annotations.get(i))
+            .map(|ann| ann.bounding_rect())
+            .reduce(union_rect)
+        else {
+            return (dx, dy);
+        };
+        (
+            clamp_axis(rect.origin.x, rect.size.width, bounds.origin.x, bounds.size.width, dx),
+            clamp_axis(rect.origin.y, rect.size.height, bounds.origin.y, bounds.size.height, dy),
+        )
+    }
+
     fn convert_event_point(&self, event: &NSEvent) -> CGPoint {
-        let window_point = event.locationInWindow();
+        let mut window_point = event.locationInWindow();
+        // Events forwarded by a mirror are in its window's coordinates
+        if let (Some(source), Some(window)) =
+            (event.window(MainThreadMarker::from(self)), self.window())
+            && source != window
+        {
+            window_point = window.convertPointFromScreen(source.convertPointToScreen(window_point));
+        }
         self.convertPoint_fromView(window_point, None)
     }
 
@@ -647,6 +1655,10 @@ impl OverlayView {
         let color = self.ivars().annotation_color.get();
         let tool = self.ivars().active_tool.get();
         let width = self.ivars().annotation_width.get();
+        let fill = self.ivars().annotation_fill.get();
+        let style = self.ivars().annotation_stroke_style.get();
+        let shadow = self.ivars().annotation_shadow.get();
+        self.ivars().annotation_anchor.set(point);
 
         let ann = match tool {
             ActiveTool::Arrow => Annotation::Arrow {
@@ -654,34 +1666,80 @@ impl OverlayView {
                 end: point,
                 color,
                 width,
+                shadow,
+            },
+            ActiveTool::Line => Annotation::Line {
+                start: point,
+                end: point,
+                color,
+                width,
+                style,
+                shadow,
             },
             ActiveTool::Rectangle => Annotation::Rect {
                 origin: point,
                 size: CGSize::ZERO,
                 color,
                 width,
+                fill,
+                corner_radius: self.ivars().annotation_corner_radius.get(),
+                style,
+                shadow,
             },
             ActiveTool::Ellipse => Annotation::Ellipse {
                 origin: point,
                 size: CGSize::ZERO,
                 color,
                 width,
+                fill,
+                style,
+                shadow,
             },
             ActiveTool::Pencil => Annotation::Pencil {
                 points: vec![point],
                 color,
                 width,
+                shadow,
+                smooth: self.ivars().annotation_smooth.get(),
+                highlighter: false,
+            },
+            ActiveTool::Highlighter => Annotation::Pencil {
+                points: vec![point],
+                color,
+                width: width * HIGHLIGHTER_WIDTH_SCALE,
+                shadow: false,
+                smooth: self.ivars().annotation_smooth.get(),
+                highlighter: true,
             },
             ActiveTool::Highlight => Annotation::Highlight {
                 origin: point,
                 size: CGSize::ZERO,
                 color,
-                opacity: 0.35,
+                opacity: self.ivars().annotation_highlight_opacity.get(),
             },
             ActiveTool::Blur => Annotation::Blur {
                 origin: point,
                 size: CGSize::ZERO,
-                block_size: 10,
+                block_size: self.ivars().annotation_block_size.get(),
+                style: self.ivars().annotation_blur_style.get(),
+            },
+            ActiveTool::Redact => Annotation::Redact {
+                origin: point,
+                size: CGSize::ZERO,
+                color: self.ivars().annotation_redact_color.get(),
+            },
+            ActiveTool::Spotlight => Annotation::Spotlight {
+                origin: point,
+                size: CGSize::ZERO,
+                shape: self.ivars().annotation_spotlight_shape.get(),
+                dim: 0.6,
+            },
+            ActiveTool::Callout => Annotation::Callout {
+                rect: CGRect::new(point, CGSize::ZERO),
+                tail_tip: point,
+                text: String::new(),
+                color,
+                font_size: self.ivars().annotation_font_size.get(),
             },
             ActiveTool::Step => {
                 let number = self.ivars().next_step_number.get();
@@ -691,8 +1749,10 @@ impl OverlayView {
                     number,
                     color,
                     radius: 14.0,
+                    shape: self.ivars().annotation_step_shape.get(),
                 };
                 self.ivars().redo_stack.borrow_mut().clear();
+                self.ivars().transform_history.borrow_mut().clear_redo();
                 self.ivars().annotations.borrow_mut().push(ann);
                 self.setNeedsDisplay(true);
                 return;
@@ -701,24 +1761,29 @@ impl OverlayView {
                 self.show_text_field(point);
                 return;
             }
-            ActiveTool::Select | ActiveTool::Crop => return,
+            ActiveTool::Select | ActiveTool::Crop | ActiveTool::Eyedropper => return,
         };
 
         *self.ivars().current_annotation.borrow_mut() = Some(ann);
     }
 
     fn show_text_field(&self, point: CGPoint) {
-        self.show_text_field_with_text(point, "", self.ivars().annotation_font_size.get());
+        let font = text_font(
+            self.ivars().annotation_font_name.borrow().as_deref(),
+            self.ivars().annotation_font_size.get(),
+            self.ivars().annotation_font_traits.get(),
+        );
+        self.show_text_field_with_text(point, "", &font);
     }
 
-    fn show_text_field_with_text(&self, point: CGPoint, initial_text: &str, font_size: CGFloat) {
+    fn show_text_field_with_text(&self, point: CGPoint, initial_text: &str, font: &NSFont) {
         // Commit any existing text field first
         self.commit_text_field();
 
         let mtm = MainThreadMarker::from(self);
         let color = self.ivars().annotation_color.get();
         let (container, text_view) =
-            crate::annotation::text_input::create_text_input(mtm, point, initial_text, font_size, color);
+            crate::annotation::text_input::create_text_input(mtm, point, initial_text, font, color);
 
         self.addSubview(&container);
         if let Some(w) = self.window() {
@@ -730,21 +1795,71 @@ impl OverlayView {
         *self.ivars().text_view.borrow_mut() = Some(text_view);
     }
 
-    /// Commit the current text field content as a Text annotation.
+    /// Open the text field to edit a Callout's text or a Step's number in place.
+    /// Committing stores the result back on the annotation at `index` as one
+    /// undoable change, or adds it when it is new.
+    fn edit_annotation_text(&self, ann: Annotation, index: Option<usize>) {
+        let (point, text, font_size) = match &ann {
+            Annotation::Callout { rect, text, font_size, .. } => {
+                let r = normalize_rect(*rect);
+                let point = CGPoint::new(r.origin.x + CALLOUT_PADDING, r.origin.y + CALLOUT_PADDING);
+                (point, text.clone(), *font_size)
+            }
+            Annotation::Step { center, number, radius, .. } => {
+                let point = CGPoint::new(center.x - radius, center.y - radius);
+                (point, number.to_string(), self.ivars().annotation_font_size.get())
+            }
+            _ => return,
+        };
+        // Callouts and steps are drawn in the system font
+        self.show_text_field_with_text(point, &text, &NSFont::systemFontOfSize(font_size));
+        *self.ivars().text_edit_target.borrow_mut() = Some((index, ann));
+    }
+
+    /// Commit the current text field content as a Text annotation (or onto the edited Callout/Step).
     pub fn commit_text_field(&self) {
         let container = self.ivars().text_container.borrow_mut().take();
         let text_view = self.ivars().text_view.borrow_mut().take();
+        let target = self.ivars().text_edit_target.borrow_mut().take();
         if let (Some(container), Some(text_view)) = (container, text_view) {
-            if let Some(text) = crate::annotation::text_input::commit_text_input(&container, &text_view) {
+            let text = crate::annotation::text_input::commit_text_input(&container, &text_view);
+            if let Some((index, mut target)) = target {
+                target.apply_edited_text(text.unwrap_or_default());
+                if let Annotation::Step { number, .. } = target {
+                    // Continue the sequence from the edited number
+                    self.ivars().next_step_number.set(number + 1);
+                }
+                let mut annotations = self.ivars().annotations.borrow_mut();
+                match index.and_then(|i| annotations.get_mut(i).map(|ann| (i, ann))) {
+                    Some((i, ann)) => {
+                        // Undo restores the original text like a move
+                        let before = std::mem::replace(ann, target);
+                        let count = annotations.len();
+                        let mut history = self.ivars().transform_history.borrow_mut();
+                        history.record(vec![(i, before)], count);
+                    }
+                    None => {
+                        self.ivars().redo_stack.borrow_mut().clear();
+                        self.ivars().transform_history.borrow_mut().clear_redo();
+                        annotations.push(target);
+                    }
+                }
+            } else if let Some(text) = text {
                 let color = self.ivars().annotation_color.get();
                 let position = self.ivars().text_position.get();
                 let font_size = self.ivars().annotation_font_size.get();
+                let background = self.ivars().annotation_text_background.get();
                 self.ivars().redo_stack.borrow_mut().clear();
+                self.ivars().transform_history.borrow_mut().clear_redo();
                 self.ivars().annotations.borrow_mut().push(Annotation::Text {
                     position,
                     text,
                     color,
                     font_size,
+                    background: background.then(|| text_background_for(color)),
+                    outline: self.ivars().annotation_text_outline.get(),
+                    font_name: self.ivars().annotation_font_name.borrow().clone(),
+                    traits: self.ivars().annotation_font_traits.get(),
                 });
             }
             self.setNeedsDisplay(true);
@@ -758,6 +1873,14 @@ impl OverlayView {
         if let Some(window) = self.window() {
             window.orderOut(None);
         }
+        self.close_mirrors();
+    }
+
+    /// Close the windows showing this overlay on other screens.
+    pub fn close_mirrors(&self) {
+        for (window, _) in self.ivars().mirrors.borrow_mut().drain(..) {
+            window.orderOut(None);
+        }
     }
 
     fn notify_selection_changed(&self) {
@@ -769,6 +1892,24 @@ impl OverlayView {
         }
     }
 
+    /// Ask the app delegate to confirm the selection (same as the toolbar's checkmark).
+    fn notify_confirm(&self) {
+        let mtm = MainThreadMarker::from(self);
+        let app = objc2_app_kit::NSApplication::sharedApplication(mtm);
+        if let Some(delegate) = app.delegate() {
+            let _: () = unsafe { objc2::msg_send![&*delegate, actionConfirm: self] };
+        }
+    }
+
+    /// Ask the app delegate to capture the highlighted window.
+    fn notify_window_picked(&self) {
+        let mtm = MainThreadMarker::from(self);
+        let app = objc2_app_kit::NSApplication::sharedApplication(mtm);
+        if let Some(delegate) = app.delegate() {
+            let _: () = unsafe { objc2::msg_send![&*delegate, windowPicked: self] };
+        }
+    }
+
     /// Notify the app delegate that the tool changed (from keyboard shortcut),
     /// so the toolbar visual state can be updated.
     fn notify_tool_changed(&self) {
@@ -780,18 +1921,33 @@ impl OverlayView {
             match tool {
                 ActiveTool::Select => { let _: () = unsafe { objc2::msg_send![d, toolSelect: self] }; }
                 ActiveTool::Arrow => { let _: () = unsafe { objc2::msg_send![d, toolArrow: self] }; }
+                ActiveTool::Line => { let _: () = unsafe { objc2::msg_send![d, toolLine: self] }; }
                 ActiveTool::Rectangle => { let _: () = unsafe { objc2::msg_send![d, toolRect: self] }; }
                 ActiveTool::Ellipse => { let _: () = unsafe { objc2::msg_send![d, toolEllipse: self] }; }
                 ActiveTool::Pencil => { let _: () = unsafe { objc2::msg_send![d, toolPencil: self] }; }
                 ActiveTool::Text => { let _: () = unsafe { objc2::msg_send![d, toolText: self] }; }
                 ActiveTool::Highlight => { let _: () = unsafe { objc2::msg_send![d, toolHighlight: self] }; }
+                ActiveTool::Highlighter => { let _: () = unsafe { objc2::msg_send![d, toolHighlighter: self] }; }
                 ActiveTool::Step => { let _: () = unsafe { objc2::msg_send![d, toolStep: self] }; }
                 ActiveTool::Blur => { let _: () = unsafe { objc2::msg_send![d, toolBlur: self] }; }
+                ActiveTool::Redact => { let _: () = unsafe { objc2::msg_send![d, toolRedact: self] }; }
+                ActiveTool::Spotlight => { let _: () = unsafe { objc2::msg_send![d, toolSpotlight: self] }; }
+                ActiveTool::Callout => { let _: () = unsafe { objc2::msg_send![d, toolCallout: self] }; }
                 ActiveTool::Crop => { let _: () = unsafe { objc2::msg_send![d, toolCrop: self] }; }
+                ActiveTool::Eyedropper => { let _: () = unsafe { objc2::msg_send![d, toolEyedropper: self] }; }
             }
         }
     }
 
+    /// Ask the app delegate to sample the image color under `point` (eyedropper).
+    fn notify_sample_color(&self, point: CGPoint) {
+        let mtm = MainThreadMarker::from(self);
+        let app = objc2_app_kit::NSApplication::sharedApplication(mtm);
+        if let Some(delegate) = app.delegate() {
+            let _: () = unsafe { objc2::msg_send![&*delegate, sampleColorAtX: point.x, y: point.y] };
+        }
+    }
+
     /// Notify the app delegate that the stroke width changed (from keyboard shortcut).
     fn notify_stroke_changed(&self, sel_name: &str) {
         let mtm = MainThreadMarker::from(self);
@@ -802,10 +1958,53 @@ impl OverlayView {
                 "strokeThin:" => { let _: () = unsafe { objc2::msg_send![d, strokeThin: self] }; }
                 "strokeMedium:" => { let _: () = unsafe { objc2::msg_send![d, strokeMedium: self] }; }
                 "strokeThick:" => { let _: () = unsafe { objc2::msg_send![d, strokeThick: self] }; }
+                "cycleStrokeStyle:" => { let _: () = unsafe { objc2::msg_send![d, cycleStrokeStyle: self] }; }
                 _ => {}
             }
         }
     }
+
+    /// Ask the app delegate to toggle the Cmd+K command palette.
+    fn notify_show_command_palette(&self) {
+        let mtm = MainThreadMarker::from(self);
+        let app = objc2_app_kit::NSApplication::sharedApplication(mtm);
+        if let Some(delegate) = app.delegate() {
+            let _: () = unsafe { objc2::msg_send![&*delegate, showCommandPalette: self] };
+        }
+    }
+
+    fn notify_clear_annotations(&self) {
+        let mtm = MainThreadMarker::from(self);
+        let app = objc2_app_kit::NSApplication::sharedApplication(mtm);
+        if let Some(delegate) = app.delegate() {
+            let _: () = unsafe { objc2::msg_send![&*delegate, actionClearAnnotations: self] };
+        }
+    }
+}
+
+// --- Context menu ---
+
+/// A right-click menu of (title, action) items sent to `target`.
+pub fn context_menu(
+    mtm: MainThreadMarker,
+    items: &[(&str, &str)],
+    target: &AnyObject,
+) -> Retained<NSMenu> {
+    let menu = NSMenu::new(mtm);
+    for (title, action) in items {
+        let sel_cstr = std::ffi::CString::new(*action).unwrap();
+        let item = unsafe {
+            NSMenuItem::initWithTitle_action_keyEquivalent(
+                mtm.alloc(),
+                &NSString::from_str(title),
+                Some(Sel::register(&sel_cstr)),
+                &NSString::from_str(""),
+            )
+        };
+        unsafe { item.setTarget(Some(target)) };
+        menu.addItem(&item);
+    }
+    menu
 }
 
 // --- Geometry helpers ---
@@ -827,6 +2026,85 @@ fn rect_contains(r: CGRect, p: CGPoint) -> bool {
         && p.y <= r.origin.y + r.size.height
 }
 
+/// Limit a move by `delta` of a span from `start` of `length` to within the span
+/// from `bounds_start` of `bounds_length`. Spans too long to fit move freely.
+fn clamp_axis(
+    start: CGFloat,
+    length: CGFloat,
+    bounds_start: CGFloat,
+    bounds_length: CGFloat,
+    delta: CGFloat,
+) -> CGFloat {
+    if length > bounds_length {
+        return delta;
+    }
+    // Never an empty range: length <= bounds_length
+    let min = bounds_start - start;
+    let max = bounds_start + bounds_length - (start + length);
+    delta.clamp(min, max)
+}
+
+/// Round `point` to the nearest crossing of a grid `spacing` points apart.
+pub fn snap_to_grid(point: CGPoint, spacing: CGFloat) -> CGPoint {
+    CGPoint::new(
+        (point.x / spacing).round() * spacing,
+        (point.y / spacing).round() * spacing,
+    )
+}
+
+/// Offset that rounds a move of (dx, dy) to whole grid steps.
+pub fn grid_offset(dx: CGFloat, dy: CGFloat, spacing: CGFloat) -> CGPoint {
+    let steps = snap_to_grid(CGPoint::new(dx, dy), spacing);
+    CGPoint::new(steps.x - dx, steps.y - dy)
+}
+
+/// Draw a faint grid `spacing` points apart over `rect`, lined up with the view origin.
+pub fn draw_grid(ctx: &CGContext, rect: CGRect, spacing: CGFloat) {
+    CGContext::save_g_state(Some(ctx));
+    CGContext::clip_to_rect(Some(ctx), rect);
+    CGContext::set_rgb_stroke_color(Some(ctx), 0.5, 0.5, 0.5, 0.25);
+    CGContext::set_line_width(Some(ctx), 0.5);
+    let (max_x, max_y) = (rect.origin.x + rect.size.width, rect.origin.y + rect.size.height);
+    let mut x = (rect.origin.x / spacing).ceil() * spacing;
+    while x <= max_x {
+        CGContext::move_to_point(Some(ctx), x, rect.origin.y);
+        CGContext::add_line_to_point(Some(ctx), x, max_y);
+        x += spacing;
+    }
+    let mut y = (rect.origin.y / spacing).ceil() * spacing;
+    while y <= max_y {
+        CGContext::move_to_point(Some(ctx), rect.origin.x, y);
+        CGContext::add_line_to_point(Some(ctx), max_x, y);
+        y += spacing;
+    }
+    CGContext::stroke_path(Some(ctx));
+    CGContext::restore_g_state(Some(ctx));
+}
+
+/// Nearest of `edges` within `SNAP_DISTANCE` of `value`, or `value` itself.
+fn snap_to_edges(value: CGFloat, edges: impl Iterator<Item = CGFloat>) -> CGFloat {
+    edges
+        .map(|edge| (edge, (edge - value).abs()))
+        .filter(|&(_, distance)| distance <= SNAP_DISTANCE)
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map_or(value, |(edge, _)| edge)
+}
+
+/// The crop handle matching a selection resize mode.
+fn handle_for_drag_mode(mode: DragMode) -> Option<HandleKind> {
+    match mode {
+        DragMode::ResizeTopLeft => Some(HandleKind::TopLeft),
+        DragMode::ResizeTopRight => Some(HandleKind::TopRight),
+        DragMode::ResizeBottomLeft => Some(HandleKind::BottomLeft),
+        DragMode::ResizeBottomRight => Some(HandleKind::BottomRight),
+        DragMode::ResizeTop => Some(HandleKind::Top),
+        DragMode::ResizeBottom => Some(HandleKind::Bottom),
+        DragMode::ResizeLeft => Some(HandleKind::Left),
+        DragMode::ResizeRight => Some(HandleKind::Right),
+        DragMode::None | DragMode::Creating | DragMode::Moving => None,
+    }
+}
+
 fn resize_rect(orig: CGRect, mode: DragMode, start: CGPoint, current: CGPoint) -> CGRect {
     let dx = current.x - start.x;
     let dy = current.y - start.y;
@@ -877,6 +2155,34 @@ fn draw_annotation_handles(ctx: &CGContext, ann: &Annotation) {
 }
 
 /// Draw a dashed highlight border around a selected annotation.
+/// Draw the smart guides shown while moving annotations.
+pub fn draw_alignment_guides(ctx: &CGContext, guides: &[AlignmentGuide]) {
+    if guides.is_empty() {
+        return;
+    }
+    CGContext::save_g_state(Some(ctx));
+    CGContext::set_rgb_stroke_color(Some(ctx), 1.0, 0.2, 0.6, 0.9);
+    CGContext::set_line_width(Some(ctx), 1.0);
+    for guide in guides {
+        CGContext::move_to_point(Some(ctx), guide.start.x, guide.start.y);
+        CGContext::add_line_to_point(Some(ctx), guide.end.x, guide.end.y);
+    }
+    CGContext::stroke_path(Some(ctx));
+    CGContext::restore_g_state(Some(ctx));
+}
+
+/// Draw the rubber-band rectangle of a marquee selection.
+pub fn draw_marquee(ctx: &CGContext, rect: CGRect) {
+    let rect = normalize_rect(rect);
+    CGContext::save_g_state(Some(ctx));
+    CGContext::set_rgb_fill_color(Some(ctx), 0.2, 0.5, 1.0, 0.12);
+    CGContext::fill_rect(Some(ctx), rect);
+    CGContext::set_rgb_stroke_color(Some(ctx), 0.2, 0.5, 1.0, 0.9);
+    CGContext::set_line_width(Some(ctx), 1.0);
+    CGContext::stroke_rect(Some(ctx), rect);
+    CGContext::restore_g_state(Some(ctx));
+}
+
 fn draw_annotation_highlight(ctx: &CGContext, ann: &Annotation) {
     let rect = ann.bounding_rect();
     let highlight = CGRect::new(
@@ -900,14 +2206,33 @@ pub fn tool_for_key(key_code: u16) -> Option<ActiveTool> {
     match key_code {
         1 => Some(ActiveTool::Select),     // S
         0 => Some(ActiveTool::Arrow),      // A
+        37 => Some(ActiveTool::Line),      // L
         15 => Some(ActiveTool::Rectangle), // R
         14 => Some(ActiveTool::Ellipse),   // E
         35 => Some(ActiveTool::Pencil),    // P
         17 => Some(ActiveTool::Text),      // T
         4 => Some(ActiveTool::Highlight),  // H
+        5 => Some(ActiveTool::Highlighter), // G
         45 => Some(ActiveTool::Step),     // N
         11 => Some(ActiveTool::Blur),     // B
+        7 => Some(ActiveTool::Redact),    // X
+        31 => Some(ActiveTool::Spotlight), // O
+        40 => Some(ActiveTool::Callout),   // K
         8 => Some(ActiveTool::Crop),      // C
+        34 => Some(ActiveTool::Eyedropper), // I
+        _ => None,
+    }
+}
+
+/// Map an arrow keyCode to a nudge offset in (flipped) view points.
+/// Moves 1 point, or 10 with Shift held.
+pub fn nudge_for_key(key_code: u16, shift: bool) -> Option<(CGFloat, CGFloat)> {
+    let step = if shift { 10.0 } else { 1.0 };
+    match key_code {
+        123 => Some((-step, 0.0)), // Left
+        124 => Some((step, 0.0)),  // Right
+        125 => Some((0.0, step)),  // Down
+        126 => Some((0.0, -step)), // Up
         _ => None,
     }
 }
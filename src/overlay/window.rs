@@ -1,11 +1,16 @@
+use std::cell::Cell;
+
 use objc2::rc::Retained;
-use objc2::{define_class, msg_send, MainThreadOnly};
+use objc2::{define_class, msg_send, DefinedClass, MainThreadOnly};
 use objc2_app_kit::{
-    NSApplication, NSBackingStoreType, NSColor, NSImage, NSScreen, NSWindow, NSWindowStyleMask,
+    NSApplication, NSBackingStoreType, NSColor, NSImage, NSScreen, NSView, NSWindow,
+    NSWindowStyleMask,
 };
-use objc2_core_graphics::{CGImage, kCGOverlayWindowLevel};
-use objc2_foundation::{MainThreadMarker, NSSize};
+use objc2_core_foundation::{CGFloat, CGPoint, CGRect};
+use objc2_core_graphics::{CGDisplayBounds, CGImage, CGMainDisplayID, kCGOverlayWindowLevel};
+use objc2_foundation::{MainThreadMarker, NSRect, NSSize};
 
+use super::mirror::{offset_within, OverlayMirrorView};
 use super::view::OverlayView;
 
 // Custom NSWindow subclass that can become key window (needed for borderless windows).
@@ -32,8 +37,11 @@ define_class!(
 );
 
 pub struct OverlayWindow {
+    /// Key window holding the view, over the screen with the mouse.
     pub window: Retained<KeyableWindow>,
     pub view: Retained<OverlayView>,
+    /// Area the overlay covers (AppKit coordinates), which the view's coordinates start from.
+    frame: Cell<CGRect>,
 }
 
 impl OverlayWindow {
@@ -60,16 +68,25 @@ impl OverlayWindow {
         window.setAcceptsMouseMovedEvents(true);
         unsafe { window.setReleasedWhenClosed(false) };
 
+        // The view spans the whole overlay, which may reach past this window onto
+        // other screens; a container lets it sit offset inside the window
+        let container = NSView::initWithFrame(mtm.alloc(), frame);
+        window.setContentView(Some(&container));
         let view = OverlayView::new(mtm, frame);
-        window.setContentView(Some(&view));
+        container.addSubview(&view);
 
-        OverlayWindow { window, view }
+        OverlayWindow { window, view, frame: Cell::new(frame) }
     }
 
-    pub fn show_with_screenshot(&self, cg_image: &CGImage, screen: &NSScreen, mtm: MainThreadMarker) {
-        let frame = screen.frame();
-        let scale_factor = screen.backingScaleFactor();
-
+    /// Show `cg_image` over `frame` (AppKit coordinates: one screen, or the union
+    /// of all screens). `scale_factor` is image pixels per point.
+    pub fn show_with_screenshot(
+        &self,
+        cg_image: &CGImage,
+        frame: NSRect,
+        scale_factor: CGFloat,
+        mtm: MainThreadMarker,
+    ) {
         let ns_image = NSImage::initWithCGImage_size(
             mtm.alloc(),
             cg_image,
@@ -78,8 +95,24 @@ impl OverlayWindow {
 
         self.view.reset();
         self.view.set_screenshot(ns_image, scale_factor);
-
-        self.window.setFrame_display(frame, true);
+        self.view.set_window_frames(window_frames_on(frame));
+        self.view.close_mirrors();
+        self.frame.set(frame);
+
+        // Displays with separate Spaces show a window on only one screen, so cover
+        // each screen with its own window; they all show and edit this one view
+        let screens = screens_in(frame, mtm);
+        let mouse = crate::screen::screen_with_mouse(mtm).frame();
+        let own_frame =
+            screens.iter().find(|s| **s == mouse).or(screens.first()).copied().unwrap_or(frame);
+        self.window.setFrame_display(own_frame, true);
+        self.view.setFrame(offset_within(frame, own_frame));
+        let mirrors = screens
+            .into_iter()
+            .filter(|s| *s != own_frame)
+            .map(|s| OverlayMirrorView::show_on_screen(&self.view, s, frame, mtm))
+            .collect();
+        *self.view.ivars().mirrors.borrow_mut() = mirrors;
 
         // Activate the app so the first click goes to the view, not to activation.
         #[allow(deprecated)]
@@ -87,9 +120,72 @@ impl OverlayWindow {
 
         self.window.makeKeyAndOrderFront(None);
         self.window.makeFirstResponder(Some(&*self.view));
+        self.view.sync_cursor_point();
     }
 
     pub fn hide(&self) {
         self.window.orderOut(None);
+        self.view.close_mirrors();
+    }
+
+    /// Area the overlay covers, in AppKit coordinates.
+    pub fn frame(&self) -> CGRect {
+        self.frame.get()
+    }
+
+    /// The overlay window on the screen holding most of `rect` (view coordinates),
+    /// for attaching the toolbar there.
+    pub fn window_for(&self, rect: CGRect) -> Retained<NSWindow> {
+        let frame = self.frame.get();
+        // View coordinates are flipped, from the top of the overlay
+        let center = CGPoint::new(
+            frame.origin.x + rect.origin.x + rect.size.width / 2.0,
+            frame.origin.y + frame.size.height - rect.origin.y - rect.size.height / 2.0,
+        );
+        let contains = |w: &NSWindow| {
+            let f = w.frame();
+            center.x >= f.origin.x
+                && center.x < f.origin.x + f.size.width
+                && center.y >= f.origin.y
+                && center.y < f.origin.y + f.size.height
+        };
+        self.view
+            .ivars()
+            .mirrors
+            .borrow()
+            .iter()
+            .find(|(window, _)| contains(window))
+            .map_or_else(|| Retained::into_super(self.window.clone()), |(w, _)| w.clone())
     }
 }
+
+/// Frames of the screens overlapping `frame`.
+fn screens_in(frame: CGRect, mtm: MainThreadMarker) -> Vec<CGRect> {
+    NSScreen::screens(mtm)
+        .iter()
+        .map(|screen| screen.frame())
+        .filter(|s| {
+            s.origin.x < frame.origin.x + frame.size.width
+                && frame.origin.x < s.origin.x + s.size.width
+                && s.origin.y < frame.origin.y + frame.size.height
+                && frame.origin.y < s.origin.y + s.size.height
+        })
+        .collect()
+}
+
+/// On-screen windows with their frames converted to the view coordinates of
+/// an overlay covering `screen_frame`, frontmost first.
+fn window_frames_on(screen_frame: CGRect) -> Vec<(u32, CGRect)> {
+    // Window bounds are in CG coordinates (top-left origin of the primary
+    // display); screen frames are in AppKit coordinates (bottom-left origin)
+    let primary_height = CGDisplayBounds(CGMainDisplayID()).size.height;
+    let top = primary_height - (screen_frame.origin.y + screen_frame.size.height);
+    crate::capture::on_screen_windows()
+        .into_iter()
+        .map(|w| {
+            let origin =
+                CGPoint::new(w.bounds.origin.x - screen_frame.origin.x, w.bounds.origin.y - top);
+            (w.id, CGRect::new(origin, w.bounds.size))
+        })
+        .collect()
+}
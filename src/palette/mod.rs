@@ -0,0 +1,4 @@
+pub mod view;
+pub mod window;
+
+pub use window::CommandPalette;
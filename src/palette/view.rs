@@ -0,0 +1,411 @@
+use std::cell::{Cell, RefCell};
+
+use objc2::rc::Retained;
+use objc2::runtime::{AnyObject, Sel};
+use objc2::{define_class, msg_send, DefinedClass, MainThreadOnly};
+use objc2_app_kit::{NSEvent, NSGraphicsContext, NSView};
+use objc2_core_foundation::{CGFloat, CGPoint, CGRect, CGSize};
+use objc2_core_graphics::CGContext;
+use objc2_foundation::{MainThreadMarker, NSRect, NSString};
+
+pub const PALETTE_WIDTH: CGFloat = 380.0;
+pub const MAX_VISIBLE_ROWS: usize = 10;
+const ROW_HEIGHT: CGFloat = 22.0;
+const SEARCH_HEIGHT: CGFloat = 34.0;
+const PADDING: CGFloat = 8.0;
+
+/// Total height of the palette view.
+pub const PALETTE_HEIGHT: CGFloat =
+    SEARCH_HEIGHT + MAX_VISIBLE_ROWS as CGFloat * ROW_HEIGHT + PADDING * 2.0;
+
+/// Every command reachable from the palette: (title, delegate selector, shortcut hint).
+const COMMANDS: &[(&str, &str, &str)] = &[
+    ("Tool: Select",            "toolSelect:",        "S"),
+    ("Tool: Arrow",             "toolArrow:",         "A"),
+    ("Tool: Line",              "toolLine:",          "L"),
+    ("Tool: Rectangle",         "toolRect:",          "R"),
+    ("Tool: Ellipse",           "toolEllipse:",       "E"),
+    ("Tool: Pencil",            "toolPencil:",        "P"),
+    ("Tool: Text",              "toolText:",          "T"),
+    ("Tool: Highlight",         "toolHighlight:",     "H"),
+    ("Tool: Highlighter",       "toolHighlighter:",   "G"),
+    ("Tool: Step",              "toolStep:",          "N"),
+    ("Tool: Blur",              "toolBlur:",          "B"),
+    ("Tool: Redact",            "toolRedact:",        "X"),
+    ("Tool: Spotlight",         "toolSpotlight:",     "O"),
+    ("Tool: Callout",           "toolCallout:",       "K"),
+    ("Tool: Crop",              "toolCrop:",          "C"),
+    ("Tool: Eyedropper",        "toolEyedropper:",    "I"),
+    ("Color: Red",              "colorRed:",          ""),
+    ("Color: Orange",           "colorOrange:",       ""),
+    ("Color: Yellow",           "colorYellow:",       ""),
+    ("Color: Green",            "colorGreen:",        ""),
+    ("Color: Blue",             "colorBlue:",         ""),
+    ("Color: Black",            "colorBlack:",        ""),
+    ("Color: White",            "colorWhite:",        ""),
+    ("Color: Custom\u{2026}",   "toggleColorPicker:", ""),
+    ("Toggle Shape Fill",       "toggleFill:",        ""),
+    ("Toggle Drop Shadow",      "toggleShadow:",      ""),
+    ("Cycle Text Font",         "cycleTextFont:",     ""),
+    ("Toggle Bold Text",        "toggleBold:",        ""),
+    ("Toggle Italic Text",      "toggleItalic:",      ""),
+    ("Toggle Text Background",  "toggleTextBackground:", ""),
+    ("Toggle Text Outline",     "toggleTextOutline:", ""),
+    ("Toggle Pencil Smoothing", "toggleSmoothing:",   ""),
+    ("Cycle Corner Radius",     "cycleCornerRadius:", ""),
+    ("Cycle Highlight Opacity", "cycleHighlightOpacity:", ""),
+    ("Stroke: Thin",            "strokeThin:",        "1"),
+    ("Stroke: Medium",          "strokeMedium:",      "2"),
+    ("Stroke: Thick",           "strokeThick:",       "3"),
+    ("Cycle Stroke Style",      "cycleStrokeStyle:",  "D"),
+    ("Toggle Blur Style",       "toggleBlurStyle:",   ""),
+    ("Cycle Step Shape",        "cycleStepShape:",    ""),
+    ("Reset Step Counter",      "resetStepCounter:",  "\u{2318}Click"),
+    ("Toggle Spotlight Shape",  "toggleSpotlightShape:", ""),
+    ("Undo",                    "actionUndo:",        "\u{2318}Z"),
+    ("Redo",                    "actionRedo:",        "\u{21E7}\u{2318}Z"),
+    ("Select All Annotations",  "actionSelectAllAnnotations:", "\u{2318}A"),
+    ("Clear All Annotations",   "actionClearAnnotations:", "\u{21E7}\u{2318}\u{232B}"),
+    ("Play / Pause",            "editorPlayPause:",   "Space"),
+    ("Reverse Play",            "editorReverse:",     ""),
+    ("Set Trim Start",          "editorSetTrimStart:", "["),
+    ("Set Trim End",            "editorSetTrimEnd:",  "]"),
+    ("Clear Trim",              "editorClearTrim:",   ""),
+    ("Cycle Fade In",           "editorCycleFadeIn:", ""),
+    ("Cycle Fade Out",          "editorCycleFadeOut:", ""),
+    ("Rotate Image Left",       "editorRotateLeft:",  "\u{2318}L"),
+    ("Rotate Image Right",      "editorRotateRight:", "\u{2318}R"),
+    ("Flip Image Horizontal",   "editorFlipHorizontal:", ""),
+    ("Flip Image Vertical",     "editorFlipVertical:", ""),
+    ("Grayscale Image",         "editorGrayscale:",   ""),
+    ("Invert Image Colors",     "editorInvertColors:", ""),
+    ("Remove Image Adjustments", "editorRemoveAdjustments:", ""),
+    ("Add Background\u{2026}",   "editorAddBackground:", ""),
+    ("Add Window Frame\u{2026}", "editorAddWindowFrame:", ""),
+    ("Save to File",            "actionSave:",        ""),
+    ("Pin to Screen",           "actionPin:",         ""),
+    ("Upload and Copy Link",    "actionUpload:",      ""),
+    ("Copy Text (OCR)",         "actionCopyText:",    ""),
+    ("Decode QR Code / Barcode", "actionDecodeBarcode:", ""),
+    ("Confirm (Copy / Export)", "actionConfirm:",     ""),
+    ("Cancel",                  "actionCancel:",      "Esc"),
+];
+
+pub struct CommandPaletteViewIvars {
+    /// Text typed into the search field.
+    query: RefCell<String>,
+    /// Indices into `COMMANDS` matching the query, best match first.
+    matches: RefCell<Vec<usize>>,
+    /// Highlighted row within `matches`.
+    selected: Cell<usize>,
+    /// First row of `matches` currently shown (keeps the selection visible).
+    scroll_offset: Cell<usize>,
+}
+
+define_class!(
+    #[unsafe(super(NSView))]
+    #[thread_kind = MainThreadOnly]
+    #[name = "CommandPaletteView"]
+    #[ivars = CommandPaletteViewIvars]
+    pub struct CommandPaletteView;
+
+    impl CommandPaletteView {
+        #[unsafe(method(isFlipped))]
+        fn is_flipped(&self) -> bool {
+            true
+        }
+
+        #[unsafe(method(acceptsFirstResponder))]
+        fn accepts_first_responder(&self) -> bool {
+            true
+        }
+
+        #[unsafe(method(acceptsFirstMouse:))]
+        fn accepts_first_mouse(&self, _event: Option<&NSEvent>) -> bool {
+            true
+        }
+
+        #[unsafe(method(drawRect:))]
+        fn draw_rect(&self, _dirty_rect: NSRect) {
+            let Some(context) = NSGraphicsContext::currentContext() else {
+                return;
+            };
+            let cg = context.CGContext();
+            let bounds = self.bounds();
+
+            // Background
+            CGContext::set_rgb_fill_color(Some(&cg), 0.12, 0.12, 0.13, 0.96);
+            CGContext::fill_rect(Some(&cg), bounds);
+
+            // Search field
+            let query = self.ivars().query.borrow();
+            let (text, alpha) = if query.is_empty() {
+                ("Type a command\u{2026}".to_string(), 0.45)
+            } else {
+                (format!("{}\u{2502}", query), 1.0)
+            };
+            drop(query);
+            draw_label(&text, CGPoint::new(PADDING + 4.0, PADDING + 6.0), 16.0, (1.0, 1.0, 1.0, alpha));
+
+            // Separator under the search field
+            CGContext::set_rgb_fill_color(Some(&cg), 1.0, 1.0, 1.0, 0.15);
+            CGContext::fill_rect(
+                Some(&cg),
+                CGRect::new(
+                    CGPoint::new(PADDING, PADDING + SEARCH_HEIGHT - 1.0),
+                    CGSize::new(bounds.size.width - PADDING * 2.0, 1.0),
+                ),
+            );
+
+            let matches = self.ivars().matches.borrow();
+            if matches.is_empty() {
+                draw_label(
+                    "No matching commands",
+                    CGPoint::new(PADDING + 4.0, PADDING + SEARCH_HEIGHT + 4.0),
+                    13.0,
+                    (1.0, 1.0, 1.0, 0.45),
+                );
+                return;
+            }
+
+            let selected = self.ivars().selected.get();
+            let offset = self.ivars().scroll_offset.get();
+            for (row, &cmd_idx) in matches.iter().skip(offset).take(MAX_VISIBLE_ROWS).enumerate() {
+                let (title, _, hint) = COMMANDS[cmd_idx];
+                let row_rect = row_rect(bounds, row);
+
+                if offset + row == selected {
+                    CGContext::set_rgb_fill_color(Some(&cg), 0.2, 0.45, 0.9, 0.9);
+                    CGContext::fill_rect(Some(&cg), row_rect);
+                }
+
+                let text_y = row_rect.origin.y + 3.0;
+                draw_label(title, CGPoint::new(row_rect.origin.x + 6.0, text_y), 13.0, (1.0, 1.0, 1.0, 1.0));
+                if !hint.is_empty() {
+                    let hint_x = row_rect.origin.x + row_rect.size.width - 70.0;
+                    draw_label(hint, CGPoint::new(hint_x, text_y), 12.0, (1.0, 1.0, 1.0, 0.5));
+                }
+            }
+        }
+
+        #[unsafe(method(keyDown:))]
+        fn key_down(&self, event: &NSEvent) {
+            let key_code = event.keyCode();
+            let flags = event.modifierFlags();
+
+            match key_code {
+                // Escape, or Cmd+K again, closes the palette
+                53 => {
+                    self.notify_delegate_hide();
+                    return;
+                }
+                40 if flags.contains(objc2_app_kit::NSEventModifierFlags::Command) => {
+                    self.notify_delegate_hide();
+                    return;
+                }
+                // Return / keypad Enter runs the highlighted command
+                36 | 76 => {
+                    self.run_selected();
+                    return;
+                }
+                // Down / Up arrows move the highlight
+                125 => {
+                    self.move_selection(1);
+                    return;
+                }
+                126 => {
+                    self.move_selection(-1);
+                    return;
+                }
+                // Backspace
+                51 => {
+                    self.ivars().query.borrow_mut().pop();
+                    self.refilter();
+                    return;
+                }
+                _ => {}
+            }
+
+            if flags.contains(objc2_app_kit::NSEventModifierFlags::Command)
+                || flags.contains(objc2_app_kit::NSEventModifierFlags::Control)
+            {
+                return;
+            }
+
+            if let Some(chars) = event.characters() {
+                let typed: String = chars.to_string().chars().filter(|c| !c.is_control()).collect();
+                if !typed.is_empty() {
+                    self.ivars().query.borrow_mut().push_str(&typed);
+                    self.refilter();
+                }
+            }
+        }
+
+        #[unsafe(method(mouseDown:))]
+        fn mouse_down(&self, event: &NSEvent) {
+            let point = self.convertPoint_fromView(event.locationInWindow(), None);
+            let list_top = PADDING + SEARCH_HEIGHT;
+            if point.y < list_top {
+                return;
+            }
+            let row = ((point.y - list_top) / ROW_HEIGHT) as usize;
+            let idx = self.ivars().scroll_offset.get() + row;
+            if row < MAX_VISIBLE_ROWS && idx < self.ivars().matches.borrow().len() {
+                self.ivars().selected.set(idx);
+                self.run_selected();
+            }
+        }
+    }
+);
+
+impl CommandPaletteView {
+    pub fn new(mtm: MainThreadMarker, frame: NSRect) -> Retained<Self> {
+        let this = mtm.alloc().set_ivars(CommandPaletteViewIvars {
+            query: RefCell::new(String::new()),
+            matches: RefCell::new(Vec::new()),
+            selected: Cell::new(0),
+            scroll_offset: Cell::new(0),
+        });
+        let view: Retained<Self> = unsafe { msg_send![super(this), initWithFrame: frame] };
+        view.refilter();
+        view
+    }
+
+    /// Clear the query and show the full command list.
+    pub fn reset(&self) {
+        self.ivars().query.borrow_mut().clear();
+        self.refilter();
+    }
+
+    /// Recompute the matching commands for the current query.
+    fn refilter(&self) {
+        let query = self.ivars().query.borrow().to_lowercase();
+        let mut scored: Vec<(i32, usize)> = COMMANDS
+            .iter()
+            .enumerate()
+            .filter_map(|(i, (title, _, _))| fuzzy_score(&query, title).map(|s| (s, i)))
+            .collect();
+        // Stable sort keeps the declared order among equal scores
+        scored.sort_by_key(|&(score, _)| std::cmp::Reverse(score));
+        *self.ivars().matches.borrow_mut() = scored.into_iter().map(|(_, i)| i).collect();
+        self.ivars().selected.set(0);
+        self.ivars().scroll_offset.set(0);
+        self.setNeedsDisplay(true);
+    }
+
+    fn move_selection(&self, delta: isize) {
+        let count = self.ivars().matches.borrow().len();
+        if count == 0 {
+            return;
+        }
+        let selected = (self.ivars().selected.get() as isize + delta).clamp(0, count as isize - 1) as usize;
+        self.ivars().selected.set(selected);
+
+        // Scroll so the highlighted row stays visible
+        let offset = self.ivars().scroll_offset.get();
+        if selected < offset {
+            self.ivars().scroll_offset.set(selected);
+        } else if selected >= offset + MAX_VISIBLE_ROWS {
+            self.ivars().scroll_offset.set(selected + 1 - MAX_VISIBLE_ROWS);
+        }
+        self.setNeedsDisplay(true);
+    }
+
+    /// Close the palette and dispatch the highlighted command to the app delegate.
+    fn run_selected(&self) {
+        let cmd_idx = self.ivars().matches.borrow().get(self.ivars().selected.get()).copied();
+        let Some(cmd_idx) = cmd_idx else {
+            return;
+        };
+        let (title, sel_name, _) = COMMANDS[cmd_idx];
+
+        // Hide first so the previous window is key again when the action runs
+        self.notify_delegate_hide();
+
+        let mtm = MainThreadMarker::from(self);
+        let app = objc2_app_kit::NSApplication::sharedApplication(mtm);
+        if let Some(delegate) = app.delegate() {
+            let sel_cstr = std::ffi::CString::new(sel_name).unwrap();
+            let sel = Sel::register(&sel_cstr);
+            let sender: &AnyObject = unsafe { &*(self as *const Self as *const AnyObject) };
+            let _: *mut AnyObject =
+                unsafe { msg_send![&*delegate, performSelector: sel, withObject: sender] };
+        }
+        eprintln!("Command palette: {}", title);
+    }
+
+    fn notify_delegate_hide(&self) {
+        let mtm = MainThreadMarker::from(self);
+        let app = objc2_app_kit::NSApplication::sharedApplication(mtm);
+        if let Some(delegate) = app.delegate() {
+            let _: () = unsafe { msg_send![&*delegate, hideCommandPalette: self] };
+        }
+    }
+}
+
+/// Rect of the `row`-th visible result row.
+fn row_rect(bounds: NSRect, row: usize) -> CGRect {
+    CGRect::new(
+        CGPoint::new(PADDING, PADDING + SEARCH_HEIGHT + row as CGFloat * ROW_HEIGHT),
+        CGSize::new(bounds.size.width - PADDING * 2.0, ROW_HEIGHT),
+    )
+}
+
+/// Score `candidate` against a lowercase `query` as a fuzzy subsequence match.
+///
+/// Returns None when the query characters don't all appear in order. Consecutive
+/// matches and matches at word starts score higher.
+fn fuzzy_score(query: &str, candidate: &str) -> Option<i32> {
+    if query.is_empty() {
+        return Some(0);
+    }
+    let cand: Vec<char> = candidate.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut pos = 0;
+    let mut prev_match: Option<usize> = None;
+
+    for qc in query.chars().filter(|c| !c.is_whitespace()) {
+        let found = cand[pos..].iter().position(|&c| c == qc)? + pos;
+        score += 1;
+        if prev_match.is_some_and(|p| p + 1 == found) {
+            score += 5;
+        }
+        if found == 0 || !cand[found - 1].is_alphanumeric() {
+            score += 3;
+        }
+        prev_match = Some(found);
+        pos = found + 1;
+    }
+
+    // Prefer shorter titles when scores tie on matched characters
+    Some(score * 10 - cand.len() as i32 / 4)
+}
+
+/// Draw a single line of text in the current NSGraphicsContext.
+fn draw_label(text: &str, point: CGPoint, font_size: CGFloat, color: (CGFloat, CGFloat, CGFloat, CGFloat)) {
+    unsafe {
+        let font: *mut AnyObject = msg_send![objc2::class!(NSFont), systemFontOfSize: font_size];
+        let ns_color: *mut AnyObject = msg_send![
+            objc2::class!(NSColor),
+            colorWithRed: color.0,
+            green: color.1,
+            blue: color.2,
+            alpha: color.3
+        ];
+
+        let font_key = NSString::from_str("NSFont");
+        let color_key = NSString::from_str("NSColor");
+        let keys: [*const AnyObject; 2] =
+            [&*font_key as *const _ as *const _, &*color_key as *const _ as *const _];
+        let vals: [*const AnyObject; 2] = [font as *const _, ns_color as *const _];
+        let dict: *mut AnyObject = msg_send![
+            objc2::class!(NSDictionary),
+            dictionaryWithObjects: vals.as_ptr(),
+            forKeys: keys.as_ptr(),
+            count: 2usize
+        ];
+
+        let ns_text = NSString::from_str(text);
+        let _: () = msg_send![&*ns_text, drawAtPoint: point, withAttributes: dict];
+    }
+}
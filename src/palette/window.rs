@@ -0,0 +1,87 @@
+use std::cell::RefCell;
+
+use objc2::rc::Retained;
+use objc2::{msg_send, MainThreadOnly};
+use objc2_app_kit::{NSApplication, NSBackingStoreType, NSColor, NSWindow, NSWindowStyleMask};
+use objc2_core_foundation::{CGPoint, CGSize};
+use objc2_core_graphics::kCGOverlayWindowLevel;
+use objc2_foundation::{MainThreadMarker, NSRect};
+
+use super::view::{CommandPaletteView, PALETTE_HEIGHT, PALETTE_WIDTH};
+use crate::overlay::window::{KeyableWindow, KeyableWindowIvars};
+
+/// Floating Cmd+K command palette shown over the overlay or the editor.
+pub struct CommandPalette {
+    pub window: Retained<KeyableWindow>,
+    pub view: Retained<CommandPaletteView>,
+    /// Window that was key before the palette opened; focus returns to it on hide.
+    previous_key_window: RefCell<Option<Retained<NSWindow>>>,
+}
+
+impl CommandPalette {
+    pub fn new(mtm: MainThreadMarker) -> Self {
+        let frame = NSRect::new(CGPoint::ZERO, CGSize::new(PALETTE_WIDTH, PALETTE_HEIGHT));
+
+        let this = KeyableWindow::alloc(mtm).set_ivars(KeyableWindowIvars {});
+        let window: Retained<KeyableWindow> = unsafe {
+            msg_send![
+                super(this),
+                initWithContentRect: frame,
+                styleMask: NSWindowStyleMask::Borderless,
+                backing: NSBackingStoreType::Buffered,
+                defer: false
+            ]
+        };
+
+        // Above the overlay, toolbar, and color panel
+        window.setLevel((kCGOverlayWindowLevel + 3) as _);
+        window.setOpaque(false);
+        window.setBackgroundColor(Some(&NSColor::clearColor()));
+        window.setHasShadow(true);
+        unsafe { window.setReleasedWhenClosed(false) };
+
+        let view = CommandPaletteView::new(mtm, frame);
+        view.setWantsLayer(true);
+        if let Some(layer) = view.layer() {
+            unsafe {
+                let _: () = msg_send![&*layer, setCornerRadius: 10.0f64];
+                let _: () = msg_send![&*layer, setMasksToBounds: true];
+            }
+        }
+        window.setContentView(Some(&view));
+
+        CommandPalette {
+            window,
+            view,
+            previous_key_window: RefCell::new(None),
+        }
+    }
+
+    /// Show the palette centered horizontally near the top of `parent`.
+    pub fn show_over(&self, parent: &NSWindow, mtm: MainThreadMarker) {
+        self.view.reset();
+
+        let parent_frame = parent.frame();
+        let x = parent_frame.origin.x + (parent_frame.size.width - PALETTE_WIDTH) / 2.0;
+        let y = parent_frame.origin.y + parent_frame.size.height * 0.75 - PALETTE_HEIGHT / 2.0;
+        let y = y.max(parent_frame.origin.y);
+        self.window.setFrameOrigin(CGPoint::new(x, y));
+
+        *self.previous_key_window.borrow_mut() = NSApplication::sharedApplication(mtm).keyWindow();
+
+        self.window.makeKeyAndOrderFront(None);
+        self.window.makeFirstResponder(Some(&*self.view));
+    }
+
+    /// Hide the palette and give focus back to the window it was opened over.
+    pub fn hide(&self) {
+        self.window.orderOut(None);
+        if let Some(prev) = self.previous_key_window.borrow_mut().take() {
+            prev.makeKeyWindow();
+        }
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.window.isVisible()
+    }
+}
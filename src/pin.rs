@@ -0,0 +1,164 @@
+use objc2::rc::Retained;
+use objc2::runtime::AnyObject;
+use objc2::{define_class, msg_send, sel, DefinedClass, MainThreadOnly};
+use objc2_app_kit::{
+    NSApplication, NSBackingStoreType, NSColor, NSEvent, NSGraphicsContext, NSMenu, NSMenuItem,
+    NSView, NSWindow, NSWindowCollectionBehavior, NSWindowStyleMask,
+};
+use objc2_core_foundation::{CFRetained, CGPoint, CGRect, CGSize};
+use objc2_core_graphics::{CGContext, CGImage, CGInterpolationQuality, kCGFloatingWindowLevel};
+use objc2_foundation::{MainThreadMarker, NSObjectProtocol, NSRect, NSString};
+
+// --- View showing the pinned image ---
+
+pub struct PinnedViewIvars {
+    image: CFRetained<CGImage>,
+}
+
+define_class!(
+    #[unsafe(super(NSView))]
+    #[thread_kind = MainThreadOnly]
+    #[name = "PinnedView"]
+    #[ivars = PinnedViewIvars]
+    pub struct PinnedView;
+
+    unsafe impl NSObjectProtocol for PinnedView {}
+
+    impl PinnedView {
+        #[unsafe(method(drawRect:))]
+        fn draw_rect(&self, _dirty_rect: NSRect) {
+            let Some(context) = NSGraphicsContext::currentContext() else {
+                return;
+            };
+            let cg = context.CGContext();
+            let bounds = self.bounds();
+
+            CGContext::set_interpolation_quality(Some(&cg), CGInterpolationQuality::High);
+            CGContext::draw_image(Some(&cg), bounds, Some(&self.ivars().image));
+
+            // Hairline outline so the pin stands apart from what's behind it
+            CGContext::set_rgb_stroke_color(Some(&cg), 0.5, 0.5, 0.5, 0.8);
+            CGContext::set_line_width(Some(&cg), 1.0);
+            CGContext::stroke_rect(
+                Some(&cg),
+                CGRect::new(
+                    CGPoint::new(0.5, 0.5),
+                    CGSize::new(bounds.size.width - 1.0, bounds.size.height - 1.0),
+                ),
+            );
+        }
+
+        #[unsafe(method(acceptsFirstMouse:))]
+        fn accepts_first_mouse(&self, _event: Option<&NSEvent>) -> bool {
+            true
+        }
+
+        /// Drag moves the pin; double-click closes it.
+        #[unsafe(method(mouseDown:))]
+        fn mouse_down(&self, event: &NSEvent) {
+            if event.clickCount() >= 2 {
+                self.notify_closed();
+            } else if let Some(window) = self.window() {
+                window.performWindowDragWithEvent(event);
+            }
+        }
+
+        #[unsafe(method(pinCopy:))]
+        fn pin_copy(&self, _sender: &AnyObject) {
+            if let Err(e) = crate::actions::copy_to_clipboard(&self.ivars().image) {
+                eprintln!("Failed to copy pinned image: {}", e);
+            }
+        }
+
+        #[unsafe(method(pinClose:))]
+        fn pin_close(&self, _sender: &AnyObject) {
+            self.notify_closed();
+        }
+    }
+);
+
+impl PinnedView {
+    fn new(mtm: MainThreadMarker, frame: NSRect, image: CFRetained<CGImage>) -> Retained<Self> {
+        let this = mtm.alloc().set_ivars(PinnedViewIvars { image });
+        let view: Retained<Self> = unsafe { msg_send![super(this), initWithFrame: frame] };
+
+        // Right-click menu
+        let menu = NSMenu::new(mtm);
+        for (title, action) in [("Copy", sel!(pinCopy:)), ("Close", sel!(pinClose:))] {
+            let item = unsafe {
+                NSMenuItem::initWithTitle_action_keyEquivalent(
+                    mtm.alloc(),
+                    &NSString::from_str(title),
+                    Some(action),
+                    &NSString::from_str(""),
+                )
+            };
+            unsafe { item.setTarget(Some(&view)) };
+            menu.addItem(&item);
+        }
+        unsafe { view.setMenu(Some(&menu)) };
+        view
+    }
+
+    /// Ask the app delegate to close this pin.
+    fn notify_closed(&self) {
+        let mtm = MainThreadMarker::from(self);
+        if let Some(delegate) = NSApplication::sharedApplication(mtm).delegate() {
+            let _: () = unsafe { msg_send![&*delegate, pinClosed: self] };
+        }
+    }
+}
+
+// --- Floating window holding one pinned screenshot ---
+
+pub struct PinnedWindow {
+    window: Retained<NSWindow>,
+    pub view: Retained<PinnedView>,
+}
+
+impl PinnedWindow {
+    /// Pin `image` over the given selection rect (in overlay local coordinates,
+    /// top-left origin). `screen_frame` is the overlay's frame in global AppKit coordinates.
+    pub fn new(
+        image: CFRetained<CGImage>,
+        selection: CGRect,
+        screen_frame: CGRect,
+        mtm: MainThreadMarker,
+    ) -> Self {
+        let window_frame = CGRect::new(
+            CGPoint::new(
+                screen_frame.origin.x + selection.origin.x,
+                screen_frame.origin.y + screen_frame.size.height
+                    - selection.origin.y - selection.size.height,
+            ),
+            selection.size,
+        );
+        let window = unsafe {
+            NSWindow::initWithContentRect_styleMask_backing_defer(
+                mtm.alloc(),
+                window_frame,
+                NSWindowStyleMask::Borderless,
+                NSBackingStoreType::Buffered,
+                false,
+            )
+        };
+
+        window.setLevel(kCGFloatingWindowLevel as _);
+        window.setOpaque(false);
+        window.setBackgroundColor(Some(&NSColor::clearColor()));
+        window.setHasShadow(true);
+        window.setCollectionBehavior(NSWindowCollectionBehavior::CanJoinAllSpaces);
+        unsafe { window.setReleasedWhenClosed(false) };
+
+        let view = PinnedView::new(mtm, NSRect::new(CGPoint::ZERO, selection.size), image);
+        window.setContentView(Some(&view));
+        window.orderFront(None);
+
+        PinnedWindow { window, view }
+    }
+
+    pub fn close(&self) {
+        self.window.orderOut(None);
+        self.window.close();
+    }
+}
@@ -0,0 +1,1113 @@
+use global_hotkey::hotkey::HotKey;
+use objc2::rc::Retained;
+use std::path::{Path, PathBuf};
+
+use objc2_core_foundation::{CGFloat, CGPoint, CGRect, CGSize};
+use objc2_foundation::{NSString, NSUserDefaults};
+
+use crate::editor::view::CropAspect;
+use crate::hotkey::HotkeyAction;
+use crate::overlay::view::ActiveTool;
+
+const POST_CAPTURE_ACTION_KEY: &str = "PostCaptureAction";
+const RECORDING_LIMIT_KEY: &str = "RecordingLimit";
+const RECORD_MICROPHONE_KEY: &str = "RecordMicrophone";
+const VIDEO_QUALITY_KEY: &str = "VideoQuality";
+const VIDEO_CODEC_KEY: &str = "VideoCodec";
+const TIMELINE_UNIT_KEY: &str = "TimelineUnit";
+const SCROLL_SETTLE_DELAY_KEY: &str = "ScrollSettleDelay";
+const SCROLL_STEP_LIMIT_KEY: &str = "ScrollStepLimit";
+const SCROLL_STEP_SIZE_KEY: &str = "ScrollStepSize";
+const SCROLL_FROZEN_TOP_KEY: &str = "ScrollFrozenTop";
+const SCROLL_FROZEN_BOTTOM_KEY: &str = "ScrollFrozenBottom";
+const SELECTION_ASPECT_KEY: &str = "SelectionAspect";
+const CROSSHAIR_GUIDES_KEY: &str = "CrosshairGuides";
+const VERTICAL_TOOLBAR_KEY: &str = "VerticalToolbar";
+const SNAP_GRID_KEY: &str = "SnapGrid";
+const KEEP_INSIDE_SELECTION_KEY: &str = "KeepAnnotationsInsideSelection";
+const ANNOTATION_TOOL_KEY: &str = "AnnotationTool";
+const ANNOTATION_COLOR_KEY: &str = "AnnotationColor";
+const ANNOTATION_STROKE_KEY: &str = "AnnotationStroke";
+const SAVE_FOLDER_KEY: &str = "SaveFolder";
+const FILENAME_TEMPLATE_KEY: &str = "FilenameTemplate";
+const SAVE_SEQUENCE_KEY: &str = "SaveSequence";
+const AUTO_SAVE_KEY: &str = "AutoSave";
+const COPY_ON_SAVE_KEY: &str = "CopyOnSave";
+const COPY_SAVED_FILE_KEY: &str = "CopySavedFile";
+const NOTIFY_ON_SAVE_KEY: &str = "NotifyOnSave";
+const SAVE_SOUND_KEY: &str = "PlaySaveSound";
+const REVEAL_ON_SAVE_KEY: &str = "RevealOnSave";
+const KEEP_HISTORY_KEY: &str = "KeepCaptureHistory";
+const UPLOAD_URL_KEY: &str = "UploadURL";
+// Only whether a header is set; the header itself lives in the Keychain
+const UPLOAD_AUTH_CONFIGURED_KEY: &str = "UploadAuthConfigured";
+// Keychain account of the upload header
+const UPLOAD_AUTH_ACCOUNT: &str = "UploadAuthHeader";
+const IMAGE_FORMAT_KEY: &str = "ImageFormat";
+const JPEG_QUALITY_KEY: &str = "JpegQuality";
+const EXPORT_SCALE_KEY: &str = "ExportScale";
+const EXPORT_MAX_WIDTH_KEY: &str = "ExportMaxWidth";
+// Followed by the display ID, one remembered selection per display
+const LAST_SELECTION_KEY_PREFIX: &str = "LastSelection";
+const CAPTURE_HOTKEY_KEY: &str = "CaptureHotkey";
+const RECORD_HOTKEY_KEY: &str = "RecordHotkey";
+const SCROLL_CAPTURE_HOTKEY_KEY: &str = "ScrollCaptureHotkey";
+const ANNOTATE_CLIPBOARD_HOTKEY_KEY: &str = "AnnotateClipboardHotkey";
+// Stored inverted so hotkeys are on when the key is missing
+const CAPTURE_HOTKEY_DISABLED_KEY: &str = "CaptureHotkeyDisabled";
+const RECORD_HOTKEY_DISABLED_KEY: &str = "RecordHotkeyDisabled";
+const SCROLL_CAPTURE_HOTKEY_DISABLED_KEY: &str = "ScrollCaptureHotkeyDisabled";
+const ANNOTATE_CLIPBOARD_HOTKEY_DISABLED_KEY: &str = "AnnotateClipboardHotkeyDisabled";
+
+/// What happens once a screenshot region has been selected.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum PostCaptureAction {
+    /// Show the annotation toolbar and let the user choose (default).
+    ShowToolbar,
+    /// Copy the selection to the clipboard immediately.
+    Copy,
+    /// Save the selection in the chosen image format to the save folder without prompting.
+    QuickSave,
+    /// Open the selection in the editor window.
+    OpenEditor,
+}
+
+impl PostCaptureAction {
+    /// All actions, in menu order. The index doubles as the menu item tag.
+    pub const ALL: [PostCaptureAction; 4] = [
+        PostCaptureAction::ShowToolbar,
+        PostCaptureAction::Copy,
+        PostCaptureAction::QuickSave,
+        PostCaptureAction::OpenEditor,
+    ];
+
+    pub fn title(self) -> &'static str {
+        match self {
+            PostCaptureAction::ShowToolbar => "Show Toolbar",
+            PostCaptureAction::Copy => "Copy to Clipboard",
+            PostCaptureAction::QuickSave => "Save to Folder",
+            PostCaptureAction::OpenEditor => "Open in Editor",
+        }
+    }
+
+    fn key(self) -> &'static str {
+        match self {
+            PostCaptureAction::ShowToolbar => "toolbar",
+            PostCaptureAction::Copy => "copy",
+            PostCaptureAction::QuickSave => "save",
+            PostCaptureAction::OpenEditor => "edit",
+        }
+    }
+
+    fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|a| a.key() == key)
+    }
+}
+
+/// Longest a screen recording may run before it stops by itself.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum RecordingLimit {
+    /// Record until stopped (default).
+    Unlimited,
+    Seconds30,
+    Minutes1,
+    Minutes5,
+    Minutes10,
+    Minutes30,
+}
+
+impl RecordingLimit {
+    /// All limits, in menu order. The index doubles as the menu item tag.
+    pub const ALL: [RecordingLimit; 6] = [
+        RecordingLimit::Unlimited,
+        RecordingLimit::Seconds30,
+        RecordingLimit::Minutes1,
+        RecordingLimit::Minutes5,
+        RecordingLimit::Minutes10,
+        RecordingLimit::Minutes30,
+    ];
+
+    pub fn title(self) -> &'static str {
+        match self {
+            RecordingLimit::Unlimited => "Unlimited",
+            RecordingLimit::Seconds30 => "30 Seconds",
+            RecordingLimit::Minutes1 => "1 Minute",
+            RecordingLimit::Minutes5 => "5 Minutes",
+            RecordingLimit::Minutes10 => "10 Minutes",
+            RecordingLimit::Minutes30 => "30 Minutes",
+        }
+    }
+
+    /// The limit in seconds, or None when unlimited.
+    pub fn seconds(self) -> Option<u64> {
+        match self {
+            RecordingLimit::Unlimited => None,
+            RecordingLimit::Seconds30 => Some(30),
+            RecordingLimit::Minutes1 => Some(60),
+            RecordingLimit::Minutes5 => Some(5 * 60),
+            RecordingLimit::Minutes10 => Some(10 * 60),
+            RecordingLimit::Minutes30 => Some(30 * 60),
+        }
+    }
+
+    fn key(self) -> &'static str {
+        match self {
+            RecordingLimit::Unlimited => "unlimited",
+            RecordingLimit::Seconds30 => "30s",
+            RecordingLimit::Minutes1 => "1m",
+            RecordingLimit::Minutes5 => "5m",
+            RecordingLimit::Minutes10 => "10m",
+            RecordingLimit::Minutes30 => "30m",
+        }
+    }
+
+    fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|l| l.key() == key)
+    }
+}
+
+/// Grid that annotations and the capture selection snap to while drawn or moved.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum SnapGrid {
+    /// No grid (default).
+    Off,
+    Points4,
+    Points8,
+    Points16,
+    Points32,
+}
+
+impl SnapGrid {
+    /// All grids, in menu order. The index doubles as the menu item tag.
+    pub const ALL: [SnapGrid; 5] = [
+        SnapGrid::Off,
+        SnapGrid::Points4,
+        SnapGrid::Points8,
+        SnapGrid::Points16,
+        SnapGrid::Points32,
+    ];
+
+    pub fn title(self) -> &'static str {
+        match self {
+            SnapGrid::Off => "Off",
+            SnapGrid::Points4 => "4 pt",
+            SnapGrid::Points8 => "8 pt",
+            SnapGrid::Points16 => "16 pt",
+            SnapGrid::Points32 => "32 pt",
+        }
+    }
+
+    /// Grid spacing in points, or None when off.
+    pub fn spacing(self) -> Option<CGFloat> {
+        match self {
+            SnapGrid::Off => None,
+            SnapGrid::Points4 => Some(4.0),
+            SnapGrid::Points8 => Some(8.0),
+            SnapGrid::Points16 => Some(16.0),
+            SnapGrid::Points32 => Some(32.0),
+        }
+    }
+
+    fn key(self) -> &'static str {
+        match self {
+            SnapGrid::Off => "off",
+            SnapGrid::Points4 => "4",
+            SnapGrid::Points8 => "8",
+            SnapGrid::Points16 => "16",
+            SnapGrid::Points32 => "32",
+        }
+    }
+
+    fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|g| g.key() == key)
+    }
+}
+
+/// Encoding quality for recordings and video exports.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum VideoQuality {
+    /// Smallest files; fine for text-heavy screen content.
+    Low,
+    /// Balanced size and quality (default).
+    Medium,
+    High,
+}
+
+impl VideoQuality {
+    /// All qualities, in menu order. The index doubles as the menu item tag.
+    pub const ALL: [VideoQuality; 3] =
+        [VideoQuality::Low, VideoQuality::Medium, VideoQuality::High];
+
+    pub fn title(self) -> &'static str {
+        match self {
+            VideoQuality::Low => "Low (Smaller Files)",
+            VideoQuality::Medium => "Medium",
+            VideoQuality::High => "High",
+        }
+    }
+
+    /// Average H.264 bits per pixel per frame; the bitrate scales with frame size and rate.
+    pub fn bits_per_pixel(self) -> f64 {
+        match self {
+            VideoQuality::Low => 0.04,
+            VideoQuality::Medium => 0.1,
+            VideoQuality::High => 0.2,
+        }
+    }
+
+    /// Constant-quality factor for VP9 (lower is better).
+    pub fn vp9_crf(self) -> u32 {
+        match self {
+            VideoQuality::Low => 40,
+            VideoQuality::Medium => 32,
+            VideoQuality::High => 24,
+        }
+    }
+
+    fn key(self) -> &'static str {
+        match self {
+            VideoQuality::Low => "low",
+            VideoQuality::Medium => "medium",
+            VideoQuality::High => "high",
+        }
+    }
+
+    fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|q| q.key() == key)
+    }
+}
+
+/// File format screenshots are saved in.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ImageFormat {
+    /// Lossless, keeps transparency (default).
+    Png,
+    /// Much smaller for large screenshots; transparency becomes white.
+    Jpeg,
+    /// Smaller than PNG and keeps transparency; needs a macOS whose ImageIO can write it.
+    WebP,
+}
+
+impl ImageFormat {
+    /// All formats, in save-panel order. The index doubles as the pop-up item index.
+    pub const ALL: [ImageFormat; 3] = [ImageFormat::Png, ImageFormat::Jpeg, ImageFormat::WebP];
+
+    pub fn title(self) -> &'static str {
+        match self {
+            ImageFormat::Png => "PNG",
+            ImageFormat::Jpeg => "JPEG",
+            ImageFormat::WebP => "WebP",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            ImageFormat::Png => "png",
+            ImageFormat::Jpeg => "jpg",
+            ImageFormat::WebP => "webp",
+        }
+    }
+
+    /// Uniform type identifier handed to `CGImageDestination`.
+    pub fn uti(self) -> &'static str {
+        match self {
+            ImageFormat::Png => "public.png",
+            ImageFormat::Jpeg => "public.jpeg",
+            ImageFormat::WebP => "org.webmproject.webp",
+        }
+    }
+
+    fn key(self) -> &'static str {
+        match self {
+            ImageFormat::Png => "png",
+            ImageFormat::Jpeg => "jpeg",
+            ImageFormat::WebP => "webp",
+        }
+    }
+
+    fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|f| f.key() == key)
+    }
+}
+
+/// Codec for recordings and MP4 exports.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum VideoCodec {
+    /// Plays everywhere (default).
+    H264,
+    /// Smaller files at the same quality, but not every player supports it.
+    Hevc,
+}
+
+impl VideoCodec {
+    /// All codecs, in menu order. The index doubles as the menu item tag.
+    pub const ALL: [VideoCodec; 2] = [VideoCodec::H264, VideoCodec::Hevc];
+
+    pub fn title(self) -> &'static str {
+        match self {
+            VideoCodec::H264 => "H.264",
+            VideoCodec::Hevc => "HEVC (H.265)",
+        }
+    }
+
+    /// Menu title with a note on the trade-off.
+    pub fn menu_title(self) -> &'static str {
+        match self {
+            VideoCodec::H264 => "H.264 (Most Compatible)",
+            VideoCodec::Hevc => "HEVC (Smaller, Less Compatible)",
+        }
+    }
+
+    /// Bitrate relative to H.264 for the same visual quality.
+    pub fn bit_rate_factor(self) -> f64 {
+        match self {
+            VideoCodec::H264 => 1.0,
+            VideoCodec::Hevc => 0.6,
+        }
+    }
+
+    fn key(self) -> &'static str {
+        match self {
+            VideoCodec::H264 => "h264",
+            VideoCodec::Hevc => "hevc",
+        }
+    }
+
+    fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|c| c.key() == key)
+    }
+}
+
+/// How the editor labels positions on the timeline.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum TimelineUnit {
+    /// Frame indices (default).
+    Frames,
+    /// Elapsed time as MM:SS.mmm, derived from the video's frame rate.
+    Time,
+}
+
+impl TimelineUnit {
+    /// All units, in menu order. The index doubles as the menu item tag.
+    pub const ALL: [TimelineUnit; 2] = [TimelineUnit::Frames, TimelineUnit::Time];
+
+    pub fn title(self) -> &'static str {
+        match self {
+            TimelineUnit::Frames => "Frames",
+            TimelineUnit::Time => "Time (MM:SS.mmm)",
+        }
+    }
+
+    /// Label for `frame` in this unit.
+    pub fn format(self, frame: usize, fps: f64) -> String {
+        match self {
+            TimelineUnit::Frames => frame.to_string(),
+            TimelineUnit::Time => {
+                let millis = (frame as f64 / fps.max(1.0) * 1000.0).round() as u64;
+                format!(
+                    "{:02}:{:02}.{:03}",
+                    millis / 60_000,
+                    millis / 1000 % 60,
+                    millis % 1000
+                )
+            }
+        }
+    }
+
+    /// Parse a typed label back into a frame index. Times accept `SS.mmm`,
+    /// `MM:SS.mmm`, or `H:MM:SS.mmm`.
+    pub fn parse(self, text: &str, fps: f64) -> Option<usize> {
+        let text = text.trim();
+        match self {
+            TimelineUnit::Frames => text.parse().ok(),
+            TimelineUnit::Time => {
+                let mut seconds = 0.0;
+                for part in text.split(':') {
+                    let value: f64 = part.trim().parse().ok()?;
+                    if value < 0.0 {
+                        return None;
+                    }
+                    seconds = seconds * 60.0 + value;
+                }
+                Some((seconds * fps.max(1.0)).round() as usize)
+            }
+        }
+    }
+
+    fn key(self) -> &'static str {
+        match self {
+            TimelineUnit::Frames => "frames",
+            TimelineUnit::Time => "time",
+        }
+    }
+
+    fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|u| u.key() == key)
+    }
+}
+
+/// How long scroll capture waits after each scroll before capturing, so the
+/// page has time to render.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ScrollSettleDelay {
+    Short,
+    /// Half a second (default).
+    Normal,
+    Long,
+    /// For pages that load content as they scroll.
+    VeryLong,
+}
+
+impl ScrollSettleDelay {
+    /// All delays, in menu order. The index doubles as the menu item tag.
+    pub const ALL: [ScrollSettleDelay; 4] = [
+        ScrollSettleDelay::Short,
+        ScrollSettleDelay::Normal,
+        ScrollSettleDelay::Long,
+        ScrollSettleDelay::VeryLong,
+    ];
+
+    pub fn title(self) -> &'static str {
+        match self {
+            ScrollSettleDelay::Short => "0.25 Seconds",
+            ScrollSettleDelay::Normal => "0.5 Seconds",
+            ScrollSettleDelay::Long => "1 Second",
+            ScrollSettleDelay::VeryLong => "2 Seconds",
+        }
+    }
+
+    pub fn seconds(self) -> f64 {
+        match self {
+            ScrollSettleDelay::Short => 0.25,
+            ScrollSettleDelay::Normal => 0.5,
+            ScrollSettleDelay::Long => 1.0,
+            ScrollSettleDelay::VeryLong => 2.0,
+        }
+    }
+
+    fn key(self) -> &'static str {
+        match self {
+            ScrollSettleDelay::Short => "0.25s",
+            ScrollSettleDelay::Normal => "0.5s",
+            ScrollSettleDelay::Long => "1s",
+            ScrollSettleDelay::VeryLong => "2s",
+        }
+    }
+
+    fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|d| d.key() == key)
+    }
+}
+
+/// Most scroll steps a scroll capture takes before it stops by itself.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ScrollStepLimit {
+    Steps25,
+    /// Default.
+    Steps50,
+    Steps100,
+    Steps200,
+}
+
+impl ScrollStepLimit {
+    /// All limits, in menu order. The index doubles as the menu item tag.
+    pub const ALL: [ScrollStepLimit; 4] = [
+        ScrollStepLimit::Steps25,
+        ScrollStepLimit::Steps50,
+        ScrollStepLimit::Steps100,
+        ScrollStepLimit::Steps200,
+    ];
+
+    pub fn title(self) -> &'static str {
+        match self {
+            ScrollStepLimit::Steps25 => "25 Steps",
+            ScrollStepLimit::Steps50 => "50 Steps",
+            ScrollStepLimit::Steps100 => "100 Steps",
+            ScrollStepLimit::Steps200 => "200 Steps",
+        }
+    }
+
+    pub fn steps(self) -> usize {
+        match self {
+            ScrollStepLimit::Steps25 => 25,
+            ScrollStepLimit::Steps50 => 50,
+            ScrollStepLimit::Steps100 => 100,
+            ScrollStepLimit::Steps200 => 200,
+        }
+    }
+
+    fn key(self) -> &'static str {
+        match self {
+            ScrollStepLimit::Steps25 => "25",
+            ScrollStepLimit::Steps50 => "50",
+            ScrollStepLimit::Steps100 => "100",
+            ScrollStepLimit::Steps200 => "200",
+        }
+    }
+
+    fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|l| l.key() == key)
+    }
+}
+
+/// How far scroll capture scrolls each step, relative to the selected region.
+/// Smaller steps leave more overlap between frames: safer to stitch, but slower.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ScrollStepSize {
+    Half,
+    /// Default.
+    TwoThirds,
+    ThreeQuarters,
+}
+
+impl ScrollStepSize {
+    /// All sizes, in menu order. The index doubles as the menu item tag.
+    pub const ALL: [ScrollStepSize; 3] = [
+        ScrollStepSize::Half,
+        ScrollStepSize::TwoThirds,
+        ScrollStepSize::ThreeQuarters,
+    ];
+
+    pub fn title(self) -> &'static str {
+        match self {
+            ScrollStepSize::Half => "1/2 of Region (Safest)",
+            ScrollStepSize::TwoThirds => "2/3 of Region",
+            ScrollStepSize::ThreeQuarters => "3/4 of Region (Fastest)",
+        }
+    }
+
+    /// Fraction of the region scrolled per step.
+    pub fn fraction(self) -> f64 {
+        match self {
+            ScrollStepSize::Half => 0.5,
+            ScrollStepSize::TwoThirds => 2.0 / 3.0,
+            ScrollStepSize::ThreeQuarters => 0.75,
+        }
+    }
+
+    fn key(self) -> &'static str {
+        match self {
+            ScrollStepSize::Half => "1/2",
+            ScrollStepSize::TwoThirds => "2/3",
+            ScrollStepSize::ThreeQuarters => "3/4",
+        }
+    }
+
+    fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|s| s.key() == key)
+    }
+}
+
+/// Height of a sticky header or footer left out of scroll capture stitching.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum FrozenMargin {
+    /// Default.
+    None,
+    /// Detect the rows that stay identical in every frame.
+    Auto,
+    Points40,
+    Points80,
+    Points120,
+}
+
+impl FrozenMargin {
+    /// All margins, in menu order. The index doubles as the menu item tag.
+    pub const ALL: [FrozenMargin; 5] = [
+        FrozenMargin::None,
+        FrozenMargin::Auto,
+        FrozenMargin::Points40,
+        FrozenMargin::Points80,
+        FrozenMargin::Points120,
+    ];
+
+    pub fn title(self) -> &'static str {
+        match self {
+            FrozenMargin::None => "None",
+            FrozenMargin::Auto => "Detect Automatically",
+            FrozenMargin::Points40 => "40 pt",
+            FrozenMargin::Points80 => "80 pt",
+            FrozenMargin::Points120 => "120 pt",
+        }
+    }
+
+    /// Fixed height in points (zero for `None` and `Auto`).
+    pub fn points(self) -> f64 {
+        match self {
+            FrozenMargin::None | FrozenMargin::Auto => 0.0,
+            FrozenMargin::Points40 => 40.0,
+            FrozenMargin::Points80 => 80.0,
+            FrozenMargin::Points120 => 120.0,
+        }
+    }
+
+    fn key(self) -> &'static str {
+        match self {
+            FrozenMargin::None => "none",
+            FrozenMargin::Auto => "auto",
+            FrozenMargin::Points40 => "40",
+            FrozenMargin::Points80 => "80",
+            FrozenMargin::Points120 => "120",
+        }
+    }
+
+    fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|m| m.key() == key)
+    }
+}
+
+fn defaults() -> Retained<NSUserDefaults> {
+    NSUserDefaults::standardUserDefaults()
+}
+
+fn get_string(key: &str) -> Option<String> {
+    defaults()
+        .stringForKey(&NSString::from_str(key))
+        .map(|s| s.to_string())
+}
+
+fn set_string(key: &str, value: &str) {
+    let value = NSString::from_str(value);
+    unsafe { defaults().setObject_forKey(Some(&value), &NSString::from_str(key)) };
+}
+
+fn get_bool(key: &str) -> bool {
+    defaults().boolForKey(&NSString::from_str(key))
+}
+
+fn set_bool(key: &str, value: bool) {
+    defaults().setBool_forKey(value, &NSString::from_str(key));
+}
+
+/// The user's preferred post-capture action (defaults to showing the toolbar).
+pub fn post_capture_action() -> PostCaptureAction {
+    get_string(POST_CAPTURE_ACTION_KEY)
+        .and_then(|k| PostCaptureAction::from_key(&k))
+        .unwrap_or(PostCaptureAction::ShowToolbar)
+}
+
+pub fn set_post_capture_action(action: PostCaptureAction) {
+    set_string(POST_CAPTURE_ACTION_KEY, action.key());
+}
+
+/// The user's recording length limit (defaults to unlimited).
+pub fn recording_limit() -> RecordingLimit {
+    get_string(RECORDING_LIMIT_KEY)
+        .and_then(|k| RecordingLimit::from_key(&k))
+        .unwrap_or(RecordingLimit::Unlimited)
+}
+
+pub fn set_recording_limit(limit: RecordingLimit) {
+    set_string(RECORDING_LIMIT_KEY, limit.key());
+}
+
+/// Whether screen recordings include microphone audio (defaults to off).
+pub fn record_microphone() -> bool {
+    get_bool(RECORD_MICROPHONE_KEY)
+}
+
+pub fn set_record_microphone(enabled: bool) {
+    set_bool(RECORD_MICROPHONE_KEY, enabled);
+}
+
+/// The user's video quality (defaults to medium).
+pub fn video_quality() -> VideoQuality {
+    get_string(VIDEO_QUALITY_KEY)
+        .and_then(|k| VideoQuality::from_key(&k))
+        .unwrap_or(VideoQuality::Medium)
+}
+
+pub fn set_video_quality(quality: VideoQuality) {
+    set_string(VIDEO_QUALITY_KEY, quality.key());
+}
+
+/// The user's video codec (defaults to H.264).
+pub fn video_codec() -> VideoCodec {
+    get_string(VIDEO_CODEC_KEY)
+        .and_then(|k| VideoCodec::from_key(&k))
+        .unwrap_or(VideoCodec::H264)
+}
+
+pub fn set_video_codec(codec: VideoCodec) {
+    set_string(VIDEO_CODEC_KEY, codec.key());
+}
+
+/// The user's editor timeline unit (defaults to frames).
+pub fn timeline_unit() -> TimelineUnit {
+    get_string(TIMELINE_UNIT_KEY)
+        .and_then(|k| TimelineUnit::from_key(&k))
+        .unwrap_or(TimelineUnit::Frames)
+}
+
+pub fn set_timeline_unit(unit: TimelineUnit) {
+    set_string(TIMELINE_UNIT_KEY, unit.key());
+}
+
+/// Aspect ratio the overlay selection is locked to (defaults to free).
+pub fn selection_aspect() -> CropAspect {
+    get_string(SELECTION_ASPECT_KEY)
+        .and_then(|k| CropAspect::ALL.into_iter().find(|a| a.label() == k))
+        .unwrap_or(CropAspect::Free)
+}
+
+pub fn set_selection_aspect(aspect: CropAspect) {
+    set_string(SELECTION_ASPECT_KEY, aspect.label());
+}
+
+/// Whether the overlay draws crosshair guide lines through the cursor (defaults to off).
+pub fn crosshair_guides() -> bool {
+    get_bool(CROSSHAIR_GUIDES_KEY)
+}
+
+pub fn set_crosshair_guides(enabled: bool) {
+    set_bool(CROSSHAIR_GUIDES_KEY, enabled);
+}
+
+/// Whether the annotation toolbar stacks its buttons in a column (defaults to a row).
+pub fn vertical_toolbar() -> bool {
+    get_bool(VERTICAL_TOOLBAR_KEY)
+}
+
+pub fn set_vertical_toolbar(vertical: bool) {
+    set_bool(VERTICAL_TOOLBAR_KEY, vertical);
+}
+
+/// The grid annotations and the selection snap to (defaults to off).
+pub fn snap_grid() -> SnapGrid {
+    get_string(SNAP_GRID_KEY)
+        .and_then(|k| SnapGrid::from_key(&k))
+        .unwrap_or(SnapGrid::Off)
+}
+
+pub fn set_snap_grid(grid: SnapGrid) {
+    set_string(SNAP_GRID_KEY, grid.key());
+}
+
+/// Whether overlay annotations are kept inside the selection while drawn and
+/// moved (defaults to off).
+pub fn keep_inside_selection() -> bool {
+    get_bool(KEEP_INSIDE_SELECTION_KEY)
+}
+
+pub fn set_keep_inside_selection(enabled: bool) {
+    set_bool(KEEP_INSIDE_SELECTION_KEY, enabled);
+}
+
+/// The last annotation tool picked, if any.
+pub fn annotation_tool() -> Option<ActiveTool> {
+    let name = get_string(ANNOTATION_TOOL_KEY)?;
+    ActiveTool::ALL.into_iter().find(|t| format!("{:?}", t) == name)
+}
+
+pub fn set_annotation_tool(tool: ActiveTool) {
+    set_string(ANNOTATION_TOOL_KEY, &format!("{:?}", tool));
+}
+
+/// The last annotation color picked (sRGB components), if any.
+pub fn annotation_color() -> Option<(f64, f64, f64)> {
+    let value = get_string(ANNOTATION_COLOR_KEY)?;
+    let parts: Vec<f64> = value.split(',').map(|p| p.trim().parse().ok()).collect::<Option<_>>()?;
+    let &[r, g, b] = parts.as_slice() else {
+        return None;
+    };
+    Some((r.clamp(0.0, 1.0), g.clamp(0.0, 1.0), b.clamp(0.0, 1.0)))
+}
+
+pub fn set_annotation_color(color: (f64, f64, f64)) {
+    set_string(ANNOTATION_COLOR_KEY, &format!("{},{},{}", color.0, color.1, color.2));
+}
+
+/// Index of the last stroke width picked (0 thin, 1 medium, 2 thick), if any.
+pub fn annotation_stroke() -> Option<usize> {
+    get_string(ANNOTATION_STROKE_KEY)
+        .and_then(|s| s.parse::<usize>().ok())
+        .filter(|&i| i < crate::toolbar::view::STROKE_WIDTHS.len())
+}
+
+pub fn set_annotation_stroke(index: usize) {
+    set_string(ANNOTATION_STROKE_KEY, &index.to_string());
+}
+
+/// Template used when none is set: `{date}`, `{time}`, and `{seq}` are filled in when saving.
+pub const DEFAULT_FILENAME_TEMPLATE: &str = "clipshot_{date}_{time}";
+
+/// JPEG quality range offered in the save panel, and the starting value.
+pub const MIN_JPEG_QUALITY: f64 = 0.1;
+pub const DEFAULT_JPEG_QUALITY: f64 = 0.8;
+
+/// Export scales offered in the save panel, in percent of the captured size.
+pub const EXPORT_SCALES: [u32; 4] = [100, 75, 50, 25];
+
+/// Folder that saves start in (and auto-save writes to); defaults to the Desktop.
+pub fn save_folder() -> PathBuf {
+    get_string(SAVE_FOLDER_KEY).map(PathBuf::from).unwrap_or_else(|| {
+        let home = std::env::var_os("HOME").unwrap_or_default();
+        PathBuf::from(home).join("Desktop")
+    })
+}
+
+pub fn set_save_folder(folder: &Path) {
+    set_string(SAVE_FOLDER_KEY, &folder.to_string_lossy());
+}
+
+/// File name template for saved captures, without extension.
+pub fn filename_template() -> String {
+    get_string(FILENAME_TEMPLATE_KEY)
+        .filter(|t| !t.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_FILENAME_TEMPLATE.to_string())
+}
+
+pub fn set_filename_template(template: &str) {
+    set_string(FILENAME_TEMPLATE_KEY, template);
+}
+
+/// The next `{seq}` number (starting at 1), advancing the stored counter.
+pub fn next_save_sequence() -> u64 {
+    let seq = get_string(SAVE_SEQUENCE_KEY).and_then(|s| s.parse().ok()).unwrap_or(0) + 1;
+    set_string(SAVE_SEQUENCE_KEY, &seq.to_string());
+    seq
+}
+
+/// The format last chosen in the image save panel (defaults to PNG).
+pub fn image_format() -> ImageFormat {
+    get_string(IMAGE_FORMAT_KEY)
+        .and_then(|k| ImageFormat::from_key(&k))
+        .unwrap_or(ImageFormat::Png)
+}
+
+pub fn set_image_format(format: ImageFormat) {
+    set_string(IMAGE_FORMAT_KEY, format.key());
+}
+
+/// JPEG compression quality from 0.1 to 1.0 (defaults to 0.8).
+pub fn jpeg_quality() -> f64 {
+    get_string(JPEG_QUALITY_KEY)
+        .and_then(|s| s.parse::<f64>().ok())
+        .map(|q| q.clamp(MIN_JPEG_QUALITY, 1.0))
+        .unwrap_or(DEFAULT_JPEG_QUALITY)
+}
+
+pub fn set_jpeg_quality(quality: f64) {
+    set_string(JPEG_QUALITY_KEY, &quality.clamp(MIN_JPEG_QUALITY, 1.0).to_string());
+}
+
+/// Size last picked in the save panel, in percent of the captured size (defaults to 100).
+pub fn export_scale() -> u32 {
+    get_string(EXPORT_SCALE_KEY)
+        .and_then(|s| s.parse().ok())
+        .filter(|scale| EXPORT_SCALES.contains(scale))
+        .unwrap_or(100)
+}
+
+pub fn set_export_scale(percent: u32) {
+    set_string(EXPORT_SCALE_KEY, &percent.to_string());
+}
+
+/// Max width last entered in the save panel, in pixels; `None` leaves the width alone.
+pub fn export_max_width() -> Option<usize> {
+    get_string(EXPORT_MAX_WIDTH_KEY).and_then(|s| s.parse().ok()).filter(|&w| w > 0)
+}
+
+pub fn set_export_max_width(width: Option<usize>) {
+    set_string(EXPORT_MAX_WIDTH_KEY, &width.unwrap_or(0).to_string());
+}
+
+/// Whether Save writes straight to the save folder instead of asking (defaults to off).
+pub fn auto_save() -> bool {
+    get_bool(AUTO_SAVE_KEY)
+}
+
+pub fn set_auto_save(enabled: bool) {
+    set_bool(AUTO_SAVE_KEY, enabled);
+}
+
+/// Whether saving also puts the result on the clipboard (defaults to off).
+pub fn copy_on_save() -> bool {
+    get_bool(COPY_ON_SAVE_KEY)
+}
+
+pub fn set_copy_on_save(enabled: bool) {
+    set_bool(COPY_ON_SAVE_KEY, enabled);
+}
+
+/// Whether saving puts the saved file itself on the clipboard (defaults to off).
+pub fn copy_saved_file() -> bool {
+    get_bool(COPY_SAVED_FILE_KEY)
+}
+
+pub fn set_copy_saved_file(enabled: bool) {
+    set_bool(COPY_SAVED_FILE_KEY, enabled);
+}
+
+/// Whether a save posts a "Saved" banner to Notification Center (defaults to off).
+pub fn notify_on_save() -> bool {
+    get_bool(NOTIFY_ON_SAVE_KEY)
+}
+
+pub fn set_notify_on_save(enabled: bool) {
+    set_bool(NOTIFY_ON_SAVE_KEY, enabled);
+}
+
+/// Whether a save plays the system screenshot sound (defaults to off).
+pub fn save_sound() -> bool {
+    get_bool(SAVE_SOUND_KEY)
+}
+
+pub fn set_save_sound(enabled: bool) {
+    set_bool(SAVE_SOUND_KEY, enabled);
+}
+
+/// Whether a save selects the new file in Finder (defaults to off).
+pub fn reveal_on_save() -> bool {
+    get_bool(REVEAL_ON_SAVE_KEY)
+}
+
+pub fn set_reveal_on_save(enabled: bool) {
+    set_bool(REVEAL_ON_SAVE_KEY, enabled);
+}
+
+/// Whether captures are kept in "Recent Captures" (defaults to off, since it
+/// stores copies of every capture).
+pub fn keep_history() -> bool {
+    get_bool(KEEP_HISTORY_KEY)
+}
+
+pub fn set_keep_history(enabled: bool) {
+    set_bool(KEEP_HISTORY_KEY, enabled);
+}
+
+/// Endpoint that uploads are POSTed to (unset until configured).
+pub fn upload_url() -> Option<String> {
+    get_string(UPLOAD_URL_KEY).filter(|s| !s.is_empty())
+}
+
+pub fn set_upload_url(url: &str) {
+    set_string(UPLOAD_URL_KEY, url);
+}
+
+/// Extra header sent with uploads, as "Name: value" (e.g. an Authorization token).
+/// Kept in the Keychain.
+pub fn upload_auth_header() -> Option<String> {
+    if !get_bool(UPLOAD_AUTH_CONFIGURED_KEY) {
+        return None;
+    }
+    crate::keychain::password(UPLOAD_AUTH_ACCOUNT).filter(|s| !s.is_empty())
+}
+
+pub fn set_upload_auth_header(header: &str) -> Result<(), String> {
+    if header.is_empty() {
+        crate::keychain::delete_password(UPLOAD_AUTH_ACCOUNT)?;
+    } else {
+        crate::keychain::set_password(UPLOAD_AUTH_ACCOUNT, header)?;
+    }
+    set_bool(UPLOAD_AUTH_CONFIGURED_KEY, !header.is_empty());
+    Ok(())
+}
+
+/// The user's scroll capture settle delay (defaults to half a second).
+pub fn scroll_settle_delay() -> ScrollSettleDelay {
+    get_string(SCROLL_SETTLE_DELAY_KEY)
+        .and_then(|k| ScrollSettleDelay::from_key(&k))
+        .unwrap_or(ScrollSettleDelay::Normal)
+}
+
+pub fn set_scroll_settle_delay(delay: ScrollSettleDelay) {
+    set_string(SCROLL_SETTLE_DELAY_KEY, delay.key());
+}
+
+/// The user's scroll capture step limit (defaults to 50).
+pub fn scroll_step_limit() -> ScrollStepLimit {
+    get_string(SCROLL_STEP_LIMIT_KEY)
+        .and_then(|k| ScrollStepLimit::from_key(&k))
+        .unwrap_or(ScrollStepLimit::Steps50)
+}
+
+pub fn set_scroll_step_limit(limit: ScrollStepLimit) {
+    set_string(SCROLL_STEP_LIMIT_KEY, limit.key());
+}
+
+/// The user's scroll capture step size (defaults to 2/3 of the region).
+pub fn scroll_step_size() -> ScrollStepSize {
+    get_string(SCROLL_STEP_SIZE_KEY)
+        .and_then(|k| ScrollStepSize::from_key(&k))
+        .unwrap_or(ScrollStepSize::TwoThirds)
+}
+
+pub fn set_scroll_step_size(size: ScrollStepSize) {
+    set_string(SCROLL_STEP_SIZE_KEY, size.key());
+}
+
+/// The user's frozen top margin for scroll capture stitching (defaults to none).
+pub fn scroll_frozen_top() -> FrozenMargin {
+    get_string(SCROLL_FROZEN_TOP_KEY)
+        .and_then(|k| FrozenMargin::from_key(&k))
+        .unwrap_or(FrozenMargin::None)
+}
+
+pub fn set_scroll_frozen_top(margin: FrozenMargin) {
+    set_string(SCROLL_FROZEN_TOP_KEY, margin.key());
+}
+
+/// The user's frozen bottom margin for scroll capture stitching (defaults to none).
+pub fn scroll_frozen_bottom() -> FrozenMargin {
+    get_string(SCROLL_FROZEN_BOTTOM_KEY)
+        .and_then(|k| FrozenMargin::from_key(&k))
+        .unwrap_or(FrozenMargin::None)
+}
+
+pub fn set_scroll_frozen_bottom(margin: FrozenMargin) {
+    set_string(SCROLL_FROZEN_BOTTOM_KEY, margin.key());
+}
+
+fn hotkey_key(action: HotkeyAction) -> &'static str {
+    match action {
+        HotkeyAction::Capture => CAPTURE_HOTKEY_KEY,
+        HotkeyAction::Record => RECORD_HOTKEY_KEY,
+        HotkeyAction::ScrollCapture => SCROLL_CAPTURE_HOTKEY_KEY,
+        HotkeyAction::AnnotateClipboard => ANNOTATE_CLIPBOARD_HOTKEY_KEY,
+    }
+}
+
+/// The user's global hotkey for `action`, or None if it was never remapped.
+pub fn hotkey(action: HotkeyAction) -> Option<HotKey> {
+    get_string(hotkey_key(action)).and_then(|s| s.parse().ok())
+}
+
+pub fn set_hotkey(action: HotkeyAction, hotkey: HotKey) {
+    set_string(hotkey_key(action), &hotkey.into_string());
+}
+
+fn hotkey_disabled_key(action: HotkeyAction) -> &'static str {
+    match action {
+        HotkeyAction::Capture => CAPTURE_HOTKEY_DISABLED_KEY,
+        HotkeyAction::Record => RECORD_HOTKEY_DISABLED_KEY,
+        HotkeyAction::ScrollCapture => SCROLL_CAPTURE_HOTKEY_DISABLED_KEY,
+        HotkeyAction::AnnotateClipboard => ANNOTATE_CLIPBOARD_HOTKEY_DISABLED_KEY,
+    }
+}
+
+/// Whether the global hotkey for `action` is registered (defaults to on).
+pub fn hotkey_enabled(action: HotkeyAction) -> bool {
+    !get_bool(hotkey_disabled_key(action))
+}
+
+pub fn set_hotkey_enabled(action: HotkeyAction, enabled: bool) {
+    set_bool(hotkey_disabled_key(action), !enabled);
+}
+
+/// The last confirmed selection on display `display_id` (overlay view coordinates).
+pub fn last_selection(display_id: u32) -> Option<CGRect> {
+    let value = get_string(&format!("{}{}", LAST_SELECTION_KEY_PREFIX, display_id))?;
+    let parts: Vec<f64> = value.split(',').map(|p| p.trim().parse().ok()).collect::<Option<_>>()?;
+    let &[x, y, width, height] = parts.as_slice() else {
+        return None;
+    };
+    Some(CGRect::new(CGPoint::new(x, y), CGSize::new(width, height)))
+}
+
+pub fn set_last_selection(display_id: u32, rect: CGRect) {
+    let value = format!(
+        "{},{},{},{}",
+        rect.origin.x, rect.origin.y, rect.size.width, rect.size.height
+    );
+    set_string(&format!("{}{}", LAST_SELECTION_KEY_PREFIX, display_id), &value);
+}
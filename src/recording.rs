@@ -6,6 +6,7 @@ use objc2_core_graphics::CGImage;
 use objc2_foundation::NSTimer;
 
 use crate::encoder::VideoEncoder;
+use crate::microphone::MicrophoneCapture;
 
 pub struct RecordingState {
     pub encoder: VideoEncoder,
@@ -17,6 +18,10 @@ pub struct RecordingState {
     pub exclude_window_id: Option<u32>,
     /// The display ID to capture frames from (locked at recording start).
     pub display_id: u32,
+    /// Recording stops once this many frames are captured. None = unlimited.
+    pub max_frames: Option<u64>,
+    /// Microphone feeding the encoder's audio track, if audio is recorded.
+    pub microphone: Option<MicrophoneCapture>,
 }
 
 impl RecordingState {
@@ -34,9 +39,25 @@ impl RecordingState {
             output_path: None,
             exclude_window_id: None,
             display_id,
+            max_frames: None,
+            microphone: None,
         }
     }
 
+    /// Whole seconds of video left before the length limit, or None when unlimited.
+    pub fn seconds_left(&self) -> Option<u64> {
+        let max_frames = self.max_frames?;
+        let fps = self.encoder.fps().max(1) as u64;
+        let frames_left = max_frames.saturating_sub(self.encoder.frame_count());
+        Some(frames_left.div_ceil(fps))
+    }
+
+    /// Returns true once the length limit has been reached.
+    pub fn limit_reached(&self) -> bool {
+        self.max_frames
+            .is_some_and(|max| self.encoder.frame_count() >= max)
+    }
+
     /// Capture one frame: grab the target display, crop to selection, feed to encoder.
     pub fn capture_frame(&mut self) {
         let full_image =
@@ -1,6 +1,6 @@
 use objc2::rc::Retained;
 use objc2_app_kit::{NSEvent, NSScreen};
-use objc2_core_foundation::CGPoint;
+use objc2_core_foundation::{CGPoint, CGRect, CGSize};
 use objc2_core_graphics::{
     CGDirectDisplayID, CGDisplayBounds, CGError, CGGetDisplaysWithPoint, CGMainDisplayID,
 };
@@ -29,6 +29,21 @@ pub fn display_with_mouse() -> CGDirectDisplayID {
     }
 }
 
+/// Union of all screen frames (AppKit coords), for an overlay spanning every display.
+pub fn all_screens_frame(mtm: MainThreadMarker) -> CGRect {
+    NSScreen::screens(mtm)
+        .iter()
+        .map(|screen| screen.frame())
+        .reduce(|a, b| {
+            let x0 = a.origin.x.min(b.origin.x);
+            let y0 = a.origin.y.min(b.origin.y);
+            let x1 = (a.origin.x + a.size.width).max(b.origin.x + b.size.width);
+            let y1 = (a.origin.y + a.size.height).max(b.origin.y + b.size.height);
+            CGRect::new(CGPoint::new(x0, y0), CGSize::new(x1 - x0, y1 - y0))
+        })
+        .unwrap_or_else(|| CGDisplayBounds(CGMainDisplayID()))
+}
+
 /// Get the NSScreen that contains the mouse cursor.
 /// Falls back to the main screen if none found.
 pub fn screen_with_mouse(mtm: MainThreadMarker) -> Retained<NSScreen> {
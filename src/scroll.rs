@@ -9,6 +9,7 @@ unsafe extern "C" {
         units: u32,
         wheel_count: u32,
         wheel1: i32,
+        ...
     ) -> *mut c_void;
     fn CGEventSetLocation(event: *mut c_void, point: CGPoint);
     fn CGEventPost(tap: u32, event: *mut c_void);
@@ -42,6 +43,29 @@ pub fn simulate_scroll(screen_point: CGPoint, delta_pixels: i32) {
     }
 }
 
+/// Simulate a pixel-based horizontal scroll event at the given screen point.
+///
+/// `delta_pixels`: negative = scroll right (content moves left), positive = scroll left.
+pub fn simulate_horizontal_scroll(screen_point: CGPoint, delta_pixels: i32) {
+    unsafe {
+        // Wheel 1 is vertical, wheel 2 horizontal
+        let event = CGEventCreateScrollWheelEvent(
+            std::ptr::null(),
+            UNIT_PIXEL,
+            2,
+            0,
+            delta_pixels,
+        );
+        if event.is_null() {
+            eprintln!("Failed to create scroll event");
+            return;
+        }
+        CGEventSetLocation(event, screen_point);
+        CGEventPost(HID_EVENT_TAP, event);
+        CFRelease(event);
+    }
+}
+
 /// Convert a point from overlay coordinates (top-left origin, relative to display)
 /// to CG global coordinates (top-left origin of primary display).
 ///
@@ -3,6 +3,32 @@ use objc2_core_foundation::{CFRetained, CGFloat, CGPoint, CGRect, CGSize};
 use objc2_core_graphics::CGImage;
 use objc2_foundation::NSTimer;
 
+/// Direction the content is scrolled during a scroll capture.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ScrollDirection {
+    /// Start at the current view and scroll toward the bottom of the page.
+    Down,
+    /// Start at the current view and scroll toward the top of the page.
+    Up,
+    /// Start at the current view and scroll toward the right (wide tables, timelines).
+    Right,
+}
+
+impl ScrollDirection {
+    /// Returns true if frames are stitched side by side rather than stacked.
+    pub fn is_horizontal(self) -> bool {
+        self == ScrollDirection::Right
+    }
+}
+
+/// Delay between captures in manual mode, short enough to keep up with hand scrolling.
+const MANUAL_POLL_INTERVAL: f64 = 0.25;
+/// Largest fraction of the selection scrolled per step. Stitching looks for the
+/// previous frame's rows 1/6 of a frame above its bottom, so steps must leave
+/// more overlap than that.
+const MAX_SCROLL_FRACTION: CGFloat = 0.75;
+const MIN_SCROLL_FRACTION: CGFloat = 0.25;
+
 /// Phase within each timer tick: scroll first, capture on the next tick.
 #[derive(Clone, Copy, PartialEq)]
 enum Phase {
@@ -25,10 +51,17 @@ pub struct ScrollCaptureState {
     pub scale_factor: CGFloat,
     /// CG global origin of the display (from CGDisplayBounds, top-left origin).
     pub screen_origin: CGPoint,
-    /// Maximum number of scroll steps before auto-stop.
+    /// Direction to scroll the content between captures.
+    pub direction: ScrollDirection,
+    /// True when the user scrolls by hand and frames are kept as new content
+    /// appears (the app never scrolls).
+    pub manual: bool,
+    /// Maximum number of scroll steps (manual mode: kept frames) before auto-stop.
     pub max_steps: usize,
     /// Delay between timer ticks in seconds.
     pub settle_delay: f64,
+    /// Fraction of the selection scrolled per step (see `set_scroll_fraction`).
+    scroll_fraction: CGFloat,
     /// Captured frames (in pixel coordinates, cropped to selection).
     pub frames: Vec<CFRetained<CGImage>>,
     /// RGBA pixel data captured at the same time as each frame.
@@ -53,13 +86,18 @@ impl ScrollCaptureState {
         scale_factor: CGFloat,
         screen_origin: CGPoint,
         display_id: u32,
+        direction: ScrollDirection,
+        manual: bool,
     ) -> Self {
         ScrollCaptureState {
             selection,
             scale_factor,
             screen_origin,
+            direction,
+            manual,
             max_steps: 50,
-            settle_delay: 0.5,
+            settle_delay: if manual { MANUAL_POLL_INTERVAL } else { 0.5 },
+            scroll_fraction: 2.0 / 3.0,
             frames: Vec::new(),
             frame_rgba: Vec::new(),
             timer: None,
@@ -70,6 +108,12 @@ impl ScrollCaptureState {
         }
     }
 
+    /// Set how far each step scrolls, as a fraction of the selection. Clamped
+    /// so consecutive frames always overlap enough to stitch.
+    pub fn set_scroll_fraction(&mut self, fraction: CGFloat) {
+        self.scroll_fraction = fraction.clamp(MIN_SCROLL_FRACTION, MAX_SCROLL_FRACTION);
+    }
+
     /// Set the border window ID to exclude from screen captures.
     pub fn set_border_window_id(&mut self, id: u32) {
         self.border_window_id = Some(id);
@@ -82,7 +126,11 @@ impl ScrollCaptureState {
     ///   Phase::Capture → capture frame, check overlap, switch to Scroll
     ///
     /// The NSTimer interval between ticks lets the scroll render before capture.
+    /// In manual mode every tick captures instead (see `tick_manual`).
     pub fn tick(&mut self) -> bool {
+        if self.manual {
+            return self.tick_manual();
+        }
         match self.phase {
             Phase::Scroll => {
                 self.step_count += 1;
@@ -91,13 +139,21 @@ impl ScrollCaptureState {
                     return false;
                 }
 
-                // Scroll down by 2/3 of selection height — guarantees at least 1/3 overlap
+                // Scroll by a fraction of the selection along the scroll axis — the
+                // rest overlaps the previous frame. Negative delta scrolls down (or right).
                 let center_x = self.selection.origin.x + self.selection.size.width / 2.0;
                 let center_y = self.selection.origin.y + self.selection.size.height / 2.0;
                 let screen_point =
                     crate::scroll::overlay_to_cg_global(center_x, center_y, self.screen_origin);
-                let scroll_amount = -(self.selection.size.height * 2.0 / 3.0) as i32;
-                crate::scroll::simulate_scroll(screen_point, scroll_amount);
+                let step = (self.selection.size.height * self.scroll_fraction) as i32;
+                match self.direction {
+                    ScrollDirection::Down => crate::scroll::simulate_scroll(screen_point, -step),
+                    ScrollDirection::Up => crate::scroll::simulate_scroll(screen_point, step),
+                    ScrollDirection::Right => {
+                        let step = (self.selection.size.width * self.scroll_fraction) as i32;
+                        crate::scroll::simulate_horizontal_scroll(screen_point, -step);
+                    }
+                }
 
                 self.phase = Phase::Capture;
                 true
@@ -122,21 +178,43 @@ impl ScrollCaptureState {
                 };
 
                 // Check overlap with previous frame to detect end of scrollable content
-                if let Some(prev) = self.frames.last() {
+                if let (Some(prev), Some(prev_rgba)) =
+                    (self.frames.last(), self.frame_rgba.last())
+                {
                     let frame_height = CGImage::height(Some(prev));
-                    let overlap = estimate_overlap(prev, &frame, frame_height);
+                    // Overlap and frame size along the scroll axis. When scrolling up
+                    // the new frame sits above the previous one, so search for its
+                    // bottom strip in the previous frame instead.
+                    let (overlap, frame_size) = match self.direction {
+                        ScrollDirection::Down => {
+                            (estimate_overlap(prev, &frame, frame_height), frame_height)
+                        }
+                        ScrollDirection::Up => {
+                            (estimate_overlap(&frame, prev, frame_height), frame_height)
+                        }
+                        ScrollDirection::Right => {
+                            let frame_width = CGImage::width(Some(prev));
+                            let overlap = crate::stitch::find_column_overlap(
+                                prev_rgba,
+                                &rgba,
+                                frame_width,
+                                frame_height,
+                            );
+                            (overlap, frame_width)
+                        }
+                    };
 
                     // 95%+ overlap means content didn't scroll — duplicate frame, stop without pushing
-                    if overlap >= frame_height * 19 / 20 {
-                        eprintln!("Scroll capture: content stopped scrolling (overlap={} / height={})", overlap, frame_height);
+                    if overlap >= frame_size * 19 / 20 {
+                        eprintln!("Scroll capture: content stopped scrolling (overlap={} / size={})", overlap, frame_size);
                         return false;
                     }
 
                     // 80%+ overlap means near end of scrollable content — push frame and stop
-                    if overlap > frame_height * 4 / 5 {
+                    if overlap > frame_size * 4 / 5 {
                         eprintln!(
-                            "Scroll capture: near end of content (overlap={} / height={}), stopping",
-                            overlap, frame_height
+                            "Scroll capture: near end of content (overlap={} / size={}), stopping",
+                            overlap, frame_size
                         );
                         self.frames.push(frame);
                         self.frame_rgba.push(rgba);
@@ -153,6 +231,59 @@ impl ScrollCaptureState {
         }
     }
 
+    /// Manual mode tick: capture the region and keep the frame once enough new
+    /// content has been scrolled into view since the last kept frame. Runs until
+    /// the user stops the capture (or `max_steps` frames are kept).
+    fn tick_manual(&mut self) -> bool {
+        let Some(frame) = self.capture_and_crop() else {
+            return true;
+        };
+        let rgba = match crate::actions::cgimage_to_rgba(&frame) {
+            Ok(data) => data,
+            Err(e) => {
+                eprintln!("Scroll capture: RGBA conversion failed: {}", e);
+                return true;
+            }
+        };
+
+        if let Some(prev_rgba) = self.frame_rgba.last() {
+            if *prev_rgba == rgba {
+                return true;
+            }
+            // Wait for at least a fifth of the frame to be new, so small scrolls
+            // and hover effects don't pile up near-duplicate frames
+            let width = CGImage::width(Some(&frame));
+            let height = CGImage::height(Some(&frame));
+            let overlap = crate::stitch::find_overlap(prev_rgba, &rgba, width, height);
+            if overlap > height * 4 / 5 {
+                return true;
+            }
+            if overlap == 0 {
+                eprintln!("Scroll capture: no overlap with previous frame (scrolled too fast?)");
+            }
+        }
+
+        self.frames.push(frame);
+        self.frame_rgba.push(rgba);
+        eprintln!("Scroll capture: frame {} captured", self.frames.len());
+        if self.frames.len() >= self.max_steps {
+            eprintln!("Scroll capture: max frames reached");
+            return false;
+        }
+        true
+    }
+
+    /// Reorder captured frames top-to-bottom (or left-to-right) as expected by
+    /// the stitchers.
+    ///
+    /// Upward captures are collected bottom-first, so they are reversed here.
+    pub fn arrange_top_to_bottom(&mut self) {
+        if self.direction == ScrollDirection::Up {
+            self.frames.reverse();
+            self.frame_rgba.reverse();
+        }
+    }
+
     /// Capture the target display and crop to the selection area.
     fn capture_and_crop(&self) -> Option<CFRetained<CGImage>> {
         let full = crate::capture::capture_display_excluding(self.display_id, self.border_window_id)?;
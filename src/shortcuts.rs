@@ -0,0 +1,265 @@
+use std::cell::Cell;
+
+use global_hotkey::hotkey::HotKey;
+use objc2::rc::Retained;
+use objc2::runtime::{AnyObject, Bool};
+use objc2::{define_class, msg_send, sel, DefinedClass, MainThreadOnly};
+use objc2_app_kit::{
+    NSAlert, NSApplication, NSBackingStoreType, NSBeep, NSBezelStyle, NSButton,
+    NSControlStateValueOff, NSControlStateValueOn, NSEvent, NSEventModifierFlags, NSWindow,
+    NSWindowStyleMask,
+};
+use objc2_core_foundation::{CGFloat, CGPoint, CGSize};
+use objc2_foundation::{MainThreadMarker, NSRect, NSString};
+
+use crate::hotkey::{describe, hotkey_from_event, HotkeyAction};
+
+const WINDOW_WIDTH: CGFloat = 400.0;
+const ROW_HEIGHT: CGFloat = 32.0;
+const PADDING: CGFloat = 20.0;
+const RECORDER_WIDTH: CGFloat = 170.0;
+const RECORDER_HEIGHT: CGFloat = 24.0;
+/// Virtual key code of Esc, which cancels recording.
+const ESCAPE_KEY_CODE: u16 = 0x35;
+
+pub struct ShortcutRecorderIvars {
+    action: Cell<HotkeyAction>,
+    hotkey: Cell<HotKey>,
+    /// True while waiting for the user to type a combo.
+    recording: Cell<bool>,
+    /// Combo typed by the user (consumed by delegate).
+    pending_hotkey: Cell<Option<HotKey>>,
+}
+
+define_class!(
+    /// Button showing an action's hotkey; click it, then type a new combo.
+    #[unsafe(super(NSButton))]
+    #[thread_kind = MainThreadOnly]
+    #[name = "ShortcutRecorder"]
+    #[ivars = ShortcutRecorderIvars]
+    pub struct ShortcutRecorder;
+
+    impl ShortcutRecorder {
+        #[unsafe(method(acceptsFirstResponder))]
+        fn accepts_first_responder(&self) -> bool {
+            true
+        }
+
+        #[unsafe(method(recordShortcut:))]
+        fn record_shortcut(&self, _sender: &AnyObject) {
+            if self.ivars().recording.get() {
+                return;
+            }
+            if let Some(window) = self.window() {
+                window.makeFirstResponder(Some(self));
+            }
+            self.ivars().recording.set(true);
+            self.setTitle(&NSString::from_str("Type Shortcut\u{2026}"));
+            let mtm = MainThreadMarker::from(self);
+            if let Some(delegate) = NSApplication::sharedApplication(mtm).delegate() {
+                let _: () = unsafe { msg_send![&*delegate, shortcutRecordingBegan: self] };
+            }
+        }
+
+        #[unsafe(method(keyDown:))]
+        fn key_down(&self, event: &NSEvent) {
+            if self.ivars().recording.get() {
+                self.record_event(event);
+            } else {
+                let _: () = unsafe { msg_send![super(self), keyDown: event] };
+            }
+        }
+
+        // Combos with Cmd arrive here instead of keyDown:
+        #[unsafe(method(performKeyEquivalent:))]
+        fn perform_key_equivalent(&self, event: &NSEvent) -> Bool {
+            if self.ivars().recording.get() {
+                self.record_event(event);
+                return Bool::YES;
+            }
+            unsafe { msg_send![super(self), performKeyEquivalent: event] }
+        }
+
+        #[unsafe(method(resignFirstResponder))]
+        fn resign_first_responder(&self) -> Bool {
+            if self.ivars().recording.get() {
+                self.finish_recording(None);
+            }
+            unsafe { msg_send![super(self), resignFirstResponder] }
+        }
+    }
+);
+
+impl ShortcutRecorder {
+    fn new(
+        mtm: MainThreadMarker,
+        frame: NSRect,
+        action: HotkeyAction,
+        hotkey: HotKey,
+    ) -> Retained<Self> {
+        let this = mtm.alloc().set_ivars(ShortcutRecorderIvars {
+            action: Cell::new(action),
+            hotkey: Cell::new(hotkey),
+            recording: Cell::new(false),
+            pending_hotkey: Cell::new(None),
+        });
+        let button: Retained<Self> = unsafe { msg_send![super(this), initWithFrame: frame] };
+        button.setBezelStyle(NSBezelStyle::Push);
+        button.setTitle(&NSString::from_str(&describe(&hotkey)));
+        unsafe {
+            button.setTarget(Some(&button));
+            button.setAction(Some(sel!(recordShortcut:)));
+        }
+        button
+    }
+
+    /// Show `hotkey` as the action's current combo.
+    pub fn set_hotkey(&self, hotkey: HotKey) {
+        self.ivars().hotkey.set(hotkey);
+        if !self.ivars().recording.get() {
+            self.setTitle(&NSString::from_str(&describe(&hotkey)));
+        }
+    }
+
+    fn record_event(&self, event: &NSEvent) {
+        let key_code = event.keyCode();
+        let flags = event.modifierFlags();
+        let modifiers = NSEventModifierFlags::Shift
+            .union(NSEventModifierFlags::Control)
+            .union(NSEventModifierFlags::Option)
+            .union(NSEventModifierFlags::Command);
+        if key_code == ESCAPE_KEY_CODE && !flags.intersects(modifiers) {
+            self.finish_recording(None);
+            return;
+        }
+        match hotkey_from_event(key_code, flags) {
+            Some(hotkey) => self.finish_recording(Some(hotkey)),
+            None => NSBeep(),
+        }
+    }
+
+    fn finish_recording(&self, hotkey: Option<HotKey>) {
+        self.ivars().recording.set(false);
+        self.ivars().pending_hotkey.set(hotkey);
+        self.setTitle(&NSString::from_str(&describe(&self.ivars().hotkey.get())));
+
+        let mtm = MainThreadMarker::from(self);
+        if let Some(delegate) = NSApplication::sharedApplication(mtm).delegate() {
+            let _: () = unsafe { msg_send![&*delegate, shortcutRecorded: self] };
+        }
+    }
+}
+
+/// "Keyboard Shortcuts" window with an on/off checkbox and a recorder for
+/// each global hotkey.
+pub struct ShortcutsWindow {
+    pub window: Retained<NSWindow>,
+    /// Enable checkboxes, indexed (and tagged) like `HotkeyAction::ALL`.
+    checkboxes: Vec<Retained<NSButton>>,
+    recorders: Vec<Retained<ShortcutRecorder>>,
+}
+
+impl ShortcutsWindow {
+    /// `hotkeys` holds each action's combo and whether it's enabled.
+    pub fn new(mtm: MainThreadMarker, hotkeys: [(HotKey, bool); 4]) -> Self {
+        let height = PADDING * 2.0 + ROW_HEIGHT * HotkeyAction::ALL.len() as CGFloat;
+        let content_rect = NSRect::new(CGPoint::ZERO, CGSize::new(WINDOW_WIDTH, height));
+        let window = unsafe {
+            NSWindow::initWithContentRect_styleMask_backing_defer(
+                mtm.alloc(),
+                content_rect,
+                NSWindowStyleMask::Titled | NSWindowStyleMask::Closable,
+                NSBackingStoreType::Buffered,
+                false,
+            )
+        };
+        window.setTitle(&NSString::from_str("Keyboard Shortcuts"));
+        unsafe { window.setReleasedWhenClosed(false) };
+
+        let mut checkboxes = Vec::new();
+        let mut recorders = Vec::new();
+        if let Some(content_view) = window.contentView() {
+            let rows = HotkeyAction::ALL.into_iter().zip(hotkeys).enumerate();
+            for (i, (action, (hotkey, enabled))) in rows {
+                // Rows top to bottom (content view is not flipped)
+                let row_y = height - PADDING - ROW_HEIGHT * (i + 1) as CGFloat;
+                let checkbox = unsafe {
+                    NSButton::checkboxWithTitle_target_action(
+                        &NSString::from_str(action.title()),
+                        None,
+                        Some(sel!(toggleHotkeyEnabled:)),
+                        mtm,
+                    )
+                };
+                checkbox.setTag(i as isize);
+                checkbox.setState(if enabled {
+                    NSControlStateValueOn
+                } else {
+                    NSControlStateValueOff
+                });
+                checkbox.setFrameOrigin(CGPoint::new(PADDING, row_y + 7.0));
+                content_view.addSubview(&checkbox);
+                checkboxes.push(checkbox);
+
+                let frame = NSRect::new(
+                    CGPoint::new(WINDOW_WIDTH - PADDING - RECORDER_WIDTH, row_y + 4.0),
+                    CGSize::new(RECORDER_WIDTH, RECORDER_HEIGHT),
+                );
+                let recorder = ShortcutRecorder::new(mtm, frame, action, hotkey);
+                content_view.addSubview(&recorder);
+                recorders.push(recorder);
+            }
+        }
+
+        ShortcutsWindow {
+            window,
+            checkboxes,
+            recorders,
+        }
+    }
+
+    /// Bring the window to the front.
+    pub fn show(&self, mtm: MainThreadMarker) {
+        #[allow(deprecated)]
+        NSApplication::sharedApplication(mtm).activateIgnoringOtherApps(true);
+        self.window.center();
+        self.window.makeKeyAndOrderFront(None);
+        // Don't start recording until a recorder is clicked
+        self.window.makeFirstResponder(None);
+    }
+
+    /// Take the combo the user just typed, with the action it's for.
+    pub fn take_recorded_hotkey(&self) -> Option<(HotkeyAction, HotKey)> {
+        self.recorders.iter().find_map(|recorder| {
+            let hotkey = recorder.ivars().pending_hotkey.take()?;
+            Some((recorder.ivars().action.get(), hotkey))
+        })
+    }
+
+    /// Check or uncheck `action`'s checkbox.
+    pub fn set_enabled(&self, action: HotkeyAction, enabled: bool) {
+        let index = HotkeyAction::ALL.iter().position(|&a| a == action);
+        if let Some(checkbox) = index.and_then(|i| self.checkboxes.get(i)) {
+            checkbox.setState(if enabled {
+                NSControlStateValueOn
+            } else {
+                NSControlStateValueOff
+            });
+        }
+    }
+
+    /// Show `hotkey` on `action`'s recorder.
+    pub fn set_hotkey(&self, action: HotkeyAction, hotkey: HotKey) {
+        if let Some(recorder) = self.recorders.iter().find(|r| r.ivars().action.get() == action) {
+            recorder.set_hotkey(hotkey);
+        }
+    }
+}
+
+/// Explain why a shortcut couldn't be used.
+pub fn show_unavailable_alert(message: &str, mtm: MainThreadMarker) {
+    let alert = NSAlert::new(mtm);
+    alert.setMessageText(&NSString::from_str("Shortcut not available"));
+    alert.setInformativeText(&NSString::from_str(message));
+    alert.runModal();
+}
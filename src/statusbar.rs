@@ -1,10 +1,30 @@
+use std::cell::{Cell, RefCell};
+use std::path::Path;
+use std::ptr::NonNull;
+use std::rc::Rc;
+use std::time::Instant;
+
+use block2::RcBlock;
+use global_hotkey::hotkey::HotKey;
 use objc2::rc::Retained;
-use objc2::runtime::Sel;
+use objc2::runtime::{Bool, ProtocolObject, Sel};
+use objc2::{define_class, msg_send, AnyThread, MainThreadOnly};
 use objc2_app_kit::{
-    NSEventModifierFlags, NSImage, NSMenu, NSMenuItem, NSStatusBar, NSStatusItem,
-    NSVariableStatusItemLength,
+    NSApplication, NSAutoresizingMaskOptions, NSCellImagePosition, NSControlStateValueOff,
+    NSControlStateValueOn, NSDragOperation, NSDraggingInfo, NSEventModifierFlags, NSImage,
+    NSMenu, NSMenuItem, NSPasteboardTypeFileURL, NSStatusBar, NSStatusBarButton, NSStatusItem,
+    NSVariableStatusItemLength, NSView,
+};
+use objc2_core_foundation::CGSize;
+use objc2_foundation::{MainThreadMarker, NSArray, NSString, NSTimer};
+
+use crate::editor::view::CropAspect;
+use crate::history::HistoryItem;
+use crate::hotkey::HotkeyAction;
+use crate::prefs::{
+    FrozenMargin, ImageFormat, PostCaptureAction, RecordingLimit, ScrollSettleDelay,
+    ScrollStepLimit, ScrollStepSize, SnapGrid, TimelineUnit, VideoCodec, VideoQuality,
 };
-use objc2_foundation::{MainThreadMarker, NSString};
 
 pub struct StatusBar {
     status_item: Retained<NSStatusItem>,
@@ -14,8 +34,65 @@ pub struct StatusBar {
     normal_items: Vec<Retained<NSMenuItem>>,
     /// "Stop Recording" item, shown only during recording/scroll capture.
     stop_recording_item: Retained<NSMenuItem>,
+    /// "Cancel Delayed Capture" item, shown only while counting down.
+    cancel_capture_delay_item: Retained<NSMenuItem>,
+    /// "Selection Aspect Ratio" submenu items, indexed like `CropAspect::ALL`.
+    selection_aspect_items: Vec<Retained<NSMenuItem>>,
+    /// "Recent Captures" submenu, rebuilt whenever the history changes.
+    recent_captures_menu: Retained<NSMenu>,
+    /// "Keep Recent Captures" toggle at the bottom of the "Recent Captures" submenu.
+    keep_history_item: Retained<NSMenuItem>,
+    /// "Crosshair Guides" toggle, checked when the overlay draws guide lines.
+    crosshair_guides_item: Retained<NSMenuItem>,
+    vertical_toolbar_item: Retained<NSMenuItem>,
+    keep_inside_selection_item: Retained<NSMenuItem>,
+    /// "Snap to Grid" submenu items, indexed like `SnapGrid::ALL`.
+    snap_grid_items: Vec<Retained<NSMenuItem>>,
+    /// "After Capture" submenu items, indexed like `PostCaptureAction::ALL`.
+    post_capture_items: Vec<Retained<NSMenuItem>>,
+    /// "Max Recording Length" submenu items, indexed like `RecordingLimit::ALL`.
+    recording_limit_items: Vec<Retained<NSMenuItem>>,
+    /// "Video Quality" submenu items, indexed like `VideoQuality::ALL`.
+    video_quality_items: Vec<Retained<NSMenuItem>>,
+    /// "Video Codec" submenu items, indexed like `VideoCodec::ALL`.
+    video_codec_items: Vec<Retained<NSMenuItem>>,
+    /// "Editor Timeline" submenu items, indexed like `TimelineUnit::ALL`.
+    timeline_unit_items: Vec<Retained<NSMenuItem>>,
+    /// "Scroll Capture Settings" submenu items, indexed like the matching `ALL`.
+    scroll_settle_delay_items: Vec<Retained<NSMenuItem>>,
+    scroll_step_limit_items: Vec<Retained<NSMenuItem>>,
+    scroll_step_size_items: Vec<Retained<NSMenuItem>>,
+    scroll_frozen_top_items: Vec<Retained<NSMenuItem>>,
+    scroll_frozen_bottom_items: Vec<Retained<NSMenuItem>>,
+    /// Items for the global hotkey actions, indexed like `HotkeyAction::ALL`.
+    hotkey_items: Vec<Retained<NSMenuItem>>,
+    /// "Record Microphone" toggle, checked when recordings include audio.
+    record_microphone_item: Retained<NSMenuItem>,
+    /// "Saving" submenu item naming the save folder.
+    save_folder_item: Retained<NSMenuItem>,
+    /// Items of the "Image Format" submenu, indexed like `ImageFormat::ALL`.
+    image_format_items: Vec<Retained<NSMenuItem>>,
+    /// "Also Copy to Clipboard" toggle, checked when saves also copy the result.
+    copy_on_save_item: Retained<NSMenuItem>,
+    /// "Copy Saved File" toggle, checked when saves put the file on the clipboard.
+    copy_saved_file_item: Retained<NSMenuItem>,
+    /// "Save Without Asking" toggle, checked when saves skip the panel.
+    auto_save_item: Retained<NSMenuItem>,
+    /// "Notify When Saved" toggle, checked when saves post a banner.
+    notify_on_save_item: Retained<NSMenuItem>,
+    /// "Play Sound When Saved" toggle, checked when saves play the screenshot sound.
+    save_sound_item: Retained<NSMenuItem>,
+    /// "Reveal in Finder When Saved" toggle, checked when saves select the file in Finder.
+    reveal_on_save_item: Retained<NSMenuItem>,
+    /// Once-a-second timer updating the elapsed time while recording.
+    recording_clock: RefCell<Option<Retained<NSTimer>>>,
+    /// Seconds left before a length-limited recording stops, shown with the elapsed time.
+    recording_time_left: Rc<Cell<Option<u64>>>,
 }
 
+/// Delays offered under "Capture After Delay". The index doubles as the menu item tag.
+pub const CAPTURE_DELAY_SECONDS: [u64; 3] = [3, 5, 10];
+
 impl StatusBar {
     pub fn new(mtm: MainThreadMarker) -> Self {
         let status_bar = NSStatusBar::systemStatusBar();
@@ -50,6 +127,102 @@ impl StatusBar {
         );
         menu.addItem(&capture_item);
 
+        // Capture After Delay submenu (countdown in the menu bar, then the overlay)
+        let delay_titles = CAPTURE_DELAY_SECONDS.map(|s| format!("{} Seconds", s));
+        let (capture_delay_item, _) = create_choice_submenu(
+            mtm,
+            "Capture After Delay",
+            delay_titles.each_ref().map(|t| (t.as_str(), false)),
+            c"captureAfterDelay:",
+        );
+        menu.addItem(&capture_delay_item);
+
+        // Capture Window (click a window in the overlay, menu only)
+        let capture_window_item = create_menu_item(
+            mtm,
+            "Capture Window",
+            c"captureWindow:",
+            "",
+            NSEventModifierFlags::empty(),
+        );
+        menu.addItem(&capture_window_item);
+
+        // Capture All Displays (one overlay spanning every screen, menu only)
+        let capture_all_displays_item = create_menu_item(
+            mtm,
+            "Capture All Displays",
+            c"captureAllDisplays:",
+            "",
+            NSEventModifierFlags::empty(),
+        );
+        menu.addItem(&capture_all_displays_item);
+
+        // Overlay submenu (selection shape, guides, toolbar layout, annotation snapping)
+        let overlay_item = unsafe {
+            NSMenuItem::initWithTitle_action_keyEquivalent(
+                mtm.alloc(),
+                &NSString::from_str("Overlay"),
+                None,
+                &NSString::from_str(""),
+            )
+        };
+        let overlay_menu = NSMenu::new(mtm);
+
+        // Selection Aspect Ratio submenu (locks the overlay selection's shape)
+        let current_aspect = crate::prefs::selection_aspect();
+        let (selection_aspect_item, selection_aspect_items) = create_choice_submenu(
+            mtm,
+            "Selection Aspect Ratio",
+            CropAspect::ALL.map(|a| (a.label(), a == current_aspect)),
+            c"setSelectionAspect:",
+        );
+        overlay_menu.addItem(&selection_aspect_item);
+
+        // Crosshair Guides toggle (full-screen lines through the cursor before selecting)
+        let crosshair_guides_item = create_menu_item(
+            mtm,
+            "Crosshair Guides",
+            c"toggleCrosshairGuides:",
+            "",
+            NSEventModifierFlags::empty(),
+        );
+        check_item(&crosshair_guides_item, crate::prefs::crosshair_guides());
+        overlay_menu.addItem(&crosshair_guides_item);
+
+        // Vertical Toolbar toggle (stack the annotation toolbar in a column)
+        let vertical_toolbar_item = create_menu_item(
+            mtm,
+            "Vertical Toolbar",
+            c"toggleVerticalToolbar:",
+            "",
+            NSEventModifierFlags::empty(),
+        );
+        check_item(&vertical_toolbar_item, crate::prefs::vertical_toolbar());
+        overlay_menu.addItem(&vertical_toolbar_item);
+
+        // Keep Annotations Inside Selection toggle (overlay annotations can't stray out)
+        let keep_inside_selection_item = create_menu_item(
+            mtm,
+            "Keep Annotations Inside Selection",
+            c"toggleKeepInsideSelection:",
+            "",
+            NSEventModifierFlags::empty(),
+        );
+        check_item(&keep_inside_selection_item, crate::prefs::keep_inside_selection());
+        overlay_menu.addItem(&keep_inside_selection_item);
+
+        // Snap to Grid submenu (annotations and the selection snap while dragged)
+        let current_grid = crate::prefs::snap_grid();
+        let (snap_grid_item, snap_grid_items) = create_choice_submenu(
+            mtm,
+            "Snap to Grid",
+            SnapGrid::ALL.map(|grid| (grid.title(), grid == current_grid)),
+            c"setSnapGrid:",
+        );
+        overlay_menu.addItem(&snap_grid_item);
+        overlay_item.setSubmenu(Some(&overlay_menu));
+        menu.addItem(&overlay_item);
+
         // Record Screen  (Ctrl+Cmd+Z)
         let record_item = create_menu_item(
             mtm,
@@ -70,6 +243,320 @@ impl StatusBar {
         );
         menu.addItem(&scroll_item);
 
+        // Scroll Capture Upward  (Shift+Ctrl+Cmd+S, menu only)
+        let scroll_up_item = create_menu_item(
+            mtm,
+            "Scroll Capture Upward",
+            c"startScrollCaptureUp:",
+            "s",
+            ctrl_cmd.union(NSEventModifierFlags::Shift),
+        );
+        menu.addItem(&scroll_up_item);
+
+        // Scroll Capture Rightward  (Option+Ctrl+Cmd+S, menu only)
+        let scroll_right_item = create_menu_item(
+            mtm,
+            "Scroll Capture Rightward",
+            c"startScrollCaptureRight:",
+            "s",
+            ctrl_cmd.union(NSEventModifierFlags::Option),
+        );
+        menu.addItem(&scroll_right_item);
+
+        // Manual Scroll Capture (the user scrolls, menu only)
+        let scroll_manual_item = create_menu_item(
+            mtm,
+            "Manual Scroll Capture",
+            c"startScrollCaptureManual:",
+            "",
+            NSEventModifierFlags::empty(),
+        );
+        menu.addItem(&scroll_manual_item);
+
+        // Scroll Capture Settings submenu (read when a scroll capture starts)
+        let scroll_settings_item = unsafe {
+            NSMenuItem::initWithTitle_action_keyEquivalent(
+                mtm.alloc(),
+                &NSString::from_str("Scroll Capture Settings"),
+                None,
+                &NSString::from_str(""),
+            )
+        };
+        let scroll_settings_menu = NSMenu::new(mtm);
+        let current_delay = crate::prefs::scroll_settle_delay();
+        let (settle_delay_item, scroll_settle_delay_items) = create_choice_submenu(
+            mtm,
+            "Wait After Scrolling",
+            ScrollSettleDelay::ALL.map(|d| (d.title(), d == current_delay)),
+            c"setScrollSettleDelay:",
+        );
+        scroll_settings_menu.addItem(&settle_delay_item);
+        let current_step_limit = crate::prefs::scroll_step_limit();
+        let (step_limit_item, scroll_step_limit_items) = create_choice_submenu(
+            mtm,
+            "Max Scroll Steps",
+            ScrollStepLimit::ALL.map(|l| (l.title(), l == current_step_limit)),
+            c"setScrollStepLimit:",
+        );
+        scroll_settings_menu.addItem(&step_limit_item);
+        let current_step_size = crate::prefs::scroll_step_size();
+        let (step_size_item, scroll_step_size_items) = create_choice_submenu(
+            mtm,
+            "Scroll Step",
+            ScrollStepSize::ALL.map(|s| (s.title(), s == current_step_size)),
+            c"setScrollStepSize:",
+        );
+        scroll_settings_menu.addItem(&step_size_item);
+        let current_frozen_top = crate::prefs::scroll_frozen_top();
+        let (frozen_top_item, scroll_frozen_top_items) = create_choice_submenu(
+            mtm,
+            "Sticky Header",
+            FrozenMargin::ALL.map(|m| (m.title(), m == current_frozen_top)),
+            c"setScrollFrozenTop:",
+        );
+        scroll_settings_menu.addItem(&frozen_top_item);
+        let current_frozen_bottom = crate::prefs::scroll_frozen_bottom();
+        let (frozen_bottom_item, scroll_frozen_bottom_items) = create_choice_submenu(
+            mtm,
+            "Sticky Footer",
+            FrozenMargin::ALL.map(|m| (m.title(), m == current_frozen_bottom)),
+            c"setScrollFrozenBottom:",
+        );
+        scroll_settings_menu.addItem(&frozen_bottom_item);
+        scroll_settings_item.setSubmenu(Some(&scroll_settings_menu));
+        menu.addItem(&scroll_settings_item);
+
+        // Compare Images
+        let compare_item = create_menu_item(
+            mtm,
+            "Compare Images\u{2026}",
+            c"compareImages:",
+            "",
+            NSEventModifierFlags::empty(),
+        );
+        menu.addItem(&compare_item);
+
+        // Open Image (annotate an existing file)
+        let open_image_item = create_menu_item(
+            mtm,
+            "Open Image\u{2026}",
+            c"openImage:",
+            "",
+            NSEventModifierFlags::empty(),
+        );
+        menu.addItem(&open_image_item);
+
+        // Annotate Clipboard Image  (Ctrl+Cmd+V)
+        let clipboard_item = create_menu_item(
+            mtm,
+            "Annotate Clipboard Image",
+            c"annotateClipboardImage:",
+            "v",
+            ctrl_cmd,
+        );
+        menu.addItem(&clipboard_item);
+
+        // Recent Captures submenu (filled by set_recent_captures)
+        let recent_captures_item = unsafe {
+            NSMenuItem::initWithTitle_action_keyEquivalent(
+                mtm.alloc(),
+                &NSString::from_str("Recent Captures"),
+                None,
+                &NSString::from_str(""),
+            )
+        };
+        let recent_captures_menu = NSMenu::new(mtm);
+        recent_captures_item.setSubmenu(Some(&recent_captures_menu));
+        menu.addItem(&recent_captures_item);
+        let keep_history_item = create_menu_item(
+            mtm,
+            "Keep Recent Captures",
+            c"toggleKeepHistory:",
+            "",
+            NSEventModifierFlags::empty(),
+        );
+        check_item(&keep_history_item, crate::prefs::keep_history());
+
+        // Keyboard Shortcuts (remap the global hotkeys)
+        let shortcuts_item = create_menu_item(
+            mtm,
+            "Keyboard Shortcuts\u{2026}",
+            c"showKeyboardShortcuts:",
+            "",
+            NSEventModifierFlags::empty(),
+        );
+        menu.addItem(&shortcuts_item);
+
+        // After Capture submenu (post-capture action preference)
+        let current_action = crate::prefs::post_capture_action();
+        let (after_capture_item, post_capture_items) = create_choice_submenu(
+            mtm,
+            "After Capture",
+            PostCaptureAction::ALL.map(|action| (action.title(), action == current_action)),
+            c"setPostCaptureAction:",
+        );
+        menu.addItem(&after_capture_item);
+
+        // Saving submenu (save folder, file name template, format, clipboard copy,
+        // skip the save panel)
+        let saving_item = unsafe {
+            NSMenuItem::initWithTitle_action_keyEquivalent(
+                mtm.alloc(),
+                &NSString::from_str("Saving"),
+                None,
+                &NSString::from_str(""),
+            )
+        };
+        let saving_menu = NSMenu::new(mtm);
+        let save_folder_item = create_menu_item(
+            mtm,
+            &save_folder_title(&crate::prefs::save_folder()),
+            c"chooseSaveFolder:",
+            "",
+            NSEventModifierFlags::empty(),
+        );
+        saving_menu.addItem(&save_folder_item);
+        let filename_item = create_menu_item(
+            mtm,
+            "File Name\u{2026}",
+            c"editFilenameTemplate:",
+            "",
+            NSEventModifierFlags::empty(),
+        );
+        saving_menu.addItem(&filename_item);
+        let current_format = crate::prefs::image_format();
+        let (image_format_item, image_format_items) = create_choice_submenu(
+            mtm,
+            "Image Format",
+            ImageFormat::ALL.map(|f| (f.title(), f == current_format)),
+            c"setImageFormat:",
+        );
+        // Formats this macOS can't encode (WebP on older systems) are disabled
+        if let Some(submenu) = image_format_item.submenu() {
+            submenu.setAutoenablesItems(false);
+        }
+        for (item, format) in image_format_items.iter().zip(ImageFormat::ALL) {
+            item.setEnabled(crate::actions::can_encode(format));
+        }
+        saving_menu.addItem(&image_format_item);
+        let copy_on_save_item = create_menu_item(
+            mtm,
+            "Also Copy to Clipboard",
+            c"toggleCopyOnSave:",
+            "",
+            NSEventModifierFlags::empty(),
+        );
+        check_item(&copy_on_save_item, crate::prefs::copy_on_save());
+        saving_menu.addItem(&copy_on_save_item);
+        let copy_saved_file_item = create_menu_item(
+            mtm,
+            "Copy Saved File",
+            c"toggleCopySavedFile:",
+            "",
+            NSEventModifierFlags::empty(),
+        );
+        check_item(&copy_saved_file_item, crate::prefs::copy_saved_file());
+        saving_menu.addItem(&copy_saved_file_item);
+        let auto_save_item = create_menu_item(
+            mtm,
+            "Save Without Asking",
+            c"toggleAutoSave:",
+            "",
+            NSEventModifierFlags::empty(),
+        );
+        check_item(&auto_save_item, crate::prefs::auto_save());
+        saving_menu.addItem(&auto_save_item);
+        let notify_on_save_item = create_menu_item(
+            mtm,
+            "Notify When Saved",
+            c"toggleNotifyOnSave:",
+            "",
+            NSEventModifierFlags::empty(),
+        );
+        check_item(&notify_on_save_item, crate::prefs::notify_on_save());
+        saving_menu.addItem(&notify_on_save_item);
+        let save_sound_item = create_menu_item(
+            mtm,
+            "Play Sound When Saved",
+            c"toggleSaveSound:",
+            "",
+            NSEventModifierFlags::empty(),
+        );
+        check_item(&save_sound_item, crate::prefs::save_sound());
+        saving_menu.addItem(&save_sound_item);
+        let reveal_on_save_item = create_menu_item(
+            mtm,
+            "Reveal in Finder When Saved",
+            c"toggleRevealOnSave:",
+            "",
+            NSEventModifierFlags::empty(),
+        );
+        check_item(&reveal_on_save_item, crate::prefs::reveal_on_save());
+        saving_menu.addItem(&reveal_on_save_item);
+        saving_item.setSubmenu(Some(&saving_menu));
+        menu.addItem(&saving_item);
+
+        // Upload Settings (endpoint and auth header for the toolbar's Upload button)
+        let upload_settings_item = create_menu_item(
+            mtm,
+            "Upload Settings\u{2026}",
+            c"editUploadSettings:",
+            "",
+            NSEventModifierFlags::empty(),
+        );
+        menu.addItem(&upload_settings_item);
+
+        // Max Recording Length submenu (auto-stop preference)
+        let current_limit = crate::prefs::recording_limit();
+        let (recording_limit_item, recording_limit_items) = create_choice_submenu(
+            mtm,
+            "Max Recording Length",
+            RecordingLimit::ALL.map(|limit| (limit.title(), limit == current_limit)),
+            c"setRecordingLimit:",
+        );
+        menu.addItem(&recording_limit_item);
+
+        // Video Quality submenu (recording and export bitrate)
+        let current_quality = crate::prefs::video_quality();
+        let (video_quality_item, video_quality_items) = create_choice_submenu(
+            mtm,
+            "Video Quality",
+            VideoQuality::ALL.map(|quality| (quality.title(), quality == current_quality)),
+            c"setVideoQuality:",
+        );
+        menu.addItem(&video_quality_item);
+
+        // Video Codec submenu (recordings and MP4 exports)
+        let current_codec = crate::prefs::video_codec();
+        let (video_codec_item, video_codec_items) = create_choice_submenu(
+            mtm,
+            "Video Codec",
+            VideoCodec::ALL.map(|codec| (codec.menu_title(), codec == current_codec)),
+            c"setVideoCodec:",
+        );
+        menu.addItem(&video_codec_item);
+
+        // Record Microphone toggle (read when a recording starts)
+        let record_microphone_item = create_menu_item(
+            mtm,
+            "Record Microphone",
+            c"toggleRecordMicrophone:",
+            "",
+            NSEventModifierFlags::empty(),
+        );
+        check_item(&record_microphone_item, crate::prefs::record_microphone());
+        menu.addItem(&record_microphone_item);
+
+        // Editor Timeline submenu (frame numbers or timestamps)
+        let current_unit = crate::prefs::timeline_unit();
+        let (timeline_unit_item, timeline_unit_items) = create_choice_submenu(
+            mtm,
+            "Editor Timeline",
+            TimelineUnit::ALL.map(|unit| (unit.title(), unit == current_unit)),
+            c"setTimelineUnit:",
+        );
+        menu.addItem(&timeline_unit_item);
+
         // Stop Recording - hidden by default
         let stop_recording_item = unsafe {
             NSMenuItem::initWithTitle_action_keyEquivalent(
@@ -82,6 +569,17 @@ impl StatusBar {
         stop_recording_item.setHidden(true);
         menu.addItem(&stop_recording_item);
 
+        // Cancel Delayed Capture (hidden until a countdown starts)
+        let cancel_capture_delay_item = create_menu_item(
+            mtm,
+            "Cancel Delayed Capture",
+            c"cancelCaptureDelay:",
+            "",
+            NSEventModifierFlags::empty(),
+        );
+        cancel_capture_delay_item.setHidden(true);
+        menu.addItem(&cancel_capture_delay_item);
+
         // Separator
         menu.addItem(&NSMenuItem::separatorItem(mtm));
 
@@ -102,9 +600,297 @@ impl StatusBar {
             status_item,
             camera_icon,
             recording_icon,
-            normal_items: vec![capture_item, record_item, scroll_item],
+            normal_items: vec![
+                capture_item.clone(),
+                capture_delay_item,
+                capture_window_item,
+                capture_all_displays_item,
+                selection_aspect_item,
+                crosshair_guides_item.clone(),
+                vertical_toolbar_item.clone(),
+                keep_inside_selection_item.clone(),
+                snap_grid_item,
+                record_item.clone(),
+                scroll_item.clone(),
+                scroll_up_item,
+                scroll_right_item,
+                scroll_manual_item,
+                scroll_settings_item,
+                compare_item,
+                open_image_item,
+                clipboard_item.clone(),
+                recent_captures_item,
+                shortcuts_item,
+                after_capture_item,
+                saving_item,
+                upload_settings_item,
+                recording_limit_item,
+                video_quality_item,
+                video_codec_item,
+                record_microphone_item.clone(),
+                timeline_unit_item,
+            ],
             stop_recording_item,
+            cancel_capture_delay_item,
+            selection_aspect_items,
+            recent_captures_menu,
+            keep_history_item,
+            crosshair_guides_item,
+            vertical_toolbar_item,
+            keep_inside_selection_item,
+            snap_grid_items,
+            post_capture_items,
+            recording_limit_items,
+            video_quality_items,
+            video_codec_items,
+            timeline_unit_items,
+            scroll_settle_delay_items,
+            scroll_step_limit_items,
+            scroll_step_size_items,
+            scroll_frozen_top_items,
+            scroll_frozen_bottom_items,
+            hotkey_items: vec![capture_item, record_item, scroll_item, clipboard_item],
+            record_microphone_item,
+            save_folder_item,
+            image_format_items,
+            copy_on_save_item,
+            copy_saved_file_item,
+            auto_save_item,
+            notify_on_save_item,
+            save_sound_item,
+            reveal_on_save_item,
+            recording_clock: RefCell::new(None),
+            recording_time_left: Rc::new(Cell::new(None)),
+        }
+    }
+
+    /// Accept files dragged onto the menu bar icon. A `StatusDropView` over the
+    /// button forwards `draggingEntered:` and `performDragOperation:` to the app
+    /// delegate.
+    pub fn accept_file_drops(&self, mtm: MainThreadMarker) {
+        let Some(button) = self.status_item.button(mtm) else {
+            return;
+        };
+        let this = mtm.alloc().set_ivars(());
+        let view: Retained<StatusDropView> =
+            unsafe { msg_send![super(this), initWithFrame: button.bounds()] };
+        view.setAutoresizingMask(
+            NSAutoresizingMaskOptions::ViewWidthSizable
+                | NSAutoresizingMaskOptions::ViewHeightSizable,
+        );
+        let types = NSArray::from_slice(&[unsafe { NSPasteboardTypeFileURL }]);
+        view.registerForDraggedTypes(&types);
+        button.addSubview(&view);
+    }
+
+    /// Rebuild the "Recent Captures" submenu from `items` (newest first). Each
+    /// item sends `openRecentCapture:` tagged with its index.
+    pub fn set_recent_captures(&self, mtm: MainThreadMarker, items: &[HistoryItem]) {
+        self.recent_captures_menu.removeAllItems();
+        if items.is_empty() {
+            let placeholder = unsafe {
+                NSMenuItem::initWithTitle_action_keyEquivalent(
+                    mtm.alloc(),
+                    &NSString::from_str("No Recent Captures"),
+                    None,
+                    &NSString::from_str(""),
+                )
+            };
+            self.recent_captures_menu.addItem(&placeholder);
+        }
+        for (i, item) in items.iter().enumerate() {
+            let menu_item = create_menu_item(
+                mtm,
+                &item.title(),
+                c"openRecentCapture:",
+                "",
+                NSEventModifierFlags::empty(),
+            );
+            menu_item.setTag(i as isize);
+            let path = NSString::from_str(&item.thumbnail_path.to_string_lossy());
+            if let Some(thumbnail) = NSImage::initWithContentsOfFile(NSImage::alloc(), &path) {
+                // Thumbnails are stored at 2x
+                let size = thumbnail.size();
+                thumbnail.setSize(CGSize::new(size.width / 2.0, size.height / 2.0));
+                menu_item.setImage(Some(&thumbnail));
+            }
+            self.recent_captures_menu.addItem(&menu_item);
+        }
+
+        self.recent_captures_menu.addItem(&NSMenuItem::separatorItem(mtm));
+        self.recent_captures_menu.addItem(&self.keep_history_item);
+        let clear_item = create_menu_item(
+            mtm,
+            "Clear Recent Captures",
+            c"clearHistory:",
+            "",
+            NSEventModifierFlags::empty(),
+        );
+        if items.is_empty() {
+            unsafe { clear_item.setAction(None) };
         }
+        self.recent_captures_menu.addItem(&clear_item);
+    }
+
+    /// Check or uncheck the "Keep Recent Captures" item.
+    pub fn set_keep_history(&self, enabled: bool) {
+        check_item(&self.keep_history_item, enabled);
+    }
+
+    /// Move the checkmark in the "After Capture" submenu to `action`.
+    pub fn set_post_capture_action(&self, action: PostCaptureAction) {
+        let index = PostCaptureAction::ALL.iter().position(|&a| a == action).unwrap_or(0);
+        check_choice(&self.post_capture_items, index);
+    }
+
+    /// Move the checkmark in the "Video Quality" submenu to `quality`.
+    pub fn set_video_quality(&self, quality: VideoQuality) {
+        let index = VideoQuality::ALL.iter().position(|&q| q == quality).unwrap_or(0);
+        check_choice(&self.video_quality_items, index);
+    }
+
+    /// Move the checkmark in the "Video Codec" submenu to `codec`.
+    pub fn set_video_codec(&self, codec: VideoCodec) {
+        let index = VideoCodec::ALL.iter().position(|&c| c == codec).unwrap_or(0);
+        check_choice(&self.video_codec_items, index);
+    }
+
+    /// Move the checkmark in the "Editor Timeline" submenu to `unit`.
+    pub fn set_timeline_unit(&self, unit: TimelineUnit) {
+        let index = TimelineUnit::ALL.iter().position(|&u| u == unit).unwrap_or(0);
+        check_choice(&self.timeline_unit_items, index);
+    }
+
+    /// Move the checkmark in the "Wait After Scrolling" submenu to `delay`.
+    pub fn set_scroll_settle_delay(&self, delay: ScrollSettleDelay) {
+        let index = ScrollSettleDelay::ALL.iter().position(|&d| d == delay).unwrap_or(0);
+        check_choice(&self.scroll_settle_delay_items, index);
+    }
+
+    /// Move the checkmark in the "Max Scroll Steps" submenu to `limit`.
+    pub fn set_scroll_step_limit(&self, limit: ScrollStepLimit) {
+        let index = ScrollStepLimit::ALL.iter().position(|&l| l == limit).unwrap_or(0);
+        check_choice(&self.scroll_step_limit_items, index);
+    }
+
+    /// Move the checkmark in the "Scroll Step" submenu to `size`.
+    pub fn set_scroll_step_size(&self, size: ScrollStepSize) {
+        let index = ScrollStepSize::ALL.iter().position(|&s| s == size).unwrap_or(0);
+        check_choice(&self.scroll_step_size_items, index);
+    }
+
+    /// Move the checkmark in the "Selection Aspect Ratio" submenu to `aspect`.
+    pub fn set_selection_aspect(&self, aspect: CropAspect) {
+        let index = CropAspect::ALL.iter().position(|&a| a == aspect).unwrap_or(0);
+        check_choice(&self.selection_aspect_items, index);
+    }
+
+    /// Check or uncheck the "Crosshair Guides" item.
+    pub fn set_crosshair_guides(&self, enabled: bool) {
+        check_item(&self.crosshair_guides_item, enabled);
+    }
+
+    /// Check or uncheck the "Vertical Toolbar" item.
+    pub fn set_vertical_toolbar(&self, vertical: bool) {
+        check_item(&self.vertical_toolbar_item, vertical);
+    }
+
+    /// Check or uncheck the "Keep Annotations Inside Selection" item.
+    pub fn set_keep_inside_selection(&self, enabled: bool) {
+        check_item(&self.keep_inside_selection_item, enabled);
+    }
+
+    /// Move the checkmark in the "Snap to Grid" submenu to `grid`.
+    pub fn set_snap_grid(&self, grid: SnapGrid) {
+        let index = SnapGrid::ALL.iter().position(|&g| g == grid).unwrap_or(0);
+        check_choice(&self.snap_grid_items, index);
+    }
+
+    /// Move the checkmark in the "Sticky Header" submenu to `margin`.
+    pub fn set_scroll_frozen_top(&self, margin: FrozenMargin) {
+        let index = FrozenMargin::ALL.iter().position(|&m| m == margin).unwrap_or(0);
+        check_choice(&self.scroll_frozen_top_items, index);
+    }
+
+    /// Move the checkmark in the "Sticky Footer" submenu to `margin`.
+    pub fn set_scroll_frozen_bottom(&self, margin: FrozenMargin) {
+        let index = FrozenMargin::ALL.iter().position(|&m| m == margin).unwrap_or(0);
+        check_choice(&self.scroll_frozen_bottom_items, index);
+    }
+
+    /// Show `hotkey` as the key equivalent of `action`'s menu item, or no key
+    /// equivalent if the hotkey is disabled (the item itself stays available).
+    pub fn set_hotkey(&self, action: HotkeyAction, hotkey: &HotKey, enabled: bool) {
+        let Some(index) = HotkeyAction::ALL.iter().position(|&a| a == action) else {
+            return;
+        };
+        if let Some(item) = self.hotkey_items.get(index) {
+            let key = if enabled {
+                crate::hotkey::key_equivalent(hotkey)
+            } else {
+                String::new()
+            };
+            item.setKeyEquivalent(&NSString::from_str(&key));
+            item.setKeyEquivalentModifierMask(crate::hotkey::modifier_flags(hotkey));
+        }
+    }
+
+    /// Check or uncheck the "Record Microphone" item.
+    pub fn set_record_microphone(&self, enabled: bool) {
+        check_item(&self.record_microphone_item, enabled);
+    }
+
+    /// Show `folder` as the save folder in the "Saving" submenu.
+    pub fn set_save_folder(&self, folder: &Path) {
+        self.save_folder_item.setTitle(&NSString::from_str(&save_folder_title(folder)));
+    }
+
+    /// Move the checkmark in the "Image Format" submenu to `format`.
+    pub fn set_image_format(&self, format: ImageFormat) {
+        let index = ImageFormat::ALL.iter().position(|&f| f == format).unwrap_or(0);
+        check_choice(&self.image_format_items, index);
+    }
+
+    /// Check or uncheck the "Also Copy to Clipboard" item.
+    pub fn set_copy_on_save(&self, enabled: bool) {
+        check_item(&self.copy_on_save_item, enabled);
+    }
+
+    /// Check or uncheck the "Copy Saved File" item.
+    pub fn set_copy_saved_file(&self, enabled: bool) {
+        check_item(&self.copy_saved_file_item, enabled);
+    }
+
+    /// Check or uncheck the "Save Without Asking" item.
+    pub fn set_auto_save(&self, enabled: bool) {
+        check_item(&self.auto_save_item, enabled);
+    }
+
+    /// Check or uncheck the "Notify When Saved" item.
+    pub fn set_notify_on_save(&self, enabled: bool) {
+        check_item(&self.notify_on_save_item, enabled);
+    }
+
+    /// Check or uncheck the "Play Sound When Saved" item.
+    pub fn set_save_sound(&self, enabled: bool) {
+        check_item(&self.save_sound_item, enabled);
+    }
+
+    /// Check or uncheck the "Reveal in Finder When Saved" item.
+    pub fn set_reveal_on_save(&self, enabled: bool) {
+        check_item(&self.reveal_on_save_item, enabled);
+    }
+
+    /// Move the checkmark in the "Max Recording Length" submenu to `limit`.
+    pub fn set_recording_limit(&self, limit: RecordingLimit) {
+        let index = RecordingLimit::ALL.iter().position(|&l| l == limit).unwrap_or(0);
+        check_choice(&self.recording_limit_items, index);
+    }
+
+    /// Record the time left before a length-limited recording stops; the
+    /// recording clock shows it on its next tick.
+    pub fn set_recording_time_left(&self, seconds: u64) {
+        self.recording_time_left.set(Some(seconds));
     }
 
     pub fn enter_recording_mode(&self, mtm: MainThreadMarker) {
@@ -120,9 +906,11 @@ impl StatusBar {
             item.setHidden(true);
         }
         self.stop_recording_item.setHidden(false);
+        self.start_recording_clock(mtm);
     }
 
     pub fn exit_recording_mode(&self, mtm: MainThreadMarker) {
+        self.stop_recording_clock();
         if let Some(button) = self.status_item.button(mtm) {
             if let Some(icon) = self.camera_icon.as_deref() {
                 button.setTitle(&NSString::from_str(""));
@@ -136,6 +924,109 @@ impl StatusBar {
         }
         self.stop_recording_item.setHidden(true);
     }
+
+    /// Show the seconds left before a delayed capture next to the camera icon,
+    /// or restore the normal menu bar item when `seconds_left` is None.
+    pub fn set_capture_countdown(&self, mtm: MainThreadMarker, seconds_left: Option<u64>) {
+        if let Some(button) = self.status_item.button(mtm) {
+            let has_icon = self.camera_icon.is_some();
+            match seconds_left {
+                Some(seconds) if has_icon => {
+                    button.setImagePosition(NSCellImagePosition::ImageLeft);
+                    button.setTitle(&NSString::from_str(&format!(" {}", seconds)));
+                }
+                Some(seconds) => {
+                    button.setTitle(&NSString::from_str(&format!("\u{1F4F7} {}", seconds)));
+                }
+                None if has_icon => button.setTitle(&NSString::from_str("")),
+                None => button.setTitle(&NSString::from_str("\u{1F4F7}")), // 📷
+            }
+        }
+        for item in &self.normal_items {
+            item.setHidden(seconds_left.is_some());
+        }
+        self.cancel_capture_delay_item.setHidden(seconds_left.is_none());
+    }
+
+    /// Replace the clock next to the recording icon with `text` (scroll capture progress).
+    pub fn set_recording_text(&self, mtm: MainThreadMarker, text: &str) {
+        self.stop_recording_clock();
+        if let Some(button) = self.status_item.button(mtm) {
+            set_recording_title(&button, self.recording_icon.is_some(), text);
+        }
+    }
+
+    /// Show a live MM:SS clock next to the recording icon, counted from now.
+    fn start_recording_clock(&self, mtm: MainThreadMarker) {
+        self.stop_recording_clock();
+        let Some(button) = self.status_item.button(mtm) else {
+            return;
+        };
+        let has_icon = self.recording_icon.is_some();
+        let time_left = self.recording_time_left.clone();
+        let started = Instant::now();
+        let show = move || {
+            let text = recording_clock_text(started.elapsed().as_secs(), time_left.get());
+            set_recording_title(&button, has_icon, &text);
+        };
+        show();
+        let block = RcBlock::new(move |_timer: NonNull<NSTimer>| show());
+        let timer = unsafe { NSTimer::scheduledTimerWithTimeInterval_repeats_block(1.0, true, &block) };
+        *self.recording_clock.borrow_mut() = Some(timer);
+    }
+
+    fn stop_recording_clock(&self) {
+        if let Some(timer) = self.recording_clock.borrow_mut().take() {
+            timer.invalidate();
+        }
+        self.recording_time_left.set(None);
+    }
+}
+
+// Covers the menu bar icon to take file drops. The status item's button and window
+// belong to AppKit, so drops land on this view instead. Clicks aren't handled here
+// and pass up to the button, which opens the menu.
+define_class!(
+    #[unsafe(super(NSView))]
+    #[thread_kind = MainThreadOnly]
+    #[name = "StatusDropView"]
+    struct StatusDropView;
+
+    impl StatusDropView {
+        #[unsafe(method(draggingEntered:))]
+        fn dragging_entered(&self, sender: &ProtocolObject<dyn NSDraggingInfo>) -> NSDragOperation {
+            let Some(delegate) = NSApplication::sharedApplication(self.mtm()).delegate() else {
+                return NSDragOperation::None;
+            };
+            unsafe { msg_send![&*delegate, draggingEntered: sender] }
+        }
+
+        #[unsafe(method(performDragOperation:))]
+        fn perform_drag_operation(&self, sender: &ProtocolObject<dyn NSDraggingInfo>) -> Bool {
+            let Some(delegate) = NSApplication::sharedApplication(self.mtm()).delegate() else {
+                return Bool::NO;
+            };
+            unsafe { msg_send![&*delegate, performDragOperation: sender] }
+        }
+    }
+);
+
+/// `MM:SS` elapsed, followed by the time left when the recording is length-limited.
+fn recording_clock_text(elapsed: u64, time_left: Option<u64>) -> String {
+    let clock = |secs: u64| format!("{:02}:{:02}", secs / 60, secs % 60);
+    match time_left {
+        Some(left) => format!("{} (\u{2212}{})", clock(elapsed), clock(left)),
+        None => clock(elapsed),
+    }
+}
+
+fn set_recording_title(button: &NSStatusBarButton, has_icon: bool, text: &str) {
+    if has_icon {
+        button.setImagePosition(NSCellImagePosition::ImageLeft);
+        button.setTitle(&NSString::from_str(&format!(" {}", text)));
+    } else {
+        button.setTitle(&NSString::from_str(&format!("\u{1F534} {}", text)));
+    }
 }
 
 fn make_template_symbol(symbol_name: &str, description: &str) -> Option<Retained<NSImage>> {
@@ -146,6 +1037,12 @@ fn make_template_symbol(symbol_name: &str, description: &str) -> Option<Retained
     Some(img)
 }
 
+/// "Save Folder: Desktop…" — the folder's name, opening a chooser.
+fn save_folder_title(folder: &Path) -> String {
+    let name = folder.file_name().map(|n| n.to_string_lossy()).unwrap_or_default();
+    format!("Save Folder: {}\u{2026}", name)
+}
+
 fn create_menu_item(
     mtm: MainThreadMarker,
     title: &str,
@@ -164,3 +1061,50 @@ fn create_menu_item(
     item.setKeyEquivalentModifierMask(modifiers);
     item
 }
+
+/// Check the item at `index` of a choice submenu and uncheck the others.
+fn check_choice(items: &[Retained<NSMenuItem>], index: usize) {
+    for (i, item) in items.iter().enumerate() {
+        check_item(item, i == index);
+    }
+}
+
+/// Check or uncheck a toggle item.
+fn check_item(item: &NSMenuItem, on: bool) {
+    item.setState(if on {
+        NSControlStateValueOn
+    } else {
+        NSControlStateValueOff
+    });
+}
+
+/// Create an item titled `title` with a submenu of `choices` (title, checked),
+/// each sending `action` tagged with its index. Returns the item and the choices.
+fn create_choice_submenu<const N: usize>(
+    mtm: MainThreadMarker,
+    title: &str,
+    choices: [(&str, bool); N],
+    action: &std::ffi::CStr,
+) -> (Retained<NSMenuItem>, Vec<Retained<NSMenuItem>>) {
+    let parent = unsafe {
+        NSMenuItem::initWithTitle_action_keyEquivalent(
+            mtm.alloc(),
+            &NSString::from_str(title),
+            None,
+            &NSString::from_str(""),
+        )
+    };
+    let submenu = NSMenu::new(mtm);
+    let mut items = Vec::new();
+    for (i, (choice, checked)) in choices.into_iter().enumerate() {
+        let item = create_menu_item(mtm, choice, action, "", NSEventModifierFlags::empty());
+        item.setTag(i as isize);
+        if checked {
+            item.setState(NSControlStateValueOn);
+        }
+        submenu.addItem(&item);
+        items.push(item);
+    }
+    parent.setSubmenu(Some(&submenu));
+    (parent, items)
+}
@@ -6,14 +6,113 @@ use objc2_core_graphics::{
     CGImageAlphaInfo,
 };
 
+/// Largest fraction of the frame height a frozen band may take (each of top and bottom).
+const MAX_FROZEN_FRACTION: usize = 3;
+
+/// A band of rows pinned to the top or bottom of every frame, such as a sticky
+/// header or footer, that stays put while the content between scrolls.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum FrozenBand {
+    /// Nothing is pinned (default).
+    None,
+    /// Use the tallest run of rows that is identical in every frame.
+    Auto,
+    /// A fixed height in pixels.
+    Rows(usize),
+}
+
 /// Stitch multiple captured frames into a single tall image by detecting overlapping regions.
 ///
 /// `rgba_data` contains pre-converted RGBA pixel data for each frame, captured at the same
 /// time as the CGImages. This avoids CGImage copy-on-write issues where backing data becomes
 /// stale between capture time and stitch time.
+///
+/// The `top` and `bottom` frozen bands are left out of overlap detection and drawn once,
+/// from the first frame, at the top and bottom of the result.
 pub fn stitch_frames(
     frames: &[CFRetained<CGImage>],
     rgba_data: &[Vec<u8>],
+    top: FrozenBand,
+    bottom: FrozenBand,
+) -> Option<CFRetained<CGImage>> {
+    if frames.is_empty() || rgba_data.len() != frames.len() {
+        return None;
+    }
+    if frames.len() == 1 {
+        return copy_cgimage(&frames[0]);
+    }
+
+    let frame_width = CGImage::width(Some(&frames[0]));
+    let frame_height = CGImage::height(Some(&frames[0]));
+
+    let (top_rows, bottom_rows) =
+        resolve_frozen_bands(rgba_data, frame_width, frame_height, top, bottom);
+    let (mut output, total_height) =
+        stitch_rgba(rgba_data, frame_width, frame_height, top_rows, bottom_rows)?;
+    rgba_to_cgimage(&mut output, frame_width, total_height)
+}
+
+/// Turn the requested frozen bands into row counts, detecting `Auto` bands
+/// and shrinking oversized ones to at most a third of the frame each.
+fn resolve_frozen_bands(
+    rgba_data: &[Vec<u8>],
+    width: usize,
+    height: usize,
+    top: FrozenBand,
+    bottom: FrozenBand,
+) -> (usize, usize) {
+    let limit = height / MAX_FROZEN_FRACTION;
+    let auto = top == FrozenBand::Auto || bottom == FrozenBand::Auto;
+    let (detected_top, detected_bottom) = if auto {
+        detect_frozen_rows(rgba_data, width, height)
+    } else {
+        (0, 0)
+    };
+    let rows = |band: FrozenBand, detected: usize| match band {
+        FrozenBand::None => 0,
+        FrozenBand::Auto => detected,
+        FrozenBand::Rows(rows) => rows.min(limit),
+    };
+    let top_rows = rows(top, detected_top);
+    let bottom_rows = rows(bottom, detected_bottom);
+    if top_rows > 0 || bottom_rows > 0 {
+        eprintln!("Stitch: frozen bands top {} rows, bottom {} rows", top_rows, bottom_rows);
+    }
+    (top_rows, bottom_rows)
+}
+
+/// Find the tallest runs of rows at the top and bottom that are identical in
+/// every frame (sticky headers and footers). Each run is capped at a third of
+/// the frame, so frames that didn't scroll at all still leave rows to stitch.
+fn detect_frozen_rows(rgba_data: &[Vec<u8>], width: usize, height: usize) -> (usize, usize) {
+    if rgba_data.len() < 2 || width == 0 {
+        return (0, 0);
+    }
+    let bpr = width * 4;
+    // Skip the rightmost 5% like `find_overlap`: the scrollbar moves in every frame
+    let byte_end = width.saturating_sub(width / 20) * 4;
+    let first = &rgba_data[0];
+    let row_is_frozen = |row: usize| {
+        let range = row * bpr..row * bpr + byte_end;
+        first.len() >= range.end
+            && rgba_data[1..]
+                .iter()
+                .all(|data| data.get(range.clone()) == Some(&first[range.clone()]))
+    };
+
+    let limit = height / MAX_FROZEN_FRACTION;
+    let top = (0..limit).take_while(|&row| row_is_frozen(row)).count();
+    let bottom = (0..limit)
+        .take_while(|&i| row_is_frozen(height - 1 - i))
+        .count();
+    (top, bottom)
+}
+
+/// Stitch frames captured while scrolling right into a single wide image,
+/// detecting overlapping columns instead of rows.
+pub fn stitch_frames_horizontally(
+    frames: &[CFRetained<CGImage>],
+    rgba_data: &[Vec<u8>],
 ) -> Option<CFRetained<CGImage>> {
     if frames.is_empty() || rgba_data.len() != frames.len() {
         return None;
@@ -25,14 +124,58 @@ pub fn stitch_frames(
     let frame_width = CGImage::width(Some(&frames[0]));
     let frame_height = CGImage::height(Some(&frames[0]));
 
+    // Transposed, each frame's columns become rows, so stacking the transposed
+    // frames vertically joins the originals side by side
+    let transposed: Vec<Vec<u8>> = rgba_data
+        .iter()
+        .map(|data| transpose_rgba(data, frame_width, frame_height))
+        .collect();
+    let (stitched, total_width) = stitch_rgba(&transposed, frame_height, frame_width, 0, 0)?;
+    let mut output = transpose_rgba(&stitched, frame_height, total_width);
+    rgba_to_cgimage(&mut output, total_width, frame_height)
+}
+
+/// Detect the column overlap between two frames scrolled horizontally
+/// (`b` to the right of `a`). Returns the overlap in pixels.
+pub fn find_column_overlap(data_a: &[u8], data_b: &[u8], width: usize, height: usize) -> usize {
+    // The row search skips the right edge of the transposed frames, which is
+    // the bottom edge (horizontal scrollbar) of the originals
+    find_overlap(
+        &transpose_rgba(data_a, width, height),
+        &transpose_rgba(data_b, width, height),
+        height,
+        width,
+    )
+}
+
+/// Stack RGBA frames top to bottom, dropping the rows each frame shares with
+/// the one above it. Returns the buffer and its height in rows.
+///
+/// The first `top_rows` and last `bottom_rows` of each frame are frozen: only
+/// the scrolling band between them is matched and stacked, and frame 0's
+/// frozen rows frame the result.
+fn stitch_rgba(
+    rgba_data: &[Vec<u8>],
+    frame_width: usize,
+    frame_height: usize,
+    top_rows: usize,
+    bottom_rows: usize,
+) -> Option<(Vec<u8>, usize)> {
+    let bpr = frame_width * 4;
+    let band_height = frame_height.checked_sub(top_rows + bottom_rows)?;
+    // Rows are contiguous, so each frame's scrolling band is a plain subslice
+    let bands: Vec<&[u8]> = rgba_data
+        .iter()
+        .map(|data| {
+            let start = (top_rows * bpr).min(data.len());
+            let end = ((top_rows + band_height) * bpr).min(data.len());
+            &data[start..end]
+        })
+        .collect();
+
     // Find overlap between each consecutive pair
     let start = std::time::Instant::now();
-    let mut overlaps = Vec::with_capacity(frames.len() - 1);
-    for i in 0..frames.len() - 1 {
-        let overlap =
-            find_overlap(&rgba_data[i], &rgba_data[i + 1], frame_width, frame_height);
-        overlaps.push(overlap);
-    }
+    let overlaps = find_overlaps_parallel(&bands, frame_width, band_height);
     eprintln!(
         "Stitch: overlap detection took {:?}, overlaps: {:?}",
         start.elapsed(),
@@ -42,7 +185,7 @@ pub fn stitch_frames(
     // Compute total canvas height
     let mut total_height = frame_height;
     for &overlap in &overlaps {
-        let addition = frame_height.saturating_sub(overlap);
+        let addition = band_height.saturating_sub(overlap);
         if addition == 0 {
             continue;
         }
@@ -56,23 +199,22 @@ pub fn stitch_frames(
     // Compose output directly from RGBA byte data (avoids CGImage stale-data issues).
     // Both source and output use the same layout: RGBA 8bpc, top-down, row-major.
     let start = std::time::Instant::now();
-    let bpr = frame_width * 4;
     let mut output = vec![0u8; bpr * total_height];
     let mut current_row: usize = 0;
 
-    // First frame: copy all rows
-    let copy_bytes = bpr * frame_height;
+    // First frame: copy all rows except the bottom frozen band, which goes last
+    let copy_bytes = bpr * (frame_height - bottom_rows);
     output[..copy_bytes].copy_from_slice(&rgba_data[0][..copy_bytes]);
-    current_row += frame_height;
+    current_row += frame_height - bottom_rows;
 
-    // Subsequent frames: copy only non-overlapping rows
+    // Subsequent frames: copy only the non-overlapping rows of the scrolling band
     for (i, &overlap) in overlaps.iter().enumerate() {
-        let addition = frame_height.saturating_sub(overlap);
+        let addition = band_height.saturating_sub(overlap);
         if addition == 0 {
             continue;
         }
 
-        let src = &rgba_data[i + 1];
+        let src = bands[i + 1];
         let src_start = overlap * bpr;
         let src_end = src_start + addition * bpr;
         let dst_start = current_row * bpr;
@@ -81,27 +223,94 @@ pub fn stitch_frames(
         current_row += addition;
     }
 
+    // Bottom frozen band, once, from the first frame
+    let src_start = (frame_height - bottom_rows) * bpr;
+    let dst_start = current_row * bpr;
+    output[dst_start..].copy_from_slice(&rgba_data[0][src_start..frame_height * bpr]);
+
     eprintln!("Stitch: canvas compositing took {:?}", start.elapsed());
+    Some((output, total_height))
+}
+
+/// Run `find_overlap` on every consecutive pair of frames, spreading the pairs
+/// across threads. Returns one overlap per pair, in frame order.
+fn find_overlaps_parallel(frames: &[&[u8]], width: usize, height: usize) -> Vec<usize> {
+    let pair_count = frames.len().saturating_sub(1);
+    if pair_count == 0 {
+        return Vec::new();
+    }
+    let threads = std::thread::available_parallelism()
+        .map_or(1, |n| n.get())
+        .min(pair_count);
+    // Each thread takes a contiguous run of pairs, so joining in spawn order
+    // keeps the overlaps in frame order
+    let pairs_per_thread = pair_count.div_ceil(threads);
+
+    std::thread::scope(|scope| {
+        let workers: Vec<_> = (0..pair_count)
+            .step_by(pairs_per_thread)
+            .map(|first| {
+                let last = (first + pairs_per_thread).min(pair_count);
+                let worker = scope.spawn(move || {
+                    (first..last)
+                        .map(|i| find_overlap(frames[i], frames[i + 1], width, height))
+                        .collect::<Vec<usize>>()
+                });
+                (last - first, worker)
+            })
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|(count, worker)| {
+                // A panicked worker stacks its frames without overlap
+                worker.join().unwrap_or_else(|_| vec![0; count])
+            })
+            .collect()
+    })
+}
 
-    // Create CGImage from the composited buffer
+/// Create a CGImage from a premultiplied RGBA 8bpc, top-down, row-major buffer.
+/// The image copies the buffer, so it can be dropped afterwards.
+pub(crate) fn rgba_to_cgimage(
+    output: &mut [u8],
+    width: usize,
+    height: usize,
+) -> Option<CFRetained<CGImage>> {
+    if width == 0 || height == 0 || output.len() < width * height * 4 {
+        return None;
+    }
     let color_space = CGColorSpace::new_device_rgb()?;
     let bitmap_info = CGImageAlphaInfo::PremultipliedLast.0;
     let ctx = unsafe {
         CGBitmapContextCreate(
             output.as_mut_ptr() as *mut _,
-            frame_width,
-            total_height,
+            width,
+            height,
             8,
-            bpr,
+            width * 4,
             Some(&color_space),
             bitmap_info,
         )
     }?;
 
-    let image = CGBitmapContextCreateImage(Some(&ctx));
-    drop(ctx);
-    drop(output);
-    image
+    CGBitmapContextCreateImage(Some(&ctx))
+}
+
+/// Swap rows and columns of a `width`×`height` RGBA buffer, producing a
+/// `height`×`width` buffer.
+fn transpose_rgba(data: &[u8], width: usize, height: usize) -> Vec<u8> {
+    let mut out = vec![0u8; width * height * 4];
+    if data.len() < out.len() {
+        return out;
+    }
+    for y in 0..height {
+        for x in 0..width {
+            let src = (y * width + x) * 4;
+            let dst = (x * height + y) * 4;
+            out[dst..dst + 4].copy_from_slice(&data[src..src + 4]);
+        }
+    }
+    out
 }
 
 /// Hash one pixel row's RGB bytes over the column range `[byte_start, byte_end)`.
@@ -125,7 +334,7 @@ fn hash_row(data: &[u8], row: usize, bpr: usize, byte_start: usize, byte_end: us
 /// 2. Pick a reference row from A, find candidate overlap values by matching its hash in B.
 /// 3. For each candidate, count contiguous matching row hashes. The candidate with the
 ///    longest run wins. Requires a minimum run of 20 rows to accept.
-fn find_overlap(
+pub fn find_overlap(
     data_a: &[u8],
     data_b: &[u8],
     width: usize,
@@ -2,34 +2,64 @@ use std::cell::{Cell, RefCell};
 
 use objc2::rc::Retained;
 use objc2::runtime::Sel;
-use objc2::{define_class, msg_send, DefinedClass, MainThreadOnly};
-use objc2_app_kit::{NSButton, NSColor, NSEvent, NSFont, NSView};
+use objc2::{define_class, msg_send, DefinedClass, MainThreadOnly, Message};
+use objc2_app_kit::{
+    NSAccessibility, NSButton, NSColor, NSEvent, NSFont, NSFontDescriptorSymbolicTraits,
+    NSSegmentSwitchTracking, NSSegmentedControl, NSTextField, NSView,
+};
 use objc2_core_foundation::{CGFloat, CGPoint, CGSize};
 use objc2_foundation::{MainThreadMarker, NSRect, NSString};
 
+use crate::annotation::model::{text_font, BlurStyle, StepShape, StrokeStyle, TEXT_FONTS};
+use crate::editor::view::CropAspect;
+
 const BUTTON_W: CGFloat = 28.0;
 const BUTTON_H: CGFloat = 24.0;
 const BUTTON_SPACING: CGFloat = 2.0;
+/// Gap between groups of buttons.
+const GROUP_SPACING: CGFloat = BUTTON_SPACING * 3.0;
+/// Gap between rows (or columns) when the buttons wrap.
+const LINE_SPACING: CGFloat = BUTTON_SPACING * 2.0;
 const TOOLBAR_PADDING: CGFloat = 4.0;
-const COLOR_WELL_W: CGFloat = 28.0;
+const HEX_FIELD_W: CGFloat = 64.0;
+/// As wide as the six stroke buttons the crop aspect picker stands in for.
+const CROP_ASPECT_W: CGFloat = 6.0 * (BUTTON_W + BUTTON_SPACING) - BUTTON_SPACING;
 
 const TOOL_BUTTONS: &[(&str, &str, &str)] = &[
     ("\u{2196}", "toolSelect:",    "Select (S)"),
     ("\u{2192}", "toolArrow:",     "Arrow (A)"),
+    ("\u{2571}", "toolLine:",      "Line (L)"),
     ("\u{25A1}", "toolRect:",      "Rectangle (R)"),
     ("\u{25CB}", "toolEllipse:",   "Ellipse (E)"),
     ("\u{270E}", "toolPencil:",    "Pencil (P)"),
     ("T",        "toolText:",      "Text (T)"),
     ("\u{25A8}", "toolHighlight:", "Highlight (H)"),
+    ("\u{1F58D}", "toolHighlighter:", "Highlighter (G)"),
     ("\u{2460}", "toolStep:",      "Step (N)"),
     ("\u{2591}", "toolBlur:",      "Blur (B)"),
+    ("\u{25AC}", "toolRedact:",    "Redact (X)"),
+    ("\u{25C9}", "toolSpotlight:", "Spotlight (O)"),
+    ("\u{1F5E8}", "toolCallout:",  "Callout (K)"),
     ("\u{2702}", "toolCrop:",      "Crop (C)"),
+    ("\u{2316}", "toolEyedropper:", "Eyedropper (I)"),
 ];
 
+/// Corner radius presets cycled by the rounded-corner button (0 = square).
+pub const CORNER_RADII: [CGFloat; 4] = [0.0, 6.0, 12.0, 20.0];
+
+/// Highlight opacity presets cycled by the opacity button.
+pub const HIGHLIGHT_OPACITIES: [CGFloat; 4] = [0.2, 0.35, 0.55, 0.8];
+
+/// Line widths and text sizes (thin/medium/thick) selected by the three stroke buttons.
+pub const STROKE_WIDTHS: [(CGFloat, CGFloat); 3] = [(1.5, 14.0), (3.0, 18.0), (5.5, 24.0)];
+
+/// Blur block sizes (fine/medium/coarse) selected by the three stroke buttons.
+pub const BLUR_BLOCK_SIZES: [usize; 3] = [5, 10, 20];
+
 const STROKE_BUTTONS: &[(&str, &str, &str)] = &[
-    ("\u{2500}", "strokeThin:",   "Thin (1)"),
-    ("\u{2501}", "strokeMedium:", "Medium (2)"),
-    ("\u{2588}", "strokeThick:",  "Thick (3)"),
+    ("\u{2500}", "strokeThin:",   "Thin / Fine Blur (1)"),
+    ("\u{2501}", "strokeMedium:", "Medium / Medium Blur (2)"),
+    ("\u{2588}", "strokeThick:",  "Thick / Coarse Blur (3)"),
 ];
 
 const PLAYBACK_BUTTONS: &[(&str, &str, &str)] = &[
@@ -42,9 +72,20 @@ const ACTION_BUTTONS: &[(&str, &str, &str)] = &[
     ("\u{21AA}", "actionRedo:",    "Redo (Cmd+Shift+Z)"),
     ("\u{2715}", "actionCancel:",  "Cancel (Esc)"),
     ("S",        "actionSave:",    "Save to File"),
+    ("\u{1F4CC}", "actionPin:",    "Pin to Screen"),
+    ("\u{2601}", "actionUpload:",  "Upload and Copy Link"),
     ("\u{2713}", "actionConfirm:", "Confirm"),
 ];
 
+/// A control in the toolbar's flow layout.
+struct ToolbarItem {
+    view: Retained<NSView>,
+    /// Size in the horizontal layout; a vertical toolbar stacks controls along their width.
+    size: CGSize,
+    /// Whether a wider gap sets it apart from the control before it.
+    starts_group: bool,
+}
+
 pub struct ToolbarViewIvars {
     /// All buttons except Confirm, for enabling/disabling.
     non_confirm_buttons: RefCell<Vec<Retained<NSButton>>>,
@@ -60,6 +101,72 @@ pub struct ToolbarViewIvars {
     color_button: RefCell<Option<Retained<NSButton>>>,
     /// Whether the color picker is currently shown.
     color_picker_active: Cell<bool>,
+    /// Current annotation color shown on the color button.
+    color: Cell<(CGFloat, CGFloat, CGFloat)>,
+    /// `#RRGGBB` entry for the annotation color.
+    hex_field: RefCell<Option<Retained<NSTextField>>>,
+    /// Fill toggle for new rectangles and ellipses.
+    fill_button: RefCell<Option<Retained<NSButton>>>,
+    /// Whether new rectangles and ellipses are filled.
+    fill_active: Cell<bool>,
+    /// Drop shadow toggle for new arrows, lines, shapes, and pencil strokes.
+    shadow_button: RefCell<Option<Retained<NSButton>>>,
+    /// Whether new annotations get a drop shadow.
+    shadow_active: Cell<bool>,
+    /// Rounded-corner button for rectangles.
+    corner_button: RefCell<Option<Retained<NSButton>>>,
+    /// Index into `CORNER_RADII` of the current corner radius.
+    corner_radius_index: Cell<usize>,
+    /// Highlight opacity button.
+    opacity_button: RefCell<Option<Retained<NSButton>>>,
+    /// Index into `HIGHLIGHT_OPACITIES` of the current highlight opacity.
+    opacity_index: Cell<usize>,
+    /// Stroke style button (solid/dashed/dotted).
+    stroke_style_button: RefCell<Option<Retained<NSButton>>>,
+    /// Current stroke style for new shapes and lines.
+    stroke_style: Cell<StrokeStyle>,
+    /// Blur style button (pixelate/Gaussian).
+    blur_style_button: RefCell<Option<Retained<NSButton>>>,
+    /// Current style for new Blur annotations.
+    blur_style: Cell<BlurStyle>,
+    /// Step marker shape button, only shown while the Step tool is active.
+    step_shape_button: RefCell<Option<Retained<NSButton>>>,
+    /// Current marker shape for new Step annotations.
+    step_shape: Cell<StepShape>,
+    /// Font family button, only shown while the Text tool is active.
+    font_button: RefCell<Option<Retained<NSButton>>>,
+    /// Index into `TEXT_FONTS` of the font for new text labels.
+    font_index: Cell<usize>,
+    /// Bold toggle, only shown while the Text tool is active.
+    bold_button: RefCell<Option<Retained<NSButton>>>,
+    /// Whether new text labels are bold.
+    bold_active: Cell<bool>,
+    /// Italic toggle, only shown while the Text tool is active.
+    italic_button: RefCell<Option<Retained<NSButton>>>,
+    /// Whether new text labels are italic.
+    italic_active: Cell<bool>,
+    /// Pencil smoothing toggle, only shown while the Pencil or Highlighter tool is active.
+    smooth_button: RefCell<Option<Retained<NSButton>>>,
+    /// Whether new pencil strokes are smoothed.
+    smooth_active: Cell<bool>,
+    /// Text background box toggle, only shown while the Text tool is active.
+    text_background_button: RefCell<Option<Retained<NSButton>>>,
+    /// Whether new text labels get a background box.
+    text_background_active: Cell<bool>,
+    /// Text outline toggle, only shown while the Text tool is active.
+    text_outline_button: RefCell<Option<Retained<NSButton>>>,
+    /// Whether new text labels are outlined.
+    text_outline_active: Cell<bool>,
+    /// Stroke width/style buttons, swapped out for the aspect picker while cropping.
+    stroke_group: RefCell<Vec<Retained<NSButton>>>,
+    /// Crop aspect ratio picker, only shown while the Crop tool is active.
+    crop_aspect_control: RefCell<Option<Retained<NSSegmentedControl>>>,
+    /// Every control in layout order.
+    items: RefCell<Vec<ToolbarItem>>,
+    /// Whether buttons are stacked top-to-bottom instead of left-to-right.
+    vertical: Cell<bool>,
+    /// Longest a row (or column) may get before the buttons wrap onto another.
+    max_length: Cell<CGFloat>,
 }
 
 define_class!(
@@ -84,18 +191,8 @@ define_class!(
 
 impl ToolbarView {
     pub fn new(mtm: MainThreadMarker) -> Retained<Self> {
-        // tools + 1 (color well) + strokes + playback + actions + 4 gaps
-        let total_slots = TOOL_BUTTONS.len()
-            + 1 // color well
-            + STROKE_BUTTONS.len()
-            + PLAYBACK_BUTTONS.len()
-            + ACTION_BUTTONS.len()
-            + 4;
-        let width =
-            TOOLBAR_PADDING * 2.0 + total_slots as CGFloat * (BUTTON_W + BUTTON_SPACING);
-        let height = TOOLBAR_PADDING * 2.0 + BUTTON_H;
-
-        let frame = NSRect::new(CGPoint::ZERO, CGSize::new(width, height));
+        // The size follows from the buttons once they're laid out
+        let frame = NSRect::new(CGPoint::ZERO, CGSize::ZERO);
         let this = mtm.alloc().set_ivars(ToolbarViewIvars {
             non_confirm_buttons: RefCell::new(Vec::new()),
             tool_buttons: RefCell::new(Vec::new()),
@@ -104,76 +201,326 @@ impl ToolbarView {
             active_stroke_index: Cell::new(1), // Medium is default
             color_button: RefCell::new(None),
             color_picker_active: Cell::new(false),
+            color: Cell::new((1.0, 0.0, 0.0)),
+            hex_field: RefCell::new(None),
+            fill_button: RefCell::new(None),
+            fill_active: Cell::new(false),
+            shadow_button: RefCell::new(None),
+            shadow_active: Cell::new(false),
+            corner_button: RefCell::new(None),
+            corner_radius_index: Cell::new(0),
+            opacity_button: RefCell::new(None),
+            opacity_index: Cell::new(1),
+            stroke_style_button: RefCell::new(None),
+            stroke_style: Cell::new(StrokeStyle::Solid),
+            blur_style_button: RefCell::new(None),
+            blur_style: Cell::new(BlurStyle::Pixelate),
+            step_shape_button: RefCell::new(None),
+            step_shape: Cell::new(StepShape::Circle),
+            font_button: RefCell::new(None),
+            font_index: Cell::new(0),
+            bold_button: RefCell::new(None),
+            bold_active: Cell::new(false),
+            italic_button: RefCell::new(None),
+            italic_active: Cell::new(false),
+            smooth_button: RefCell::new(None),
+            smooth_active: Cell::new(true),
+            text_background_button: RefCell::new(None),
+            text_background_active: Cell::new(false),
+            text_outline_button: RefCell::new(None),
+            text_outline_active: Cell::new(false),
+            stroke_group: RefCell::new(Vec::new()),
+            crop_aspect_control: RefCell::new(None),
+            items: RefCell::new(Vec::new()),
+            vertical: Cell::new(false),
+            max_length: Cell::new(CGFloat::INFINITY),
         });
         let view: Retained<Self> = unsafe { msg_send![super(this), initWithFrame: frame] };
-
-        let mut x = TOOLBAR_PADDING;
+        let mut items = Vec::new();
         let mut non_confirm = Vec::new();
         let mut tool_btns = Vec::new();
 
         for (label, sel_name, tooltip) in TOOL_BUTTONS {
-            let btn = create_button(mtm, label, sel_name, tooltip, x, TOOLBAR_PADDING);
-            view.addSubview(&btn);
+            let btn = create_button(mtm, label, sel_name, tooltip);
+            view.add_item(&mut items, &btn, false);
             tool_btns.push(btn.clone());
             non_confirm.push(btn);
-            x += BUTTON_W + BUTTON_SPACING;
         }
 
-        x += BUTTON_SPACING * 2.0;
-
         // Color button (toggles the color picker)
-        let color_btn = create_button(mtm, "\u{25A0}", "toggleColorPicker:", "Color", x, TOOLBAR_PADDING);
+        let color_btn = create_button(mtm, "\u{25A0}", "toggleColorPicker:", "Color");
         // Set initial color (red) via attributed title
         set_button_title_color(&color_btn, "\u{25A0}", &NSColor::redColor());
-        view.addSubview(&color_btn);
+        view.add_item(&mut items, &color_btn, true);
         non_confirm.push(color_btn.clone());
         *view.ivars().color_button.borrow_mut() = Some(color_btn);
-        x += COLOR_WELL_W + BUTTON_SPACING;
 
-        x += BUTTON_SPACING * 2.0;
+        // Hex color entry (Enter applies, current color is mirrored back)
+        let hex_frame = NSRect::new(CGPoint::ZERO, CGSize::new(HEX_FIELD_W, BUTTON_H - 2.0));
+        let hex_field: Retained<NSTextField> =
+            unsafe { msg_send![mtm.alloc::<NSTextField>(), initWithFrame: hex_frame] };
+        hex_field.setStringValue(&NSString::from_str(&format_hex_color((1.0, 0.0, 0.0))));
+        hex_field.setFont(Some(&NSFont::monospacedDigitSystemFontOfSize_weight(11.0, 0.0)));
+        unsafe {
+            hex_field.setAction(Some(Sel::register(c"hexColorEntered:")));
+            hex_field.setTarget(None);
+        }
+        hex_field.setToolTip(Some(&NSString::from_str("Hex Color (#RRGGBB)")));
+        hex_field.setAccessibilityLabel(Some(&NSString::from_str("Hex Color")));
+        view.add_item(&mut items, &hex_field, false);
+        *view.ivars().hex_field.borrow_mut() = Some(hex_field);
+
+        // Fill toggle (solid rectangles/ellipses)
+        let fill_btn = create_button(mtm, "\u{25E7}", "toggleFill:", "Fill Shapes");
+        view.add_item(&mut items, &fill_btn, false);
+        non_confirm.push(fill_btn.clone());
+        *view.ivars().fill_button.borrow_mut() = Some(fill_btn);
+
+        // Drop shadow toggle (arrows, lines, shapes, pencil)
+        let shadow_btn = create_button(mtm, "\u{274F}", "toggleShadow:", "Drop Shadow");
+        view.add_item(&mut items, &shadow_btn, false);
+        non_confirm.push(shadow_btn.clone());
+        *view.ivars().shadow_button.borrow_mut() = Some(shadow_btn);
+
+        // Corner radius (cycles through CORNER_RADII for new rectangles)
+        let corner_btn = create_button(mtm, "\u{25A2}", "cycleCornerRadius:", "Corner Radius: Square");
+        view.add_item(&mut items, &corner_btn, false);
+        non_confirm.push(corner_btn.clone());
+        *view.ivars().corner_button.borrow_mut() = Some(corner_btn);
+
+        // Highlight opacity (cycles through HIGHLIGHT_OPACITIES)
+        let opacity_btn = create_button(mtm, "\u{25D1}", "cycleHighlightOpacity:", "Highlight Opacity: 35%");
+        view.add_item(&mut items, &opacity_btn, false);
+        non_confirm.push(opacity_btn.clone());
+        *view.ivars().opacity_button.borrow_mut() = Some(opacity_btn);
+
+        // Crop aspect ratio picker, shown in place of the stroke buttons while cropping
+        let aspect_frame = NSRect::new(CGPoint::ZERO, CGSize::new(CROP_ASPECT_W, BUTTON_H));
+        let aspect_control: Retained<NSSegmentedControl> =
+            unsafe { msg_send![mtm.alloc::<NSSegmentedControl>(), initWithFrame: aspect_frame] };
+        aspect_control.setSegmentCount(CropAspect::ALL.len() as isize);
+        let segment_w = (aspect_frame.size.width - 8.0) / CropAspect::ALL.len() as CGFloat;
+        for (i, aspect) in CropAspect::ALL.iter().enumerate() {
+            aspect_control.setLabel_forSegment(&NSString::from_str(aspect.label()), i as isize);
+            aspect_control.setWidth_forSegment(segment_w, i as isize);
+        }
+        aspect_control.setTrackingMode(NSSegmentSwitchTracking::SelectOne);
+        aspect_control.setSelectedSegment(0);
+        aspect_control.setFont(Some(&NSFont::systemFontOfSize(11.0)));
+        unsafe {
+            aspect_control.setAction(Some(Sel::register(c"cropAspectChanged:")));
+            aspect_control.setTarget(None);
+        }
+        aspect_control.setToolTip(Some(&NSString::from_str("Crop Aspect Ratio")));
+        aspect_control.setAccessibilityLabel(Some(&NSString::from_str("Crop Aspect Ratio")));
+        aspect_control.setHidden(true);
+        view.add_item(&mut items, &aspect_control, true);
+        *view.ivars().crop_aspect_control.borrow_mut() = Some(aspect_control);
 
         let mut stroke_btns = Vec::new();
-        for (label, sel_name, tooltip) in STROKE_BUTTONS {
-            let btn = create_button(mtm, label, sel_name, tooltip, x, TOOLBAR_PADDING);
-            view.addSubview(&btn);
+        for (i, (label, sel_name, tooltip)) in STROKE_BUTTONS.iter().enumerate() {
+            let btn = create_button(mtm, label, sel_name, tooltip);
+            view.add_item(&mut items, &btn, i == 0);
             stroke_btns.push(btn.clone());
             non_confirm.push(btn);
-            x += BUTTON_W + BUTTON_SPACING;
         }
 
-        x += BUTTON_SPACING * 2.0;
+        // Stroke style (cycles solid/dashed/dotted)
+        let style_btn = create_button(mtm, stroke_style_label(StrokeStyle::Solid), "cycleStrokeStyle:", "Stroke Style: Solid (D)");
+        view.add_item(&mut items, &style_btn, false);
+        non_confirm.push(style_btn.clone());
+        *view.ivars().stroke_style_button.borrow_mut() = Some(style_btn);
+
+        // Blur style (pixelate/Gaussian)
+        let blur_btn = create_button(mtm, blur_style_label(BlurStyle::Pixelate), "toggleBlurStyle:", "Blur Style: Pixelate");
+        view.add_item(&mut items, &blur_btn, false);
+        non_confirm.push(blur_btn.clone());
+        *view.ivars().blur_style_button.borrow_mut() = Some(blur_btn);
+
+        // Step marker shape (circle/square/diamond), visible with the Step tool
+        let step_btn = create_button(mtm, step_shape_label(StepShape::Circle), "cycleStepShape:", "Step Shape: Circle");
+        step_btn.setHidden(true);
+        view.add_item(&mut items, &step_btn, false);
+        non_confirm.push(step_btn.clone());
+        *view.ivars().step_shape_button.borrow_mut() = Some(step_btn.clone());
+
+        // Text font family, bold, italic, background box, and outline, visible with the Text tool
+        let font_btn = create_button(mtm, "Aa", "cycleTextFont:", "Font: System");
+        font_btn.setHidden(true);
+        view.add_item(&mut items, &font_btn, false);
+        non_confirm.push(font_btn.clone());
+        *view.ivars().font_button.borrow_mut() = Some(font_btn);
+
+        let bold_btn = create_button(mtm, "B", "toggleBold:", "Bold");
+        bold_btn.setFont(Some(&NSFont::boldSystemFontOfSize(12.0)));
+        bold_btn.setHidden(true);
+        view.add_item(&mut items, &bold_btn, false);
+        non_confirm.push(bold_btn.clone());
+        *view.ivars().bold_button.borrow_mut() = Some(bold_btn);
+
+        let italic_btn = create_button(mtm, "I", "toggleItalic:", "Italic");
+        let italic_font = text_font(None, 12.0, NSFontDescriptorSymbolicTraits::TraitItalic);
+        italic_btn.setFont(Some(&italic_font));
+        italic_btn.setHidden(true);
+        view.add_item(&mut items, &italic_btn, false);
+        non_confirm.push(italic_btn.clone());
+        *view.ivars().italic_button.borrow_mut() = Some(italic_btn);
+
+        let text_bg_btn = create_button(mtm, "\u{25A3}", "toggleTextBackground:", "Text Background");
+        text_bg_btn.setHidden(true);
+        view.add_item(&mut items, &text_bg_btn, false);
+        non_confirm.push(text_bg_btn.clone());
+        *view.ivars().text_background_button.borrow_mut() = Some(text_bg_btn);
+
+        let text_outline_btn = create_button(mtm, "\u{24B6}", "toggleTextOutline:", "Text Outline");
+        text_outline_btn.setHidden(true);
+        view.add_item(&mut items, &text_outline_btn, false);
+        non_confirm.push(text_outline_btn.clone());
+        *view.ivars().text_outline_button.borrow_mut() = Some(text_outline_btn);
+
+        // Pencil smoothing, visible with the Pencil and Highlighter tools
+        let smooth_btn = create_button(mtm, "\u{3030}", "toggleSmoothing:", "Smooth Pencil Strokes");
+        smooth_btn.setHidden(true);
+        view.add_item(&mut items, &smooth_btn, false);
+        non_confirm.push(smooth_btn.clone());
+        *view.ivars().smooth_button.borrow_mut() = Some(smooth_btn);
+
+        // Stroke width/style buttons, hidden while the aspect picker is shown
+        let mut stroke_group: Vec<Retained<NSButton>> = stroke_btns.clone();
+        for btn in [&view.ivars().stroke_style_button, &view.ivars().blur_style_button] {
+            stroke_group.extend(btn.borrow().iter().cloned());
+        }
+        stroke_group.push(step_btn);
+        *view.ivars().stroke_group.borrow_mut() = stroke_group;
 
-        for (label, sel_name, tooltip) in PLAYBACK_BUTTONS {
-            let btn = create_button(mtm, label, sel_name, tooltip, x, TOOLBAR_PADDING);
-            view.addSubview(&btn);
+        for (i, (label, sel_name, tooltip)) in PLAYBACK_BUTTONS.iter().enumerate() {
+            let btn = create_button(mtm, label, sel_name, tooltip);
+            view.add_item(&mut items, &btn, i == 0);
             non_confirm.push(btn);
-            x += BUTTON_W + BUTTON_SPACING;
         }
 
-        x += BUTTON_SPACING * 2.0;
-
         for (i, (label, sel_name, tooltip)) in ACTION_BUTTONS.iter().enumerate() {
-            let btn = create_button(mtm, label, sel_name, tooltip, x, TOOLBAR_PADDING);
-            view.addSubview(&btn);
+            let btn = create_button(mtm, label, sel_name, tooltip);
+            view.add_item(&mut items, &btn, i == 0);
             // Last button in ACTION_BUTTONS is Confirm — don't add it to non_confirm
             if i < ACTION_BUTTONS.len() - 1 {
                 non_confirm.push(btn);
             }
-            x += BUTTON_W + BUTTON_SPACING;
         }
 
+        *view.ivars().items.borrow_mut() = items;
         *view.ivars().non_confirm_buttons.borrow_mut() = non_confirm;
         *view.ivars().tool_buttons.borrow_mut() = tool_btns;
         *view.ivars().stroke_buttons.borrow_mut() = stroke_btns;
 
-        // Set initial active tool state (Select = index 0)
+        // Set initial active tool state (Select = index 0); this also lays out the buttons
         view.set_active_tool(0);
         // Set initial active stroke state (Medium = index 1)
         view.set_active_stroke(1);
+        // Pencil smoothing is on by default
+        view.set_smooth_active(true);
 
         view
     }
 
+    /// Add `control` to the view and to the end of the layout. `starts_group`
+    /// sets it apart from the control before it with a wider gap.
+    fn add_item(&self, items: &mut Vec<ToolbarItem>, control: &NSView, starts_group: bool) {
+        self.addSubview(control);
+        items.push(ToolbarItem {
+            view: control.retain(),
+            size: control.frame().size,
+            starts_group,
+        });
+    }
+
+    /// Wrap the buttons onto more rows (or columns) when a row would be longer
+    /// than `length` points.
+    pub fn set_max_length(&self, length: CGFloat) {
+        if self.ivars().max_length.replace(length) != length {
+            self.relayout();
+        }
+    }
+
+    /// Place the visible controls left to right, or top to bottom when vertical,
+    /// wrapping onto another row (or column) past `max_length`, and resize to fit.
+    /// Hidden controls take no space, so the bar closes up around the
+    /// tool-specific buttons. The window keeps its top-left corner.
+    fn relayout(&self) {
+        let vertical = self.ivars().vertical.get();
+        let max_length = self.ivars().max_length.get();
+        let items = self.ivars().items.borrow();
+        let mut pos = TOOLBAR_PADDING;
+        let mut line = 0;
+        let mut longest: CGFloat = 0.0;
+        let mut line_empty = true;
+        let mut group_gap = false;
+        for item in items.iter() {
+            group_gap |= item.starts_group;
+            if item.view.isHidden() {
+                continue;
+            }
+            let length = item.size.width;
+            if !line_empty {
+                let gap = if group_gap { GROUP_SPACING } else { BUTTON_SPACING };
+                if pos + gap + length + TOOLBAR_PADDING > max_length {
+                    longest = longest.max(pos);
+                    pos = TOOLBAR_PADDING;
+                    line += 1;
+                } else {
+                    pos += gap;
+                }
+            }
+            line_empty = false;
+            group_gap = false;
+
+            // Rows stack downward, columns rightward
+            let across = TOOLBAR_PADDING
+                + line as CGFloat * (if vertical { BUTTON_W } else { BUTTON_H } + LINE_SPACING);
+            // Controls wider than a column (the crop aspect picker) are turned on their side
+            let turns = length > BUTTON_W;
+            if turns {
+                item.view.setFrameCenterRotation(0.0);
+            }
+            if !vertical {
+                let y = across + (BUTTON_H - item.size.height) / 2.0;
+                item.view.setFrame(NSRect::new(CGPoint::new(pos, y), item.size));
+            } else if turns {
+                // Lay it out horizontally around the column's center, then rotate into place
+                let center = CGPoint::new(across + BUTTON_W / 2.0, pos + length / 2.0);
+                item.view.setFrame(NSRect::new(
+                    CGPoint::new(center.x - length / 2.0, center.y - BUTTON_H / 2.0),
+                    CGSize::new(length, BUTTON_H),
+                ));
+                item.view.setFrameCenterRotation(90.0);
+            } else {
+                item.view.setFrame(NSRect::new(
+                    CGPoint::new(across, pos + (length - BUTTON_H) / 2.0),
+                    CGSize::new(BUTTON_W, BUTTON_H),
+                ));
+            }
+            pos += length;
+        }
+
+        let length = longest.max(pos) + TOOLBAR_PADDING;
+        let lines = (line + 1) as CGFloat;
+        let size = if vertical {
+            let width = TOOLBAR_PADDING * 2.0 + lines * BUTTON_W + (lines - 1.0) * LINE_SPACING;
+            CGSize::new(width, length)
+        } else {
+            let height = TOOLBAR_PADDING * 2.0 + lines * BUTTON_H + (lines - 1.0) * LINE_SPACING;
+            CGSize::new(length, height)
+        };
+        self.setFrameSize(size);
+        if let Some(window) = self.window() {
+            let frame = window.frame();
+            let top = frame.origin.y + frame.size.height;
+            let origin = CGPoint::new(frame.origin.x, top - size.height);
+            window.setFrame_display(NSRect::new(origin, size), true);
+        }
+        self.setNeedsDisplay(true);
+    }
+
     /// Enable or disable all buttons except the Confirm button.
     pub fn set_non_confirm_buttons_enabled(&self, enabled: bool) {
         let buttons = self.ivars().non_confirm_buttons.borrow();
@@ -183,6 +530,22 @@ impl ToolbarView {
         }
     }
 
+    /// Stack the buttons top-to-bottom (or restore the left-to-right row) and resize to fit.
+    /// The hex field doesn't fit a single column, so it's hidden while vertical,
+    /// and the crop aspect picker is turned on its side.
+    pub fn set_vertical(&self, vertical: bool) {
+        self.ivars().vertical.set(vertical);
+        if let Some(ref field) = *self.ivars().hex_field.borrow() {
+            field.setHidden(vertical);
+        }
+        self.relayout();
+    }
+
+    /// Whether the buttons are stacked vertically.
+    pub fn is_vertical(&self) -> bool {
+        self.ivars().vertical.get()
+    }
+
     /// Set the active tool button by index. Updates visual state.
     pub fn set_active_tool(&self, index: usize) {
         let tool_buttons = self.ivars().tool_buttons.borrow();
@@ -198,6 +561,33 @@ impl ToolbarView {
             btn.setBezelStyle(objc2_app_kit::NSBezelStyle::SmallSquare);
         }
         self.ivars().active_tool_index.set(index);
+
+        let active_sel = TOOL_BUTTONS.get(index).map(|(_, sel, _)| *sel);
+        let crop_active = active_sel == Some("toolCrop:");
+        for btn in self.ivars().stroke_group.borrow().iter() {
+            btn.setHidden(crop_active);
+        }
+        if let Some(ref btn) = *self.ivars().step_shape_button.borrow() {
+            btn.setHidden(active_sel != Some("toolStep:"));
+        }
+        for btn in [
+            &self.ivars().font_button,
+            &self.ivars().bold_button,
+            &self.ivars().italic_button,
+            &self.ivars().text_background_button,
+            &self.ivars().text_outline_button,
+        ] {
+            if let Some(ref btn) = *btn.borrow() {
+                btn.setHidden(active_sel != Some("toolText:"));
+            }
+        }
+        if let Some(ref btn) = *self.ivars().smooth_button.borrow() {
+            btn.setHidden(!matches!(active_sel, Some("toolPencil:" | "toolHighlighter:")));
+        }
+        if let Some(ref control) = *self.ivars().crop_aspect_control.borrow() {
+            control.setHidden(!crop_active);
+        }
+        self.relayout();
     }
 
     /// Set the active stroke button by index. Updates visual state.
@@ -217,10 +607,29 @@ impl ToolbarView {
 
     /// Set the color button's displayed color programmatically.
     pub fn set_color(&self, r: CGFloat, g: CGFloat, b: CGFloat) {
+        self.ivars().color.set((r, g, b));
         if let Some(ref btn) = *self.ivars().color_button.borrow() {
             let color = NSColor::colorWithSRGBRed_green_blue_alpha(r, g, b, 1.0);
             set_button_title_color(btn, "\u{25A0}", &color);
         }
+        if let Some(ref field) = *self.ivars().hex_field.borrow() {
+            field.setStringValue(&NSString::from_str(&format_hex_color((r, g, b))));
+        }
+    }
+
+    /// Current annotation color shown on the color button.
+    pub fn color(&self) -> (CGFloat, CGFloat, CGFloat) {
+        self.ivars().color.get()
+    }
+
+    /// Text currently typed into the hex color field.
+    pub fn hex_field_value(&self) -> String {
+        self.ivars()
+            .hex_field
+            .borrow()
+            .as_ref()
+            .map(|field| field.stringValue().to_string())
+            .unwrap_or_default()
     }
 
     /// Get the frame of the color button (for positioning the color panel).
@@ -248,6 +657,302 @@ impl ToolbarView {
     pub fn is_color_picker_active(&self) -> bool {
         self.ivars().color_picker_active.get()
     }
+
+    /// Set the fill toggle's active/inactive visual state.
+    pub fn set_fill_active(&self, active: bool) {
+        self.ivars().fill_active.set(active);
+        if let Some(ref btn) = *self.ivars().fill_button.borrow() {
+            #[allow(deprecated)]
+            if active {
+                btn.setBezelStyle(objc2_app_kit::NSBezelStyle::SmallSquare);
+            } else {
+                btn.setBezelStyle(objc2_app_kit::NSBezelStyle::Inline);
+            }
+        }
+    }
+
+    /// Whether new rectangles and ellipses are filled.
+    pub fn is_fill_active(&self) -> bool {
+        self.ivars().fill_active.get()
+    }
+
+    /// Set the drop shadow toggle's active/inactive visual state.
+    pub fn set_shadow_active(&self, active: bool) {
+        self.ivars().shadow_active.set(active);
+        if let Some(ref btn) = *self.ivars().shadow_button.borrow() {
+            #[allow(deprecated)]
+            if active {
+                btn.setBezelStyle(objc2_app_kit::NSBezelStyle::SmallSquare);
+            } else {
+                btn.setBezelStyle(objc2_app_kit::NSBezelStyle::Inline);
+            }
+        }
+    }
+
+    /// Whether new annotations get a drop shadow.
+    pub fn is_shadow_active(&self) -> bool {
+        self.ivars().shadow_active.get()
+    }
+
+    /// Set the font for new text labels. The button previews it in its own title.
+    pub fn set_font_index(&self, index: usize) {
+        self.ivars().font_index.set(index);
+        if let Some(ref btn) = *self.ivars().font_button.borrow() {
+            let (title, name) = TEXT_FONTS[index];
+            btn.setToolTip(Some(&NSString::from_str(&format!("Font: {}", title))));
+            let traits = NSFontDescriptorSymbolicTraits::empty();
+            btn.setFont(Some(&text_font(name, 12.0, traits)));
+        }
+    }
+
+    /// Index into `TEXT_FONTS` of the font for new text labels.
+    pub fn font_index(&self) -> usize {
+        self.ivars().font_index.get()
+    }
+
+    /// Set the bold toggle's active/inactive visual state.
+    pub fn set_bold_active(&self, active: bool) {
+        self.ivars().bold_active.set(active);
+        if let Some(ref btn) = *self.ivars().bold_button.borrow() {
+            #[allow(deprecated)]
+            if active {
+                btn.setBezelStyle(objc2_app_kit::NSBezelStyle::SmallSquare);
+            } else {
+                btn.setBezelStyle(objc2_app_kit::NSBezelStyle::Inline);
+            }
+        }
+    }
+
+    /// Whether new text labels are bold.
+    pub fn is_bold_active(&self) -> bool {
+        self.ivars().bold_active.get()
+    }
+
+    /// Set the italic toggle's active/inactive visual state.
+    pub fn set_italic_active(&self, active: bool) {
+        self.ivars().italic_active.set(active);
+        if let Some(ref btn) = *self.ivars().italic_button.borrow() {
+            #[allow(deprecated)]
+            if active {
+                btn.setBezelStyle(objc2_app_kit::NSBezelStyle::SmallSquare);
+            } else {
+                btn.setBezelStyle(objc2_app_kit::NSBezelStyle::Inline);
+            }
+        }
+    }
+
+    /// Whether new text labels are italic.
+    pub fn is_italic_active(&self) -> bool {
+        self.ivars().italic_active.get()
+    }
+
+    /// Bold/italic styling for new text labels, from the two toggles.
+    pub fn font_traits(&self) -> NSFontDescriptorSymbolicTraits {
+        let mut traits = NSFontDescriptorSymbolicTraits::empty();
+        traits.set(NSFontDescriptorSymbolicTraits::TraitBold, self.ivars().bold_active.get());
+        traits.set(NSFontDescriptorSymbolicTraits::TraitItalic, self.ivars().italic_active.get());
+        traits
+    }
+
+    /// Set the pencil smoothing toggle's active/inactive visual state.
+    pub fn set_smooth_active(&self, active: bool) {
+        self.ivars().smooth_active.set(active);
+        if let Some(ref btn) = *self.ivars().smooth_button.borrow() {
+            #[allow(deprecated)]
+            if active {
+                btn.setBezelStyle(objc2_app_kit::NSBezelStyle::SmallSquare);
+            } else {
+                btn.setBezelStyle(objc2_app_kit::NSBezelStyle::Inline);
+            }
+        }
+    }
+
+    /// Whether new pencil strokes are smoothed.
+    pub fn is_smooth_active(&self) -> bool {
+        self.ivars().smooth_active.get()
+    }
+
+    /// Set the text background toggle's active/inactive visual state.
+    pub fn set_text_background_active(&self, active: bool) {
+        self.ivars().text_background_active.set(active);
+        if let Some(ref btn) = *self.ivars().text_background_button.borrow() {
+            #[allow(deprecated)]
+            if active {
+                btn.setBezelStyle(objc2_app_kit::NSBezelStyle::SmallSquare);
+            } else {
+                btn.setBezelStyle(objc2_app_kit::NSBezelStyle::Inline);
+            }
+        }
+    }
+
+    /// Whether new text labels get a background box.
+    pub fn is_text_background_active(&self) -> bool {
+        self.ivars().text_background_active.get()
+    }
+
+    /// Set the text outline toggle's active/inactive visual state.
+    pub fn set_text_outline_active(&self, active: bool) {
+        self.ivars().text_outline_active.set(active);
+        if let Some(ref btn) = *self.ivars().text_outline_button.borrow() {
+            #[allow(deprecated)]
+            if active {
+                btn.setBezelStyle(objc2_app_kit::NSBezelStyle::SmallSquare);
+            } else {
+                btn.setBezelStyle(objc2_app_kit::NSBezelStyle::Inline);
+            }
+        }
+    }
+
+    /// Whether new text labels are outlined.
+    pub fn is_text_outline_active(&self) -> bool {
+        self.ivars().text_outline_active.get()
+    }
+
+    /// Set the current corner radius preset. Highlights the button for rounded corners.
+    pub fn set_corner_radius_index(&self, index: usize) {
+        self.ivars().corner_radius_index.set(index);
+        if let Some(ref btn) = *self.ivars().corner_button.borrow() {
+            let radius = CORNER_RADII[index];
+            let tooltip = if radius > 0.0 {
+                format!("Corner Radius: {}", radius)
+            } else {
+                "Corner Radius: Square".to_string()
+            };
+            btn.setToolTip(Some(&NSString::from_str(&tooltip)));
+            #[allow(deprecated)]
+            if radius > 0.0 {
+                btn.setBezelStyle(objc2_app_kit::NSBezelStyle::SmallSquare);
+            } else {
+                btn.setBezelStyle(objc2_app_kit::NSBezelStyle::Inline);
+            }
+        }
+    }
+
+    /// Index into `CORNER_RADII` of the current corner radius.
+    pub fn corner_radius_index(&self) -> usize {
+        self.ivars().corner_radius_index.get()
+    }
+
+    /// Set the current highlight opacity preset (shown in the button's tooltip).
+    pub fn set_opacity_index(&self, index: usize) {
+        self.ivars().opacity_index.set(index);
+        if let Some(ref btn) = *self.ivars().opacity_button.borrow() {
+            let percent = (HIGHLIGHT_OPACITIES[index] * 100.0).round();
+            let tooltip = format!("Highlight Opacity: {}%", percent);
+            btn.setToolTip(Some(&NSString::from_str(&tooltip)));
+        }
+    }
+
+    /// Index into `HIGHLIGHT_OPACITIES` of the current highlight opacity.
+    pub fn opacity_index(&self) -> usize {
+        self.ivars().opacity_index.get()
+    }
+
+    /// Show the given stroke style on the style button.
+    pub fn set_stroke_style(&self, style: StrokeStyle) {
+        self.ivars().stroke_style.set(style);
+        if let Some(ref btn) = *self.ivars().stroke_style_button.borrow() {
+            btn.setTitle(&NSString::from_str(stroke_style_label(style)));
+            let tooltip = format!("Stroke Style: {:?} (D)", style);
+            btn.setToolTip(Some(&NSString::from_str(&tooltip)));
+        }
+    }
+
+    /// Current stroke style for new shapes and lines.
+    pub fn stroke_style(&self) -> StrokeStyle {
+        self.ivars().stroke_style.get()
+    }
+
+    /// Show the given blur style on the blur style button.
+    pub fn set_blur_style(&self, style: BlurStyle) {
+        self.ivars().blur_style.set(style);
+        if let Some(ref btn) = *self.ivars().blur_style_button.borrow() {
+            btn.setTitle(&NSString::from_str(blur_style_label(style)));
+            let tooltip = format!("Blur Style: {:?}", style);
+            btn.setToolTip(Some(&NSString::from_str(&tooltip)));
+        }
+    }
+
+    /// Current style for new Blur annotations.
+    pub fn blur_style(&self) -> BlurStyle {
+        self.ivars().blur_style.get()
+    }
+
+    /// Show the given marker shape on the step shape button.
+    pub fn set_step_shape(&self, shape: StepShape) {
+        self.ivars().step_shape.set(shape);
+        if let Some(ref btn) = *self.ivars().step_shape_button.borrow() {
+            btn.setTitle(&NSString::from_str(step_shape_label(shape)));
+            let tooltip = format!("Step Shape: {:?}", shape);
+            btn.setToolTip(Some(&NSString::from_str(&tooltip)));
+        }
+    }
+
+    /// Current marker shape for new Step annotations.
+    pub fn step_shape(&self) -> StepShape {
+        self.ivars().step_shape.get()
+    }
+
+    /// Aspect ratio picked for the Crop tool.
+    pub fn crop_aspect(&self) -> CropAspect {
+        let selected = self
+            .ivars()
+            .crop_aspect_control
+            .borrow()
+            .as_ref()
+            .map_or(0, |control| control.selectedSegment());
+        CropAspect::ALL
+            .get(selected.max(0) as usize)
+            .copied()
+            .unwrap_or(CropAspect::Free)
+    }
+}
+
+/// Parse `#RRGGBB` (the `#` is optional) into RGB components (0.0–1.0).
+pub fn parse_hex_color(text: &str) -> Option<(CGFloat, CGFloat, CGFloat)> {
+    let hex = text.trim();
+    let hex = hex.strip_prefix('#').unwrap_or(hex);
+    if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok().map(|v| v as CGFloat / 255.0);
+    Some((channel(0)?, channel(2)?, channel(4)?))
+}
+
+/// Format RGB components (0.0–1.0) as `#RRGGBB`.
+pub fn format_hex_color(color: (CGFloat, CGFloat, CGFloat)) -> String {
+    let to_byte = |c: CGFloat| (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+    format!("#{:02X}{:02X}{:02X}", to_byte(color.0), to_byte(color.1), to_byte(color.2))
+}
+
+fn stroke_style_label(style: StrokeStyle) -> &'static str {
+    match style {
+        StrokeStyle::Solid => "\u{2500}",
+        StrokeStyle::Dashed => "\u{254C}",
+        StrokeStyle::Dotted => "\u{2508}",
+    }
+}
+
+fn step_shape_label(shape: StepShape) -> &'static str {
+    match shape {
+        StepShape::Circle => "\u{25CF}",
+        StepShape::Square => "\u{25A0}",
+        StepShape::Diamond => "\u{25C6}",
+    }
+}
+
+fn blur_style_label(style: BlurStyle) -> &'static str {
+    match style {
+        BlurStyle::Pixelate => "\u{25A6}",
+        BlurStyle::Gaussian => "\u{25CD}",
+    }
+}
+
+/// A button's spoken name: its tooltip without the shortcut or current state,
+/// e.g. "Arrow" for "Arrow (A)" and "Stroke Style" for "Stroke Style: Solid (D)".
+fn accessibility_label(tooltip: &str) -> &str {
+    let end = tooltip.find([':', '(']).unwrap_or(tooltip.len());
+    tooltip[..end].trim_end()
 }
 
 fn create_button(
@@ -255,10 +960,8 @@ fn create_button(
     label: &str,
     sel_name: &str,
     tooltip: &str,
-    x: CGFloat,
-    y: CGFloat,
 ) -> Retained<NSButton> {
-    let frame = NSRect::new(CGPoint::new(x, y), CGSize::new(BUTTON_W, BUTTON_H));
+    let frame = NSRect::new(CGPoint::ZERO, CGSize::new(BUTTON_W, BUTTON_H));
     let sel_cstr = std::ffi::CString::new(sel_name).unwrap();
     let button: Retained<NSButton> = unsafe { msg_send![mtm.alloc(), initWithFrame: frame] };
     button.setTitle(&NSString::from_str(label));
@@ -267,6 +970,8 @@ fn create_button(
         button.setTarget(None);
         button.setToolTip(Some(&NSString::from_str(tooltip)));
     }
+    // The glyph titles mean nothing to VoiceOver; announce the button's name instead
+    button.setAccessibilityLabel(Some(&NSString::from_str(accessibility_label(tooltip))));
     button.setFont(Some(&NSFont::systemFontOfSize(12.0)));
     #[allow(deprecated)]
     button.setBezelStyle(objc2_app_kit::NSBezelStyle::Inline);
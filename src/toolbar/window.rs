@@ -1,4 +1,5 @@
 use objc2::rc::Retained;
+use objc2::{define_class, msg_send, MainThreadOnly};
 use objc2_app_kit::{
     NSBackingStoreType, NSColor, NSColorPanel, NSPanel, NSView, NSWindowStyleMask,
 };
@@ -8,8 +9,26 @@ use objc2_foundation::MainThreadMarker;
 
 use super::view::ToolbarView;
 
+// Borderless panels can't become key by default; the hex color field needs typing focus.
+pub struct KeyablePanelIvars {}
+
+define_class!(
+    #[unsafe(super(NSPanel))]
+    #[thread_kind = MainThreadOnly]
+    #[name = "KeyablePanel"]
+    #[ivars = KeyablePanelIvars]
+    pub struct KeyablePanel;
+
+    impl KeyablePanel {
+        #[unsafe(method(canBecomeKeyWindow))]
+        fn can_become_key_window(&self) -> bool {
+            true
+        }
+    }
+);
+
 pub struct ToolbarWindow {
-    pub panel: Retained<NSPanel>,
+    pub panel: Retained<KeyablePanel>,
     pub view: Retained<ToolbarView>,
 }
 
@@ -18,14 +37,16 @@ impl ToolbarWindow {
         let view = ToolbarView::new(mtm);
         let view_frame = view.frame();
 
-        let panel =
-            NSPanel::initWithContentRect_styleMask_backing_defer(
-                mtm.alloc(),
-                view_frame,
-                NSWindowStyleMask::Borderless | NSWindowStyleMask::NonactivatingPanel,
-                NSBackingStoreType::Buffered,
-                false,
-            );
+        let this = KeyablePanel::alloc(mtm).set_ivars(KeyablePanelIvars {});
+        let panel: Retained<KeyablePanel> = unsafe {
+            msg_send![
+                super(this),
+                initWithContentRect: view_frame,
+                styleMask: NSWindowStyleMask::Borderless | NSWindowStyleMask::NonactivatingPanel,
+                backing: NSBackingStoreType::Buffered,
+                defer: false
+            ]
+        };
 
         panel.setLevel((kCGOverlayWindowLevel + 1) as _);
         panel.setOpaque(false);
@@ -34,6 +55,8 @@ impl ToolbarWindow {
         panel.setHidesOnDeactivate(false);
         panel.setFloatingPanel(true);
         panel.setWorksWhenModal(true);
+        // Only take key focus for the hex field, not for button clicks
+        panel.setBecomesKeyOnlyIfNeeded(true);
         unsafe { panel.setReleasedWhenClosed(false) };
 
         panel.setContentView(Some(&view));
@@ -41,36 +64,83 @@ impl ToolbarWindow {
         ToolbarWindow { panel, view }
     }
 
+    /// Switch between the horizontal row and the vertical column, resizing the panel to fit.
+    pub fn set_vertical(&self, vertical: bool) {
+        self.view.set_vertical(vertical);
+        if let Some(screen) = self.panel.screen() {
+            self.fit_within(screen.visibleFrame());
+        }
+    }
+
+    /// Wrap the buttons onto more rows (or columns) so the toolbar fits in
+    /// `visible_frame`, the usable part of a screen in global AppKit coordinates.
+    pub fn fit_within(&self, visible_frame: CGRect) {
+        let length = if self.view.is_vertical() {
+            visible_frame.size.height
+        } else {
+            visible_frame.size.width
+        };
+        self.view.set_max_length(length);
+    }
+
     /// Position the toolbar below the selection rect, or above if below is off-screen.
+    /// A vertical toolbar goes to the right of the selection instead, or to the left.
+    /// The toolbar wraps to fit `visible_frame` (the screen minus the menu bar
+    /// and Dock) and is always kept fully within it, overlapping the selection
+    /// when there's no room on either side.
     /// `selection` is in overlay-local flipped coordinates (top-left origin).
     /// `screen_frame` is the overlay's screen frame in global AppKit coordinates.
-    pub fn show_near_selection(&self, selection: CGRect, screen_frame: CGRect) {
+    pub fn show_near_selection(
+        &self,
+        selection: CGRect,
+        screen_frame: CGRect,
+        visible_frame: CGRect,
+    ) {
+        self.fit_within(visible_frame);
         let toolbar_size = self.view.frame().size;
 
         // Selection coords are local to the overlay (which covers the screen).
         // Convert to global AppKit coords by adding screen_frame.origin and
         // flipping Y from top-left to bottom-left.
         let gap: CGFloat = 8.0;
-
-        // Try below the selection first (in flipped coords: below = higher y)
-        let flipped_y = selection.origin.y + selection.size.height + gap;
-        let screen_y = screen_frame.origin.y + screen_frame.size.height - flipped_y - toolbar_size.height;
-
-        // Center horizontally with the selection (offset by screen origin)
-        let x = screen_frame.origin.x + selection.origin.x + (selection.size.width - toolbar_size.width) / 2.0;
-        let x = x.max(screen_frame.origin.x)
-            .min(screen_frame.origin.x + screen_frame.size.width - toolbar_size.width);
-
-        let final_y = if screen_y < screen_frame.origin.y {
-            // Not enough space below; place above the selection
-            let flipped_y_above = selection.origin.y - gap - toolbar_size.height;
-            screen_frame.origin.y + screen_frame.size.height - flipped_y_above - toolbar_size.height
+        let sel_left = screen_frame.origin.x + selection.origin.x;
+        let sel_right = sel_left + selection.size.width;
+        let sel_top = screen_frame.origin.y + screen_frame.size.height - selection.origin.y;
+        let sel_bottom = sel_top - selection.size.height;
+        let screen_left = visible_frame.origin.x;
+        let screen_right = visible_frame.origin.x + visible_frame.size.width;
+        let screen_bottom = visible_frame.origin.y;
+        let screen_top = visible_frame.origin.y + visible_frame.size.height;
+
+        let (x, y) = if self.view.is_vertical() {
+            // Top-aligned with the selection, to its right if that fits, else to its left
+            let right_x = sel_right + gap;
+            let left_x = sel_left - gap - toolbar_size.width;
+            let x = if right_x + toolbar_size.width <= screen_right || left_x < screen_left {
+                right_x
+            } else {
+                left_x
+            };
+            (x, sel_top - toolbar_size.height)
         } else {
-            screen_y
+            // Centered with the selection, below it if that fits, else above it
+            let x = sel_left + (selection.size.width - toolbar_size.width) / 2.0;
+            let below_y = sel_bottom - gap - toolbar_size.height;
+            let above_y = sel_top + gap;
+            let y = if below_y >= screen_bottom || above_y + toolbar_size.height > screen_top {
+                below_y
+            } else {
+                above_y
+            };
+            (x, y)
         };
 
-        let origin = CGPoint::new(x, final_y);
-        self.panel.setFrameOrigin(origin);
+        // Clamp into the visible frame; when the toolbar is still larger,
+        // keep its right end (Confirm) and top reachable
+        let x = x.max(screen_left).min(screen_right - toolbar_size.width);
+        let y = y.max(screen_bottom).min(screen_top - toolbar_size.height);
+
+        self.panel.setFrameOrigin(CGPoint::new(x, y));
         // Use orderFrontRegardless — in accessory/agent apps, orderFront(None)
         // may fail to bring the panel above the key overlay window.
         self.panel.orderFrontRegardless();
@@ -86,7 +156,6 @@ impl ToolbarWindow {
 
         // Add the color panel as a child of the toolbar panel so it stays
         // in the same window group as the overlay → toolbar hierarchy.
-        use objc2::msg_send;
         let _: () = unsafe {
             msg_send![&*self.panel, addChildWindow: &*color_panel, ordered: 1i64]
         };
@@ -117,7 +186,6 @@ impl ToolbarWindow {
         let color_panel = NSColorPanel::sharedColorPanel(mtm);
 
         // Remove the color panel from the child window group
-        use objc2::msg_send;
         let _: () = unsafe {
             msg_send![&*self.panel, removeChildWindow: &*color_panel]
         };
@@ -0,0 +1,153 @@
+use std::path::Path;
+
+use block2::RcBlock;
+use dispatch2::DispatchQueue;
+use objc2_foundation::{
+    NSData, NSError, NSHTTPURLResponse, NSMutableURLRequest, NSString, NSURL, NSURLResponse,
+    NSURLSession,
+};
+
+/// Form field the file is sent in.
+const FILE_FIELD: &str = "file";
+/// Seconds without progress before an upload gives up.
+const TIMEOUT_SECONDS: f64 = 60.0;
+
+/// POST the file at `path` to the configured endpoint as multipart form data.
+/// The file is read before this returns, so the caller may delete it. When the
+/// server answers, the link in its response is copied to the clipboard and a
+/// notification reports the outcome.
+pub fn upload_file(path: &Path) -> Result<(), String> {
+    let endpoint = crate::prefs::upload_url().ok_or("No upload URL is set")?;
+    let url = NSURL::URLWithString(&NSString::from_str(&endpoint)).ok_or("Invalid upload URL")?;
+    let bytes = std::fs::read(path).map_err(|e| e.to_string())?;
+    let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("capture");
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    let boundary = format!("ClipShot-{:x}", nanos);
+    let body = multipart_body(&boundary, file_name, content_type(path), &bytes);
+
+    let request = NSMutableURLRequest::requestWithURL(&url);
+    request.setHTTPMethod(&NSString::from_str("POST"));
+    request.setTimeoutInterval(TIMEOUT_SECONDS);
+    let content_type = format!("multipart/form-data; boundary={}", boundary);
+    request.setValue_forHTTPHeaderField(
+        Some(&NSString::from_str(&content_type)),
+        &NSString::from_str("Content-Type"),
+    );
+    if let Some(header) = crate::prefs::upload_auth_header() {
+        let (name, value) = parse_header(&header).ok_or("Auth header must be \"Name: value\"")?;
+        request.setValue_forHTTPHeaderField(
+            Some(&NSString::from_str(value)),
+            &NSString::from_str(name),
+        );
+    }
+
+    // Runs on the session's queue; the result is handled back on the main thread
+    let handler = RcBlock::new(
+        |data: *mut NSData, response: *mut NSURLResponse, error: *mut NSError| {
+            let result = unsafe { response_link(data.as_ref(), response.as_ref(), error.as_ref()) };
+            DispatchQueue::main().exec_async(move || finish(result));
+        },
+    );
+    let task = unsafe {
+        NSURLSession::sharedSession().uploadTaskWithRequest_fromData_completionHandler(
+            &request,
+            Some(&NSData::with_bytes(&body)),
+            &handler,
+        )
+    };
+    task.resume();
+    eprintln!("Uploading {} ({} bytes) to {}", file_name, bytes.len(), endpoint);
+    Ok(())
+}
+
+/// A one-file multipart/form-data body.
+fn multipart_body(boundary: &str, file_name: &str, content_type: &str, bytes: &[u8]) -> Vec<u8> {
+    let head = format!(
+        "--{}\r\nContent-Disposition: form-data; name=\"{}\"; filename=\"{}\"\r\n\
+         Content-Type: {}\r\n\r\n",
+        boundary,
+        FILE_FIELD,
+        file_name.replace('"', "'"),
+        content_type
+    );
+    let tail = format!("\r\n--{}--\r\n", boundary);
+    let mut body = Vec::with_capacity(head.len() + bytes.len() + tail.len());
+    body.extend_from_slice(head.as_bytes());
+    body.extend_from_slice(bytes);
+    body.extend_from_slice(tail.as_bytes());
+    body
+}
+
+fn content_type(path: &Path) -> &'static str {
+    let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("").to_lowercase();
+    match ext.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "webp" => "image/webp",
+        "gif" => "image/gif",
+        "mp4" => "video/mp4",
+        "webm" => "video/webm",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Split "Name: value" into its trimmed parts.
+fn parse_header(header: &str) -> Option<(&str, &str)> {
+    let (name, value) = header.split_once(':')?;
+    let name = name.trim();
+    (!name.is_empty()).then_some((name, value.trim()))
+}
+
+/// The link from a finished upload, or why there isn't one.
+fn response_link(
+    data: Option<&NSData>,
+    response: Option<&NSURLResponse>,
+    error: Option<&NSError>,
+) -> Result<String, String> {
+    if let Some(error) = error {
+        return Err(error.localizedDescription().to_string());
+    }
+    let status = response
+        .and_then(|r| r.downcast_ref::<NSHTTPURLResponse>())
+        .map(|r| r.statusCode())
+        .unwrap_or(0);
+    if !(200..300).contains(&status) {
+        return Err(format!("The server answered with HTTP {}", status));
+    }
+    let body = data.map(|d| d.to_vec()).unwrap_or_default();
+    find_link(&String::from_utf8_lossy(&body))
+        .ok_or_else(|| "The server's response has no link".to_string())
+}
+
+/// The first http(s) URL in `body`: a bare link, or one inside JSON such as
+/// `{"url": "https:\/\/example.com\/a.png"}`.
+fn find_link(body: &str) -> Option<String> {
+    let body = body.replace("\\/", "/");
+    let start = ["https://", "http://"].iter().filter_map(|p| body.find(p)).min()?;
+    let link = body[start..]
+        .chars()
+        .take_while(|c| !c.is_whitespace() && !matches!(c, '"' | '\'' | '<' | '>'))
+        .collect();
+    Some(link)
+}
+
+/// Copy the link and report the outcome (main thread).
+fn finish(result: Result<String, String>) {
+    match result {
+        Ok(link) => {
+            let copied = arboard::Clipboard::new().and_then(|mut c| c.set_text(link.clone()));
+            if let Err(e) = copied {
+                eprintln!("Failed to copy upload link: {}", e);
+            }
+            eprintln!("Uploaded: {}", link);
+            crate::notify::show("Upload Complete", &format!("Link copied: {}", link));
+        }
+        Err(e) => {
+            eprintln!("Upload failed: {}", e);
+            crate::notify::show("Upload Failed", &e);
+        }
+    }
+}